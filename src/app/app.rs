@@ -1,85 +1,460 @@
+use crate::app::dir_cache::{self, DirCache, Listed, Rest, Shared};
+use crate::app::messages::{Level, Messages};
+use crate::app::metrics::Metrics;
+use crate::app::sort::SortBy;
+use crate::app::watcher::DirWatcher;
 use crate::configuration::configuration::read_config;
-use crate::ui::display::{pane::get_du, pane::get_pwd};
-use crate::ui::input::{run_app::Command, stateful_list::StatefulList};
+use crate::configuration::directories::dir_values;
+use crate::configuration::frecency::{self, JumpDb, Visit};
+use crate::configuration::theme::Theme;
+use crate::git::{read_git_info, GitInfo};
+use crate::ui::display::{
+    file_colors::LsColors, icons::IconSet, layout::PaneLayout, pane::get_du, pane::get_pwd,
+    text_window::TextWindow,
+};
+use crate::ui::input::{
+    archive::Format,
+    checksum::Digests,
+    conflicts::Policy,
+    dir_tree,
+    du::Usage,
+    elevate::Elevated,
+    export::TreeFormat,
+    filter::FileFilter,
+    flatten::{self, FlatJob},
+    follow::Follow,
+    fzf_backend::FzfBackend,
+    grep::Grep,
+    hooks::Hooks,
+    jobs::{BackgroundJob, JobSummary},
+    locking::Retry,
+    motion::Pending,
+    mounts::MountWatch,
+    mouse::PaneBorder,
+    open_with::Association,
+    pager::Pager,
+    paging::{self, Pages, Pane},
+    palette::Action,
+    plugins::Plugins,
+    popups::Geometry,
+    popups::Popup,
+    preflight::Confirm,
+    previewers::Previewers,
+    preview_search::PreviewSearch,
+    properties::Properties,
+    remote::{self, PendingConnect, Remote},
+    run_app::Command,
+    schedule::Scheduled,
+    stateful_list::StatefulList,
+    terminal_title::TerminalTitle,
+    typeahead::TypeAhead,
+    undo::Operation,
+};
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
     style::Style,
     widgets::{ListState, Widget},
 };
-use std::fs::{self, read_dir, File};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::ffi::{OsStr, OsString};
+use std::fs::{self, read_dir, DirEntry, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 pub struct App {
-    pub files: StatefulList<(String, String)>,
-    pub dirs: StatefulList<(String, String)>,
+    // (name as the file system has it, label shown for it)
+    pub files: StatefulList<(OsString, String)>,
+    pub dirs: StatefulList<(OsString, String)>,
     pub content: StatefulList<String>,
     pub cur_du: String,
     pub cur_dir: String,
     pub show_popup: bool,
     pub show_nav: bool,
+    // directories the navigator's input can be completed to
+    pub nav_completions: Vec<String>,
+    pub type_ahead: Option<TypeAhead>,
+    pub pending: Pending,
     pub show_fzf: bool,
     pub show_help: bool,
     pub show_bookmark: bool,
-    pub fzf_results: StatefulList<String>,
-    pub selected_fzf_result: usize,
+    pub fzf_results: StatefulList<PathBuf>,
+    pub fzf_query: String,
+    pub fzf_resume: bool,
+    pub fzf_respect_ignore: bool,
+    // fd or rg listing the files instead of the builtin walk
+    pub fzf_backend: FzfBackend,
     pub selected_item_state: ListState,
     pub last_command: Option<Command>,
     pub bookmarked_dirs: StatefulList<String>,
+    pub bookmark_notes: HashMap<String, (String, String)>,
+    pub editing_bookmark: Option<String>,
     pub excluded_directories: Vec<String>,
     pub show_hidden: bool,
     pub show_ops_menu: bool,
-    pub selected_files: Vec<String>,
-    pub selected_dirs: Vec<String>,
+    pub selected_files: Vec<PathBuf>,
+    pub selected_dirs: Vec<PathBuf>,
     pub ops_menu: StatefulList<String>,
+    pub jump_list: StatefulList<String>,
+    pub jump_index: usize,
+    pub show_jumps: bool,
+    pub show_mounts: bool,
+    // (mount point, line shown for it)
+    pub mounts: StatefulList<(String, String)>,
+    pub mount_watch: MountWatch,
+    pub git: Option<GitInfo>,
+    pub hide_ignored: bool,
+    pub git_refreshed: Option<(PathBuf, Instant)>,
+    pub show_treemap: bool,
+    pub treemap: StatefulList<(OsString, u64)>,
+    pub show_kinds: bool,
+    pub kinds: StatefulList<String>,
+    pub kinds_job: Option<Receiver<Vec<String>>>,
+    pub show_du: bool,
+    pub du: StatefulList<Usage>,
+    pub du_job: Option<Receiver<(String, u64)>>,
+    pub show_diff: bool,
+    // the lines of `diff -u`, and which two files they compare
+    pub diff: StatefulList<String>,
+    pub diff_title: String,
+    pub show_compress: bool,
+    pub compress: StatefulList<Format>,
+    // the format picked in the dialog, for a name typed without its suffix
+    pub compress_format: Option<Format>,
+    // the archive waiting on its password
+    pub compress_name: Option<String>,
+    pub show_export: bool,
+    // (format, to the clipboard)
+    pub export: StatefulList<(TreeFormat, bool)>,
+    pub export_depth: usize,
+    // picked in the dialog, waiting for the file name
+    pub export_format: Option<TreeFormat>,
+    // started with --from-stdin, quitting prints the marked paths
+    pub from_stdin: bool,
+    pub show_piped: bool,
+    pub piped: StatefulList<PathBuf>,
+    pub watcher: Option<DirWatcher>,
+    pub files_area: Rect,
+    pub dirs_area: Rect,
+    pub preview_area: Rect,
+    pub last_click: Option<(Instant, u16, u16)>,
+    pub preview_file: OsString,
+    pub preview_scroll: usize,
+    pub preview_at_end: bool,
+    // when the selection last moved, the preview waits `preview_delay`
+    // for it to settle and says whether it's still waiting
+    pub preview_moved: Instant,
+    pub preview_delay: Duration,
+    pub preview_held: bool,
+    // j/k and the other motions scroll the preview, 3
+    pub preview_focused: bool,
+    pub preview_search: Option<PreviewSearch>,
+    pub follow: Option<Follow>,
+    // v, the selected file on the whole screen
+    pub pager: Option<Pager>,
+    pub screen_area: Rect,
+    pub preview_width: u16,
+    pub files_height: u16,
+    // one list with the directories and then the files, `unified_listing`
+    pub unified_listing: bool,
+    // the unified list's rows, only its selection, offset and height are
+    // used, the entries stay in files and dirs
+    pub listing: StatefulList<()>,
+    pub layout: PaneLayout,
+    pub sort: SortBy,
+    // j past the last item goes back to the first, `wrap_lists`
+    pub wrap_lists: bool,
+    // kB, MB, ... instead of KiB, MiB, ..., `size_units=si`
+    pub si_units: bool,
+    // the Directories pane as a tree, `dir_tree`
+    pub dir_tree: bool,
+    // every file below the current directory in the Files pane, F
+    pub flatten: bool,
+    pub flat_job: Option<FlatJob>,
+    // where the flat listing in the Files pane was walked
+    pub flat_dir: Option<PathBuf>,
+    // how far the panes' listings are read, a directory too big to read in
+    // one go lists its first entries while the rest come in the background
+    pub file_pages: Option<Pages>,
+    pub dir_pages: Option<Pages>,
+    pub dir_cache: DirCache,
+    // what one pane's refresh read, so the other's doesn't read it again
+    pub shared_listing: Option<Shared>,
+    // the globs the Files pane is restricted to, *
+    pub file_filter: Option<FileFilter>,
+    pub expanded_dirs: HashSet<PathBuf>,
+    // what the parent column of the three column layout lists, directories
+    // first, for the directory it was read in
+    pub parent_entries: Option<(PathBuf, Vec<(String, bool)>)>,
+    pub dragging: Option<PaneBorder>,
+    pub show_report: bool,
+    pub report_title: String,
+    pub report: StatefulList<String>,
+    pub checksum_job: Option<Receiver<Vec<String>>>,
+    pub diagnostics_job: Option<Receiver<Vec<String>>>,
+    pub hash_job: Option<Receiver<(PathBuf, Digests)>>,
+    // the checksums in the report, by line
+    pub hashes: Option<Vec<String>>,
+    pub theme: Theme,
+    pub show_open_with: bool,
+    // the open_with= lines from the config, and the ones the menu offers
+    pub associations: Vec<Association>,
+    pub open_with: StatefulList<Association>,
+    pub open_with_entry: OsString,
+    pub show_themes: bool,
+    pub themes: StatefulList<String>,
+    pub ls_colors: Option<LsColors>,
+    pub icons: IconSet,
+    pub pending_retry: Option<Retry>,
+    // refused for its permissions, Enter on the report runs it as root
+    pub pending_elevate: Option<Elevated>,
+    pub pending_confirm: Option<Confirm>,
+    pub properties: Option<Properties>,
+    // sanity caps, 0 turns one off
+    pub max_delete_files: usize,
+    // confirm=, whether deletes and overwrites ask first
+    pub confirm: Policy,
+    pub max_entries: usize,
+    pub show_palette: bool,
+    pub show_frecent: bool,
+    pub frecent: StatefulList<String>,
+    pub frecent_query: String,
+    pub show_grep: bool,
+    pub grep: Grep,
+    pub frecency: Vec<Visit>,
+    // zoxide or autojump, shared with the shell when the config says so,
+    // and what it answered when the popup opened
+    pub jump_db: Option<JumpDb>,
+    pub jump_db_dirs: Option<Vec<String>>,
+    // the directory last counted as a visit
+    pub last_visit: Option<PathBuf>,
+    pub palette: StatefulList<(String, Action)>,
+    pub palette_query: String,
+    pub pick: bool,
+    // first row of the UI in inline mode, None when it has the whole screen
+    pub inline: Option<u16>,
+    pub choose_dir: Option<PathBuf>,
+    pub choose_file: bool,
+    // pick up where the last session left off, from the config
+    pub resume: bool,
+    pub pinned: Vec<String>,
+    pub job_history: Vec<JobSummary>,
+    // the schedule= jobs from the config, and the ones cancelled this session
+    pub schedule: Vec<Scheduled>,
+    pub cancelled_schedule: Vec<String>,
+    // the report is the job history, or the output of a background job
+    pub report_jobs: bool,
+    pub report_output: Option<usize>,
+    pub background_jobs: Vec<BackgroundJob>,
+    // another machine the panes list instead of the current directory, and
+    // the one a password is being typed for
+    pub remote: Option<Remote>,
+    pub pending_connect: Option<PendingConnect>,
+    // what the scripts in the plugins directory registered
+    pub plugins: Plugins,
+    // the hook= shell commands from the config
+    pub hooks: Hooks,
+    // the previewer= commands from the config and what they printed
+    pub previewers: Previewers,
+    // how far into a text file the preview reads
+    pub text_window: TextWindow,
+    // the title and OSC 7 directory told to the terminal
+    pub terminal_title: TerminalTitle,
+    pub popup_geometry: HashMap<Popup, Geometry>,
+    pub messages: Messages,
+    pub show_metrics: bool,
+    // where traverse was started, relative paths are copied from here
+    pub launch_dir: PathBuf,
+    // the encrypted archive the password prompt is for
+    pub password_for: Option<PathBuf>,
+    // whether a new file starts from a matching template, Tab flips it
+    pub use_template: bool,
+    // a password prompt showing what's typed, Tab flips it
+    pub reveal_input: bool,
+    pub metrics: Metrics,
+    pub undo_stack: Vec<Operation>,
+    pub redo_stack: Vec<Operation>,
 }
 
 impl App {
     pub fn new() -> App {
-        let mut files = StatefulList::with_items(vec![]);
-        for entry in read_dir("./").unwrap() {
-            let entry = entry.unwrap();
-            if entry.metadata().unwrap().is_file() {
-                let temp = entry.file_name().into_string().unwrap();
-                files.items.push((temp.clone(), temp));
-            }
-        }
-
-        let mut dirs = StatefulList::with_items(vec![(("../".to_string(), "../".to_string()))]);
-        for entry in read_dir("./").unwrap() {
-            let entry = entry.unwrap();
-
-            if entry.metadata().unwrap().is_dir() {
-                let temp = entry.file_name().into_string().unwrap();
-                dirs.items.push((temp.clone(), temp));
-            }
-        }
-
         let cur_dir = get_pwd();
-        let cur_du = get_du();
 
-        App {
-            files,
-            dirs,
-            cur_du,
+        let mut app = App {
+            files: StatefulList::with_items(vec![]),
+            dirs: StatefulList::with_items(vec![]),
+            cur_du: String::new(),
             cur_dir,
             content: StatefulList::with_items(vec![]),
             show_popup: false,
             show_nav: false,
+            nav_completions: vec![],
+            type_ahead: None,
+            pending: Pending::default(),
             show_fzf: false,
             show_bookmark: false,
             show_help: false,
             fzf_results: StatefulList::with_items(vec![]),
-            selected_fzf_result: 0,
+            fzf_query: String::new(),
+            fzf_resume: false,
+            fzf_respect_ignore: true,
+            fzf_backend: FzfBackend::default(),
             selected_item_state: ListState::default(),
             last_command: None,
             bookmarked_dirs: StatefulList::with_items(vec![]),
+            bookmark_notes: HashMap::new(),
+            editing_bookmark: None,
             excluded_directories: vec![],
             show_hidden: false,
             show_ops_menu: false,
             selected_files: vec![],
             selected_dirs: vec![],
             ops_menu: StatefulList::with_items(vec![]),
-        }
+            jump_list: StatefulList::with_items(vec![]),
+            jump_index: 0,
+            show_jumps: false,
+            show_mounts: false,
+            mounts: StatefulList::with_items(vec![]),
+            mount_watch: MountWatch::default(),
+            git: None,
+            hide_ignored: false,
+            git_refreshed: None,
+            show_treemap: false,
+            treemap: StatefulList::with_items(vec![]),
+            show_kinds: false,
+            kinds: StatefulList::with_items(vec![]),
+            kinds_job: None,
+            show_du: false,
+            du: StatefulList::with_items(vec![]),
+            du_job: None,
+            show_diff: false,
+            diff: StatefulList::with_items(vec![]),
+            diff_title: String::new(),
+            show_compress: false,
+            compress: StatefulList::with_items(vec![]),
+            compress_format: None,
+            compress_name: None,
+            show_export: false,
+            export: StatefulList::with_items(vec![]),
+            export_depth: 3,
+            export_format: None,
+            from_stdin: false,
+            show_piped: false,
+            piped: StatefulList::with_items(vec![]),
+            watcher: std::env::current_dir()
+                .ok()
+                .and_then(|cwd| DirWatcher::new(&cwd)),
+            files_area: Rect::default(),
+            dirs_area: Rect::default(),
+            preview_area: Rect::default(),
+            last_click: None,
+            preview_file: OsString::new(),
+            preview_scroll: 0,
+            preview_at_end: true,
+            preview_moved: Instant::now(),
+            preview_delay: Duration::from_millis(100),
+            preview_held: false,
+            preview_focused: false,
+            preview_search: None,
+            follow: None,
+            pager: None,
+            screen_area: Rect::default(),
+            preview_width: 50,
+            files_height: 45,
+            unified_listing: false,
+            listing: StatefulList::with_items(vec![]),
+            layout: PaneLayout::Split,
+            sort: SortBy::Name,
+            wrap_lists: true,
+            si_units: false,
+            dir_tree: false,
+            flatten: false,
+            flat_job: None,
+            flat_dir: None,
+            file_pages: None,
+            dir_pages: None,
+            dir_cache: DirCache::default(),
+            shared_listing: None,
+            file_filter: None,
+            expanded_dirs: HashSet::new(),
+            parent_entries: None,
+            dragging: None,
+            show_report: false,
+            report_title: String::new(),
+            report: StatefulList::with_items(vec![]),
+            checksum_job: None,
+            diagnostics_job: None,
+            hash_job: None,
+            hashes: None,
+            theme: Theme::default(),
+            show_open_with: false,
+            associations: vec![],
+            open_with: StatefulList::with_items(vec![]),
+            open_with_entry: OsString::new(),
+            show_themes: false,
+            themes: StatefulList::with_items(vec![]),
+            ls_colors: LsColors::from_env(),
+            icons: IconSet::None,
+            pending_retry: None,
+            pending_elevate: None,
+            pending_confirm: None,
+            properties: None,
+            max_delete_files: 10_000,
+            confirm: Policy::Overwrites,
+            max_entries: 1_000_000,
+            show_palette: false,
+            show_frecent: false,
+            frecent: StatefulList::with_items(vec![]),
+            frecent_query: String::new(),
+            show_grep: false,
+            grep: Grep::default(),
+            frecency: frecency::load(),
+            jump_db: None,
+            jump_db_dirs: None,
+            last_visit: None,
+            palette: StatefulList::with_items(vec![]),
+            palette_query: String::new(),
+            pick: false,
+            inline: None,
+            choose_dir: None,
+            choose_file: false,
+            resume: false,
+            pinned: vec![],
+            job_history: vec![],
+            schedule: vec![],
+            cancelled_schedule: vec![],
+            report_jobs: false,
+            report_output: None,
+            background_jobs: vec![],
+            remote: None,
+            pending_connect: None,
+            plugins: Plugins::default(),
+            hooks: Hooks::default(),
+            previewers: Previewers::default(),
+            text_window: TextWindow::default(),
+            terminal_title: TerminalTitle::default(),
+            popup_geometry: HashMap::new(),
+            messages: Messages::default(),
+            show_metrics: false,
+            launch_dir: std::env::current_dir().unwrap_or_default(),
+            password_for: None,
+            use_template: true,
+            reveal_input: false,
+            metrics: Metrics::default(),
+            undo_stack: vec![],
+            redo_stack: vec![],
+        };
+
+        app.update_files();
+        app.update_dirs();
+        // after the config is read, for its size units
+        app.cur_du = get_du(app.si_units);
+
+        app
     }
 
     pub fn op_menu_init(&mut self) {
@@ -92,83 +467,432 @@ impl App {
         read_config(self);
     }
 
-    pub fn update_files(&mut self) {
-        self.read_config();
-        self.files.items.clear();
-
-        let mut file_entries: Vec<(String, String)> = vec![];
-
-        for entry in read_dir("./").unwrap() {
-            let entry = entry.unwrap();
-            if entry.metadata().unwrap().is_file() {
-                let temp = entry.file_name().into_string().unwrap();
-                if temp == "swapfile" {
-                    // previewing this file devastates the terminal,
-                    // mine anyway
-                    continue;
-                }
+    // git status is costly on big repos, it's read again once the watcher
+    // sees the directory, the index or HEAD change. Without a watcher it's
+    // only re-read every so often
+    pub fn refresh_git(&mut self) {
+        let cwd = match std::env::current_dir() {
+            Ok(cwd) => cwd,
+            Err(_) => return,
+        };
+
+        if let Some((dir, at)) = &self.git_refreshed {
+            let fresh = self.watcher.is_some() || at.elapsed() < Duration::from_secs(2);
+            if *dir == cwd && fresh {
+                self.metrics.git_hits += 1;
+                return;
+            }
+        }
+
+        self.metrics.git_misses += 1;
+        self.git = read_git_info(&cwd);
+        self.git_refreshed = Some((cwd, Instant::now()));
+
+        if let Some(watcher) = self.watcher.as_mut() {
+            watcher.watch_git(self.git.as_ref().map(|git| git.git_dir.as_path()));
+        }
+    }
+
+    // follow the cwd around and pick up changes made by other processes
+    pub fn poll_watcher(&mut self) {
+        let cwd = match std::env::current_dir() {
+            Ok(cwd) => cwd,
+            // deleted under us
+            Err(_) => return self.leave_removed_dir(),
+        };
+
+        // still in the same directory, but it (or a parent) was renamed, the
+        // cwd follows it by itself
+        let last = PathBuf::from(self.cur_dir.trim_end());
+        let renamed = cwd != last && !last.exists();
+
+        if renamed {
+            self.cur_dir = get_pwd();
+            self.notify(format!(
+                "{} was renamed to {}",
+                last.display(),
+                cwd.display()
+            ));
+        }
 
-                if temp.starts_with(".") && !self.show_hidden {
-                    continue;
+        let (changed, git_changed) = match self.watcher.as_mut() {
+            Some(watcher) => {
+                if watcher.dir != cwd {
+                    watcher.watch(&cwd);
                 }
 
-                file_entries.push((temp.clone(), temp));
+                (watcher.changed() || renamed, watcher.git_changed())
             }
+            None => (renamed, false),
+        };
+
+        if !changed && !git_changed {
+            return;
         }
 
-        file_entries.sort_by(|a, b| {
-            let a_starts_with_dot = a.0.starts_with(".");
-            let b_starts_with_dot = b.0.starts_with(".");
+        // a commit, checkout or git add elsewhere only needs the status again
+        self.metrics.watcher_refreshes += 1;
+        self.git_refreshed = None;
+        if changed {
+            self.dir_cache.invalidate(&cwd);
+            self.shared_listing = None;
+        }
+        self.update_files();
+        self.update_dirs();
+        self.clamp_selection();
+    }
+
+    // go to the nearest directory that's still there
+    fn leave_removed_dir(&mut self) {
+        let gone = PathBuf::from(self.cur_dir.trim_end());
+        let nearest = gone
+            .ancestors()
+            .find(|dir| read_dir(dir).is_ok())
+            .map(Path::to_path_buf);
 
-            if a_starts_with_dot && !b_starts_with_dot {
-                std::cmp::Ordering::Greater
-            } else if !a_starts_with_dot && b_starts_with_dot {
-                std::cmp::Ordering::Less
-            } else {
-                a.0.cmp(&b.0)
+        let nearest = match nearest {
+            Some(nearest) if std::env::set_current_dir(&nearest).is_ok() => nearest,
+            _ => return,
+        };
+
+        self.cur_dir = get_pwd();
+        self.notify_error(format!(
+            "{} was removed, moved to {}",
+            gone.display(),
+            nearest.display()
+        ));
+
+        self.git_refreshed = None;
+        self.update_files();
+        self.update_dirs();
+        self.clamp_selection();
+    }
+
+    // keep the selections on an entry after the listings shrank
+    pub fn clamp_selection(&mut self) {
+        if let Some(selected) = self.files.state.selected() {
+            if selected >= self.files.items.len() {
+                self.files
+                    .state
+                    .select(Some(self.files.items.len().saturating_sub(1)));
             }
-        });
+        }
 
-        for file in file_entries {
-            self.files.items.push(file);
+        if let Some(selected) = self.dirs.state.selected() {
+            if selected >= self.dirs.items.len() {
+                self.dirs
+                    .state
+                    .select(Some(self.dirs.items.len().saturating_sub(1)));
+            }
         }
     }
 
-    pub fn update_dirs(&mut self) {
-        self.dirs.items.clear();
-        self.dirs.items.push(("../".to_string(), "../".to_string()));
+    fn is_git_ignored(&self, name: &str) -> bool {
+        self.hide_ignored && self.git.as_ref().is_some_and(|git| git.is_ignored(name))
+    }
+
+    pub fn update_files(&mut self) {
+        self.read_config();
+        if self.remote.is_some() {
+            self.file_pages = None;
+            self.dir_pages = None;
+            return remote::list(self);
+        }
+
+        self.refresh_git();
+        self.pinned = std::env::current_dir()
+            .map(|cwd| dir_values(&cwd, "pin"))
+            .unwrap_or_default();
+
+        if self.flatten {
+            self.file_pages = None;
+            return flatten::list(self);
+        }
 
-        let mut dir_entries: Vec<(String, String)> = vec![];
+        // the directory can vanish or lose its permissions under us
+        let (entries, rest) = match self.listing(Pane::Files) {
+            Ok(listing) => listing,
+            Err(_) => {
+                self.file_pages = None;
+                return self.files.items.clear();
+            }
+        };
 
-        for entry in read_dir("./").unwrap() {
-            let entry = entry.unwrap();
+        let mut file_entries = entries
+            .iter()
+            .filter(|entry| entry.is_file)
+            .filter_map(|entry| self.file_entry(entry))
+            .collect::<Vec<(OsString, String)>>();
+        sort_files(&mut file_entries, &self.pinned, self.sort);
 
-            if entry.metadata().unwrap().is_dir() {
-                let temp = entry.file_name().into_string().unwrap();
+        paging::list(self, Pane::Files, vec![], file_entries, rest);
+    }
 
-                if temp.starts_with(".") && !self.show_hidden {
-                    continue;
-                }
+    // the current directory's entries, from the cache while it's unchanged,
+    // else read with the rest left for the background when there's more than
+    // a page. A refresh lists it once for both panes, and counts once
+    fn listing(&mut self, pane: Pane) -> io::Result<(Arc<Vec<Listed>>, Option<Rest>)> {
+        let cwd = std::env::current_dir()?;
+        let modified = dir_cache::modified(&cwd);
 
-                dir_entries.push((temp.clone(), temp.clone()));
+        if let Some(shared) = self.shared_listing.take() {
+            if shared.pane != pane
+                && shared.dir == cwd
+                && shared.modified.is_some()
+                && shared.modified == modified
+            {
+                return Ok((shared.entries, shared.rest));
             }
         }
 
-        dir_entries.sort_by(|a, b| {
-            let a_starts_with_dot = a.0.starts_with(".");
-            let b_starts_with_dot = b.0.starts_with(".");
+        let started = Instant::now();
+
+        if let Some(entries) = self.dir_cache.get(&cwd) {
+            self.metrics.listing_hits += 1;
+            self.metrics.last_listing = started.elapsed();
+            self.shared_listing = Some(Shared {
+                pane,
+                dir: cwd,
+                modified,
+                entries: entries.clone(),
+                rest: None,
+            });
+            return Ok((entries, None));
+        }
+        self.metrics.listing_misses += 1;
+
+        let (page, rest) = paging::first_page(read_dir(&cwd)?);
+        let page = page.iter().map(Listed::read).collect::<Vec<Listed>>();
 
-            if a_starts_with_dot && !b_starts_with_dot {
-                std::cmp::Ordering::Greater
-            } else if !a_starts_with_dot && b_starts_with_dot {
-                std::cmp::Ordering::Less
-            } else {
-                a.0.cmp(&b.0)
+        let (entries, rest, other_rest) = match rest {
+            Some(entries) => {
+                let (files_rx, dirs_rx) = paging::spawn(entries);
+                let (rx, other_rx) = match pane {
+                    Pane::Files => (files_rx, dirs_rx),
+                    Pane::Dirs => (dirs_rx, files_rx),
+                };
+                let rest = Rest {
+                    rx,
+                    read: Some(page.clone()),
+                    modified,
+                };
+                let other_rest = Rest {
+                    rx: other_rx,
+                    read: None,
+                    modified,
+                };
+                (Arc::new(page), Some(rest), Some(other_rest))
             }
+            None => (
+                self.dir_cache.insert(cwd.clone(), modified, page),
+                None,
+                None,
+            ),
+        };
+
+        self.shared_listing = Some(Shared {
+            pane,
+            dir: cwd,
+            modified,
+            entries: entries.clone(),
+            rest: other_rest,
         });
+        self.metrics.last_listing = started.elapsed();
+        Ok((entries, rest))
+    }
+
+    // a file as the Files pane lists it, none when it's left out
+    pub fn file_entry(&self, entry: &Listed) -> Option<(OsString, String)> {
+        if entry.name == "swapfile" {
+            // previewing this file devastates the terminal,
+            // mine anyway
+            return None;
+        }
+
+        if entry.hidden && !self.show_hidden {
+            return None;
+        }
+
+        let lossy = entry.name.to_string_lossy();
+        if self.is_git_ignored(&lossy) {
+            return None;
+        }
+        if let Some(filter) = &self.file_filter {
+            if !filter.matches(&lossy) {
+                return None;
+            }
+        }
+
+        Some((entry.name.clone(), entry.label.clone()))
+    }
+
+    pub fn update_dirs(&mut self) {
+        // the remote listing filled both panes already
+        if self.remote.is_some() {
+            return;
+        }
+
+        self.refresh_git();
+        self.parent_entries = None;
+        // nothing is above / or C:\
+        let at_root = std::env::current_dir().is_ok_and(|cwd| cwd.parent().is_none());
+        let head = if at_root {
+            vec![]
+        } else {
+            vec![("../".into(), "../".to_string())]
+        };
+
+        // the tree reads every directory whole, its children sit between
+        // the entries a later page would be sorted in with
+        if self.dir_tree && !self.unified() {
+            self.dir_pages = None;
+            self.dirs.items = head;
+            let dir_entries = self.subdirs(OsStr::new(""));
+            self.dirs.items.extend(dir_entries);
+            return dir_tree::expand(self);
+        }
+
+        let (entries, rest) = match self.listing(Pane::Dirs) {
+            Ok(listing) => listing,
+            Err(_) => {
+                self.dir_pages = None;
+                return self.dirs.items = head;
+            }
+        };
+
+        let mut dir_entries = entries
+            .iter()
+            .filter(|entry| entry.is_dir)
+            .filter_map(|entry| self.dir_entry(OsStr::new(""), entry))
+            .collect::<Vec<(OsString, String)>>();
+        sort_dirs(&mut dir_entries, self.sort);
+
+        paging::list(self, Pane::Dirs, head, dir_entries, rest);
+    }
+
+    // the directories in `prefix` (the current one when empty, else ending
+    // in /), named by their path from the current directory
+    pub fn subdirs(&self, prefix: &OsStr) -> Vec<(OsString, String)> {
+        let dir = if prefix.is_empty() {
+            OsStr::new("./")
+        } else {
+            prefix
+        };
+        let entries = match read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return vec![],
+        };
+
+        let mut dir_entries = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| Listed::read(&entry))
+            .filter(|entry| entry.is_dir)
+            .filter_map(|entry| self.dir_entry(prefix, &entry))
+            .collect::<Vec<(OsString, String)>>();
+        sort_dirs(&mut dir_entries, self.sort);
+
+        dir_entries
+    }
+
+    // a directory in `prefix` as the Directories pane lists it, none when
+    // it's left out
+    pub fn dir_entry(&self, prefix: &OsStr, entry: &Listed) -> Option<(OsString, String)> {
+        if entry.hidden && !self.show_hidden {
+            return None;
+        }
+
+        let mut name = prefix.to_os_string();
+        name.push(&entry.name);
+        if self.is_git_ignored(&name.to_string_lossy()) {
+            return None;
+        }
+
+        Some((name, entry.label.clone()))
+    }
+
+    // the directory under the cursor, none when the pane is empty, which a
+    // drive root without directories is
+    pub fn selected_dir(&self) -> Option<OsString> {
+        let idx = self.dirs.state.selected()?;
+        self.dirs.items.get(idx).map(|dir| dir.0.clone())
+    }
+
+    // the file under the cursor, none in an empty directory
+    pub fn selected_file(&self) -> Option<OsString> {
+        self.files.selected_item().map(|file| file.0.clone())
+    }
+
+    // name of the entry under the cursor, in whichever pane has it
+    pub fn selected_entry(&self) -> Option<OsString> {
+        let name = match self.files.state.selected() {
+            Some(idx) => &self.files.items.get(idx)?.0,
+            None => &self.dirs.items.get(self.dirs.state.selected()?)?.0,
+        };
+
+        // the components leave out a trailing separator
+        Some(
+            Path::new(name)
+                .components()
+                .as_path()
+                .as_os_str()
+                .to_os_string(),
+        )
+    }
+
+    // the popup shared by everything that has a few lines to say
+    pub fn open_report(&mut self, title: &str, lines: Vec<String>) {
+        self.report_title = title.to_string();
+        self.report.items = lines;
+        self.report.state.select(None);
+        self.show_report = true;
+        self.pending_confirm = None;
+        self.pending_elevate = None;
+        self.properties = None;
+        self.hashes = None;
+        self.report_jobs = false;
+        self.report_output = None;
+    }
+
+    // a passing note in the status bar
+    pub fn notify(&mut self, text: impl Into<String>) {
+        self.messages.push(text.into(), Level::Info);
+    }
+
+    pub fn notify_error(&mut self, text: impl Into<String>) {
+        self.messages.push(text.into(), Level::Error);
+    }
 
-        for dir in dir_entries {
-            self.dirs.items.push(dir);
+    // a failed operation says what went wrong instead of taking the UI down
+    pub fn show_error(&mut self, what: &str, err: impl std::fmt::Display) {
+        self.pending_retry = None;
+        self.notify_error(format!("{}: {}", what, err));
+        self.open_report(
+            "Error",
+            vec![
+                format!("{}: {}", what, err),
+                "Press Enter or Esc to dismiss.".to_string(),
+            ],
+        );
+    }
+
+    // the directory as one list, by choice or because the layout has room
+    // for only one
+    pub fn unified(&self) -> bool {
+        self.unified_listing || self.layout == PaneLayout::Miller
+    }
+
+    // directories that can't be listed are refused, they'd only leave the
+    // panes empty
+    pub fn change_dir(&mut self, path: &Path) -> bool {
+        match read_dir(path).and_then(|_| std::env::set_current_dir(path)) {
+            Ok(_) => {
+                self.cur_dir = get_pwd();
+                true
+            }
+            Err(e) => {
+                self.show_error(&format!("Couldn't open {}", path.display()), e);
+                false
+            }
         }
     }
 
@@ -176,22 +900,176 @@ impl App {
         self.show_bookmark = true;
     }
 
-    pub fn create_file(input: &str) -> bool {
-        if File::create(input).is_ok() {
-            true
+    pub fn create_file(input: &str) -> io::Result<()> {
+        File::create(input).map(|_| ())
+    }
+
+    #[cfg(unix)]
+    pub fn create_symlink(target: &Path, input: &str) -> io::Result<()> {
+        std::os::unix::fs::symlink(target, input)
+    }
+
+    #[cfg(windows)]
+    pub fn create_symlink(target: &Path, input: &str) -> io::Result<()> {
+        if target.is_dir() {
+            std::os::windows::fs::symlink_dir(target, input)
+        } else {
+            std::os::windows::fs::symlink_file(target, input)
+        }
+    }
+
+    pub fn create_dir(input: &str) -> io::Result<()> {
+        fs::create_dir(input)
+    }
+}
+
+// a dot name, or on Windows one Explorer hides for its hidden or system
+// attribute
+pub fn is_hidden(entry: &DirEntry) -> bool {
+    if entry.file_name().to_string_lossy().starts_with('.') {
+        return true;
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+
+        const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+        const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
+        if let Ok(metadata) = entry.metadata() {
+            return metadata.file_attributes() & (FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM)
+                != 0;
+        }
+    }
+
+    false
+}
+
+// pinned files first, in the order they were pinned, then the rest by name
+// with the dotfiles last, or by size or age
+pub fn sort_files(entries: &mut [(OsString, String)], pinned: &[String], sort: SortBy) {
+    let pin_rank = |name: &OsStr| pinned.iter().position(|pin| name == pin.as_str());
+
+    if sort != SortBy::Name {
+        // a stat per entry, only sorting by something else than the name
+        // pays for it
+        return entries.sort_by_cached_key(|entry| {
+            let rank = pin_rank(&entry.0).unwrap_or(usize::MAX);
+            let key = sort.key(fs::metadata(&entry.0).ok());
+            (rank, Reverse(key), entry.0.clone())
+        });
+    }
+
+    entries.sort_by(|a, b| {
+        let a_starts_with_dot = a.0.as_encoded_bytes().starts_with(b".");
+        let b_starts_with_dot = b.0.as_encoded_bytes().starts_with(b".");
+
+        // pinned files go first, in the order they were pinned
+        let pins = (pin_rank(&a.0), pin_rank(&b.0));
+
+        if pins.0.is_some() || pins.1.is_some() {
+            match pins {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                _ => std::cmp::Ordering::Greater,
+            }
+        } else if a_starts_with_dot && !b_starts_with_dot {
+            std::cmp::Ordering::Greater
+        } else if !a_starts_with_dot && b_starts_with_dot {
+            std::cmp::Ordering::Less
         } else {
-            false
+            a.0.cmp(&b.0)
         }
+    });
+}
+
+// by name with the hidden ones last, or newest first
+pub fn sort_dirs(entries: &mut [(OsString, String)], sort: SortBy) {
+    if sort == SortBy::Modified {
+        return entries.sort_by_cached_key(|entry| {
+            let key = sort.key(fs::metadata(&entry.0).ok());
+            (Reverse(key), entry.0.clone())
+        });
     }
 
-    pub fn create_dir(input: &str) -> bool {
-        format!("./{}", input);
-        if fs::create_dir(input).is_ok() {
-            true
+    entries.sort_by(|a, b| {
+        let a_starts_with_dot = a.1.starts_with(".");
+        let b_starts_with_dot = b.1.starts_with(".");
+
+        if a_starts_with_dot && !b_starts_with_dot {
+            std::cmp::Ordering::Greater
+        } else if !a_starts_with_dot && b_starts_with_dot {
+            std::cmp::Ordering::Less
         } else {
-            false
+            a.0.cmp(&b.0)
+        }
+    });
+}
+
+pub fn is_symlink(entry: &DirEntry) -> bool {
+    entry
+        .file_type()
+        .map(|file_type| file_type.is_symlink())
+        .unwrap_or(false)
+}
+
+// what the panes show, "name -> target" for symlinks
+pub fn link_label(entry: &DirEntry, name: &OsStr) -> String {
+    let name = display_name(name);
+    if !is_symlink(entry) {
+        return name;
+    }
+
+    match fs::read_link(entry.path()) {
+        Ok(target) => format!("{} -> {}", name, display_name(target)),
+        Err(_) => name,
+    }
+}
+
+// a name as it can be shown: bytes that aren't UTF-8 and control characters,
+// which would garble the terminal, are written out as \xNN or \u{NNNN}
+pub fn display_name(name: impl AsRef<OsStr>) -> String {
+    let name = name.as_ref();
+    let mut shown = String::new();
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+
+        for chunk in name.as_bytes().utf8_chunks() {
+            for c in chunk.valid().chars() {
+                push_char(&mut shown, c);
+            }
+            for byte in chunk.invalid() {
+                shown.push_str(&format!("\\x{:02x}", byte));
+            }
         }
     }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::ffi::OsStrExt;
+
+        for unit in char::decode_utf16(name.encode_wide()) {
+            match unit {
+                Ok(c) => push_char(&mut shown, c),
+                // half of a surrogate pair, on its own
+                Err(err) => shown.push_str(&format!("\\u{{{:04x}}}", err.unpaired_surrogate())),
+            }
+        }
+    }
+
+    shown
+}
+
+fn push_char(shown: &mut String, c: char) {
+    if !c.is_control() {
+        shown.push(c);
+    } else if c.is_ascii() {
+        shown.push_str(&format!("\\x{:02x}", c as u32));
+    } else {
+        shown.push_str(&format!("\\u{{{:04x}}}", c as u32));
+    }
 }
 
 pub struct InputBox<'a> {
@@ -204,3 +1082,4 @@ impl<'a> Widget for InputBox<'a> {
         buf.set_stringn(area.x, area.y, self.text, area.width as usize, self.style);
     }
 }
+
@@ -0,0 +1,142 @@
+use crate::app::app::{is_hidden, is_symlink, link_label};
+use crate::ui::input::paging::Pane;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::{self, DirEntry};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::time::{Instant, SystemTime};
+
+// directories kept, the one used longest ago dropped first
+const CACHED: usize = 32;
+
+// an entry as the directory has it, before hidden, ignored or filtered ones
+// are left out, so toggling those doesn't read it again
+#[derive(Clone)]
+pub struct Listed {
+    pub name: OsString,
+    // what the panes show, "name -> target" for symlinks
+    pub label: String,
+    // links count as what they point at
+    pub is_file: bool,
+    pub is_dir: bool,
+    pub hidden: bool,
+}
+
+impl Listed {
+    pub fn read(entry: &DirEntry) -> Listed {
+        // the type read along with the entry spares a stat for everything
+        // but links
+        let (is_file, is_dir) = match entry.file_type() {
+            Ok(file_type) if !file_type.is_symlink() => (file_type.is_file(), file_type.is_dir()),
+            _ => match fs::metadata(entry.path()) {
+                Ok(metadata) => (metadata.is_file(), metadata.is_dir()),
+                // broken symlinks have nothing to follow, list them with the files
+                Err(_) => (is_symlink(entry), false),
+            },
+        };
+
+        let name = entry.file_name();
+        Listed {
+            label: link_label(entry, &name),
+            name,
+            is_file,
+            is_dir,
+            hidden: is_hidden(entry),
+        }
+    }
+}
+
+// a directory read past its first page, the rest comes in the background.
+// Both panes get every batch, the one holding the first page gives the
+// whole listing to the cache once the rest is in
+pub struct Rest {
+    pub rx: Receiver<Vec<Listed>>,
+    pub read: Option<Vec<Listed>>,
+    pub modified: Option<SystemTime>,
+}
+
+// a listing `pane` read for its refresh, waiting for the other pane's
+pub struct Shared {
+    pub pane: Pane,
+    pub dir: PathBuf,
+    pub modified: Option<SystemTime>,
+    pub entries: Arc<Vec<Listed>>,
+    pub rest: Option<Rest>,
+}
+
+struct Cached {
+    modified: SystemTime,
+    used: Instant,
+    entries: Arc<Vec<Listed>>,
+}
+
+// directories listed lately, so going back to one doesn't read it again. A
+// listing is good while the directory's mtime is what it was when it was
+// read, the watcher drops the current one on any change it sees
+#[derive(Default)]
+pub struct DirCache {
+    dirs: HashMap<PathBuf, Cached>,
+}
+
+impl DirCache {
+    pub fn get(&mut self, dir: &Path) -> Option<Arc<Vec<Listed>>> {
+        let modified = modified(dir);
+        let cached = self.dirs.get_mut(dir)?;
+
+        if Some(cached.modified) != modified {
+            self.dirs.remove(dir);
+            return None;
+        }
+
+        cached.used = Instant::now();
+        Some(cached.entries.clone())
+    }
+
+    // `modified` is taken before reading, a change while it was read makes
+    // the next look miss. Without one it isn't kept
+    pub fn insert(
+        &mut self,
+        dir: PathBuf,
+        modified: Option<SystemTime>,
+        entries: Vec<Listed>,
+    ) -> Arc<Vec<Listed>> {
+        let entries = Arc::new(entries);
+        let modified = match modified {
+            Some(modified) => modified,
+            None => return entries,
+        };
+
+        if self.dirs.len() >= CACHED && !self.dirs.contains_key(&dir) {
+            let oldest = self
+                .dirs
+                .iter()
+                .min_by_key(|(_, cached)| cached.used)
+                .map(|(dir, _)| dir.clone());
+            if let Some(oldest) = oldest {
+                self.dirs.remove(&oldest);
+            }
+        }
+
+        self.dirs.insert(
+            dir,
+            Cached {
+                modified,
+                used: Instant::now(),
+                entries: entries.clone(),
+            },
+        );
+        entries
+    }
+
+    pub fn invalidate(&mut self, dir: &Path) {
+        self.dirs.remove(dir);
+    }
+}
+
+pub fn modified(dir: &Path) -> Option<SystemTime> {
+    fs::metadata(dir)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
@@ -0,0 +1,58 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// how long each message stays in the status bar, dimmed for the last part
+const SHOWN: Duration = Duration::from_secs(3);
+const FADE: Duration = Duration::from_millis(800);
+// a burst of messages shouldn't keep the bar busy for long
+const MAX_QUEUED: usize = 4;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Level {
+    Info,
+    Error,
+}
+
+pub struct Message {
+    pub text: String,
+    pub level: Level,
+    // when it reached the front of the queue
+    shown: Option<Instant>,
+}
+
+// messages for the status bar, shown one after the other
+#[derive(Default)]
+pub struct Messages {
+    queue: VecDeque<Message>,
+}
+
+impl Messages {
+    pub fn push(&mut self, text: String, level: Level) {
+        if self.queue.len() >= MAX_QUEUED {
+            self.queue.pop_front();
+        }
+
+        self.queue.push_back(Message {
+            text,
+            level,
+            shown: None,
+        });
+    }
+
+    // the message to show now and whether it's fading out
+    pub fn current(&mut self) -> Option<(&Message, bool)> {
+        loop {
+            let front = self.queue.front_mut()?;
+            let elapsed = front.shown.get_or_insert_with(Instant::now).elapsed();
+
+            if elapsed < SHOWN {
+                return self
+                    .queue
+                    .front()
+                    .map(|message| (message, elapsed > SHOWN - FADE));
+            }
+
+            self.queue.pop_front();
+        }
+    }
+}
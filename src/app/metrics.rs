@@ -0,0 +1,78 @@
+use std::time::{Duration, Instant};
+
+// frames averaged over, about a minute when idle
+const WINDOW: u32 = 240;
+
+// internals shown in the debug pane, for reporting slowness precisely
+#[derive(Default)]
+pub struct Metrics {
+    pub frames: u64,
+    pub last_frame: Duration,
+    // moving average, and the slowest of the last WINDOW frames
+    pub average_frame: Duration,
+    pub slowest_frame: Duration,
+    slowest_at: u64,
+    pub last_preview: Duration,
+    pub events: u64,
+    // events already waiting when a frame was done drawing, the UI is
+    // falling behind when this climbs
+    pub queued_events: u64,
+    pub git_hits: u64,
+    pub git_misses: u64,
+    pub watcher_refreshes: u64,
+    pub listing_hits: u64,
+    pub listing_misses: u64,
+    // reading the current directory, or finding it in the cache
+    pub last_listing: Duration,
+}
+
+impl Metrics {
+    pub fn frame(&mut self, started: Instant) {
+        let took = started.elapsed();
+
+        self.frames += 1;
+        self.last_frame = took;
+        self.average_frame = if self.frames == 1 {
+            took
+        } else {
+            (self.average_frame * (WINDOW - 1) + took) / WINDOW
+        };
+
+        if took >= self.slowest_frame || self.frames - self.slowest_at > WINDOW as u64 {
+            self.slowest_frame = took;
+            self.slowest_at = self.frames;
+        }
+    }
+
+    pub fn event(&mut self, queued: bool) {
+        self.events += 1;
+        if queued {
+            self.queued_events += 1;
+        }
+    }
+
+    // share of git lookups answered from the cached status, in percent
+    pub fn git_hit_rate(&self) -> u64 {
+        (self.git_hits * 100)
+            .checked_div(self.git_hits + self.git_misses)
+            .unwrap_or(0)
+    }
+
+    // share of directory listings answered from the cache, in percent
+    pub fn listing_hit_rate(&self) -> u64 {
+        (self.listing_hits * 100)
+            .checked_div(self.listing_hits + self.listing_misses)
+            .unwrap_or(0)
+    }
+}
+
+// threads of the whole process, where the OS says
+pub fn threads() -> Option<usize> {
+    std::fs::read_to_string("/proc/self/status")
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("Threads:"))?
+        .trim()
+        .parse()
+        .ok()
+}
@@ -1 +1,7 @@
 pub mod app;
+pub mod dir_cache;
+pub mod messages;
+pub mod metrics;
+pub mod shell_init;
+pub mod sort;
+pub mod watcher;
@@ -0,0 +1,30 @@
+// wrappers that cd the calling shell to wherever traverse was left, through
+// --choose-dir, like nnn and lf do
+const POSIX: &str = r#"rt() {
+    local tmp dir
+    tmp="$(mktemp)" || return
+    command rt --choose-dir "$tmp" "$@"
+    dir="$(cat "$tmp")"
+    rm -f "$tmp"
+    if [ -n "$dir" ] && [ "$dir" != "$PWD" ]; then
+        cd "$dir" || return
+    fi
+}"#;
+
+const FISH: &str = r#"function rt
+    set -l tmp (mktemp); or return
+    command rt --choose-dir $tmp $argv
+    set -l dir (cat $tmp)
+    rm -f $tmp
+    if test -n "$dir"; and test "$dir" != "$PWD"
+        cd $dir
+    end
+end"#;
+
+pub fn shell_init(shell: &str) -> Option<&'static str> {
+    match shell {
+        "bash" | "zsh" => Some(POSIX),
+        "fish" => Some(FISH),
+        _ => None,
+    }
+}
@@ -0,0 +1,59 @@
+use std::fs::Metadata;
+use std::time::UNIX_EPOCH;
+
+// what the panes are sorted by, pinned files stay first either way
+#[derive(Clone, Copy, PartialEq)]
+pub enum SortBy {
+    Name,
+    // biggest first, directories by name
+    Size,
+    // newest first
+    Modified,
+}
+
+impl SortBy {
+    // `sort=` in the config
+    pub fn from_config(value: &str) -> SortBy {
+        match value {
+            "size" => SortBy::Size,
+            "modified" | "mtime" => SortBy::Modified,
+            _ => SortBy::Name,
+        }
+    }
+
+    pub fn config_name(self) -> &'static str {
+        match self {
+            SortBy::Name => "name",
+            SortBy::Size => "size",
+            SortBy::Modified => "modified",
+        }
+    }
+
+    // the palette cycles through them
+    pub fn next(self) -> SortBy {
+        match self {
+            SortBy::Name => SortBy::Size,
+            SortBy::Size => SortBy::Modified,
+            SortBy::Modified => SortBy::Name,
+        }
+    }
+
+    // what an entry is ranked by, bigger goes first
+    pub fn key(self, metadata: Option<Metadata>) -> u128 {
+        let metadata = match metadata {
+            Some(metadata) => metadata,
+            None => return 0,
+        };
+
+        match self {
+            SortBy::Name => 0,
+            SortBy::Size => metadata.len() as u128,
+            SortBy::Modified => metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|age| age.as_nanos())
+                .unwrap_or(0),
+        }
+    }
+}
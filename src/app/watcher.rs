@@ -0,0 +1,135 @@
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::{Duration, Instant};
+
+// a burst of events (a build, a checkout) is waited out until it goes quiet
+// for QUIET, but never for longer than MAX_DELAY, so the panes refresh a few
+// times per second at most while it lasts
+const QUIET: Duration = Duration::from_millis(100);
+const MAX_DELAY: Duration = Duration::from_millis(300);
+
+// watches the cwd (not its children) for entries being created,
+// removed or renamed by other processes, and the repository's git directory
+// for its index and HEAD being written
+pub struct DirWatcher {
+    watcher: RecommendedWatcher,
+    rx: Receiver<notify::Result<Event>>,
+    pub dir: PathBuf,
+    git_dir: Option<PathBuf>,
+    // first and latest event that hasn't been reported yet
+    pending: Option<(Instant, Instant)>,
+    git_pending: Option<(Instant, Instant)>,
+    // every event received, for the debug pane
+    pub events: u64,
+}
+
+impl DirWatcher {
+    pub fn new(dir: &Path) -> Option<DirWatcher> {
+        let (tx, rx) = channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = tx.send(event);
+        })
+        .ok()?;
+
+        watcher.watch(dir, RecursiveMode::NonRecursive).ok()?;
+
+        Some(DirWatcher {
+            watcher,
+            rx,
+            dir: dir.to_path_buf(),
+            git_dir: None,
+            pending: None,
+            git_pending: None,
+            events: 0,
+        })
+    }
+
+    pub fn watch(&mut self, dir: &Path) {
+        let _ = self.watcher.unwatch(&self.dir);
+        let _ = self.watcher.watch(dir, RecursiveMode::NonRecursive);
+        self.dir = dir.to_path_buf();
+
+        // whatever is queued belongs to the old directory
+        while self.rx.try_recv().is_ok() {}
+        self.pending = None;
+    }
+
+    // the git directory of the repository the cwd is in, None outside of one.
+    // git writes the index and HEAD to a lock file renamed over them, the
+    // directory is watched rather than the files
+    pub fn watch_git(&mut self, git_dir: Option<&Path>) {
+        if self.git_dir.as_deref() == git_dir {
+            return;
+        }
+
+        if let Some(old) = self.git_dir.take() {
+            let _ = self.watcher.unwatch(&old);
+        }
+        if let Some(git_dir) = git_dir {
+            if self
+                .watcher
+                .watch(git_dir, RecursiveMode::NonRecursive)
+                .is_ok()
+            {
+                self.git_dir = Some(git_dir.to_path_buf());
+            }
+        }
+        self.git_pending = None;
+    }
+
+    fn drain(&mut self) {
+        while let Ok(event) = self.rx.try_recv() {
+            self.events += 1;
+
+            let event = match event {
+                Ok(event) if !event.kind.is_access() => event,
+                _ => continue,
+            };
+
+            let pending = match &self.git_dir {
+                Some(git_dir) if event.paths.iter().all(|path| path.starts_with(git_dir)) => {
+                    // objects, logs and the lock files don't change the status
+                    let status = event.paths.iter().any(|path| {
+                        path.parent() == Some(git_dir.as_path())
+                            && path
+                                .file_name()
+                                .is_some_and(|name| name == "index" || name == "HEAD")
+                    });
+                    if !status {
+                        continue;
+                    }
+                    &mut self.git_pending
+                }
+                _ => &mut self.pending,
+            };
+
+            let now = Instant::now();
+            let first = pending.map_or(now, |(first, _)| first);
+            *pending = Some((first, now));
+        }
+    }
+
+    // drains the queue, true once the changes to the directory since the
+    // last refresh have settled (or have kept coming for too long)
+    pub fn changed(&mut self) -> bool {
+        self.drain();
+        settled(&mut self.pending)
+    }
+
+    // the same for the index and HEAD, the git status is read again then
+    pub fn git_changed(&mut self) -> bool {
+        self.drain();
+        settled(&mut self.git_pending)
+    }
+}
+
+fn settled(pending: &mut Option<(Instant, Instant)>) -> bool {
+    match *pending {
+        Some((first, last)) if last.elapsed() >= QUIET || first.elapsed() >= MAX_DELAY => {
+            *pending = None;
+            true
+        }
+        _ => false,
+    }
+}
@@ -1,8 +1,21 @@
 use crate::app::app::App;
+use crate::app::sort::SortBy;
+use crate::configuration::frecency::JumpDb;
+use crate::configuration::theme::load_theme;
+use crate::ui::display::icons::IconSet;
+use crate::ui::display::layout::PaneLayout;
+use crate::ui::input::conflicts::Policy;
+use crate::ui::input::fzf_backend::Backend;
+use crate::ui::input::grep::GrepBackend;
+use crate::ui::input::hooks;
+use crate::ui::input::open_with;
+use crate::ui::input::previewers;
+use crate::ui::input::schedule::configure;
 use dirs::config_dir;
 use std::fs;
 use std::io::BufRead;
 use std::io::Write;
+use std::time::Duration;
 
 pub fn read_config(app: &mut App) {
     let config_path = config_dir().unwrap().join("traverse/config.txt");
@@ -29,6 +42,14 @@ pub fn read_config(app: &mut App) {
 
     let file = fs::File::open(config_path).unwrap();
     let reader = std::io::BufReader::new(file);
+    let mut schedule = vec![];
+    let mut open_with = vec![];
+    let mut hooks = vec![];
+    let mut previewers = vec![];
+
+    // the defaults, so a line taken out of the config stops applying
+    app.si_units = false;
+    app.wrap_lists = true;
 
     for line in reader.lines() {
         let line = line.unwrap();
@@ -44,6 +65,156 @@ pub fn read_config(app: &mut App) {
             }
         }
 
+        if line.starts_with("max_delete_files") {
+            let value = line.split('=').nth(1).unwrap_or("").trim();
+            app.max_delete_files = value.parse().unwrap_or(10_000);
+        }
+
+        if line.starts_with("confirm") {
+            let value = line.split('=').nth(1).unwrap_or("").trim();
+            app.confirm = Policy::from_config(value);
+        }
+
+        if line.starts_with("max_walk_entries") {
+            let value = line.split('=').nth(1).unwrap_or("").trim();
+            app.max_entries = value.parse().unwrap_or(1_000_000);
+        }
+
+        if line.starts_with("size_units") {
+            let value = line.split('=').nth(1).unwrap_or("").trim();
+            app.si_units = value.eq_ignore_ascii_case("si");
+        }
+
+        if line.starts_with("wrap_lists") {
+            let value = line.split('=').nth(1).unwrap_or("").trim();
+            app.wrap_lists = !value.eq_ignore_ascii_case("false");
+        }
+
+        if line.starts_with("unified_listing") {
+            let value = line.split('=').nth(1).unwrap_or("").trim();
+            app.unified_listing = value.eq_ignore_ascii_case("true");
+        }
+
+        if line.starts_with("dir_tree") {
+            let value = line.split('=').nth(1).unwrap_or("").trim();
+            app.dir_tree = value.eq_ignore_ascii_case("true");
+        }
+
+        if line.starts_with("layout") {
+            let value = line.split('=').nth(1).unwrap_or("").trim();
+            app.layout = PaneLayout::from_config(value);
+        }
+
+        if line.starts_with("sort=") {
+            let value = line.split('=').nth(1).unwrap_or("").trim();
+            app.sort = SortBy::from_config(value);
+        }
+
+        if line.starts_with("terminal_title") {
+            let value = line.split('=').nth(1).unwrap_or("").trim();
+            app.terminal_title.title = !value.eq_ignore_ascii_case("false");
+        }
+
+        if line.starts_with("osc7") {
+            let value = line.split('=').nth(1).unwrap_or("").trim();
+            app.terminal_title.osc7 = !value.eq_ignore_ascii_case("false");
+        }
+
+        if line.starts_with("frecency") {
+            let value = line.split('=').nth(1).unwrap_or("").trim();
+            app.jump_db = JumpDb::from_config(value);
+        }
+
+        if line.starts_with("grep_backend") {
+            let value = line.split('=').nth(1).unwrap_or("").trim();
+            app.grep.backend = GrepBackend::from_config(value);
+        }
+
+        if line.starts_with("grep_compressed") {
+            let value = line.split('=').nth(1).unwrap_or("").trim();
+            app.grep.compressed = value.eq_ignore_ascii_case("true");
+        }
+
+        if line.starts_with("grep_max_size") {
+            let value = line.split('=').nth(1).unwrap_or("").trim();
+            app.grep.max_size = value.parse::<u64>().unwrap_or(16) * 1024 * 1024;
+        }
+
+        if line.starts_with("fzf_backend") {
+            let value = line.split('=').nth(1).unwrap_or("").trim();
+            app.fzf_backend.backend = Backend::from_config(value);
+        }
+
+        if line.starts_with("preview_timeout") {
+            let value = line.split('=').nth(1).unwrap_or("").trim();
+            app.previewers.timeout = Duration::from_secs(value.parse().unwrap_or(5));
+        }
+
+        if line.starts_with("preview_delay") {
+            let value = line.split('=').nth(1).unwrap_or("").trim();
+            app.preview_delay = Duration::from_millis(value.parse().unwrap_or(100));
+        }
+
+        if line.starts_with("preview_max_bytes") {
+            let value = line.split('=').nth(1).unwrap_or("").trim();
+            app.text_window.max_bytes = match value.parse().unwrap_or(16 * 1024 * 1024) {
+                0 => u64::MAX,
+                max => max,
+            };
+        }
+
+        if line.starts_with("preview_max_lines") {
+            let value = line.split('=').nth(1).unwrap_or("").trim();
+            app.text_window.max_lines = match value.parse().unwrap_or(100_000) {
+                0 => usize::MAX,
+                max => max,
+            };
+        }
+
+        if line.starts_with("resume") {
+            let value = line.split('=').nth(1).unwrap_or("").trim().to_string();
+            app.resume = value.eq_ignore_ascii_case("true");
+        }
+
+        // schedule=5m refresh, one line per job
+        if let Some(spec) = line.strip_prefix("schedule=") {
+            schedule.push(spec.to_string());
+        }
+
+        // open_with=pdf: zathura {} &, one line per association
+        if let Some(spec) = line.strip_prefix("open_with=") {
+            open_with.push(spec.to_string());
+        }
+
+        // hook=enter_dir: tmux rename-window "$(basename "$TRAVERSE_DIR")"
+        if let Some(spec) = line.strip_prefix("hook=") {
+            hooks.push(spec.to_string());
+        }
+
+        // previewer=pdf: pdftotext -l 3 {} -, one line per command
+        if let Some(spec) = line.strip_prefix("previewer=") {
+            previewers.push(spec.to_string());
+        }
+
+        if line.starts_with("icons") {
+            let mut split = line.split("=");
+            let value = split.nth(1).unwrap_or("").trim().to_string();
+
+            app.icons = IconSet::from_config(&value);
+        }
+
+        // only reload on change, user themes are read from disk
+        if line.starts_with("theme") {
+            let mut split = line.split("=");
+            let value = split.nth(1).unwrap_or("").trim().to_string();
+
+            if value != app.theme.name {
+                if let Some(theme) = load_theme(&value) {
+                    app.theme = theme;
+                }
+            }
+        }
+
         if line.contains("excluded_directories") {
             let mut split = line.split("=");
             let value = split.nth(1).unwrap().trim().to_string();
@@ -59,4 +230,28 @@ pub fn read_config(app: &mut App) {
             }
         }
     }
+
+    configure(app, schedule);
+    open_with::configure(app, open_with);
+    hooks::configure(app, hooks);
+    previewers::configure(app, previewers);
+}
+
+// remember a setting changed at runtime, replacing any earlier value
+pub fn write_config(key: &str, value: &str) {
+    let config_path = match config_dir() {
+        Some(dir) => dir.join("traverse/config.txt"),
+        None => return,
+    };
+
+    let config = fs::read_to_string(&config_path).unwrap_or_default();
+
+    let mut lines = config
+        .lines()
+        .filter(|line| !line.starts_with(key))
+        .map(|line| line.to_string())
+        .collect::<Vec<String>>();
+    lines.push(format!("{}={}", key, value));
+
+    let _ = fs::write(config_path, lines.join("\n"));
 }
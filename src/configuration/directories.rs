@@ -0,0 +1,59 @@
+use dirs::config_dir;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// settings that only apply to one directory live in
+// <config>/traverse/directories.txt, one `dir<TAB>key<TAB>value` line each,
+// keys with several values take one line per value
+fn settings_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("traverse/directories.txt"))
+}
+
+pub fn dir_values(dir: &Path, key: &str) -> Vec<String> {
+    let settings = match settings_path().map(fs::read_to_string) {
+        Some(Ok(settings)) => settings,
+        _ => return vec![],
+    };
+    let dir = dir.to_string_lossy();
+
+    settings
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            match (fields.next(), fields.next(), fields.next()) {
+                (Some(line_dir), Some(line_key), Some(value))
+                    if line_dir == dir && line_key == key =>
+                {
+                    Some(value.to_string())
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+// replaces every value of `key` for `dir`
+pub fn set_dir_values(dir: &Path, key: &str, values: &[String]) {
+    let path = match settings_path() {
+        Some(path) => path,
+        None => return,
+    };
+    let dir = dir.to_string_lossy();
+    let prefix = format!("{}\t{}\t", dir, key);
+
+    let settings = fs::read_to_string(&path).unwrap_or_default();
+    let mut lines = settings
+        .lines()
+        .filter(|line| !line.starts_with(&prefix))
+        .map(|line| line.to_string())
+        .collect::<Vec<String>>();
+
+    for value in values {
+        lines.push(format!("{}{}", prefix, value));
+    }
+
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let _ = fs::write(path, lines.join("\n"));
+}
@@ -0,0 +1,197 @@
+use dirs::config_dir;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// once the ranks add up to more than this, every rank shrinks so old
+// favourites make way for new ones, like zoxide's aging
+const MAX_RANK: f64 = 1000.0;
+
+pub struct Visit {
+    pub path: String,
+    pub rank: f64,
+    // seconds since the epoch
+    pub last: u64,
+}
+
+impl Visit {
+    // frequency weighted by how recently it was visited
+    pub fn score(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last);
+
+        let weight = if age < 3600 {
+            4.0
+        } else if age < 86400 {
+            2.0
+        } else if age < 604800 {
+            0.5
+        } else {
+            0.25
+        };
+
+        self.rank * weight
+    }
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+// one "rank<TAB>last visit<TAB>path" line per directory in
+// <config>/traverse/frecency.txt
+fn store_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("traverse/frecency.txt"))
+}
+
+pub fn load() -> Vec<Visit> {
+    let store = match store_path().map(fs::read_to_string) {
+        Some(Ok(store)) => store,
+        _ => return vec![],
+    };
+
+    store
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let rank = fields.next()?.parse().ok()?;
+            let last = fields.next()?.parse().ok()?;
+            let path = fields.next()?.to_string();
+
+            Some(Visit { path, rank, last })
+        })
+        .collect()
+}
+
+pub fn save(visits: &[Visit]) {
+    let path = match store_path() {
+        Some(path) => path,
+        None => return,
+    };
+
+    let lines = visits
+        .iter()
+        .map(|visit| format!("{}\t{}\t{}", visit.rank, visit.last, visit.path))
+        .collect::<Vec<String>>();
+
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let _ = fs::write(path, lines.join("\n"));
+}
+
+pub fn visit(visits: &mut Vec<Visit>, path: &str) {
+    let now = now();
+
+    match visits.iter_mut().find(|visit| visit.path == path) {
+        Some(visit) => {
+            visit.rank += 1.0;
+            visit.last = now;
+        }
+        None => visits.push(Visit {
+            path: path.to_string(),
+            rank: 1.0,
+            last: now,
+        }),
+    }
+
+    let total = visits.iter().map(|visit| visit.rank).sum::<f64>();
+    if total > MAX_RANK {
+        let factor = 0.9 * MAX_RANK / total;
+
+        for visit in visits.iter_mut() {
+            visit.rank *= factor;
+        }
+        visits.retain(|visit| visit.rank >= 1.0);
+    }
+}
+
+pub fn forget(visits: &mut Vec<Visit>, path: &str) {
+    visits.retain(|visit| visit.path != path);
+}
+
+// a shell's jump database to share visits with instead, frecency=zoxide or
+// frecency=autojump in the config
+#[derive(Clone, Copy, PartialEq)]
+pub enum JumpDb {
+    Zoxide,
+    Autojump,
+}
+
+impl JumpDb {
+    pub fn from_config(value: &str) -> Option<JumpDb> {
+        match value.to_ascii_lowercase().as_str() {
+            "zoxide" => Some(JumpDb::Zoxide),
+            "autojump" => Some(JumpDb::Autojump),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            JumpDb::Zoxide => "zoxide",
+            JumpDb::Autojump => "autojump",
+        }
+    }
+
+    // the visit is told in the background, the UI doesn't wait on it
+    pub fn add(self, path: &Path) {
+        let flag = match self {
+            JumpDb::Zoxide => "add",
+            JumpDb::Autojump => "--add",
+        };
+
+        let mut command = Command::new(self.name());
+        command
+            .arg(flag)
+            .arg(path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+
+        if let Ok(mut child) = command.spawn() {
+            thread::spawn(move || child.wait());
+        }
+    }
+
+    // its directories, the highest ranked first
+    pub fn query(self) -> io::Result<Vec<String>> {
+        let mut command = Command::new(self.name());
+        match self {
+            JumpDb::Zoxide => command.args(["query", "--list"]),
+            JumpDb::Autojump => command.arg("--stat"),
+        };
+
+        let output = command
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "{} exited with {}",
+                self.name(),
+                output.status
+            )));
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        Ok(match self {
+            JumpDb::Zoxide => stdout.lines().map(str::to_string).collect(),
+            // "weight:<TAB>path", lowest first, then a summary after a rule
+            JumpDb::Autojump => stdout
+                .lines()
+                .filter_map(|line| {
+                    let (weight, path) = line.split_once(":\t")?;
+                    weight.trim().parse::<f64>().ok()?;
+                    Some(path.to_string())
+                })
+                .rev()
+                .collect(),
+        })
+    }
+}
@@ -1 +1,6 @@
 pub mod configuration;
+pub mod directories;
+pub mod frecency;
+pub mod session;
+pub mod templates;
+pub mod theme;
@@ -0,0 +1,226 @@
+use crate::app::app::App;
+use crate::ui::display::pane::get_pwd;
+use crate::ui::input::popups::{Geometry, Popup};
+use dirs::config_dir;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// where traverse was left, written on quit to <config>/traverse/session.txt
+// as key=value lines, `buffer` once per entry of the move/copy buffer and
+// `popup` once per popup that was moved or resized
+fn session_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("traverse/session.txt"))
+}
+
+// a name or path as a value: bytes that aren't UTF-8, line breaks and
+// backslashes are written as \xNN, so names that aren't text come back as
+// they were
+#[cfg(unix)]
+fn encode(name: &OsStr) -> String {
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut encoded = String::new();
+    for chunk in name.as_bytes().utf8_chunks() {
+        for c in chunk.valid().chars() {
+            match c {
+                '\\' | '\n' | '\r' => encoded.push_str(&format!("\\x{:02x}", c as u8)),
+                c => encoded.push(c),
+            }
+        }
+        for byte in chunk.invalid() {
+            encoded.push_str(&format!("\\x{:02x}", byte));
+        }
+    }
+
+    encoded
+}
+
+#[cfg(unix)]
+fn decode(value: &str) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+
+    let bytes = value.as_bytes();
+    let mut decoded = vec![];
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        let escaped = value
+            .get(idx..idx + 4)
+            .and_then(|escape| escape.strip_prefix("\\x"))
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                idx += 4;
+            }
+            None => {
+                decoded.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+
+    OsString::from_vec(decoded)
+}
+
+// Windows names are UTF-16, and its paths are full of backslashes
+#[cfg(not(unix))]
+fn encode(name: &OsStr) -> String {
+    name.to_string_lossy().to_string()
+}
+
+#[cfg(not(unix))]
+fn decode(value: &str) -> OsString {
+    OsString::from(value)
+}
+
+pub fn save(app: &App) {
+    // a picker is a detour, not where the user was working
+    if app.pick {
+        return;
+    }
+
+    let path = match session_path() {
+        Some(path) => path,
+        None => return,
+    };
+
+    let cwd = match std::env::current_dir() {
+        Ok(cwd) => cwd,
+        Err(_) => return,
+    };
+
+    let mut lines = vec![
+        format!("cwd={}", encode(cwd.as_os_str())),
+        format!("preview_width={}", app.preview_width),
+        format!("files_height={}", app.files_height),
+    ];
+
+    if app.dirs.state.selected().is_some() {
+        lines.push("pane=dirs".to_string());
+    }
+    if let Some(entry) = app.selected_entry() {
+        lines.push(format!("selected={}", encode(&entry)));
+    }
+    if let Some(bookmark) = app
+        .bookmarked_dirs
+        .state
+        .selected()
+        .and_then(|idx| app.bookmarked_dirs.items.get(idx))
+    {
+        lines.push(format!("bookmark={}", bookmark));
+    }
+    lines.extend(
+        app.selected_files
+            .iter()
+            .map(|file| format!("buffer={}", encode(file.as_os_str()))),
+    );
+
+    // popup=name,x,y,width,height, as far as it was moved from its default
+    let mut popups = app
+        .popup_geometry
+        .iter()
+        .map(|(popup, geometry)| {
+            format!(
+                "popup={},{},{},{},{}",
+                popup.name(),
+                geometry.x,
+                geometry.y,
+                geometry.width,
+                geometry.height
+            )
+        })
+        .collect::<Vec<String>>();
+    popups.sort();
+    lines.extend(popups);
+
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let _ = fs::write(path, lines.join("\n"));
+}
+
+// back to the saved directory with the same entry, buffer and layout,
+// anything that no longer exists is skipped
+pub fn restore(app: &mut App) {
+    let session = match session_path().map(fs::read_to_string) {
+        Some(Ok(session)) => session,
+        _ => return,
+    };
+
+    let mut selected = None;
+    let mut dirs_pane = false;
+
+    for line in session.lines() {
+        let (key, value) = match line.split_once('=') {
+            Some(pair) => pair,
+            None => continue,
+        };
+
+        match key {
+            "cwd" => {
+                let _ = std::env::set_current_dir(decode(value));
+            }
+            "preview_width" => app.preview_width = value.parse().unwrap_or(50).clamp(20, 80),
+            "files_height" => app.files_height = value.parse().unwrap_or(45).clamp(10, 80),
+            "pane" => dirs_pane = value == "dirs",
+            "selected" => selected = Some(decode(value)),
+            "bookmark" => {
+                let idx = app.bookmarked_dirs.items.iter().position(|b| b == value);
+                app.bookmarked_dirs.state.select(idx);
+            }
+            "buffer" => {
+                let file = PathBuf::from(decode(value));
+                if file.exists() && !app.selected_files.contains(&file) {
+                    app.selected_files.push(file);
+                }
+            }
+            "popup" => {
+                if let Some((popup, geometry)) = parse_popup(value) {
+                    app.popup_geometry.insert(popup, geometry);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    app.cur_dir = get_pwd();
+    app.update_files();
+    app.update_dirs();
+
+    // the pane the cursor was in, on the same entry when it's still there
+    if dirs_pane {
+        let idx = selected
+            .and_then(|name| {
+                app.dirs
+                    .items
+                    .iter()
+                    .position(|entry| Path::new(&entry.0).components().as_path() == name)
+            })
+            .unwrap_or(0);
+        app.files.state.select(None);
+        app.dirs.state.select(Some(idx));
+    } else if let Some(idx) =
+        selected.and_then(|name| app.files.items.iter().position(|entry| entry.0 == *name))
+    {
+        app.files.state.select(Some(idx));
+        app.dirs.state.select(None);
+    }
+}
+
+// name,x,y,width,height, drawing keeps it on whatever screen it's restored to
+fn parse_popup(value: &str) -> Option<(Popup, Geometry)> {
+    let mut fields = value.split(',');
+    let popup = Popup::from_name(fields.next()?)?;
+    let mut next = || fields.next()?.trim().parse::<i32>().ok();
+
+    let geometry = Geometry {
+        x: next()?,
+        y: next()?,
+        width: next()?,
+        height: next()?,
+    };
+    Some((popup, geometry))
+}
@@ -0,0 +1,50 @@
+use dirs::config_dir;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+fn templates_dir() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("traverse/templates"))
+}
+
+// <config>/traverse/templates/<name> for that exact name (main.rs,
+// Makefile), otherwise the first template with the same extension
+pub fn template_for(name: &str) -> Option<PathBuf> {
+    let name = Path::new(name).file_name()?;
+    let dir = templates_dir()?;
+
+    let exact = dir.join(name);
+    if exact.is_file() {
+        return Some(exact);
+    }
+
+    let extension = Path::new(name).extension()?;
+    let mut matching = fs::read_dir(&dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension() == Some(extension))
+        .collect::<Vec<PathBuf>>();
+    matching.sort();
+
+    matching.into_iter().next()
+}
+
+// the new file gets the template's contents and permissions, {{name}} is
+// replaced by the new file's name without its extension
+pub fn fill(path: &Path, template: &Path) -> io::Result<()> {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    match fs::read_to_string(template) {
+        Ok(text) => fs::write(path, text.replace("{{name}}", &stem))?,
+        // not text, copied as it is
+        Err(_) => {
+            fs::copy(template, path)?;
+        }
+    }
+
+    fs::set_permissions(path, fs::metadata(template)?.permissions())
+}
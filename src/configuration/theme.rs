@@ -0,0 +1,305 @@
+use dirs::config_dir;
+use ratatui::style::Color;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+pub const BUILT_IN: [&str; 4] = ["dark", "light", "solarized", "gruvbox"];
+
+#[derive(Clone)]
+pub struct Theme {
+    pub name: String,
+    // selected entries and other things that should catch the eye
+    pub highlight: Color,
+    // popups, the details panes and headings
+    pub border: Color,
+    // the pane that has the selection, input boxes
+    pub focus: Color,
+    pub unfocused: Color,
+    pub link: Color,
+    pub error: Color,
+    pub warning: Color,
+    pub success: Color,
+    pub dim: Color,
+    pub muted: Color,
+    pub keyword: Color,
+    pub number: Color,
+    // label text on top of colored blocks, like the treemap
+    pub inverse: Color,
+    pub palette: Vec<Color>,
+    // file type colors in the listings, when LS_COLORS isn't set
+    pub directory: Color,
+    pub executable: Color,
+    pub archive: Color,
+    pub image: Color,
+    pub extensions: HashMap<String, Color>,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            name: "dark".to_string(),
+            highlight: Color::LightGreen,
+            border: Color::LightYellow,
+            focus: Color::LightBlue,
+            unfocused: Color::White,
+            link: Color::LightCyan,
+            error: Color::LightRed,
+            warning: Color::LightYellow,
+            success: Color::LightGreen,
+            dim: Color::DarkGray,
+            muted: Color::Gray,
+            keyword: Color::LightMagenta,
+            number: Color::LightCyan,
+            inverse: Color::Black,
+            palette: vec![
+                Color::Blue,
+                Color::Magenta,
+                Color::Cyan,
+                Color::Yellow,
+                Color::Red,
+                Color::Gray,
+            ],
+            directory: Color::LightBlue,
+            executable: Color::LightGreen,
+            archive: Color::LightRed,
+            image: Color::LightMagenta,
+            extensions: HashMap::new(),
+        }
+    }
+}
+
+impl Theme {
+    pub fn built_in(name: &str) -> Option<Theme> {
+        let theme = match name {
+            "dark" => Theme::default(),
+            "light" => Theme {
+                name: "light".to_string(),
+                highlight: Color::Blue,
+                border: Color::Magenta,
+                focus: Color::Blue,
+                unfocused: Color::Black,
+                link: Color::Cyan,
+                error: Color::Red,
+                warning: Color::Yellow,
+                success: Color::Green,
+                dim: Color::Gray,
+                muted: Color::DarkGray,
+                keyword: Color::Magenta,
+                number: Color::Cyan,
+                inverse: Color::White,
+                palette: vec![
+                    Color::LightBlue,
+                    Color::LightMagenta,
+                    Color::LightCyan,
+                    Color::LightYellow,
+                    Color::LightRed,
+                    Color::Gray,
+                ],
+                directory: Color::Blue,
+                executable: Color::Green,
+                archive: Color::Red,
+                image: Color::Magenta,
+                extensions: HashMap::new(),
+            },
+            "solarized" => Theme {
+                name: "solarized".to_string(),
+                highlight: Color::Rgb(133, 153, 0),
+                border: Color::Rgb(181, 137, 0),
+                focus: Color::Rgb(38, 139, 210),
+                unfocused: Color::Rgb(147, 161, 161),
+                link: Color::Rgb(42, 161, 152),
+                error: Color::Rgb(220, 50, 47),
+                warning: Color::Rgb(203, 75, 22),
+                success: Color::Rgb(133, 153, 0),
+                dim: Color::Rgb(88, 110, 117),
+                muted: Color::Rgb(131, 148, 150),
+                keyword: Color::Rgb(108, 113, 196),
+                number: Color::Rgb(211, 54, 130),
+                inverse: Color::Rgb(0, 43, 54),
+                palette: vec![
+                    Color::Rgb(38, 139, 210),
+                    Color::Rgb(108, 113, 196),
+                    Color::Rgb(42, 161, 152),
+                    Color::Rgb(181, 137, 0),
+                    Color::Rgb(211, 54, 130),
+                    Color::Rgb(147, 161, 161),
+                ],
+                directory: Color::Rgb(38, 139, 210),
+                executable: Color::Rgb(133, 153, 0),
+                archive: Color::Rgb(220, 50, 47),
+                image: Color::Rgb(211, 54, 130),
+                extensions: HashMap::new(),
+            },
+            "gruvbox" => Theme {
+                name: "gruvbox".to_string(),
+                highlight: Color::Rgb(184, 187, 38),
+                border: Color::Rgb(250, 189, 47),
+                focus: Color::Rgb(131, 165, 152),
+                unfocused: Color::Rgb(235, 219, 178),
+                link: Color::Rgb(142, 192, 124),
+                error: Color::Rgb(251, 73, 52),
+                warning: Color::Rgb(254, 128, 25),
+                success: Color::Rgb(184, 187, 38),
+                dim: Color::Rgb(146, 131, 116),
+                muted: Color::Rgb(189, 174, 147),
+                keyword: Color::Rgb(211, 134, 155),
+                number: Color::Rgb(211, 134, 155),
+                inverse: Color::Rgb(40, 40, 40),
+                palette: vec![
+                    Color::Rgb(69, 133, 136),
+                    Color::Rgb(177, 98, 134),
+                    Color::Rgb(104, 157, 106),
+                    Color::Rgb(215, 153, 33),
+                    Color::Rgb(204, 36, 29),
+                    Color::Rgb(168, 153, 132),
+                ],
+                directory: Color::Rgb(131, 165, 152),
+                executable: Color::Rgb(184, 187, 38),
+                archive: Color::Rgb(251, 73, 52),
+                image: Color::Rgb(211, 134, 155),
+                extensions: HashMap::new(),
+            },
+            _ => return None,
+        };
+
+        Some(theme)
+    }
+
+    // overrides one color from a `key=color` line of a user theme
+    fn set(&mut self, key: &str, value: &str) {
+        if let Some(extension) = key.strip_prefix("*.") {
+            if let Some(color) = parse_color(value) {
+                self.extensions.insert(extension.to_lowercase(), color);
+            }
+            return;
+        }
+
+        if key == "palette" {
+            let palette = value
+                .split(',')
+                .filter_map(parse_color)
+                .collect::<Vec<Color>>();
+
+            if !palette.is_empty() {
+                self.palette = palette;
+            }
+            return;
+        }
+
+        let color = match parse_color(value) {
+            Some(color) => color,
+            None => return,
+        };
+
+        match key {
+            "highlight" => self.highlight = color,
+            "border" => self.border = color,
+            "focus" => self.focus = color,
+            "unfocused" => self.unfocused = color,
+            "link" => self.link = color,
+            "error" => self.error = color,
+            "warning" => self.warning = color,
+            "success" => self.success = color,
+            "dim" => self.dim = color,
+            "muted" => self.muted = color,
+            "keyword" => self.keyword = color,
+            "number" => self.number = color,
+            "inverse" => self.inverse = color,
+            "directory" => self.directory = color,
+            "executable" => self.executable = color,
+            "archive" => self.archive = color,
+            "image" => self.image = color,
+            _ => {}
+        }
+    }
+}
+
+// color names as ratatui spells them (any case, with or without
+// underscores), or #rrggbb
+pub fn parse_color(value: &str) -> Option<Color> {
+    let value = value.trim().to_lowercase().replace(['_', '-', ' '], "");
+
+    if let Some(hex) = value.strip_prefix('#') {
+        // slicing a non-ASCII one could split a character
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        return Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+    }
+
+    let color = match value.as_str() {
+        "reset" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "darkgray" | "darkgrey" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return None,
+    };
+
+    Some(color)
+}
+
+fn themes_dir() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("traverse/themes"))
+}
+
+// user themes live in <config>/traverse/themes/<name>.txt, they start from
+// `base=<built-in>` (dark when missing) and override colors with key=color
+fn user_theme(name: &str) -> Option<Theme> {
+    let contents = fs::read_to_string(themes_dir()?.join(format!("{}.txt", name))).ok()?;
+
+    let mut theme = contents
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .find(|(key, _)| key.trim() == "base")
+        .and_then(|(_, base)| Theme::built_in(base.trim()))
+        .unwrap_or_default();
+    theme.name = name.to_string();
+
+    for (key, value) in contents.lines().filter_map(|line| line.split_once('=')) {
+        theme.set(key.trim(), value);
+    }
+
+    Some(theme)
+}
+
+pub fn load_theme(name: &str) -> Option<Theme> {
+    Theme::built_in(name).or_else(|| user_theme(name))
+}
+
+pub fn theme_names() -> Vec<String> {
+    let mut names = BUILT_IN
+        .iter()
+        .map(|name| name.to_string())
+        .collect::<Vec<String>>();
+
+    if let Some(Ok(entries)) = themes_dir().map(fs::read_dir) {
+        let mut user = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+            .filter_map(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+            .filter(|name| !BUILT_IN.contains(&name.as_str()))
+            .collect::<Vec<String>>();
+
+        user.sort();
+        names.extend(user);
+    }
+
+    names
+}
@@ -0,0 +1,241 @@
+use crate::configuration::theme::Theme;
+use git2::{Repository, Status, StatusOptions};
+use ratatui::style::Color;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// how far back to look for the last commit touching a directory
+const MAX_COMMITS: usize = 2000;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum GitStatus {
+    Modified,
+    Staged,
+    Untracked,
+    Ignored,
+}
+
+impl GitStatus {
+    pub fn marker(&self) -> &'static str {
+        match self {
+            GitStatus::Modified => "M",
+            GitStatus::Staged => "A",
+            GitStatus::Untracked => "?",
+            GitStatus::Ignored => "!",
+        }
+    }
+
+    pub fn color(&self, theme: &Theme) -> Color {
+        match self {
+            GitStatus::Modified => theme.warning,
+            GitStatus::Staged => theme.success,
+            GitStatus::Untracked => theme.error,
+            GitStatus::Ignored => theme.dim,
+        }
+    }
+
+    // a directory shows the most pressing status of anything beneath it
+    fn priority(&self) -> u8 {
+        match self {
+            GitStatus::Modified => 3,
+            GitStatus::Staged => 2,
+            GitStatus::Untracked => 1,
+            GitStatus::Ignored => 0,
+        }
+    }
+
+    fn from_status(status: Status) -> Option<GitStatus> {
+        if status.intersects(
+            Status::WT_MODIFIED | Status::WT_DELETED | Status::WT_RENAMED | Status::WT_TYPECHANGE,
+        ) {
+            Some(GitStatus::Modified)
+        } else if status.intersects(
+            Status::INDEX_NEW
+                | Status::INDEX_MODIFIED
+                | Status::INDEX_DELETED
+                | Status::INDEX_RENAMED
+                | Status::INDEX_TYPECHANGE,
+        ) {
+            Some(GitStatus::Staged)
+        } else if status.contains(Status::WT_NEW) {
+            Some(GitStatus::Untracked)
+        } else if status.contains(Status::IGNORED) {
+            Some(GitStatus::Ignored)
+        } else {
+            None
+        }
+    }
+}
+
+// how many changed files sit beneath a directory
+#[derive(Clone, Copy, Default)]
+pub struct DirSummary {
+    pub modified: usize,
+    pub staged: usize,
+    pub untracked: usize,
+}
+
+pub struct GitInfo {
+    pub branch: Option<String>,
+    // keyed by the entry name within the directory that was read
+    pub statuses: HashMap<String, GitStatus>,
+    pub summaries: HashMap<String, DirSummary>,
+    workdir: PathBuf,
+    // .git, or the worktree's own directory in it, where the index and HEAD are
+    pub git_dir: PathBuf,
+    // the directory that was read, relative to the work tree
+    prefix: PathBuf,
+    // looked up on demand, the whole thing is thrown away on the next refresh
+    last_commits: HashMap<String, Option<String>>,
+}
+
+impl GitInfo {
+    pub fn status(&self, name: &str) -> Option<GitStatus> {
+        self.statuses.get(name).copied()
+    }
+
+    pub fn is_ignored(&self, name: &str) -> bool {
+        self.status(name) == Some(GitStatus::Ignored)
+    }
+
+    pub fn summary(&self, name: &str) -> DirSummary {
+        self.summaries.get(name).copied().unwrap_or_default()
+    }
+
+    pub fn last_commit(&mut self, name: &str) -> Option<String> {
+        if let Some(commit) = self.last_commits.get(name) {
+            return commit.clone();
+        }
+
+        let commit = Repository::open(&self.workdir)
+            .ok()
+            .and_then(|repo| last_commit(&repo, &self.prefix.join(name)));
+
+        self.last_commits.insert(name.to_string(), commit.clone());
+        commit
+    }
+}
+
+pub fn ago(seconds: i64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs() as i64)
+        .unwrap_or(seconds);
+
+    match (now - seconds).max(0) {
+        secs if secs < 60 => "just now".to_string(),
+        secs if secs < 3600 => format!("{} minutes ago", secs / 60),
+        secs if secs < 86400 => format!("{} hours ago", secs / 3600),
+        secs if secs < 86400 * 60 => format!("{} days ago", secs / 86400),
+        secs if secs < 86400 * 730 => format!("{} months ago", secs / (86400 * 30)),
+        secs => format!("{} years ago", secs / (86400 * 365)),
+    }
+}
+
+// the newest commit whose tree differs at `path` from all of its parents,
+// comparing tree entry ids so nothing has to be diffed
+fn last_commit(repo: &Repository, path: &Path) -> Option<String> {
+    let mut walk = repo.revwalk().ok()?;
+    walk.push_head().ok()?;
+
+    for oid in walk.take(MAX_COMMITS) {
+        let commit = repo.find_commit(oid.ok()?).ok()?;
+        let entry_id = |commit: &git2::Commit| {
+            commit
+                .tree()
+                .ok()
+                .and_then(|tree| tree.get_path(path).ok())
+                .map(|entry| entry.id())
+        };
+
+        let id = entry_id(&commit);
+        let touched = if commit.parent_count() == 0 {
+            id.is_some()
+        } else {
+            commit.parents().all(|parent| entry_id(&parent) != id)
+        };
+
+        if touched {
+            return Some(format!(
+                "{} {} ({}, {})",
+                &commit.id().to_string()[..7],
+                commit.summary().unwrap_or_default(),
+                ago(commit.time().seconds()),
+                commit.author().name().unwrap_or_default()
+            ));
+        }
+    }
+
+    None
+}
+
+pub fn read_git_info(dir: &Path) -> Option<GitInfo> {
+    let repo = Repository::discover(dir).ok()?;
+    let workdir = repo.workdir()?.canonicalize().ok()?;
+    let git_dir = repo.path().canonicalize().ok()?;
+    let dir = dir.canonicalize().ok()?;
+    let prefix = dir.strip_prefix(&workdir).ok()?.to_path_buf();
+
+    let branch = match repo.head() {
+        Ok(head) => head.shorthand().map(|name| name.to_string()),
+        // a fresh repository has no commits to point HEAD at yet
+        Err(_) => None,
+    };
+
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(true)
+        .include_ignored(true)
+        .recurse_untracked_dirs(false)
+        .recurse_ignored_dirs(false);
+
+    let mut statuses = HashMap::new();
+    let mut summaries: HashMap<String, DirSummary> = HashMap::new();
+
+    if let Ok(entries) = repo.statuses(Some(&mut opts)) {
+        for entry in entries.iter() {
+            let status = match GitStatus::from_status(entry.status()) {
+                Some(status) => status,
+                None => continue,
+            };
+
+            let path = match entry.path() {
+                Some(path) => Path::new(path).to_path_buf(),
+                None => continue,
+            };
+
+            let relative = match path.strip_prefix(&prefix) {
+                Ok(relative) => relative.to_path_buf(),
+                Err(_) => continue,
+            };
+
+            let name = match relative.components().next() {
+                Some(name) => name.as_os_str().to_string_lossy().to_string(),
+                None => continue,
+            };
+
+            let summary = summaries.entry(name.clone()).or_default();
+            match status {
+                GitStatus::Modified => summary.modified += 1,
+                GitStatus::Staged => summary.staged += 1,
+                GitStatus::Untracked => summary.untracked += 1,
+                GitStatus::Ignored => {}
+            }
+
+            let current: Option<&GitStatus> = statuses.get(&name);
+            if current.is_none_or(|current| status.priority() > current.priority()) {
+                statuses.insert(name, status);
+            }
+        }
+    }
+
+    Some(GitInfo {
+        branch,
+        statuses,
+        summaries,
+        workdir,
+        git_dir,
+        prefix,
+        last_commits: HashMap::new(),
+    })
+}
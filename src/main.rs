@@ -1,9 +1,82 @@
 mod app;
 mod ui;
 mod configuration;
+mod git;
+mod vfs;
 
-use ui::display::render::init;
+use app::shell_init::shell_init;
+use clap::Parser;
+use std::path::PathBuf;
+use ui::display::inline;
+use ui::display::render::{init, Options};
+
+/// A terminal file manager
+#[derive(Parser)]
+#[command(name = "rt", version)]
+struct Cli {
+    /// Directory to start in, or a file to start next to with it selected
+    path: Option<PathBuf>,
+
+    /// Choose a path and print it to stdout: Enter on a file picks the file,
+    /// ESC or q picks the current directory, CTRL + c picks nothing
+    #[arg(long, conflicts_with = "choose_file")]
+    pick: bool,
+
+    /// Choose a file and print it to stdout: Enter on a file picks it,
+    /// quitting picks nothing
+    #[arg(long)]
+    choose_file: bool,
+
+    /// Draw in LINES lines under the prompt instead of the whole screen,
+    /// keeping the scrollback
+    #[arg(
+        long,
+        value_name = "LINES",
+        num_args = 0..=1,
+        default_missing_value = "20",
+        value_parser = clap::value_parser!(u16).range(inline::MIN_HEIGHT as i64..)
+    )]
+    inline: Option<u16>,
+
+    /// Start where the last session was left: directory, selection,
+    /// move/copy buffer and pane sizes (also `resume=true` in the config)
+    #[arg(long, conflicts_with = "path")]
+    resume: bool,
+
+    /// Read paths from stdin, one per line, and start with them marked in a
+    /// listing of their own (V); quitting prints the marked ones to stdout
+    #[arg(long, conflicts_with_all = ["pick", "choose_file", "inline"])]
+    from_stdin: bool,
+
+    /// Write the last directory to FILE on quit
+    #[arg(long, value_name = "FILE")]
+    choose_dir: Option<PathBuf>,
+
+    /// Print a shell function that cds to the last directory on quit,
+    /// e.g. eval "$(rt --init bash)"
+    #[arg(long, value_name = "SHELL", value_parser = ["bash", "zsh", "fish"])]
+    init: Option<String>,
+}
 
 fn main() {
-    init().unwrap();
+    let cli = Cli::parse();
+
+    if let Some(shell) = cli.init {
+        if let Some(script) = shell_init(&shell) {
+            println!("{}", script);
+        }
+        return;
+    }
+
+    let options = Options {
+        start: cli.path,
+        pick: cli.pick || cli.choose_file,
+        choose_file: cli.choose_file,
+        inline: cli.inline,
+        choose_dir: cli.choose_dir,
+        resume: cli.resume,
+        from_stdin: cli.from_stdin,
+    };
+
+    init(options).unwrap();
 }
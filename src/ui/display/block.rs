@@ -8,6 +8,21 @@ pub fn block_binds(app: &mut App) -> bool {
         || app.show_popup
         || app.show_bookmark
         || app.show_ops_menu
+        || app.show_jumps
+        || app.show_mounts
+        || app.show_open_with
+        || app.show_treemap
+        || app.show_kinds
+        || app.show_du
+        || app.show_diff
+        || app.show_compress
+        || app.show_export
+        || app.show_piped
+        || app.show_report
+        || app.show_themes
+        || app.show_palette
+        || app.show_frecent
+        || app.show_grep
     {
         return true;
     }
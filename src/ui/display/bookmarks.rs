@@ -1,12 +1,13 @@
 use crate::app::app::App;
 use crate::ui::input::nav::abbreviate_path;
+use crate::ui::input::popups::{self, Popup};
 use ratatui::backend::Backend;
 use ratatui::layout::Alignment;
 use ratatui::widgets::Clear;
 use ratatui::widgets::ListItem;
 use ratatui::{
     layout::Rect,
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     widgets::{Block, Borders, List},
     Frame,
 };
@@ -18,13 +19,18 @@ pub fn render_bookmark<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect)
         let block_x = (size.width - block_width) / 2;
         let block_y = (size.height - block_height) / 2;
 
-        let area = Rect::new(block_x, block_y, block_width, block_height);
+        let area = popups::area(
+            app,
+            Popup::Bookmarks,
+            Rect::new(block_x, block_y, block_width, block_height),
+            size,
+        );
 
         let bookmark_block = Block::default()
             .style(Style::default().add_modifier(Modifier::BOLD))
             .border_style(
                 Style::default()
-                    .fg(Color::LightYellow)
+                    .fg(app.theme.border)
                     .add_modifier(Modifier::BOLD),
             )
             .borders(Borders::ALL)
@@ -37,7 +43,12 @@ pub fn render_bookmark<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect)
             .bookmarked_dirs
             .items
             .iter()
-            .map(|i| ListItem::new(abbreviate_path(i)))
+            .map(|i| match app.bookmark_notes.get(i) {
+                Some((label, _)) if !label.is_empty() => {
+                    ListItem::new(format!("{}  {}", label, abbreviate_path(i)))
+                }
+                _ => ListItem::new(abbreviate_path(i)),
+            })
             .collect::<Vec<ListItem>>();
 
         let bookmark_list = List::new(bookmark_text)
@@ -50,12 +61,11 @@ pub fn render_bookmark<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect)
             .highlight_style(
                 Style::default()
                     .add_modifier(Modifier::BOLD)
-                    .fg(Color::LightGreen),
+                    .fg(app.theme.highlight),
             )
             .highlight_symbol("> ");
 
-        let bookmark_list_area =
-            Rect::new(block_x + 1, block_y + 1, block_width - 2, block_height - 2);
+        let bookmark_list_area = Rect::new(area.x + 1, area.y + 1, area.width - 2, area.height - 2);
 
         f.render_stateful_widget(
             bookmark_list,
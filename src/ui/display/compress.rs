@@ -0,0 +1,61 @@
+use crate::app::app::App;
+use ratatui::backend::Backend;
+use ratatui::layout::Alignment;
+use ratatui::widgets::Clear;
+use ratatui::widgets::ListItem;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    widgets::{Block, Borders, List},
+    Frame,
+};
+
+pub fn render_compress<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
+    if !app.show_compress {
+        return;
+    }
+
+    let width = 44.min(size.width);
+    let height = (app.compress.items.len() as u16 + 2).min(size.height);
+    let area = Rect::new(
+        (size.width - width) / 2,
+        (size.height - height) / 2,
+        width,
+        height,
+    );
+
+    let items = app
+        .compress
+        .items
+        .iter()
+        .map(|format| {
+            ListItem::new(format!(
+                "{:<9}{}",
+                format.suffix().trim_start_matches('.'),
+                format.description()
+            ))
+        })
+        .collect::<Vec<ListItem>>();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Compress into")
+                .title_alignment(Alignment::Center)
+                .border_style(
+                    Style::default()
+                        .fg(app.theme.border)
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .fg(app.theme.highlight),
+        )
+        .highlight_symbol("> ");
+
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut app.compress.state);
+}
@@ -0,0 +1,157 @@
+use crate::configuration::theme::Theme;
+use crate::ui::display::pane::convert_bytes;
+use crate::ui::input::archive::decompressor;
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Span, Spans},
+};
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+const HEAD_LINES: usize = 500;
+const TAIL_LINES: usize = 200;
+// decompressing stops here, against bombs and runaway logs
+const MAX_DECOMPRESSED: u64 = 256 * 1024 * 1024;
+// bigger files only get their head, the tail means decompressing it all
+const MAX_TAILED: u64 = 16 * 1024 * 1024;
+
+#[derive(Clone)]
+struct Decompressed {
+    head: Vec<String>,
+    tail: VecDeque<String>,
+    lines: usize,
+    // stopped at the head, or at MAX_DECOMPRESSED
+    partial: bool,
+    capped: bool,
+    binary: bool,
+}
+
+// the last file read to its end, a preview is drawn every frame and the tail
+// takes the whole stream
+static LAST: Mutex<Option<(PathBuf, SystemTime, Decompressed)>> = Mutex::new(None);
+
+fn decompress(reader: Box<dyn Read>, whole: bool) -> Decompressed {
+    let mut reader = BufReader::new(reader.take(MAX_DECOMPRESSED));
+    let mut decompressed = Decompressed {
+        head: vec![],
+        tail: VecDeque::new(),
+        lines: 0,
+        partial: false,
+        capped: false,
+        binary: false,
+    };
+    let mut read = 0;
+    let mut buffer = vec![];
+
+    loop {
+        buffer.clear();
+        match reader.read_until(b'\n', &mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(count) => read += count as u64,
+        }
+
+        if decompressed.lines < HEAD_LINES && buffer.contains(&0) {
+            decompressed.binary = true;
+            return decompressed;
+        }
+
+        let line = String::from_utf8_lossy(&buffer)
+            .trim_end_matches(['\n', '\r'])
+            .replace('\t', "    ");
+        decompressed.lines += 1;
+
+        if decompressed.head.len() < HEAD_LINES {
+            decompressed.head.push(line);
+        } else if !whole {
+            decompressed.partial = true;
+            break;
+        } else {
+            decompressed.tail.push_back(line);
+            if decompressed.tail.len() > TAIL_LINES {
+                decompressed.tail.pop_front();
+            }
+        }
+    }
+
+    decompressed.capped = read >= MAX_DECOMPRESSED;
+    decompressed
+}
+
+fn muted(text: String, theme: &Theme) -> Spans<'static> {
+    Spans::from(Span::styled(text, Style::default().fg(theme.muted)))
+}
+
+// foo.log.gz shows as the log, its head and, when it's small enough to read
+// through, its tail
+pub fn compressed_lines(path: &Path, theme: &Theme, si: bool) -> Option<Vec<Spans<'static>>> {
+    let (kind, reader) = decompressor(path)?;
+    let metadata = path.metadata().ok()?;
+    let whole = metadata.len() <= MAX_TAILED;
+
+    let decompressed = match (whole, metadata.modified()) {
+        (true, Ok(modified)) => {
+            let mut last = LAST.lock().ok()?;
+            match last.as_ref() {
+                Some((cached, at, decompressed)) if cached == path && *at == modified => {
+                    decompressed.clone()
+                }
+                _ => {
+                    let decompressed = decompress(reader, true);
+                    *last = Some((path.to_path_buf(), modified, decompressed.clone()));
+                    decompressed
+                }
+            }
+        }
+        _ => decompress(reader, false),
+    };
+
+    let mut lines = vec![Spans::from(Span::styled(
+        format!(
+            "{} compressed, {} on disk",
+            kind,
+            convert_bytes(metadata.len(), si)
+        ),
+        Style::default()
+            .fg(theme.border)
+            .add_modifier(Modifier::BOLD),
+    ))];
+
+    if decompressed.binary {
+        lines.push(muted("Binary data".to_string(), theme));
+        return Some(lines);
+    }
+
+    lines.extend(decompressed.head.into_iter().map(Spans::from));
+
+    if decompressed.partial {
+        lines.push(muted(
+            format!(
+                "... the first {} lines, too big to decompress for the tail",
+                HEAD_LINES
+            ),
+            theme,
+        ));
+        return Some(lines);
+    }
+
+    let skipped = decompressed.lines - HEAD_LINES.min(decompressed.lines) - decompressed.tail.len();
+    if skipped > 0 {
+        lines.push(muted(format!("... {} lines ...", skipped), theme));
+    }
+    lines.extend(decompressed.tail.into_iter().map(Spans::from));
+
+    if decompressed.capped {
+        lines.push(muted(
+            format!(
+                "... stopped after {} decompressed",
+                convert_bytes(MAX_DECOMPRESSED, si)
+            ),
+            theme,
+        ));
+    }
+
+    Some(lines)
+}
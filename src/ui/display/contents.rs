@@ -1,33 +1,107 @@
-use crate::app::app::App;
+use crate::app::app::{display_name, App};
+use crate::ui::display::preview::{git_lines, preview_lines, Source};
+use crate::ui::input::{follow, plugins, preview_search, previewers, remote};
 use ratatui::backend::Backend;
 use ratatui::widgets::Paragraph;
 use ratatui::{
     layout::Rect,
     style::Style,
+    text::{Span, Spans},
     widgets::{Block, Borders, List, ListItem},
     Frame,
 };
 use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
 use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+// only for directories inside the current one, `../` isn't in the status
+fn dir_git_lines(app: &mut App, name: &str) -> Vec<Spans<'static>> {
+    let git = match app.git.as_mut() {
+        Some(git) if name != ".." => git,
+        _ => return vec![],
+    };
+
+    let summary = git.summary(name);
+    let ignored = git.is_ignored(name);
+    let last_commit = if ignored { None } else { git.last_commit(name) };
+
+    git_lines(summary, ignored, last_commit, &app.theme)
+}
 
 pub fn render_contents<B: Backend>(f: &mut Frame<B>, app: &mut App, chunks: &[Rect]) {
     let contents_block = Block::default().borders(Borders::ALL).title("Preview");
     f.render_widget(contents_block, chunks[0]);
 
-    let selected_file = match app.files.state.selected() {
-        Some(i) => match app.files.items.get(i) {
-            Some(item) => &item.0,
-            None => "",
-        },
-        None => "",
+    // a selected directory previews its children
+    let selected_file = match (app.files.state.selected(), app.dirs.state.selected()) {
+        (Some(i), _) => app.files.items.get(i).map(|item| item.0.as_os_str()),
+        (None, Some(i)) => app.dirs.items.get(i).map(|item| item.0.as_os_str()),
+        _ => None,
     };
+    // owned, plugin previewers need the app
+    let selected_file = selected_file.unwrap_or_default().to_os_string();
+    let selected_file = selected_file.as_os_str();
 
-    let mut content = String::new();
     let max_lines = chunks[0].height as usize - 2;
 
+    // a different file starts at the top again
+    if app.preview_file != selected_file {
+        app.preview_file = selected_file.to_os_string();
+        app.preview_scroll = 0;
+        app.preview_moved = Instant::now();
+        previewers::cancel(app);
+    }
+
+    // holding j doesn't read every file passed on the way
+    app.preview_held = app.preview_moved.elapsed() < app.preview_delay;
+    if app.preview_held {
+        app.preview_at_end = true;
+        return;
+    }
+
+    let scroll = app.preview_scroll;
+
+    // the last lines that fit, the newest at the bottom
+    if let Some(follow) = follow::active(app) {
+        let search = preview_search::active(app);
+        let lines = follow.lines[follow.lines.len().saturating_sub(max_lines)..]
+            .iter()
+            .map(|line| match search {
+                Some(search) => preview_search::highlight(line, search, &app.theme),
+                None => Spans::from(line.clone()),
+            })
+            .collect();
+        render_lines(f, chunks[0], lines, 0);
+        app.preview_at_end = true;
+        return;
+    }
+
+    // a remote entry, read over the connection
+    if app.remote.is_some() {
+        let selected_file = selected_file.to_string_lossy().to_string();
+        if let Some(lines) = remote::preview_lines(app, &selected_file) {
+            app.preview_at_end = render_lines(f, chunks[0], lines, scroll);
+        }
+        return;
+    }
+
     if !selected_file.is_empty() {
+        // never follow a dangling link, just say where it points
+        if let Ok(target) = std::fs::read_link(selected_file) {
+            if std::fs::metadata(selected_file).is_err() {
+                let broken = Paragraph::new(format!(
+                    "Broken symlink: {} -> {}",
+                    display_name(selected_file),
+                    display_name(target)
+                ))
+                .style(Style::default())
+                .block(Block::default().borders(Borders::ALL).title("Preview"));
+                f.render_widget(broken, chunks[0]);
+                return;
+            }
+        }
+
         let metadata = match std::fs::metadata(selected_file) {
             Ok(metadata) => metadata,
             Err(err) => {
@@ -36,8 +110,41 @@ pub fn render_contents<B: Backend>(f: &mut Frame<B>, app: &mut App, chunks: &[Re
             }
         };
 
+        // a plugin's previewer before the built-in ones
+        if metadata.is_file() {
+            if let Some(lines) = plugins::preview(app, Path::new(selected_file)) {
+                let lines = lines.into_iter().map(Spans::from).collect();
+                app.preview_at_end = render_lines(f, chunks[0], lines, scroll);
+                return;
+            }
+
+            // then the previewer= commands, their output comes in the background
+            if let Some(lines) = previewers::preview(app, Path::new(selected_file)) {
+                let lines = lines.into_iter().map(Spans::from).collect();
+                app.preview_at_end = render_lines(f, chunks[0], lines, scroll);
+                return;
+            }
+        }
+
+        let path = Path::new(selected_file);
+        if let Some(mut lines) = preview_lines(
+            Source::Local(path),
+            &app.theme,
+            app.show_hidden,
+            app.si_units,
+        ) {
+            if app.files.state.selected().is_none() && metadata.is_dir() {
+                let name = selected_file.to_string_lossy();
+                let name = name.trim_end_matches('/');
+                lines.splice(0..0, dir_git_lines(app, name));
+            }
+
+            app.preview_at_end = render_lines(f, chunks[0], lines, scroll);
+            return;
+        }
+
         if !metadata.is_file() {
-            println!("Not a regular file: {}", selected_file);
+            println!("Not a regular file: {}", display_name(selected_file));
             return;
         }
 
@@ -53,29 +160,32 @@ pub fn render_contents<B: Backend>(f: &mut Frame<B>, app: &mut App, chunks: &[Re
             return;
         }
 
-        let reader = BufReader::new(file);
-        for (num, line) in reader.lines().enumerate() {
-            if num >= max_lines {
-                break;
-            }
-
-            match line {
-                Ok(line) => {
-                    content.push_str(&line);
-                    content.push('\n');
-                }
-                #[allow(unused_variables)]
-                Err(err) => {
-                    continue;
-                }
-            }
+        // only the lines in view are read, never past the configured limits
+        let window = app
+            .text_window
+            .read(selected_file, file, scroll, max_lines, app.si_units);
+        app.preview_at_end = window.at_end;
+
+        let search = preview_search::active(app);
+        let mut lines = window
+            .lines
+            .iter()
+            .map(|line| match search {
+                Some(search) => preview_search::highlight(line, search, &app.theme),
+                None => Spans::from(line.clone()),
+            })
+            .collect::<Vec<Spans<'static>>>();
+        if let Some(footer) = window.footer {
+            lines.push(Spans::from(Span::styled(
+                footer,
+                Style::default().fg(app.theme.dim),
+            )));
         }
+        render_lines(f, chunks[0], lines, 0);
+        return;
     }
 
-    let items = List::new(vec![ListItem::new(content)])
-        .block(Block::default().borders(Borders::ALL).title("Preview"));
-
-    f.render_stateful_widget(items, chunks[0], &mut app.files.state);
+    app.preview_at_end = true;
 
     if selected_file.is_empty() {
         let placeholder = Paragraph::new("No file selected")
@@ -85,6 +195,52 @@ pub fn render_contents<B: Backend>(f: &mut Frame<B>, app: &mut App, chunks: &[Re
     }
 }
 
+// how long until a held back preview is drawn, the loop doesn't wait for
+// the next tick
+pub fn settles_in(app: &App) -> Option<Duration> {
+    if !app.preview_held {
+        return None;
+    }
+
+    app.preview_delay
+        .checked_sub(app.preview_moved.elapsed())
+        .filter(|left| !left.is_zero())
+}
+
+// the border and title of the focused preview, drawn over the rest
+pub fn render_focus<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(preview_search::title(app))
+        .border_style(Style::default().fg(app.theme.focus));
+    f.render_widget(block, area);
+}
+
+// previews that come as styled lines rather than plain file contents,
+// returns whether the last line is visible
+fn render_lines<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    lines: Vec<Spans<'static>>,
+    scroll: usize,
+) -> bool {
+    let max_lines = area.height.saturating_sub(2) as usize;
+
+    let at_end = lines.len() <= scroll + max_lines;
+
+    let items = lines
+        .into_iter()
+        .skip(scroll)
+        .take(max_lines)
+        .map(ListItem::new)
+        .collect::<Vec<ListItem>>();
+
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title("Preview"));
+    f.render_widget(list, area);
+
+    at_end
+}
+
 fn is_binary(file: &mut File) -> std::io::Result<bool> {
     let mut buffer = vec![0; 1024];
     file.read(&mut buffer)?;
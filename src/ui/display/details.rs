@@ -1,12 +1,13 @@
 use crate::app::app::App;
 use crate::ui::display::pane::selected_pane_content;
+use crate::ui::input::bookmark::selected_entry_path;
 use ratatui::backend::Backend;
 use ratatui::layout::Alignment;
 use ratatui::widgets::Paragraph;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
-    text::Spans,
+    style::Style,
+    text::{Span, Spans},
     widgets::{Block, Borders, List, ListItem},
     Frame,
 };
@@ -46,42 +47,71 @@ pub fn render_details<B: Backend>(
     }
 
     let selected_file = match app.files.state.selected() {
-        Some(i) => match app.files.items.get(i) {
-            Some(item) => &item.0,
-            None => "",
-        },
-        None => "",
-    };
+        Some(i) => app.files.items.get(i).map(|item| item.0.as_os_str()),
+        None => None,
+    }
+    .unwrap_or_default();
 
+    // a drive root without directories has nothing in the pane, not even ../
     let selected_dir = match app.dirs.state.selected() {
-        Some(i) => &app.dirs.items[i].0,
-        None => "",
-    };
+        Some(i) => app.dirs.items.get(i).map(|item| item.0.as_os_str()),
+        None => None,
+    }
+    .unwrap_or_default();
 
-    let selected_item = if !selected_file.is_empty() {
-        selected_pane_content(&selected_file.to_string())
+    // nothing local to stat for a remote entry
+    let mut selected_item = if let Some(remote) = &app.remote {
+        vec![ListItem::new(Spans::from(format!(
+            "On {}",
+            remote.vfs.name()
+        )))]
+    } else if !selected_file.is_empty() {
+        selected_pane_content(selected_file, app.si_units)
     } else if !selected_dir.is_empty() {
-        selected_pane_content(&selected_dir.to_string())
+        selected_pane_content(selected_dir, app.si_units)
     } else {
         vec![ListItem::new(Spans::from("No file selected"))]
     };
 
+    if let Some(path) = selected_entry_path(app) {
+        let path = path.to_string_lossy().to_string();
+
+        if app.bookmarked_dirs.items.contains(&path) {
+            let line = match app.bookmark_notes.get(&path) {
+                Some((label, note)) if note.is_empty() => format!("Bookmark: {}", label),
+                Some((label, note)) => format!("Bookmark: {} - {}", label, note),
+                None => "Bookmarked (e to add a label and note)".to_string(),
+            };
+
+            selected_item.push(ListItem::new(Spans::from(Span::styled(
+                line,
+                Style::default().fg(app.theme.highlight),
+            ))));
+        }
+    }
+
     let items = List::new(selected_item).block(
         Block::default()
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::LightYellow))
+            .border_style(Style::default().fg(app.theme.border))
             .title("Details")
             .title_alignment(Alignment::Left),
     );
     f.render_widget(items, details_chunks[0]);
 
+    let git = app.git.as_ref().filter(|_| app.remote.is_none());
+    let pwd_title = match git.and_then(|git| git.branch.as_ref()) {
+        Some(branch) => format!("Current Directory ({})", branch),
+        None => "Current Directory".to_string(),
+    };
+
     let pwd_paragraph = Paragraph::new(cur_dir)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::LightYellow))
+                .border_style(Style::default().fg(app.theme.border))
                 .title_alignment(Alignment::Center)
-                .title("Current Directory"),
+                .title(pwd_title),
         )
         .alignment(Alignment::Center);
     f.render_widget(pwd_paragraph, details_chunks[1]);
@@ -90,7 +120,7 @@ pub fn render_details<B: Backend>(
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::LightYellow))
+                .border_style(Style::default().fg(app.theme.border))
                 .title("Disk Usage")
                 .title_alignment(Alignment::Right),
         )
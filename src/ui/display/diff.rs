@@ -0,0 +1,65 @@
+use crate::app::app::App;
+use ratatui::backend::Backend;
+use ratatui::layout::Alignment;
+use ratatui::widgets::Clear;
+use ratatui::widgets::ListItem;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, List},
+    Frame,
+};
+
+// the whole screen, additions and removals colored like git does
+pub fn render_diff<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
+    if app.show_diff {
+        f.render_widget(Clear, size);
+
+        let (mut added, mut removed) = (0, 0);
+
+        let diff_text = app
+            .diff
+            .items
+            .iter()
+            .enumerate()
+            .map(|(idx, line)| {
+                // the first two lines name the files, they start with --- and +++
+                let style = if idx < 2 {
+                    Style::default().add_modifier(Modifier::BOLD)
+                } else if line.starts_with("@@") {
+                    Style::default().fg(app.theme.link)
+                } else if line.starts_with('+') {
+                    added += 1;
+                    Style::default().fg(app.theme.success)
+                } else if line.starts_with('-') {
+                    removed += 1;
+                    Style::default().fg(app.theme.error)
+                } else {
+                    Style::default()
+                };
+
+                ListItem::new(Spans::from(Span::styled(line.clone(), style)))
+            })
+            .collect::<Vec<ListItem>>();
+
+        let title = format!("{} - {} added, {} removed", app.diff_title, added, removed);
+
+        let diff_list = List::new(diff_text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title)
+                    .border_style(
+                        Style::default()
+                            .fg(app.theme.border)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                    .title_alignment(Alignment::Center),
+            )
+            .highlight_style(Style::default().bg(app.theme.muted))
+            .highlight_symbol("> ");
+
+        f.render_stateful_widget(diff_list, size, &mut app.diff.state);
+    }
+}
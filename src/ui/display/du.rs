@@ -0,0 +1,123 @@
+use crate::app::app::App;
+use crate::ui::display::pane::convert_bytes;
+use ratatui::backend::Backend;
+use ratatui::layout::Alignment;
+use ratatui::widgets::Clear;
+use ratatui::widgets::ListItem;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, List},
+    Frame,
+};
+
+const BAR_WIDTH: usize = 20;
+
+// a bar relative to the biggest entry, its size and share, then the name
+pub fn render_du<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
+    if app.show_du {
+        let block_width = (size.width * 2 / 3).max(50).min(size.width);
+        let block_height = size.height * 2 / 3;
+        let block_x = (size.width - block_width) / 2;
+        let block_y = (size.height - block_height) / 2;
+
+        let area = Rect::new(block_x, block_y, block_width, block_height);
+
+        f.render_widget(Clear, area);
+
+        let total: u64 = app.du.items.iter().filter_map(|usage| usage.size).sum();
+        let biggest = app
+            .du
+            .items
+            .iter()
+            .filter_map(|usage| usage.size)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        let du_text = app
+            .du
+            .items
+            .iter()
+            .map(|usage| {
+                let name = if usage.is_dir {
+                    format!("{}/", usage.name)
+                } else {
+                    usage.name.clone()
+                };
+
+                let size = match usage.size {
+                    Some(size) => size,
+                    None => {
+                        return ListItem::new(Spans::from(vec![
+                            Span::styled(
+                                format!("{:<w$} {:>10} {:>4}  ", "", "...", "", w = BAR_WIDTH),
+                                Style::default().fg(app.theme.dim),
+                            ),
+                            Span::raw(name),
+                        ]))
+                    }
+                };
+
+                let filled = (size as f64 / biggest as f64 * BAR_WIDTH as f64).round() as usize;
+                let share = (size * 100).checked_div(total).unwrap_or(0);
+
+                ListItem::new(Spans::from(vec![
+                    Span::styled(
+                        format!("{:<w$}", "█".repeat(filled), w = BAR_WIDTH),
+                        Style::default().fg(app.theme.highlight),
+                    ),
+                    Span::raw(format!(
+                        " {:>10} {:>3}%  {}",
+                        convert_bytes(size, app.si_units),
+                        share,
+                        name
+                    )),
+                ]))
+            })
+            .collect::<Vec<ListItem>>();
+
+        let counting = app
+            .du
+            .items
+            .iter()
+            .filter(|usage| usage.size.is_none())
+            .count();
+        let title = if counting > 0 {
+            format!(
+                "Disk usage of {} - {} so far, {} left to count",
+                app.cur_dir.trim_end(),
+                convert_bytes(total, app.si_units),
+                counting
+            )
+        } else {
+            format!(
+                "Disk usage of {} - {}",
+                app.cur_dir.trim_end(),
+                convert_bytes(total, app.si_units)
+            )
+        };
+
+        let du_list = List::new(du_text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title)
+                    .border_style(
+                        Style::default()
+                            .fg(app.theme.border)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                    .title_alignment(Alignment::Center),
+            )
+            .highlight_style(
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .fg(app.theme.highlight),
+            )
+            .highlight_symbol("> ");
+
+        f.render_stateful_widget(du_list, area, &mut app.du.state);
+    }
+}
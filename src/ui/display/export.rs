@@ -0,0 +1,67 @@
+use crate::app::app::App;
+use ratatui::backend::Backend;
+use ratatui::layout::Alignment;
+use ratatui::widgets::Clear;
+use ratatui::widgets::ListItem;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    widgets::{Block, Borders, List},
+    Frame,
+};
+
+// the current directory as a text, Markdown or HTML tree
+pub fn render_export<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
+    if !app.show_export {
+        return;
+    }
+
+    let title = format!("Export the tree, depth {} (+/-)", app.export_depth);
+    let width = 44.min(size.width);
+    let height = (app.export.items.len() as u16 + 2).min(size.height);
+    let area = Rect::new(
+        (size.width - width) / 2,
+        (size.height - height) / 2,
+        width,
+        height,
+    );
+
+    let items = app
+        .export
+        .items
+        .iter()
+        .map(|(format, to_clipboard)| {
+            ListItem::new(format!(
+                "{:<10}{}",
+                format.name(),
+                if *to_clipboard {
+                    "to the clipboard"
+                } else {
+                    "to a file"
+                }
+            ))
+        })
+        .collect::<Vec<ListItem>>();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_alignment(Alignment::Center)
+                .border_style(
+                    Style::default()
+                        .fg(app.theme.border)
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .fg(app.theme.highlight),
+        )
+        .highlight_symbol("> ");
+
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut app.export.state);
+}
@@ -0,0 +1,230 @@
+use crate::configuration::theme::Theme;
+use ratatui::style::{Color, Modifier, Style};
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs::{self, Metadata};
+
+pub const ARCHIVES: [&str; 14] = [
+    "zip", "tar", "gz", "tgz", "bz2", "xz", "zst", "7z", "rar", "lz4", "lzma", "deb", "rpm", "jar",
+];
+
+pub const IMAGES: [&str; 11] = [
+    "png", "jpg", "jpeg", "gif", "bmp", "svg", "webp", "ico", "tif", "tiff", "avif",
+];
+
+// the parts of LS_COLORS a listing needs: file kinds (di, ln, ex, ...) and
+// name patterns (*.tar, *README)
+pub struct LsColors {
+    kinds: HashMap<String, Style>,
+    patterns: Vec<(String, Style)>,
+    link_as_target: bool,
+}
+
+impl LsColors {
+    pub fn from_env() -> Option<LsColors> {
+        let value = std::env::var("LS_COLORS").ok()?;
+
+        if value.trim().is_empty() {
+            return None;
+        }
+
+        Some(LsColors::parse(&value))
+    }
+
+    fn parse(value: &str) -> LsColors {
+        let mut colors = LsColors {
+            kinds: HashMap::new(),
+            patterns: vec![],
+            link_as_target: false,
+        };
+
+        for entry in value.split(':') {
+            let (key, codes) = match entry.split_once('=') {
+                Some(entry) => entry,
+                None => continue,
+            };
+
+            if key == "ln" && codes == "target" {
+                colors.link_as_target = true;
+            } else if let Some(pattern) = key.strip_prefix('*') {
+                colors
+                    .patterns
+                    .push((pattern.to_lowercase(), sgr_style(codes)));
+            } else {
+                colors.kinds.insert(key.to_string(), sgr_style(codes));
+            }
+        }
+
+        // longer patterns first, so *.tar.gz wins over *.gz
+        colors
+            .patterns
+            .sort_by_key(|(pattern, _)| std::cmp::Reverse(pattern.len()));
+        colors
+    }
+
+    fn kind(&self, kind: &str) -> Option<Style> {
+        self.kinds.get(kind).copied()
+    }
+
+    fn pattern(&self, name: &str) -> Option<Style> {
+        let name = name.to_lowercase();
+
+        self.patterns
+            .iter()
+            .find(|(pattern, _)| name.ends_with(pattern.as_str()))
+            .map(|(_, style)| *style)
+    }
+}
+
+// "01;38;5;208" and friends, anything unknown is skipped
+fn sgr_style(codes: &str) -> Style {
+    let codes = codes
+        .split(';')
+        .filter_map(|code| code.parse::<u8>().ok())
+        .collect::<Vec<u8>>();
+
+    let mut style = Style::default();
+    let mut i = 0;
+
+    while i < codes.len() {
+        match codes[i] {
+            1 => style = style.add_modifier(Modifier::BOLD),
+            2 => style = style.add_modifier(Modifier::DIM),
+            3 => style = style.add_modifier(Modifier::ITALIC),
+            4 => style = style.add_modifier(Modifier::UNDERLINED),
+            5 => style = style.add_modifier(Modifier::SLOW_BLINK),
+            7 => style = style.add_modifier(Modifier::REVERSED),
+            code @ (30..=37 | 90..=97) => style = style.fg(basic_color(code % 10, code >= 90)),
+            code @ (40..=47 | 100..=107) => style = style.bg(basic_color(code % 10, code >= 100)),
+            code @ (38 | 48) => {
+                let color = match codes.get(i + 1) {
+                    Some(5) => codes.get(i + 2).map(|n| {
+                        i += 2;
+                        Color::Indexed(*n)
+                    }),
+                    Some(2) if codes.len() > i + 4 => {
+                        let color = Color::Rgb(codes[i + 2], codes[i + 3], codes[i + 4]);
+                        i += 4;
+                        Some(color)
+                    }
+                    _ => None,
+                };
+
+                if let Some(color) = color {
+                    style = if code == 38 {
+                        style.fg(color)
+                    } else {
+                        style.bg(color)
+                    };
+                }
+            }
+            _ => {}
+        }
+
+        i += 1;
+    }
+
+    style
+}
+
+fn basic_color(code: u8, light: bool) -> Color {
+    match (code, light) {
+        (0, false) => Color::Black,
+        (1, false) => Color::Red,
+        (2, false) => Color::Green,
+        (3, false) => Color::Yellow,
+        (4, false) => Color::Blue,
+        (5, false) => Color::Magenta,
+        (6, false) => Color::Cyan,
+        (7, false) => Color::Gray,
+        (0, true) => Color::DarkGray,
+        (1, true) => Color::LightRed,
+        (2, true) => Color::LightGreen,
+        (3, true) => Color::LightYellow,
+        (4, true) => Color::LightBlue,
+        (5, true) => Color::LightMagenta,
+        (6, true) => Color::LightCyan,
+        _ => Color::White,
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(windows)]
+fn is_executable(_metadata: &Metadata) -> bool {
+    false
+}
+
+fn theme_style(name: &str, metadata: &Metadata, theme: &Theme) -> Style {
+    if metadata.is_dir() {
+        return Style::default().fg(theme.directory);
+    }
+
+    if is_executable(metadata) {
+        return Style::default().fg(theme.executable);
+    }
+
+    let extension = match name.rsplit_once('.') {
+        Some((_, extension)) => extension.to_lowercase(),
+        None => return Style::default(),
+    };
+
+    if let Some(color) = theme.extensions.get(&extension) {
+        Style::default().fg(*color)
+    } else if ARCHIVES.contains(&extension.as_str()) {
+        Style::default().fg(theme.archive)
+    } else if IMAGES.contains(&extension.as_str()) {
+        Style::default().fg(theme.image)
+    } else {
+        Style::default()
+    }
+}
+
+fn ls_style(name: &str, metadata: &Metadata, colors: &LsColors) -> Style {
+    if metadata.is_dir() {
+        return colors.kind("di").unwrap_or_default();
+    }
+
+    if is_executable(metadata) {
+        if let Some(style) = colors.kind("ex") {
+            return style;
+        }
+    }
+
+    colors
+        .pattern(name)
+        .or_else(|| colors.kind("fi"))
+        .unwrap_or_default()
+}
+
+// color a listing entry like `ls --color` would, from LS_COLORS when it is
+// set and from the theme otherwise
+pub fn entry_style(path: &OsStr, colors: Option<&LsColors>, theme: &Theme) -> Style {
+    let link = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return Style::default(),
+    };
+    let target = fs::metadata(path);
+    // the patterns only look at the suffix
+    let name = &*path.to_string_lossy();
+
+    if link.file_type().is_symlink() {
+        return match (colors, target) {
+            (Some(colors), Ok(target)) if colors.link_as_target => ls_style(name, &target, colors),
+            (Some(colors), Ok(_)) => colors.kind("ln").unwrap_or_default(),
+            (Some(colors), Err(_)) => colors.kind("or").unwrap_or_default(),
+            (None, Ok(_)) => Style::default().fg(theme.link),
+            (None, Err(_)) => Style::default().fg(theme.error),
+        };
+    }
+
+    match colors {
+        Some(colors) => ls_style(name, &link, colors),
+        None => theme_style(name, &link, theme),
+    }
+}
@@ -1,73 +1,88 @@
 use super::pane::get_pwd;
-use crate::app::app::App;
+use crate::app::app::{is_hidden, App};
+use crate::ui::display::file_colors::entry_style;
+use crate::ui::display::icons::icon;
+use crate::ui::input::{flatten, listing};
 use ratatui::backend::Backend;
 use ratatui::layout::Alignment;
+use ratatui::text::{Span, Spans};
+use ratatui::widgets::ListState;
 use ratatui::{
     layout::Rect,
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     widgets::{Block, Borders, List, ListItem},
     Frame,
 };
+use std::ffi::OsString;
+use std::fs;
+use std::path::Path;
 
 pub fn render_files<B: Backend>(f: &mut Frame<B>, app: &mut App, chunks: &[Rect]) {
+    let title = match &app.file_filter {
+        Some(filter) => format!("{} matching {}", flatten::title(app), filter.pattern),
+        None => flatten::title(app),
+    };
     let files_block = Block::default()
         .borders(Borders::ALL)
-        .title("Files")
+        .title(title.clone())
         .title_alignment(Alignment::Center);
     f.render_widget(files_block, chunks[0]);
 
-    app.update_files();
-
-    let files = app
-        .files
-        .items
+    app.files
+        .update_offset(chunks[0].height.saturating_sub(2) as usize);
+    let (window, mut state) = app.files.window();
+    let files = app.files.items[window]
         .iter()
-        .map(|i| ListItem::new(i.0.clone()))
+        .map(|entry| entry_item(entry, app))
         .collect::<Vec<ListItem>>();
 
     let items = List::new(files)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Files")
+                .title(title.clone())
                 .title_alignment(Alignment::Center),
         )
         .highlight_symbol("> ")
         .highlight_style(
             Style::default()
-                .fg(Color::LightGreen)
+                .fg(app.theme.highlight)
                 .add_modifier(Modifier::BOLD),
         );
 
     if app.files.items.len() == 0 {
-        let empty = vec![ListItem::new("No files in this directory")];
+        let empty = match &app.file_filter {
+            Some(filter) => format!("No files match {}", filter.pattern),
+            None => "No files in this directory".to_string(),
+        };
+        let empty = vec![ListItem::new(empty)];
         let empty_list = List::new(empty)
-            .block(Block::default().borders(Borders::ALL).title("Files"))
+            .block(Block::default().borders(Borders::ALL).title(title.clone()))
             .highlight_symbol("> ")
             .highlight_style(
                 Style::default()
-                    .fg(Color::LightGreen)
+                    .fg(app.theme.highlight)
                     .add_modifier(Modifier::BOLD),
             );
         f.render_stateful_widget(empty_list, chunks[0], &mut app.files.state);
         return;
     }
 
-    f.render_stateful_widget(items, chunks[0], &mut app.files.state);
+    f.render_stateful_widget(items, chunks[0], &mut state);
 
     if app.files.state.selected().is_some() {
         let files_block = Block::default()
             .borders(Borders::ALL)
-            .title("Files")
+            .title(title.clone())
             .title_alignment(Alignment::Center)
-            .border_style(Style::default().fg(Color::LightBlue));
+            .border_style(Style::default().fg(app.theme.focus));
         f.render_widget(files_block, chunks[0]);
     } else {
         let files_block = Block::default()
             .borders(Borders::ALL)
-            .title("Files")
+            .title(title.clone())
             .title_alignment(Alignment::Center)
-            .border_style(Style::default().fg(Color::White));
+            .border_style(Style::default().fg(app.theme.unfocused));
         f.render_widget(files_block, chunks[0]);
     }
 }
@@ -75,50 +90,204 @@ pub fn render_files<B: Backend>(f: &mut Frame<B>, app: &mut App, chunks: &[Rect]
 pub fn render_dirs<B: Backend>(f: &mut Frame<B>, app: &mut App, chunks: &[Rect]) {
     app.cur_dir = get_pwd();
 
+    let title = match &app.dir_pages {
+        Some(pages) if pages.streaming() => {
+            format!("Directories, {} so far", app.dirs.items.len())
+        }
+        _ => "Directories".to_string(),
+    };
     let dirs_block = Block::default()
         .borders(Borders::ALL)
-        .title("Directories")
+        .title(title.clone())
         .title_alignment(Alignment::Center);
     f.render_widget(dirs_block, chunks[0]);
 
-    let dirs = app
-        .dirs
-        .items
+    app.dirs
+        .update_offset(chunks[0].height.saturating_sub(2) as usize);
+    let (window, mut state) = app.dirs.window();
+    let dirs = app.dirs.items[window]
         .iter()
-        .map(|i| ListItem::new(i.0.clone()))
+        .map(|entry| entry_item(entry, app))
         .collect::<Vec<ListItem>>();
 
-    app.update_dirs();
-
     let items = List::new(dirs)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Directories")
+                .title(title.clone())
                 .title_alignment(Alignment::Center),
         )
         .highlight_symbol("> ")
         .highlight_style(
             Style::default()
-                .fg(Color::LightGreen)
+                .fg(app.theme.highlight)
                 .add_modifier(Modifier::BOLD),
         );
 
-    f.render_stateful_widget(items, chunks[0], &mut app.dirs.state);
+    f.render_stateful_widget(items, chunks[0], &mut state);
 
     if app.dirs.state.selected().is_some() {
         let dirs_block = Block::default()
             .borders(Borders::ALL)
-            .title("Directories")
+            .title(title.clone())
             .title_alignment(Alignment::Center)
-            .border_style(Style::default().fg(Color::LightBlue));
+            .border_style(Style::default().fg(app.theme.focus));
         f.render_widget(dirs_block, chunks[0]);
     } else {
         let dirs_block = Block::default()
             .borders(Borders::ALL)
-            .title("Directories")
+            .title(title.clone())
             .title_alignment(Alignment::Center)
-            .border_style(Style::default().fg(Color::White));
+            .border_style(Style::default().fg(app.theme.unfocused));
         f.render_widget(dirs_block, chunks[0]);
     }
 }
+
+// the directories and then the files in one list, `unified_listing`
+pub fn render_listing<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+    app.cur_dir = get_pwd();
+
+    listing::sync(app, area.height.saturating_sub(2) as usize);
+    let (window, mut state) = app.listing.window();
+    let entries = app
+        .dirs
+        .items
+        .iter()
+        .chain(app.files.items.iter())
+        .skip(window.start)
+        .take(window.len())
+        .map(|entry| entry_item(entry, app))
+        .collect::<Vec<ListItem>>();
+
+    let border = if app.listing.state.selected().is_some() {
+        app.theme.focus
+    } else {
+        app.theme.unfocused
+    };
+
+    let items = List::new(entries)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Files and Directories")
+                .title_alignment(Alignment::Center)
+                .border_style(Style::default().fg(border)),
+        )
+        .highlight_symbol("> ")
+        .highlight_style(
+            Style::default()
+                .fg(app.theme.highlight)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    f.render_stateful_widget(items, area, &mut state);
+}
+
+// the parent directory's entries with the current one selected, the left
+// column of the three column layout
+pub fn render_parent<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+    let cur_dir = std::env::current_dir().unwrap_or_default();
+    let parent = cur_dir.parent().map(Path::to_path_buf);
+
+    if app.parent_entries.as_ref().map(|(dir, _)| dir) != Some(&cur_dir) {
+        let entries = parent
+            .as_deref()
+            .map(|parent| parent_entries(parent, app.show_hidden))
+            .unwrap_or_default();
+        app.parent_entries = Some((cur_dir.clone(), entries));
+    }
+
+    let name = cur_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string());
+    let entries = app
+        .parent_entries
+        .as_ref()
+        .map(|(_, entries)| entries.as_slice())
+        .unwrap_or_default();
+
+    let mut state = ListState::default();
+    state.select(
+        entries
+            .iter()
+            .position(|(entry, is_dir)| *is_dir && Some(entry) == name.as_ref()),
+    );
+
+    let items = entries
+        .iter()
+        .map(|(name, is_dir)| {
+            if *is_dir {
+                ListItem::new(format!("{}/", name))
+            } else {
+                ListItem::new(name.clone()).style(Style::default().fg(app.theme.dim))
+            }
+        })
+        .collect::<Vec<ListItem>>();
+
+    let title = parent
+        .and_then(|parent| {
+            parent
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+        })
+        .unwrap_or_else(|| "/".to_string());
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_alignment(Alignment::Center)
+                .border_style(Style::default().fg(app.theme.unfocused)),
+        )
+        .highlight_symbol("> ")
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD));
+
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+// directories first, then files, hidden ones only when the panes show them
+fn parent_entries(parent: &Path, show_hidden: bool) -> Vec<(String, bool)> {
+    let mut entries = fs::read_dir(parent)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| show_hidden || !is_hidden(entry))
+                .map(|entry| {
+                    let is_dir = entry.path().is_dir();
+                    (entry.file_name().to_string_lossy().to_string(), is_dir)
+                })
+                .collect::<Vec<(String, bool)>>()
+        })
+        .unwrap_or_default();
+
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    entries
+}
+
+// names are colored by file type and may get an icon, inside a repository
+// the git status is shown in front of them
+fn entry_item(entry: &(OsString, String), app: &App) -> ListItem<'static> {
+    let style = entry_style(&entry.0, app.ls_colors.as_ref(), &app.theme);
+    let name = Span::styled(format!("{}{}", icon(&entry.0, app.icons), entry.1), style);
+    let pin = if app.pinned.iter().any(|pin| entry.0 == pin.as_str()) {
+        Span::styled("  (pinned)", Style::default().fg(app.theme.dim))
+    } else {
+        Span::raw("")
+    };
+
+    match app.git.as_ref() {
+        Some(git) => {
+            let marker = match git.status(&entry.0.to_string_lossy()) {
+                Some(status) => Span::styled(
+                    format!("{} ", status.marker()),
+                    Style::default().fg(status.color(&app.theme)),
+                ),
+                None => Span::raw("  "),
+            };
+
+            ListItem::new(Spans::from(vec![marker, name, pin]))
+        }
+        None => ListItem::new(Spans::from(vec![name, pin])),
+    }
+}
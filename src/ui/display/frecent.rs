@@ -0,0 +1,60 @@
+use crate::app::app::App;
+use ratatui::backend::Backend;
+use ratatui::layout::Alignment;
+use ratatui::widgets::{Clear, ListItem, Paragraph};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    widgets::{Block, Borders, List},
+    Frame,
+};
+
+pub fn render_frecent<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect, input: &str) {
+    if app.show_frecent {
+        let block_width = f.size().width / 2;
+        let block_height = f.size().height / 2;
+        let block_x = (size.width - block_width) / 2;
+        let block_y = (size.height - block_height) / 3;
+
+        let area = Rect::new(block_x, block_y, block_width, block_height);
+        let input_area = Rect::new(block_x, block_y, block_width, 3);
+        let list_area = Rect::new(block_x, block_y + 3, block_width, block_height - 3);
+
+        f.render_widget(Clear, area);
+
+        let query = Paragraph::new(format!(": {}", input)).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Frequent Directories")
+                .title_alignment(Alignment::Center)
+                .border_style(
+                    Style::default()
+                        .fg(app.theme.border)
+                        .add_modifier(Modifier::BOLD),
+                ),
+        );
+        f.render_widget(query, input_area);
+
+        let dirs_text = app
+            .frecent
+            .items
+            .iter()
+            .map(|path| ListItem::new(path.clone()))
+            .collect::<Vec<ListItem>>();
+
+        let dirs_list = List::new(dirs_text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(app.theme.border)),
+            )
+            .highlight_style(
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .fg(app.theme.highlight),
+            )
+            .highlight_symbol("> ");
+
+        f.render_stateful_widget(dirs_list, list_area, &mut app.frecent.state);
+    }
+}
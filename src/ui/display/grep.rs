@@ -0,0 +1,82 @@
+use crate::app::app::{display_name, App};
+use ratatui::backend::Backend;
+use ratatui::layout::Alignment;
+use ratatui::text::{Span, Spans};
+use ratatui::widgets::{Clear, ListItem, Paragraph};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    widgets::{Block, Borders, List},
+    Frame,
+};
+
+pub fn render_grep<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect, input: &str) {
+    if !app.show_grep {
+        return;
+    }
+
+    let block_width = f.size().width * 3 / 4;
+    let block_height = f.size().height * 2 / 3;
+    let block_x = (size.width - block_width) / 2;
+    let block_y = (size.height - block_height) / 3;
+
+    let area = Rect::new(block_x, block_y, block_width, block_height);
+    let input_area = Rect::new(block_x, block_y, block_width, 3);
+    let list_area = Rect::new(block_x, block_y + 3, block_width, block_height - 3);
+
+    f.render_widget(Clear, area);
+
+    let query = Paragraph::new(format!(": {}", input)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Search File Contents (Enter searches, again goes to the hit)")
+            .title_alignment(Alignment::Center)
+            .border_style(
+                Style::default()
+                    .fg(app.theme.border)
+                    .add_modifier(Modifier::BOLD),
+            ),
+    );
+    f.render_widget(query, input_area);
+
+    let searching = app.grep.searching();
+    let hits = &mut app.grep.hits;
+    let title = match (searching, hits.items.len()) {
+        (true, count) => format!("searching... {}", count),
+        (false, 1) => "1 hit".to_string(),
+        (false, count) => format!("{} hits", count),
+    };
+
+    hits.update_offset(list_area.height.saturating_sub(2) as usize);
+    let (window, mut state) = hits.window();
+
+    let items = hits.items[window]
+        .iter()
+        .map(|hit| {
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    format!("{}:{}: ", display_name(&hit.path), hit.line),
+                    Style::default().fg(app.theme.muted),
+                ),
+                Span::raw(hit.text.clone()),
+            ]))
+        })
+        .collect::<Vec<ListItem>>();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_alignment(Alignment::Center)
+                .border_style(Style::default().fg(app.theme.border)),
+        )
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .fg(app.theme.highlight),
+        )
+        .highlight_symbol("> ");
+
+    f.render_stateful_widget(list, list_area, &mut state);
+}
@@ -5,7 +5,7 @@ use ratatui::widgets::Clear;
 use ratatui::widgets::Paragraph;
 use ratatui::{
     layout::Rect,
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     widgets::{Block, Borders},
     Frame,
 };
@@ -23,7 +23,7 @@ pub fn render_help<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
             .style(Style::default().add_modifier(Modifier::BOLD))
             .border_style(
                 Style::default()
-                    .fg(Color::LightYellow)
+                    .fg(app.theme.border)
                     .add_modifier(Modifier::BOLD),
             )
             .borders(Borders::ALL)
@@ -39,27 +39,80 @@ pub fn render_help<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
 ESC | q: Quit the application.
 1: Select the Files pane.
 2: Select the Directories pane.
+3: Focus the preview, j/k and the page keys scroll it, / searches it and n/N go to the next or previous match. Esc leaves it.
+F (in the focused preview): Follow the end of the file as it grows, like tail -f.
+v: View the selected file on the whole screen, / searches, : jumps to a line, h/l scroll sideways, q closes it.
 
 j: Select the next item in the current pane.
 k: Select the previous item in the current pane.
+gg | G: Select the first or the last item, 10j or 5G with a count.
+CTRL + u | CTRL + d: Move up or down by half a page.
+PageUp | PageDown | Home | End: Move by a page, or to the first or last item.
+h | l: Up to the parent or into the selected directory, in the three column layout.
+h | l | Left | Right: Collapse or expand the selected directory, in the directory tree.
+F: List every file below the current directory in the Files pane, by its path, or go back.
+*: Show only the files matching globs or extensions (*.rs **/*.toml md), empty for all.
+< / >: Shrink or grow the preview pane.
 
-n: Create a new file or directory, depending on the current pane.
-CTRL + d: Delete the selected file or directory, (to bin).
+n: Create a new file or directory, depending on the current pane. Tab skips a matching file template.
+Delete: Delete the selected file or directory, (to bin).
 r: Rename the selected file or directory.
+L: Create a symlink to the selected file or directory.
+y | Y: Copy the selected entry's absolute path or its name to the clipboard.
+CTRL + y: Copy its path relative to the directory traverse was started in.
+C: Copy the buffer, or the selected entry, as files to paste into GUI apps.
+CTRL + v: Copy the files on the clipboard, from a GUI file manager, into the current directory.
+P: Pin or unpin the selected file at the top of the Files pane.
+i: Properties, with alternate data streams or the resource fork on Windows and macOS.
+u | CTRL + r: Undo or redo the last rename, move, creation or deletion.
 
-f: Navigate to a directory using a relative or absolute path.
-x: Extract the selected archive, to the current directory.
-w: Open fzf.
+f: Navigate to a directory using a relative or absolute path, ~ and $VARS expand and Tab completes.
+/: Type-ahead, the next letters jump to the first entry starting with them.
+x: Extract the selected archive, to the current directory. Asks for the password of encrypted zips and 7z.
+X: Compress the buffer, or the selected entry, into zip, tar.gz, tar.xz, tar.zst, 7z or tar.
+R: Export the current directory as a text, Markdown or HTML tree, to a file or the clipboard. +/- set the depth.
+I: Hide or show git ignored files.
+H: MD5, SHA1, SHA256 and BLAKE3 of the selected file, Enter copies one.
+m: Write a SHA256SUMS manifest for the buffered files, or the current directory.
+M: Verify the SHA256SUMS manifest in the current directory.
+!: Run a shell command, {} is the selected entry, {files} the buffer. A trailing & runs it in the background.
+O: Open the selected entry with one of the commands set up for it with open_with= in the config.
+Plugin commands from the Lua scripts in <config-dir>/traverse/plugins are in the palette (:), with the keys they bind.
+:connect user@host: Browse a host over SFTP (or ftp://, dav://, davs://, s3://bucket), Enter downloads, p uploads the buffer, :disconnect to leave.
+S: Drop to a shell in the current directory, exit it to come back.
+W | E: Under WSL, copy the Windows path or open with a Windows app.
+t: Switch the color theme.
+F12: Debug pane with frame times, events, caches and background jobs.
+:: Command palette, fuzzy search every action.
+T: Treemap of the disk usage below the current directory.
+K: Count the files below the current directory by kind.
+U: Disk usage of every entry in the current directory, Enter drills in.
+w: Open fzf, after jumping to a result it reopens the same results.
+BACKSPACE: Back to the fzf results after jumping to one.
 
 c: Append the selected file or directory to the move/copy buffer.
 p: Opens the move/copy buffer menu, (enter on any option is in 
             relation to your current directory).
+=: Compare the two files in the buffer, or the one in it with the selected one.
+V: The paths piped in with --from-stdin, SPACE marks or unmarks one.
+J: Running and finished jobs (Enter shows the output, CTRL + d kills one), the last copies, moves and deletes, and the scheduled jobs.
 
 b: Shows bookarks menu.
 z: Add current directory to bookmarks.
+Z: Add the selected file or directory to bookmarks.
+e: Edit the label and note of a bookmark, as 'label | note'.
 
+CTRL + o: Jump back to the previous location.
+CTRL + i | TAB: Jump forward again.
+': Shows the jump list.
+CTRL + j: Fuzzy jump to frequently and recently used directories.
+D: Shows the drives and mount points with their free space, e ejects the highlighted removable one.
+
+CTRL + g: Include or skip gitignored files in fzf.
 CTRL + n: 'Next' item in results.
-CTRL + p: 'Previous' item in results.",
+CTRL + p: 'Previous' item in results.
+ALT + arrows: Move the fzf, bookmark or report popup, with SHIFT resize it.
+ALT + 0: Put the popup back where it was.",
         );
 
         let help_para = Paragraph::new(help_text)
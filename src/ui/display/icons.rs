@@ -0,0 +1,107 @@
+use crate::ui::display::file_colors::{ARCHIVES, IMAGES};
+use std::ffi::OsStr;
+use std::fs;
+
+// nerd font glyphs need a patched font, ascii markers work everywhere
+#[derive(Clone, Copy, PartialEq)]
+pub enum IconSet {
+    None,
+    Nerd,
+    Ascii,
+}
+
+impl IconSet {
+    pub fn from_config(value: &str) -> IconSet {
+        match value.to_lowercase().as_str() {
+            "nerd" | "true" => IconSet::Nerd,
+            "ascii" => IconSet::Ascii,
+            _ => IconSet::None,
+        }
+    }
+}
+
+pub const AUDIO: [&str; 6] = ["mp3", "flac", "wav", "ogg", "m4a", "opus"];
+pub const VIDEO: [&str; 6] = ["mp4", "mkv", "webm", "avi", "mov", "wmv"];
+
+fn nerd_icon(extension: &str) -> &'static str {
+    match extension {
+        "rs" => "\u{e7a8}",
+        "py" | "ipynb" => "\u{e606}",
+        "js" | "mjs" | "cjs" => "\u{e74e}",
+        "ts" | "tsx" => "\u{e628}",
+        "go" => "\u{e626}",
+        "java" | "jar" => "\u{e738}",
+        "c" | "h" => "\u{e61e}",
+        "cpp" | "cc" | "hpp" => "\u{e61d}",
+        "html" | "htm" => "\u{e736}",
+        "css" | "scss" => "\u{e749}",
+        "md" => "\u{f48a}",
+        "json" => "\u{e60b}",
+        "toml" | "yaml" | "yml" | "ini" | "conf" => "\u{e615}",
+        "sh" | "bash" | "zsh" | "fish" => "\u{f489}",
+        "lock" => "\u{f023}",
+        "pdf" => "\u{f1c1}",
+        "txt" | "log" => "\u{f15c}",
+        ext if ARCHIVES.contains(&ext) => "\u{f410}",
+        ext if IMAGES.contains(&ext) => "\u{f1c5}",
+        ext if AUDIO.contains(&ext) => "\u{f001}",
+        ext if VIDEO.contains(&ext) => "\u{f03d}",
+        _ => "\u{f15b}",
+    }
+}
+
+// like `ls -F`: / directories, @ links, * executables, # archives,
+// ~ images and media, - everything else
+fn ascii_icon(extension: &str) -> &'static str {
+    match extension {
+        ext if ARCHIVES.contains(&ext) => "#",
+        ext if IMAGES.contains(&ext) || AUDIO.contains(&ext) || VIDEO.contains(&ext) => "~",
+        _ => "-",
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(windows)]
+fn is_executable(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+// the glyph and a space in front of an entry, empty when icons are off
+pub fn icon(path: &OsStr, set: IconSet) -> String {
+    if set == IconSet::None {
+        return String::new();
+    }
+
+    let is_symlink = fs::symlink_metadata(path)
+        .map(|metadata| metadata.file_type().is_symlink())
+        .unwrap_or(false);
+    let metadata = fs::metadata(path).ok();
+    let is_dir = metadata.as_ref().is_some_and(|metadata| metadata.is_dir());
+    let is_exec = metadata.as_ref().is_some_and(is_executable);
+
+    let extension = path
+        .to_string_lossy()
+        .trim_end_matches('/')
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_lowercase())
+        .unwrap_or_default();
+
+    let glyph = match (set, is_symlink, is_dir, is_exec) {
+        (IconSet::Nerd, true, _, _) => "\u{f0c1}",
+        (IconSet::Nerd, _, true, _) => "\u{f115}",
+        (IconSet::Nerd, _, _, true) => "\u{f489}",
+        (IconSet::Nerd, ..) => nerd_icon(&extension),
+        (_, true, _, _) => "@",
+        (_, _, true, _) => "/",
+        (_, _, _, true) => "*",
+        _ => ascii_icon(&extension),
+    };
+
+    format!("{} ", glyph)
+}
@@ -0,0 +1,130 @@
+use exif::{In, Reader, Tag};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+// the EXIF fields worth showing, a line each group as the pane is short:
+// when and with what, then the exposure
+const FIELDS: [&[(Tag, &str)]; 2] = [
+    &[
+        (Tag::DateTimeOriginal, "Taken "),
+        (Tag::Model, ""),
+        (Tag::LensModel, ""),
+    ],
+    &[
+        (Tag::FocalLength, ""),
+        (Tag::FNumber, ""),
+        (Tag::ExposureTime, ""),
+        (Tag::PhotographicSensitivity, "ISO "),
+    ],
+];
+
+fn channels(count: u8) -> &'static str {
+    match count {
+        1 => "grayscale",
+        2 => "grayscale + alpha",
+        3 => "RGB",
+        4 => "RGBA",
+        _ => "channels",
+    }
+}
+
+// bits per sample and the color model, from the first bytes of the file
+fn color_depth(path: &Path) -> Option<String> {
+    let mut header = vec![];
+    File::open(path)
+        .ok()?
+        .take(64 * 1024)
+        .read_to_end(&mut header)
+        .ok()?;
+
+    match header.as_slice() {
+        [0x89, b'P', b'N', b'G', ..] if header.len() > 25 => {
+            let depth = header[24];
+            let color = match header[25] {
+                0 => "grayscale",
+                2 => "RGB",
+                3 => "palette",
+                4 => "grayscale + alpha",
+                6 => "RGBA",
+                _ => return None,
+            };
+            Some(format!("{}-bit {}", depth, color))
+        }
+        [b'G', b'I', b'F', ..] if header.len() > 10 => {
+            let bits = (header[10] & 0x07) + 1;
+            Some(format!("palette, up to {} colors", 1u32 << bits))
+        }
+        [b'B', b'M', ..] if header.len() > 29 => {
+            let bits = u16::from_le_bytes([header[28], header[29]]);
+            Some(format!("{}-bit", bits))
+        }
+        // the frame header (SOFn) carries the precision and components
+        [0xFF, 0xD8, ..] => {
+            let mut at = 2;
+            while at + 9 < header.len() {
+                if header[at] != 0xFF {
+                    return None;
+                }
+                let marker = header[at + 1];
+                let length = u16::from_be_bytes([header[at + 2], header[at + 3]]) as usize;
+                if (0xC0..=0xCF).contains(&marker) && ![0xC4, 0xC8, 0xCC].contains(&marker) {
+                    let precision = header[at + 4];
+                    let components = header[at + 9];
+                    let model = match components {
+                        1 => "grayscale",
+                        3 => "YCbCr",
+                        4 => "CMYK",
+                        count => channels(count),
+                    };
+                    return Some(format!("{}-bit {}", precision, model));
+                }
+                at += 2 + length;
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+// dimensions, color depth and the camera's EXIF data, for the Details pane
+pub fn image_lines(path: &Path) -> Vec<String> {
+    let mut lines = vec![];
+
+    let mut format = match imagesize::size(path) {
+        Ok(size) => format!("{} x {}", size.width, size.height),
+        Err(_) => return lines,
+    };
+    if let Some(depth) = color_depth(path) {
+        format.push_str(&format!(", {}", depth));
+    }
+    lines.push(format);
+
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(_) => return lines,
+    };
+    let exif = match Reader::new().read_from_container(&mut BufReader::new(file)) {
+        Ok(exif) => exif,
+        Err(_) => return lines,
+    };
+
+    for group in FIELDS {
+        let values = group
+            .iter()
+            .filter_map(|(tag, label)| {
+                let field = exif.get_field(*tag, In::PRIMARY)?;
+                let value = field.display_value().with_unit(&exif).to_string();
+                Some(format!("{}{}", label, value.trim_matches('"')))
+            })
+            .collect::<Vec<String>>();
+        if !values.is_empty() {
+            lines.push(values.join("  "));
+        }
+    }
+    if exif.get_field(Tag::GPSLatitude, In::PRIMARY).is_some() {
+        lines.push("Has a GPS location".to_string());
+    }
+
+    lines
+}
@@ -0,0 +1,95 @@
+use crossterm::{
+    cursor::{self, MoveTo},
+    execute,
+    terminal::{self, Clear, ClearType},
+};
+use ratatui::{backend::Backend, buffer::Cell, layout::Rect};
+use std::io::{self, Write};
+
+pub const MIN_HEIGHT: u16 = 10;
+
+// makes room for the UI below the cursor, scrolling the terminal if the
+// cursor is too close to the bottom, and returns the row the UI starts at
+pub fn reserve<W: Write>(output: &mut W, height: u16) -> io::Result<u16> {
+    let (_, rows) = terminal::size()?;
+    let height = height.min(rows);
+
+    output.write_all("\n".repeat(height.saturating_sub(1) as usize).as_bytes())?;
+    output.flush()?;
+
+    let (_, row) = cursor::position()?;
+    Ok(row.saturating_sub(height.saturating_sub(1)))
+}
+
+// draws the whole UI as if it had the terminal to itself, shifted down to
+// the reserved lines, so the rest of the screen and the scrollback stay put
+pub struct InlineBackend<B: Backend + Write> {
+    inner: B,
+    top: u16,
+    height: u16,
+}
+
+impl<B: Backend + Write> InlineBackend<B> {
+    pub fn new(inner: B, top: u16, height: u16) -> InlineBackend<B> {
+        InlineBackend { inner, top, height }
+    }
+}
+
+impl<B: Backend + Write> Backend for InlineBackend<B> {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        let top = self.top;
+        self.inner
+            .draw(content.map(|(x, y, cell)| (x, y + top, cell)))
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        self.inner.hide_cursor()
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        self.inner.show_cursor()
+    }
+
+    fn get_cursor(&mut self) -> io::Result<(u16, u16)> {
+        let (x, y) = self.inner.get_cursor()?;
+        Ok((x, y.saturating_sub(self.top)))
+    }
+
+    fn set_cursor(&mut self, x: u16, y: u16) -> io::Result<()> {
+        self.inner.set_cursor(x, y + self.top)
+    }
+
+    // only the reserved lines, and the cursor is left where they start
+    fn clear(&mut self) -> io::Result<()> {
+        execute!(
+            self.inner,
+            MoveTo(0, self.top),
+            Clear(ClearType::FromCursorDown)
+        )
+    }
+
+    fn size(&self) -> io::Result<Rect> {
+        let size = self.inner.size()?;
+        let height = self.height.min(size.height.saturating_sub(self.top));
+
+        Ok(Rect::new(0, 0, size.width, height))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Backend::flush(&mut self.inner)
+    }
+}
+
+// raw escape sequences, like toggling mouse capture, go straight through
+impl<B: Backend + Write> Write for InlineBackend<B> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Write::flush(&mut self.inner)
+    }
+}
@@ -1,22 +1,80 @@
-use crate::app::app::App;
+use crate::app::app::{display_name, App};
+use crate::configuration::templates::template_for;
+use crate::ui::input::run_app::Command;
+use crate::ui::input::secret;
 use ratatui::backend::Backend;
 use ratatui::layout::Alignment;
 use ratatui::widgets::{Clear, Paragraph};
 use ratatui::{
     layout::Rect,
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     widgets::{Block, Borders},
     Frame,
 };
 
 pub fn render_input<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect, input: &mut String) {
     if app.show_popup {
+        let shell = app.last_command == Some(Command::Shell);
+        let password = secret::is_secret(app.last_command.as_ref());
+
         let block = Block::default()
             .title("Name")
             .borders(Borders::ALL)
             .title_alignment(Alignment::Center);
 
-        let input_box_width = 30;
+        // a password only ever shows as how long it is
+        let text = secret::masked(app, input);
+        let mut title = if shell {
+            "Shell command ({} = entry, {files} = move/copy buffer)".to_string()
+        } else if app.last_command == Some(Command::CreateFile) {
+            match template_for(input) {
+                Some(template) if app.use_template => format!(
+                    "From template {} (Tab: empty)",
+                    template.file_name().unwrap_or_default().to_string_lossy()
+                ),
+                Some(_) => "Empty file (Tab: from template)".to_string(),
+                None => "Input".to_string(),
+            }
+        } else if app.last_command == Some(Command::FilterFiles) {
+            "Show files matching (*.rs **/*.toml, empty for all)".to_string()
+        } else if app.last_command == Some(Command::PreviewSearch) {
+            "Search the preview".to_string()
+        } else if app.last_command == Some(Command::ConflictName) {
+            "Name it (Esc cancels the whole operation)".to_string()
+        } else if app.last_command == Some(Command::Compress) {
+            "Archive name".to_string()
+        } else if app.last_command == Some(Command::Connect) {
+            "Connect to (user@host for SFTP, ftp://, dav://, davs://host or s3://bucket)".to_string()
+        } else if app.last_command == Some(Command::SudoPassword) {
+            "Password for sudo".to_string()
+        } else if app.last_command == Some(Command::ArchivePassword) {
+            match app.compress_format {
+                Some(format) if format.single_file() => "Passphrase".to_string(),
+                _ => "Password (empty for none)".to_string(),
+            }
+        } else if let Some(pending) = app.pending_connect.as_ref().filter(|_| password) {
+            format!("Password for {}", pending.target)
+        } else if password {
+            format!(
+                "Password for {}",
+                app.password_for
+                    .as_deref()
+                    .map_or("the archive".to_string(), display_name)
+            )
+        } else {
+            "Input".to_string()
+        };
+
+        if password {
+            title.push_str(secret::hint(app));
+        }
+
+        // commands get long, give them most of the width
+        let input_box_width = if shell {
+            (size.width * 3 / 4).max(30)
+        } else {
+            (title.chars().count() as u16 + 4).max(30).min(size.width)
+        };
         let input_box_height = 3;
         let input_box_x = (size.width - input_box_width) / 4 + 3;
         let input_box_y = (size.height - input_box_height) / 1;
@@ -26,13 +84,13 @@ pub fn render_input<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect, inp
         f.render_widget(Clear, area);
         f.render_widget(block, area);
 
-        let input_box = Paragraph::new(input.clone())
+        let input_box = Paragraph::new(text)
             .style(Style::default())
             .block(
                 Block::default()
-                    .title("Input")
+                    .title(title)
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::LightBlue)),
+                    .border_style(Style::default().fg(app.theme.focus)),
             )
             .style(Style::default().add_modifier(Modifier::BOLD))
             .alignment(Alignment::Left);
@@ -0,0 +1,67 @@
+use crate::app::app::App;
+use crate::ui::input::nav::abbreviate_path;
+use ratatui::backend::Backend;
+use ratatui::layout::Alignment;
+use ratatui::widgets::Clear;
+use ratatui::widgets::ListItem;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    widgets::{Block, Borders, List},
+    Frame,
+};
+
+pub fn render_jumps<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
+    if app.show_jumps {
+        let block_width = f.size().width / 3;
+        let block_height = f.size().height / 3;
+        let block_x = (size.width - block_width) / 2;
+        let block_y = (size.height - block_height) / 2;
+
+        let area = Rect::new(block_x, block_y, block_width, block_height);
+
+        let jumps_block = Block::default()
+            .style(Style::default().add_modifier(Modifier::BOLD))
+            .border_style(
+                Style::default()
+                    .fg(app.theme.border)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .borders(Borders::ALL)
+            .title_alignment(Alignment::Center);
+
+        f.render_widget(Clear, area);
+        f.render_widget(jumps_block, area);
+
+        // the entry we'd return to with ctrl+o / ctrl+i is marked with '*'
+        let jumps_text = app
+            .jump_list
+            .items
+            .iter()
+            .enumerate()
+            .map(|(idx, path)| {
+                let marker = if idx == app.jump_index { "*" } else { " " };
+                ListItem::new(format!("{}{:>3}  {}", marker, idx, abbreviate_path(path)))
+            })
+            .collect::<Vec<ListItem>>();
+
+        let jumps_list = List::new(jumps_text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Jump List")
+                    .title_alignment(Alignment::Center),
+            )
+            .highlight_style(
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .fg(app.theme.highlight),
+            )
+            .highlight_symbol("> ");
+
+        let jumps_list_area =
+            Rect::new(block_x + 1, block_y + 1, block_width - 2, block_height - 2);
+
+        f.render_stateful_widget(jumps_list, jumps_list_area, &mut app.jump_list.state);
+    }
+}
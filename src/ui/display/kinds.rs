@@ -0,0 +1,45 @@
+use crate::app::app::App;
+use ratatui::backend::Backend;
+use ratatui::layout::Alignment;
+use ratatui::widgets::Clear;
+use ratatui::widgets::ListItem;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    widgets::{Block, Borders, List},
+    Frame,
+};
+
+pub fn render_kinds<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
+    if app.show_kinds {
+        let block_width = (size.width / 2).max(40).min(size.width);
+        let block_height = size.height / 2;
+        let block_x = (size.width - block_width) / 2;
+        let block_y = (size.height - block_height) / 2;
+
+        let area = Rect::new(block_x, block_y, block_width, block_height);
+
+        f.render_widget(Clear, area);
+
+        let kinds_text = app
+            .kinds
+            .items
+            .iter()
+            .map(|line| ListItem::new(line.clone()))
+            .collect::<Vec<ListItem>>();
+
+        let kinds_list = List::new(kinds_text).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("What's in {}", app.cur_dir.trim_end()))
+                .border_style(
+                    Style::default()
+                        .fg(app.theme.border)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .title_alignment(Alignment::Center),
+        );
+
+        f.render_stateful_widget(kinds_list, area, &mut app.kinds.state);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::app::app::App;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum PaneLayout {
+    // the preview on the left, files above directories on the right
+    Split,
+    // like ranger: the parent directory, the current one as a single list
+    // and the preview
+    Miller,
+}
+
+impl PaneLayout {
+    // `layout=` in the config
+    pub fn from_config(value: &str) -> PaneLayout {
+        match value {
+            "miller" | "columns" => PaneLayout::Miller,
+            _ => PaneLayout::Split,
+        }
+    }
+
+    pub fn config_name(self) -> &'static str {
+        match self {
+            PaneLayout::Split => "split",
+            PaneLayout::Miller => "miller",
+        }
+    }
+}
+
+// where each pane goes, files and dirs are the same area when the directory
+// is shown as one list
+pub struct Areas {
+    pub preview: Rect,
+    pub files: Rect,
+    pub dirs: Rect,
+    pub parent: Option<Rect>,
+    pub details: Rect,
+}
+
+pub fn areas(app: &App, size: Rect) -> Areas {
+    let preview_width = (size.width as u32 * app.preview_width as u32 / 100) as u16;
+    let ninety_percent = (size.height as f32 * 0.9) as u16;
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(ninety_percent), Constraint::Min(1)])
+        .split(size);
+
+    let details = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(size.width / 2)])
+        .split(rows[1])[0];
+
+    match app.layout {
+        PaneLayout::Split => {
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Length(preview_width), Constraint::Min(1)])
+                .split(size);
+
+            let preview = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(ninety_percent), Constraint::Min(1)])
+                .split(chunks[0])[0];
+
+            let lists = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Percentage(app.files_height),
+                    Constraint::Percentage(90 - app.files_height),
+                    Constraint::Percentage(10),
+                ])
+                .split(chunks[1]);
+
+            let (files, dirs) = if app.unified_listing {
+                let listing = lists[0].union(lists[1]);
+                (listing, listing)
+            } else {
+                (lists[0], lists[1])
+            };
+
+            Areas {
+                preview,
+                files,
+                dirs,
+                parent: None,
+                details,
+            }
+        }
+        PaneLayout::Miller => {
+            // the parent gets a third of what the preview leaves
+            let lists_width = size.width.saturating_sub(preview_width);
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Length(lists_width / 3),
+                    Constraint::Length(lists_width - lists_width / 3),
+                    Constraint::Min(1),
+                ])
+                .split(rows[0]);
+
+            Areas {
+                preview: chunks[2],
+                files: chunks[1],
+                dirs: chunks[1],
+                parent: Some(chunks[0]),
+                details,
+            }
+        }
+    }
+}
@@ -0,0 +1,368 @@
+use crate::configuration::theme::Theme;
+use crate::ui::display::icons::{AUDIO, VIDEO};
+use crate::ui::display::pane::convert_bytes;
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Span, Spans},
+};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+// what's worth knowing about a media file, from symphonia or ffprobe
+#[derive(Clone, Default)]
+struct Info {
+    container: String,
+    // seconds
+    duration: Option<f64>,
+    // bits per second
+    bitrate: Option<u64>,
+    // "Video: h264, 1920x1080, 23.98 fps", one per track
+    streams: Vec<String>,
+    tags: Vec<(String, String)>,
+}
+
+// the file, when it was modified and what was found in it
+type Probed = (PathBuf, Option<SystemTime>, Option<Info>);
+
+// the last file looked at, the preview is drawn every frame and asking
+// ffprobe that often would be slow
+static LAST: Mutex<Option<Probed>> = Mutex::new(None);
+
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.parse().ok(),
+        _ => None,
+    }
+}
+
+// "24000/1001" frames per second
+fn rate(value: &Value) -> Option<f64> {
+    let (numer, denom) = value.as_str()?.split_once('/')?;
+    let (numer, denom) = (numer.parse::<f64>().ok()?, denom.parse::<f64>().ok()?);
+    (denom > 0.0 && numer > 0.0).then(|| numer / denom)
+}
+
+fn channels(count: u64) -> String {
+    match count {
+        1 => "mono".to_string(),
+        2 => "stereo".to_string(),
+        count => format!("{} channels", count),
+    }
+}
+
+fn ffprobe(path: &Path) -> Option<Info> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "quiet",
+            "-print_format",
+            "json",
+            "-show_format",
+            "-show_streams",
+        ])
+        .arg(path)
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let probed: Value = serde_json::from_slice(&output.stdout).ok()?;
+    let format = &probed["format"];
+
+    let mut info = Info {
+        container: format["format_long_name"]
+            .as_str()
+            .or(format["format_name"].as_str())
+            .unwrap_or_default()
+            .to_string(),
+        duration: number(&format["duration"]),
+        bitrate: number(&format["bit_rate"]).map(|rate| rate as u64),
+        ..Info::default()
+    };
+
+    for stream in probed["streams"].as_array().into_iter().flatten() {
+        let codec = stream["codec_name"].as_str().unwrap_or("unknown");
+        let mut parts = vec![codec.to_string()];
+        let kind = match stream["codec_type"].as_str() {
+            Some("video") => {
+                // cover art comes as a one frame video stream
+                if stream["disposition"]["attached_pic"].as_u64() == Some(1) {
+                    continue;
+                }
+                if let (Some(width), Some(height)) =
+                    (stream["width"].as_u64(), stream["height"].as_u64())
+                {
+                    parts.push(format!("{}x{}", width, height));
+                }
+                if let Some(fps) = rate(&stream["avg_frame_rate"]) {
+                    parts.push(format!("{:.2} fps", fps));
+                }
+                "Video"
+            }
+            Some("audio") => {
+                if let Some(sample_rate) = number(&stream["sample_rate"]) {
+                    parts.push(format!("{} Hz", sample_rate));
+                }
+                if let Some(count) = stream["channels"].as_u64() {
+                    parts.push(channels(count));
+                }
+                "Audio"
+            }
+            Some("subtitle") => "Subtitles",
+            _ => continue,
+        };
+        if let Some(bitrate) = number(&stream["bit_rate"]) {
+            parts.push(format!("{} kb/s", bitrate as u64 / 1000));
+        }
+        if let Some(language) = stream["tags"]["language"].as_str() {
+            parts.push(language.to_string());
+        }
+        info.streams.push(format!("{}: {}", kind, parts.join(", ")));
+    }
+
+    if let Some(tags) = format["tags"].as_object() {
+        for (key, value) in tags {
+            if let Some(value) = value.as_str() {
+                info.tags.push((key.to_lowercase(), value.to_string()));
+            }
+        }
+    }
+
+    Some(info)
+}
+
+// audio (and the audio of mp4 and mkv) without anything installed
+#[cfg(feature = "media")]
+fn symphonia(path: &Path) -> Option<Info> {
+    use symphonia::core::formats::FormatOptions;
+    use symphonia::core::io::MediaSourceStream;
+    use symphonia::core::meta::{MetadataOptions, StandardTagKey};
+    use symphonia::core::probe::Hint;
+
+    let file = std::fs::File::open(path).ok()?;
+    let size = file.metadata().ok()?.len();
+    let source = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(extension) = path.extension() {
+        hint.with_extension(&extension.to_string_lossy());
+    }
+
+    let mut probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            source,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .ok()?;
+
+    let mut info = Info {
+        container: path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_uppercase())
+            .unwrap_or_default(),
+        ..Info::default()
+    };
+
+    for track in probed.format.tracks() {
+        let params = &track.codec_params;
+        let codec = symphonia::default::get_codecs()
+            .get_codec(params.codec)
+            .map(|codec| codec.short_name)
+            .unwrap_or("unknown");
+
+        let mut parts = vec![codec.to_string()];
+        if let Some(sample_rate) = params.sample_rate {
+            parts.push(format!("{} Hz", sample_rate));
+        }
+        if let Some(channels) = params.channels {
+            parts.push(self::channels(channels.count() as u64));
+        }
+        if let Some(bits) = params.bits_per_sample {
+            parts.push(format!("{} bit", bits));
+        }
+        info.streams.push(format!("Audio: {}", parts.join(", ")));
+
+        if info.duration.is_none() {
+            if let (Some(time_base), Some(frames)) = (params.time_base, params.n_frames) {
+                let time = time_base.calc_time(frames);
+                info.duration = Some(time.seconds as f64 + time.frac);
+            }
+        }
+    }
+
+    // the whole file over its length is close enough for a preview
+    info.bitrate = info
+        .duration
+        .filter(|duration| *duration > 0.0)
+        .map(|duration| (size as f64 * 8.0 / duration) as u64);
+
+    // tags ahead of the stream (ID3) and in the container (Vorbis comments)
+    let mut revisions = vec![];
+    if let Some(metadata) = probed.metadata.get() {
+        if let Some(revision) = metadata.current() {
+            revisions.push(revision.tags().to_vec());
+        }
+    }
+    if let Some(revision) = probed.format.metadata().current() {
+        revisions.push(revision.tags().to_vec());
+    }
+    for tag in revisions.into_iter().flatten() {
+        let key = match tag.std_key {
+            Some(StandardTagKey::TrackTitle) => "title".to_string(),
+            Some(key) => format!("{:?}", key).to_lowercase(),
+            None => tag.key.to_lowercase(),
+        };
+        info.tags.push((key, tag.value.to_string()));
+    }
+
+    Some(info)
+}
+
+#[cfg(not(feature = "media"))]
+fn symphonia(_path: &Path) -> Option<Info> {
+    None
+}
+
+fn probe(path: &Path, video: bool) -> Option<Info> {
+    // only ffprobe knows about video, symphonia reads audio alone
+    if video {
+        ffprobe(path).or_else(|| symphonia(path))
+    } else {
+        symphonia(path).or_else(|| ffprobe(path))
+    }
+}
+
+fn duration(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    if seconds >= 3600 {
+        format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+// the tags people look for first, the rest after them
+const TAG_ORDER: [&str; 8] = [
+    "title",
+    "artist",
+    "album",
+    "albumartist",
+    "date",
+    "tracknumber",
+    "genre",
+    "comment",
+];
+
+// whether it's a video and what was found in it, probed once per change.
+// None when it's neither audio nor video
+fn probed(path: &Path) -> Option<(bool, Option<Info>)> {
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let video = VIDEO.contains(&extension.as_str());
+    if !video && !AUDIO.contains(&extension.as_str()) {
+        return None;
+    }
+
+    let modified = path
+        .metadata()
+        .and_then(|metadata| metadata.modified())
+        .ok();
+    let mut last = LAST.lock().unwrap_or_else(|e| e.into_inner());
+    let info = match last.as_ref() {
+        Some((probed, at, info)) if probed == path && *at == modified => info.clone(),
+        _ => {
+            let info = probe(path, video);
+            *last = Some((path.to_path_buf(), modified, info.clone()));
+            info
+        }
+    };
+
+    Some((video, info))
+}
+
+fn heading(video: bool, info: &Info) -> String {
+    let kind = if video { "Video" } else { "Audio" };
+    let mut heading = format!("{}: {}", kind, info.container);
+    if let Some(seconds) = info.duration {
+        heading.push_str(&format!(", {}", duration(seconds)));
+    }
+    if let Some(bitrate) = info.bitrate {
+        heading.push_str(&format!(", {} kb/s", bitrate / 1000));
+    }
+    heading
+}
+
+// the details pane's lines for an audio or video file, from the same probe
+// as the preview
+pub fn media_details(path: &Path) -> Option<Vec<String>> {
+    let (video, info) = probed(path)?;
+
+    Some(match info {
+        Some(info) => {
+            let mut lines = vec![heading(video, &info)];
+            lines.extend(info.streams);
+            lines
+        }
+        None => vec!["Media details not available".to_string()],
+    })
+}
+
+// duration, tracks and tags of audio and video files
+pub fn media_lines(path: &Path, theme: &Theme, si: bool) -> Option<Vec<Spans<'static>>> {
+    let (video, info) = probed(path)?;
+
+    let title = Style::default()
+        .fg(theme.border)
+        .add_modifier(Modifier::BOLD);
+    let kind = if video { "Video" } else { "Audio" };
+    let info = match info {
+        Some(info) => info,
+        None => {
+            return Some(vec![
+                Spans::from(Span::styled(format!("{} file", kind), title)),
+                Spans::from("Install ffprobe (part of ffmpeg) to see what's in it"),
+            ])
+        }
+    };
+
+    let mut lines = vec![Spans::from(Span::styled(heading(video, &info), title))];
+    if let Ok(metadata) = path.metadata() {
+        lines.push(Spans::from(format!(
+            "Size: {}",
+            convert_bytes(metadata.len(), si)
+        )));
+    }
+
+    lines.push(Spans::from(""));
+    lines.extend(info.streams.into_iter().map(Spans::from));
+
+    let mut tags = info.tags;
+    tags.sort_by_key(|(key, _)| {
+        let known = TAG_ORDER.iter().position(|known| known == key);
+        (known.unwrap_or(TAG_ORDER.len()), key.clone())
+    });
+    tags.dedup_by(|a, b| a.0 == b.0);
+    if !tags.is_empty() {
+        lines.push(Spans::from(""));
+        lines.push(Spans::from(Span::styled("Tags", title)));
+        for (key, value) in tags {
+            let value = value.replace('\n', " ");
+            lines.push(Spans::from(format!("  {}: {}", key, value)));
+        }
+    }
+
+    Some(lines)
+}
@@ -0,0 +1,117 @@
+use crate::app::app::App;
+use crate::app::metrics::threads;
+use crate::ui::input::jobs;
+use crate::ui::input::paging::Pages;
+use crate::ui::input::schedule::running;
+use ratatui::backend::Backend;
+use ratatui::widgets::Clear;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use std::time::Duration;
+
+const WIDTH: u16 = 46;
+
+fn ms(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+}
+
+// live internals in the top right corner, drawn over everything but not
+// taking the keys, so the slow part can be watched while it's slow
+pub fn render_metrics<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
+    if !app.show_metrics {
+        return;
+    }
+
+    let metrics = &app.metrics;
+
+    let jobs = [
+        ("checksums", app.checksum_job.is_some()),
+        ("hash", app.hash_job.is_some()),
+        ("diagnostics", app.diagnostics_job.is_some()),
+        ("kinds", app.kinds_job.is_some()),
+        ("disk usage", app.du_job.is_some()),
+        ("flat listing", app.flat_job.is_some()),
+        (
+            "paged listing",
+            app.file_pages
+                .iter()
+                .chain(&app.dir_pages)
+                .any(Pages::reading),
+        ),
+        ("scheduled", running(app)),
+        ("background", jobs::running(app) > 0),
+    ]
+    .iter()
+    .filter(|(_, running)| *running)
+    .map(|(name, _)| *name)
+    .collect::<Vec<&str>>();
+
+    let lines = [
+        format!("Frames:    {}", metrics.frames),
+        format!(
+            "Frame:     {} last, {} avg",
+            ms(metrics.last_frame),
+            ms(metrics.average_frame)
+        ),
+        format!("Slowest:   {} lately", ms(metrics.slowest_frame)),
+        format!("Preview:   {} to render", ms(metrics.last_preview)),
+        format!(
+            "Events:    {}, {} queued behind a frame",
+            metrics.events, metrics.queued_events
+        ),
+        format!(
+            "Git cache: {}% hits ({} / {})",
+            metrics.git_hit_rate(),
+            metrics.git_hits,
+            metrics.git_hits + metrics.git_misses
+        ),
+        format!(
+            "Dir cache: {}% hits ({} / {})",
+            metrics.listing_hit_rate(),
+            metrics.listing_hits,
+            metrics.listing_hits + metrics.listing_misses
+        ),
+        format!("Listing:   {} to read", ms(metrics.last_listing)),
+        match &app.watcher {
+            Some(watcher) => format!(
+                "Watcher:   {} events, {} refreshes",
+                watcher.events, metrics.watcher_refreshes
+            ),
+            None => "Watcher:   not running".to_string(),
+        },
+        format!(
+            "Jobs:      {}",
+            if jobs.is_empty() {
+                "none".to_string()
+            } else {
+                jobs.join(", ")
+            }
+        ),
+        match threads() {
+            Some(threads) => format!("Threads:   {}", threads),
+            None => "Threads:   unknown".to_string(),
+        },
+    ];
+
+    let width = WIDTH.min(size.width);
+    let height = (lines.len() as u16 + 2).min(size.height);
+    let area = Rect::new(size.width - width, 0, width, height);
+
+    let paragraph = Paragraph::new(lines.join("\n")).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Metrics (F12)")
+            .border_style(
+                Style::default()
+                    .fg(app.theme.border)
+                    .add_modifier(Modifier::BOLD),
+            ),
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
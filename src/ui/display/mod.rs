@@ -1,11 +1,44 @@
 pub mod bookmarks;
+pub mod compress;
+pub mod compressed;
 pub mod contents;
 pub mod details;
+pub mod diff;
+pub mod export;
+pub mod du;
+pub mod file_colors;
 pub mod files_dirs;
+pub mod frecent;
+pub mod grep;
 pub mod inputs;
+pub mod jumps;
+pub mod metrics;
+pub mod kinds;
+pub mod layout;
+pub mod media;
+pub mod mounts;
 pub mod navs;
+pub mod notebook;
+pub mod open_with;
+pub mod palette;
+pub mod pager;
 pub mod pane;
+pub mod piped;
+pub mod preview;
 pub mod render;
+pub mod report;
+pub mod status;
+pub mod text_window;
+pub mod themes;
 pub mod help;
+pub mod icons;
+pub mod image_info;
+pub mod inline;
 pub mod block;
 pub mod ops;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+#[cfg(feature = "pdf")]
+pub mod pdf;
+pub mod treemap;
+pub mod zip_index;
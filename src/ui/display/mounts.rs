@@ -0,0 +1,61 @@
+use crate::app::app::App;
+use ratatui::backend::Backend;
+use ratatui::layout::Alignment;
+use ratatui::widgets::Clear;
+use ratatui::widgets::ListItem;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    widgets::{Block, Borders, List},
+    Frame,
+};
+
+pub fn render_mounts<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
+    if app.show_mounts {
+        let block_width = f.size().width * 2 / 3;
+        let block_height = f.size().height / 3;
+        let block_x = (size.width - block_width) / 2;
+        let block_y = (size.height - block_height) / 2;
+
+        let area = Rect::new(block_x, block_y, block_width, block_height);
+
+        let mounts_block = Block::default()
+            .style(Style::default().add_modifier(Modifier::BOLD))
+            .border_style(
+                Style::default()
+                    .fg(app.theme.border)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .borders(Borders::ALL)
+            .title_alignment(Alignment::Center);
+
+        f.render_widget(Clear, area);
+        f.render_widget(mounts_block, area);
+
+        let mounts_text = app
+            .mounts
+            .items
+            .iter()
+            .map(|(_, line)| ListItem::new(line.clone()))
+            .collect::<Vec<ListItem>>();
+
+        let mounts_list = List::new(mounts_text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Mounts")
+                    .title_alignment(Alignment::Center),
+            )
+            .highlight_style(
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .fg(app.theme.highlight),
+            )
+            .highlight_symbol("> ");
+
+        let mounts_list_area =
+            Rect::new(block_x + 1, block_y + 1, block_width - 2, block_height - 2);
+
+        f.render_stateful_widget(mounts_list, mounts_list_area, &mut app.mounts.state);
+    }
+}
@@ -1,11 +1,13 @@
-use crate::app::app::App;
+use crate::app::app::{display_name, App};
+use crate::ui::input::fzf_backend;
+use crate::ui::input::popups::{self, Popup};
 use ratatui::backend::Backend;
 use ratatui::layout::Alignment;
 use ratatui::widgets::ListItem;
 use ratatui::widgets::{Clear, Paragraph};
 use ratatui::{
     layout::Rect,
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     widgets::{Block, Borders, List},
     Frame,
 };
@@ -22,9 +24,10 @@ pub fn render_navigator<B: Backend>(
             .borders(Borders::ALL)
             .title_alignment(Alignment::Center);
 
-        let input_box_width = 30;
+        let input_box_width = (input.chars().count() as u16 + 4).max(30).min(size.width);
         let input_box_height = 3;
-        let input_box_x = (size.width - input_box_width) / 4 + 3;
+        let input_box_x =
+            ((size.width - input_box_width) / 4 + 3).min(size.width - input_box_width);
         let input_box_y = (size.height - input_box_height) / 1;
 
         let area = Rect::new(input_box_x, input_box_y, input_box_width, input_box_height);
@@ -37,14 +40,70 @@ pub fn render_navigator<B: Backend>(
             .block(Block::default().title("Navigator").borders(Borders::ALL))
             .style(
                 Style::default()
-                    .fg(Color::LightBlue)
+                    .fg(app.theme.focus)
                     .add_modifier(Modifier::BOLD),
             )
             .alignment(Alignment::Left);
         f.render_widget(input_box, area);
+
+        render_completions(f, app, area, size);
     }
 }
 
+const MAX_COMPLETIONS: usize = 8;
+
+// the directories Tab completes to, under the input or above it when the
+// input sits at the bottom
+fn render_completions<B: Backend>(f: &mut Frame<B>, app: &App, input_area: Rect, size: Rect) {
+    if app.nav_completions.is_empty() {
+        return;
+    }
+
+    let shown = app.nav_completions.len().min(MAX_COMPLETIONS);
+    let height = (shown as u16 + 2).min(size.height);
+    let below = input_area.y + input_area.height;
+    let y = if below + height <= size.height {
+        below
+    } else {
+        input_area.y.saturating_sub(height)
+    };
+    let area = Rect::new(input_area.x, y, input_area.width, height);
+
+    let mut items = app
+        .nav_completions
+        .iter()
+        .take(MAX_COMPLETIONS)
+        .map(|completion| {
+            // just the last component, the input shows the rest
+            let name = completion
+                .trim_end_matches('/')
+                .rsplit('/')
+                .next()
+                .unwrap_or(completion);
+            ListItem::new(format!("{}/", name))
+        })
+        .collect::<Vec<ListItem>>();
+
+    let more = app.nav_completions.len() - shown;
+    if more > 0 {
+        items.pop();
+        items.push(
+            ListItem::new(format!("... {} more", more + 1))
+                .style(Style::default().fg(app.theme.muted)),
+        );
+    }
+
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Tab to complete")
+            .border_style(Style::default().fg(app.theme.border)),
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(list, area);
+}
+
 pub fn render_fzf<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
     if app.show_fzf {
         let block_width = f.size().width / 1;
@@ -52,14 +111,28 @@ pub fn render_fzf<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
         let block_x = (size.width - block_width) / 2;
         let block_y = (size.height - block_height) / 2;
 
-        let area = Rect::new(block_x, block_y, block_width, block_height);
+        let area = popups::area(
+            app,
+            Popup::Fzf,
+            Rect::new(block_x, block_y, block_width, block_height),
+            size,
+        );
+
+        let mut title = if app.fzf_respect_ignore {
+            "FZF (skipping gitignored, CTRL + g to include)".to_string()
+        } else {
+            "FZF (including gitignored, CTRL + g to skip)".to_string()
+        };
+        if fzf_backend::streaming(app) {
+            title.push_str(&format!(" listing... {}", app.fzf_backend.files.len()));
+        }
 
         let results_block = Block::default()
             .style(Style::default().add_modifier(Modifier::BOLD))
-            .title("FZF")
+            .title(title)
             .border_style(
                 Style::default()
-                    .fg(Color::LightYellow)
+                    .fg(app.theme.border)
                     .add_modifier(Modifier::BOLD),
             )
             .borders(Borders::ALL)
@@ -68,11 +141,14 @@ pub fn render_fzf<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
         f.render_widget(Clear, area);
         f.render_widget(results_block, area);
 
-        let results_text = app
-            .fzf_results
-            .items
+        let results_list_area = Rect::new(area.x + 1, area.y + 1, area.width - 2, area.height - 2);
+        app.fzf_results
+            .update_offset(results_list_area.height.saturating_sub(2) as usize);
+        let (window, mut state) = app.fzf_results.window();
+
+        let results_text = app.fzf_results.items[window]
             .iter()
-            .map(|i| ListItem::new(i.clone()))
+            .map(|path| ListItem::new(display_name(path)))
             .collect::<Vec<ListItem>>();
 
         let results_list = List::new(results_text)
@@ -85,13 +161,10 @@ pub fn render_fzf<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
             .highlight_style(
                 Style::default()
                     .add_modifier(Modifier::BOLD)
-                    .fg(Color::LightGreen),
+                    .fg(app.theme.highlight),
             )
             .highlight_symbol("> ");
 
-        let results_list_area =
-            Rect::new(block_x + 1, block_y + 1, block_width - 2, block_height - 2);
-
-        f.render_stateful_widget(results_list, results_list_area, &mut app.fzf_results.state);
+        f.render_stateful_widget(results_list, results_list_area, &mut state);
     }
 }
@@ -0,0 +1,247 @@
+use crate::configuration::theme::Theme;
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Span, Spans},
+};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+const PYTHON_KEYWORDS: [&str; 35] = [
+    "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif",
+    "else", "except", "False", "finally", "for", "from", "global", "if", "import", "in", "is",
+    "lambda", "None", "nonlocal", "not", "or", "pass", "raise", "return", "True", "try", "while",
+    "with", "yield",
+];
+
+// how many lines of a cell's output are shown before it gets summarized
+const OUTPUT_LINES: usize = 3;
+// parsing needs the whole document, bigger ones are previewed as text
+const MAX_SIZE: u64 = 64 * 1024 * 1024;
+
+// notebooks are json, render the cells instead of the raw document
+pub fn notebook_lines(path: &Path, theme: &Theme) -> Option<Vec<Spans<'static>>> {
+    if path.metadata().ok()?.len() > MAX_SIZE {
+        return None;
+    }
+    let data = fs::read_to_string(path).ok()?;
+    let notebook: Value = serde_json::from_str(&data).ok()?;
+    let cells = notebook.get("cells")?.as_array()?;
+
+    let language = notebook
+        .pointer("/metadata/language_info/name")
+        .or_else(|| notebook.pointer("/metadata/kernelspec/language"))
+        .and_then(|language| language.as_str())
+        .unwrap_or("python")
+        .to_string();
+
+    let mut lines = vec![Spans::from(Span::styled(
+        format!("Notebook: {} cells, {}", cells.len(), language),
+        Style::default()
+            .fg(theme.border)
+            .add_modifier(Modifier::BOLD),
+    ))];
+
+    for cell in cells {
+        lines.push(Spans::from(""));
+
+        let source = joined(cell.get("source"));
+
+        match cell.get("cell_type").and_then(|kind| kind.as_str()) {
+            Some("markdown") => {
+                for line in source.lines() {
+                    lines.push(markdown_line(line, theme));
+                }
+            }
+            Some("code") => {
+                let count = match cell.get("execution_count").and_then(|count| count.as_u64()) {
+                    Some(count) => count.to_string(),
+                    None => " ".to_string(),
+                };
+
+                lines.push(Spans::from(Span::styled(
+                    format!("In [{}]:", count),
+                    Style::default().fg(theme.focus),
+                )));
+
+                for line in source.lines() {
+                    lines.push(code_line(line, language == "python", theme));
+                }
+
+                if let Some(outputs) = cell.get("outputs").and_then(|outputs| outputs.as_array()) {
+                    for output in outputs {
+                        output_lines(output, &count, &mut lines, theme);
+                    }
+                }
+            }
+            _ => {
+                for line in source.lines() {
+                    lines.push(Spans::from(Span::styled(
+                        line.to_string(),
+                        Style::default().fg(theme.dim),
+                    )));
+                }
+            }
+        }
+    }
+
+    Some(lines)
+}
+
+// cell sources and outputs are either a string or a list of lines
+fn joined(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter_map(|part| part.as_str())
+            .collect::<Vec<&str>>()
+            .concat(),
+        _ => String::new(),
+    }
+}
+
+fn markdown_line(line: &str, theme: &Theme) -> Spans<'static> {
+    let trimmed = line.trim_start();
+
+    if trimmed.starts_with('#') {
+        let heading = trimmed.trim_start_matches('#').trim().to_string();
+        return Spans::from(Span::styled(
+            heading,
+            Style::default()
+                .fg(theme.border)
+                .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+        ));
+    }
+
+    if trimmed.starts_with("- ") || trimmed.starts_with("* ") {
+        let indent = line.len() - trimmed.len();
+        return Spans::from(format!("{}• {}", " ".repeat(indent), &trimmed[2..]));
+    }
+
+    if trimmed.starts_with("```") {
+        return Spans::from(Span::styled(
+            line.to_string(),
+            Style::default().fg(theme.dim),
+        ));
+    }
+
+    Spans::from(line.to_string())
+}
+
+// just enough highlighting to tell code apart: keywords, strings,
+// comments and numbers
+fn code_line(line: &str, python: bool, theme: &Theme) -> Spans<'static> {
+    let mut spans = vec![Span::raw("  ")];
+    let chars: Vec<char> = line.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c == '#' {
+            let rest: String = chars[i..].iter().collect();
+            spans.push(Span::styled(rest, Style::default().fg(theme.dim)));
+            break;
+        }
+
+        if c == '"' || c == '\'' {
+            let start = i;
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                if chars[i] == '\\' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            i = (i + 1).min(chars.len());
+
+            let text: String = chars[start..i].iter().collect();
+            spans.push(Span::styled(text, Style::default().fg(theme.success)));
+            continue;
+        }
+
+        if c.is_alphanumeric() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+
+            let word: String = chars[start..i].iter().collect();
+            let style = if python && PYTHON_KEYWORDS.contains(&word.as_str()) {
+                Style::default()
+                    .fg(theme.keyword)
+                    .add_modifier(Modifier::BOLD)
+            } else if c.is_ascii_digit() {
+                Style::default().fg(theme.number)
+            } else {
+                Style::default()
+            };
+
+            spans.push(Span::styled(word, style));
+            continue;
+        }
+
+        spans.push(Span::raw(c.to_string()));
+        i += 1;
+    }
+
+    Spans::from(spans)
+}
+
+fn output_lines(output: &Value, count: &str, lines: &mut Vec<Spans<'static>>, theme: &Theme) {
+    let dim = Style::default().fg(theme.muted);
+
+    let text = match output.get("output_type").and_then(|kind| kind.as_str()) {
+        Some("stream") => joined(output.get("text")),
+        Some("execute_result") | Some("display_data") => {
+            let data = match output.get("data") {
+                Some(data) => data,
+                None => return,
+            };
+
+            if data.get("text/plain").is_some() && data.get("image/png").is_none() {
+                joined(data.get("text/plain"))
+            } else {
+                // rich output can't be shown here, name what it is instead
+                let kinds = data
+                    .as_object()
+                    .map(|kinds| kinds.keys().cloned().collect::<Vec<String>>().join(", "))
+                    .unwrap_or_default();
+                format!("[{} output]", kinds)
+            }
+        }
+        Some("error") => {
+            let name = output.get("ename").and_then(|name| name.as_str());
+            let value = output.get("evalue").and_then(|value| value.as_str());
+
+            lines.push(Spans::from(Span::styled(
+                format!("Error: {}: {}", name.unwrap_or(""), value.unwrap_or("")),
+                Style::default().fg(theme.error),
+            )));
+            return;
+        }
+        _ => return,
+    };
+
+    let total = text.lines().count();
+
+    for (idx, line) in text.lines().take(OUTPUT_LINES).enumerate() {
+        let prefix = if idx == 0 {
+            format!("Out[{}]: ", count)
+        } else {
+            " ".repeat(count.len() + 7)
+        };
+        lines.push(Spans::from(Span::styled(
+            format!("{}{}", prefix, line),
+            dim,
+        )));
+    }
+
+    if total > OUTPUT_LINES {
+        lines.push(Spans::from(Span::styled(
+            format!("... {} more lines", total - OUTPUT_LINES),
+            dim,
+        )));
+    }
+}
@@ -0,0 +1,75 @@
+use crate::app::app::{display_name, App};
+use ratatui::backend::Backend;
+use ratatui::layout::Alignment;
+use ratatui::widgets::Clear;
+use ratatui::widgets::ListItem;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    widgets::{Block, Borders, List},
+    Frame,
+};
+
+pub fn render_open_with<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
+    if app.show_open_with {
+        let block_width = (f.size().width / 2).max(40).min(size.width);
+        let block_height = (app.open_with.items.len() as u16 + 4).min(size.height);
+        let block_x = (size.width - block_width) / 2;
+        let block_y = (size.height - block_height) / 2;
+
+        let area = Rect::new(block_x, block_y, block_width, block_height);
+
+        let open_with_block = Block::default()
+            .style(Style::default().add_modifier(Modifier::BOLD))
+            .border_style(
+                Style::default()
+                    .fg(app.theme.border)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .borders(Borders::ALL)
+            .title_alignment(Alignment::Center);
+
+        f.render_widget(Clear, area);
+        f.render_widget(open_with_block, area);
+
+        let open_with_text = app
+            .open_with
+            .items
+            .iter()
+            .map(|association| {
+                if association.detached {
+                    ListItem::new(format!("{}  (in the background)", association.command))
+                } else {
+                    ListItem::new(association.command.clone())
+                }
+            })
+            .collect::<Vec<ListItem>>();
+
+        let open_with_list = List::new(open_with_text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!("Open {} with", display_name(&app.open_with_entry)))
+                    .title_alignment(Alignment::Center),
+            )
+            .highlight_style(
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .fg(app.theme.highlight),
+            )
+            .highlight_symbol("> ");
+
+        let open_with_list_area = Rect::new(
+            block_x + 1,
+            block_y + 1,
+            block_width.saturating_sub(2),
+            block_height.saturating_sub(2),
+        );
+
+        f.render_stateful_widget(
+            open_with_list,
+            open_with_list_area,
+            &mut app.open_with.state,
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use crate::app::app::App;
+use crate::app::app::{display_name, App};
 use crate::ui::input::nav::abbreviate_path;
 use ratatui::backend::Backend;
 use ratatui::layout::Alignment;
@@ -6,7 +6,7 @@ use ratatui::widgets::Clear;
 use ratatui::widgets::ListItem;
 use ratatui::{
     layout::Rect,
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     widgets::{Block, Borders, List},
     Frame,
 };
@@ -25,7 +25,7 @@ pub fn render_ops_menu<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect)
             .style(Style::default().add_modifier(Modifier::BOLD))
             .border_style(
                 Style::default()
-                    .fg(Color::LightYellow)
+                    .fg(app.theme.border)
                     .add_modifier(Modifier::BOLD),
             )
             .title_alignment(Alignment::Center);
@@ -47,7 +47,7 @@ pub fn render_ops_menu<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect)
                     .title("Operations")
                     .border_style(
                         Style::default()
-                            .fg(Color::LightYellow)
+                            .fg(app.theme.border)
                             .add_modifier(Modifier::BOLD),
                     )
                     .title_alignment(Alignment::Center),
@@ -55,7 +55,7 @@ pub fn render_ops_menu<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect)
             .highlight_style(
                 Style::default()
                     .add_modifier(Modifier::BOLD)
-                    .fg(Color::LightGreen),
+                    .fg(app.theme.highlight),
             )
             .highlight_symbol("> ");
 
@@ -68,7 +68,11 @@ pub fn render_ops_menu<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect)
 
         f.render_stateful_widget(ops_list, ops_menu_list_area, &mut app.ops_menu.state);
 
-        let mut selected_files_clone = app.selected_files.clone();
+        let mut selected_files_clone = app
+            .selected_files
+            .iter()
+            .map(display_name)
+            .collect::<Vec<String>>();
 
         if selected_files_clone.is_empty() {
             selected_files_clone.push("No files staged for operation".to_string());
@@ -85,7 +89,7 @@ pub fn render_ops_menu<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect)
                 .title("Currently Selected Files/Dirs")
                 .border_style(
                     Style::default()
-                        .fg(Color::LightYellow)
+                        .fg(app.theme.border)
                         .add_modifier(Modifier::BOLD),
                 )
                 .borders(Borders::ALL)
@@ -0,0 +1,105 @@
+use crate::app::app::{display_name, App};
+use crate::ui::input::pager::Prompt;
+use crate::ui::input::preview_search;
+use ratatui::backend::Backend;
+use ratatui::widgets::{Clear, ListItem, Paragraph};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, List},
+    Frame,
+};
+
+// the part of `line` from column `left` that fits in `width`
+fn visible(line: &str, left: usize, width: usize) -> String {
+    line.chars().skip(left).take(width).collect()
+}
+
+// the whole screen, with where it is and the keys on the last row
+pub fn render_pager<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
+    let theme = app.theme.clone();
+    let pager = match app.pager.as_mut() {
+        Some(pager) => pager,
+        None => return,
+    };
+    f.render_widget(Clear, size);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(display_name(&pager.path))
+        .border_style(
+            Style::default()
+                .fg(theme.border)
+                .add_modifier(Modifier::BOLD),
+        );
+    let inner = block.inner(size);
+    f.render_widget(block, size);
+    if inner.height < 2 {
+        return;
+    }
+
+    // the bottom row of the block is the pager's own status line
+    pager.height = inner.height as usize - 1;
+    pager.top = pager
+        .top
+        .min(pager.lines.len().saturating_sub(pager.height));
+    let width = inner.width as usize;
+
+    let items = pager
+        .lines
+        .iter()
+        .skip(pager.top)
+        .take(pager.height)
+        .map(|line| {
+            let line = visible(line, pager.left, width);
+            ListItem::new(match &pager.search {
+                Some(search) => preview_search::highlight(&line, search, &theme),
+                None => Spans::from(line),
+            })
+        })
+        .collect::<Vec<ListItem>>();
+    f.render_widget(
+        List::new(items),
+        Rect::new(inner.x, inner.y, inner.width, inner.height - 1),
+    );
+
+    let status = match &pager.prompt {
+        Some((Prompt::Search, text)) => Spans::from(format!("/{}", text)),
+        Some((Prompt::Line, text)) => Spans::from(format!(":{}", text)),
+        None => {
+            let first = (pager.top + 1).min(pager.lines.len());
+            let last = (pager.top + pager.height).min(pager.lines.len());
+            let mut place = format!(
+                "lines {}-{} of {} ({}%)",
+                first,
+                last,
+                pager.lines.len(),
+                pager.percent()
+            );
+            if pager.left > 0 {
+                place.push_str(&format!(", column {}", pager.left + 1));
+            }
+            if let Some(search) = &pager.search {
+                place.push_str(&format!(
+                    ", {} {} of {}",
+                    search.query,
+                    search.current + 1,
+                    search.matches.len()
+                ));
+            }
+
+            Spans::from(vec![
+                Span::styled(place, Style::default().add_modifier(Modifier::BOLD)),
+                Span::styled(
+                    "  / search, n/N next, : line, h/l sideways, q closes",
+                    Style::default().fg(theme.dim),
+                ),
+            ])
+        }
+    };
+    f.render_widget(
+        Paragraph::new(status),
+        Rect::new(inner.x, inner.y + inner.height - 1, inner.width, 1),
+    );
+}
@@ -1,4 +1,9 @@
+use crate::app::app::display_name;
+use crate::ui::display::file_colors::IMAGES;
+use crate::ui::display::image_info::image_lines;
+use crate::ui::display::media::media_details;
 use ratatui::{text::Spans, widgets::ListItem};
+use std::ffi::OsStr;
 use std::path::Path;
 use std::process::Command;
 use sysinfo::{DiskExt, System, SystemExt};
@@ -15,108 +20,52 @@ struct SelectedPane<T> {
     pub items: Vec<T>,
 }
 
-pub fn selected_pane_content(input: &String) -> Vec<ListItem<'static>> {
-    let file = Path::new(&input);
+pub fn selected_pane_content(input: &OsStr, si: bool) -> Vec<ListItem<'static>> {
+    let file = Path::new(input);
 
-    if let Some(ext) = file.extension() {
-        if ext == "png" || ext == "jpg" {
-            let output = Command::new("file")
-                .arg(file)
-                .output()
-                .expect("failed to execute process");
-
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            let mut items = Vec::new();
-
-            for line in output_str.lines() {
-                items.push(ListItem::new(Spans::from(line.to_string())));
-            }
-
-            return items;
-        }
-
-        if ext == "mp4" || ext == "mp3" {
-            let output = Command::new("ffprobe")
-                .arg(file)
-                .output()
-                .expect("failed to execute process");
-
-            if output.stdout.is_empty() {
-                return vec![ListItem::new(Spans::from("Cannot get details of file"))];
-            }
-
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            let mut items = Vec::new();
-
-            for line in output_str.lines() {
-                items.push(ListItem::new(Spans::from(line.to_string())));
-            }
+    // probed once with the preview, not on every render
+    if let Some(lines) = media_details(file) {
+        return lines
+            .into_iter()
+            .map(|line| ListItem::new(Spans::from(line)))
+            .collect();
+    }
 
-            return items;
-        }
+    if let Ok(target) = std::fs::read_link(file) {
+        let state = if file.exists() {
+            "symlink"
+        } else {
+            "broken symlink"
+        };
+
+        return vec![ListItem::new(Spans::from(format!(
+            "{} -> {}  ({})",
+            display_name(input),
+            display_name(target),
+            state
+        )))];
     }
 
     if file.is_dir() {
-        let mut items = Vec::new();
-        let output = Command::new("ls")
-            .arg("-ld")
-            .arg(file)
-            .output()
-            .expect("failed to execute process");
-
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        let output_vec = output_str.split_whitespace().collect::<Vec<&str>>();
-
-        let perms = output_vec[0];
-        let owner = output_vec[2];
-        let size = output_vec[4];
-        let date = output_vec[5];
-        let day = output_vec[6];
-        let time = output_vec[7];
-
-        if output.stdout.is_empty() {
-            return vec![ListItem::new(Spans::from("No directory selected"))];
-        }
-
-        #[allow(unused_variables)]
-        for line in output_str.lines() {
-            items.push(ListItem::new(Spans::from(format!(
-                "{}  {}  {}  {} {}/{}",
-                perms, owner, size, date, day, time
-            ))));
-        }
-
-        return items;
+        return vec![ListItem::new(Spans::from(
+            ls_line(file, "-ld", si).unwrap_or_else(|| "Details not available".to_string()),
+        ))];
     }
 
     if file.is_file() {
-        let mut items = Vec::new();
-        let output = Command::new("ls")
-            .arg("-lh")
-            .arg(file)
-            .output()
-            .expect("failed to execute process");
-
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        let output_vec: Vec<&str> = output_str.split_whitespace().collect();
-
-        if output.stdout.is_empty() {
-            return vec![ListItem::new(Spans::from("No file selected"))];
-        }
-
-        let perms = output_vec[0];
-        let owner = output_vec[2];
-        let size = output_vec[4];
-        let date = output_vec[5];
-        let day = output_vec[6];
-        let time = output_vec[7];
-
-        #[allow(unused_variables)]
-        for line in output_str.lines() {
-            items.push(ListItem::new(Spans::from(format!(
-                "{}  {}  {}  {} {}/{}",
-                perms, owner, size, date, day, time
-            ))));
+        let mut items = vec![ListItem::new(Spans::from(
+            ls_line(file, "-l", si).unwrap_or_else(|| "Details not available".to_string()),
+        ))];
+
+        // images add what they show and, from a camera, when and with what
+        let extension = file
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if IMAGES.contains(&extension.as_str()) {
+            for line in image_lines(file) {
+                items.push(ListItem::new(Spans::from(line)));
+            }
         }
         return items;
     }
@@ -124,16 +73,33 @@ pub fn selected_pane_content(input: &String) -> Vec<ListItem<'static>> {
     vec![ListItem::new(Spans::from("No file selected"))]
 }
 
-pub fn get_pwd() -> String {
-    let output = Command::new("pwd")
-        .output()
-        .expect("failed to execute process");
-
+// permissions, owner, size and date as ls shows them. None where there's no
+// ls to ask or it said nothing
+fn ls_line(file: &Path, flag: &str, si: bool) -> Option<String> {
+    let output = Command::new("ls").arg(flag).arg(file).output().ok()?;
     let output_str = String::from_utf8_lossy(&output.stdout);
-    format!("{}", output_str)
+    let output_vec = output_str.split_whitespace().collect::<Vec<&str>>();
+    if output_vec.len() < 8 {
+        return None;
+    }
+
+    let size = convert_bytes(std::fs::metadata(file).map(|m| m.len()).unwrap_or(0), si);
+    Some(format!(
+        "{}  {}  {}  {} {}/{}",
+        output_vec[0], output_vec[2], size, output_vec[5], output_vec[6], output_vec[7]
+    ))
 }
 
-pub fn get_du() -> String {
+// newline-terminated like pwd's output, which it used to be. Empty when
+// the directory is gone from under us
+pub fn get_pwd() -> String {
+    match std::env::current_dir() {
+        Ok(dir) => format!("{}\n", dir.to_string_lossy()),
+        Err(_) => String::new(),
+    }
+}
+
+pub fn get_du(si: bool) -> String {
     let mut sys = System::new_all();
     sys.refresh_all();
 
@@ -144,32 +110,61 @@ pub fn get_du() -> String {
 
         return format!(
             "{} used / {} total / {} free ",
-            convert_bytes(used),
-            convert_bytes(total),
-            convert_bytes(free),
+            convert_bytes(used, si),
+            convert_bytes(total, si),
+            convert_bytes(free, si),
         );
     } else {
         return String::from("No disk found");
     }
 }
 
-fn convert_bytes(bytes: u64) -> String {
-    let mut bytes = bytes;
+// every size shown anywhere goes through here, so they all read the same.
+// KiB, MiB, ... (powers of 1024) unless `si` (app.si_units, from
+// `size_units=si`) asks for kB, MB, ... (powers of 1000)
+pub fn convert_bytes(bytes: u64, si: bool) -> String {
+    let (base, units) = if si {
+        (1000.0, ["B", "kB", "MB", "GB", "TB", "PB"])
+    } else {
+        (1024.0, ["B", "KiB", "MiB", "GiB", "TiB", "PiB"])
+    };
+
+    let mut size = bytes as f64;
     let mut unit = 0;
 
-    while bytes > 1024 {
-        bytes /= 1024;
+    while size >= base && unit < units.len() - 1 {
+        size /= base;
         unit += 1;
     }
 
-    let unit = match unit {
-        0 => "B",
-        1 => "KB",
-        2 => "MB",
-        3 => "GB",
-        4 => "TB",
-        _ => "PB",
-    };
+    if unit == 0 {
+        format!("{} {}", bytes, units[0])
+    } else {
+        format!("{:.1} {}", size, units[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::convert_bytes;
 
-    format!("{} {}", bytes, unit)
+    #[test]
+    fn binary_units_by_default() {
+        assert_eq!(convert_bytes(0, false), "0 B");
+        assert_eq!(convert_bytes(1023, false), "1023 B");
+        assert_eq!(convert_bytes(1024, false), "1.0 KiB");
+        assert_eq!(convert_bytes(1536 * 1024, false), "1.5 MiB");
+    }
+
+    #[test]
+    fn si_units_in_powers_of_1000() {
+        assert_eq!(convert_bytes(999, true), "999 B");
+        assert_eq!(convert_bytes(1000, true), "1.0 kB");
+        assert_eq!(convert_bytes(2_500_000_000, true), "2.5 GB");
+    }
+
+    #[test]
+    fn stops_at_the_largest_unit() {
+        assert_eq!(convert_bytes(u64::MAX, false), "16384.0 PiB");
+    }
 }
@@ -0,0 +1,84 @@
+use crate::configuration::theme::Theme;
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Span, Spans},
+};
+use std::fs::File;
+use std::path::Path;
+
+const PREVIEW_ROWS: usize = 50;
+const COLUMN_WIDTH: usize = 14;
+
+fn cell(text: &str) -> String {
+    let mut text = text.replace('\n', " ");
+
+    if text.chars().count() > COLUMN_WIDTH {
+        text = text.chars().take(COLUMN_WIDTH - 1).collect::<String>() + "…";
+    }
+
+    format!("{:<width$}", text, width = COLUMN_WIDTH)
+}
+
+// schema, row count and the first rows of the file as a table
+pub fn parquet_lines(path: &Path, theme: &Theme) -> Option<Vec<Spans<'static>>> {
+    let file = File::open(path).ok()?;
+    let reader = SerializedFileReader::new(file).ok()?;
+    let metadata = reader.metadata().file_metadata();
+    let schema = metadata.schema_descr();
+
+    let title = Style::default()
+        .fg(theme.border)
+        .add_modifier(Modifier::BOLD);
+
+    let mut lines = vec![
+        Spans::from(Span::styled(
+            format!(
+                "Parquet: {} rows, {} columns, {} row groups",
+                metadata.num_rows(),
+                schema.num_columns(),
+                reader.num_row_groups()
+            ),
+            title,
+        )),
+        Spans::from(""),
+        Spans::from(Span::styled("Schema", title)),
+    ];
+
+    for column in schema.columns() {
+        let kind = match column.logical_type() {
+            Some(logical) => format!("{:?} ({:?})", column.physical_type(), logical),
+            None => format!("{:?}", column.physical_type()),
+        };
+
+        lines.push(Spans::from(format!("  {}: {}", column.path(), kind)));
+    }
+
+    lines.push(Spans::from(""));
+
+    let header = schema
+        .columns()
+        .iter()
+        .map(|column| cell(column.name()))
+        .collect::<Vec<String>>()
+        .join(" │ ");
+    lines.push(Spans::from(Span::styled(
+        header,
+        Style::default()
+            .fg(theme.focus)
+            .add_modifier(Modifier::BOLD),
+    )));
+
+    if let Ok(rows) = reader.get_row_iter(None) {
+        for row in rows.take(PREVIEW_ROWS) {
+            let values = row
+                .get_column_iter()
+                .map(|(_, field)| cell(&field.to_string()))
+                .collect::<Vec<String>>()
+                .join(" │ ");
+            lines.push(Spans::from(values));
+        }
+    }
+
+    Some(lines)
+}
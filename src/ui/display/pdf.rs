@@ -0,0 +1,140 @@
+use crate::configuration::theme::Theme;
+use pdf_extract::{Document, PlainTextOutput};
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Span, Spans},
+};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::SystemTime;
+
+// enough to tell documents apart
+const PAGES: u32 = 3;
+// the whole file is parsed, bigger ones are for a viewer
+const MAX_SIZE: u64 = 64 * 1024 * 1024;
+
+struct Extracted {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    // the page count and the text of the first pages, or why there's none
+    text: Result<(usize, Vec<String>), String>,
+}
+
+// the last PDF read, and the one being read in the background. Parsing
+// takes a while on big documents and the preview is drawn every frame
+struct State {
+    last: Option<Extracted>,
+    reading: Option<PathBuf>,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    last: None,
+    reading: None,
+});
+
+fn extract(path: &Path) -> Result<(usize, Vec<String>), String> {
+    let mut document = Document::load(path).map_err(|e| e.to_string())?;
+    if document.is_encrypted() && document.decrypt("").is_err() {
+        return Err("encrypted".to_string());
+    }
+
+    let pages = document.get_pages().len();
+    let mut texts = vec![];
+    for page in 1..=PAGES.min(pages as u32) {
+        let mut text = String::new();
+        let mut output = PlainTextOutput::new(&mut text);
+        if pdf_extract::output_doc_page(&document, &mut output, page).is_err() {
+            break;
+        }
+        texts.push(text);
+    }
+
+    Ok((pages, texts))
+}
+
+fn start(path: PathBuf, modified: Option<SystemTime>) {
+    let spawned = thread::Builder::new()
+        // the panic hook keeps quiet for these, see render.rs
+        .name("preview".to_string())
+        .spawn(move || {
+            // a malformed file can make the parser give up halfway
+            let text = panic::catch_unwind(AssertUnwindSafe(|| extract(&path)))
+                .unwrap_or_else(|_| Err("couldn't be parsed".to_string()));
+
+            let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+            state.reading = None;
+            state.last = Some(Extracted {
+                path,
+                modified,
+                text,
+            });
+        });
+
+    if spawned.is_err() {
+        STATE.lock().unwrap_or_else(|e| e.into_inner()).reading = None;
+    }
+}
+
+// the page count and the text of the first pages, read in the background
+pub fn pdf_lines(path: &Path, theme: &Theme) -> Option<Vec<Spans<'static>>> {
+    let title = Style::default()
+        .fg(theme.border)
+        .add_modifier(Modifier::BOLD);
+    let heading = |text: String| vec![Spans::from(Span::styled(text, title))];
+
+    let metadata = path.metadata().ok()?;
+    if metadata.len() > MAX_SIZE {
+        return Some(heading("PDF: too large to read for a preview".to_string()));
+    }
+    let modified = metadata.modified().ok();
+
+    let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+    let extracted = match &state.last {
+        Some(last) if last.path == path && last.modified == modified => last,
+        _ => {
+            // one at a time, the next one starts once it's done
+            if state.reading.is_none() {
+                state.reading = Some(path.to_path_buf());
+                drop(state);
+                start(path.to_path_buf(), modified);
+            }
+            return Some(heading("PDF: reading...".to_string()));
+        }
+    };
+
+    let (pages, texts) = match &extracted.text {
+        Ok(extracted) => extracted,
+        Err(e) => return Some(heading(format!("PDF: {}", e))),
+    };
+
+    let mut lines = heading(format!(
+        "PDF: {} page{}",
+        pages,
+        if *pages == 1 { "" } else { "s" }
+    ));
+    for (page, text) in texts.iter().enumerate() {
+        lines.push(Spans::from(""));
+        lines.push(Spans::from(Span::styled(
+            format!("── Page {} ──", page + 1),
+            Style::default().fg(theme.dim),
+        )));
+
+        // the layout leaves runs of empty lines, one is enough
+        let mut blank = true;
+        for line in text.lines().map(str::trim_end) {
+            if line.is_empty() && blank {
+                continue;
+            }
+            blank = line.is_empty();
+            lines.push(Spans::from(line.to_string()));
+        }
+    }
+    if texts.iter().all(|text| text.trim().is_empty()) {
+        lines.push(Spans::from(""));
+        lines.push(Spans::from("No text, the pages may be scanned images"));
+    }
+
+    Some(lines)
+}
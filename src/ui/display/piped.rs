@@ -0,0 +1,73 @@
+use crate::app::app::{display_name, App};
+use ratatui::backend::Backend;
+use ratatui::layout::Alignment;
+use ratatui::widgets::Clear;
+use ratatui::widgets::ListItem;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    widgets::{Block, Borders, List},
+    Frame,
+};
+
+// the paths piped in with --from-stdin, marked ones are in the move/copy buffer
+pub fn render_piped<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
+    if app.show_piped {
+        let block_width = (size.width * 2 / 3).max(50).min(size.width);
+        let block_height = size.height * 2 / 3;
+        let block_x = (size.width - block_width) / 2;
+        let block_y = (size.height - block_height) / 2;
+
+        let area = Rect::new(block_x, block_y, block_width, block_height);
+
+        f.render_widget(Clear, area);
+
+        let marked = app
+            .piped
+            .items
+            .iter()
+            .filter(|path| app.selected_files.contains(path))
+            .count();
+
+        let piped_text = app
+            .piped
+            .items
+            .iter()
+            .map(|path| {
+                if app.selected_files.contains(path) {
+                    ListItem::new(format!("[x] {}", display_name(path)))
+                } else {
+                    ListItem::new(format!("[ ] {}", display_name(path)))
+                        .style(Style::default().fg(app.theme.dim))
+                }
+            })
+            .collect::<Vec<ListItem>>();
+
+        let title = format!(
+            "Piped paths - {} of {} marked, SPACE marks, ENTER jumps",
+            marked,
+            app.piped.items.len()
+        );
+
+        let piped_list = List::new(piped_text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title)
+                    .border_style(
+                        Style::default()
+                            .fg(app.theme.border)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                    .title_alignment(Alignment::Center),
+            )
+            .highlight_style(
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .fg(app.theme.highlight),
+            )
+            .highlight_symbol("> ");
+
+        f.render_stateful_widget(piped_list, area, &mut app.piped.state);
+    }
+}
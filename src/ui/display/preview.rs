@@ -0,0 +1,465 @@
+use crate::app::app::{display_name, is_hidden};
+use crate::configuration::theme::Theme;
+use crate::git::DirSummary;
+use crate::ui::display::compressed::compressed_lines;
+use crate::ui::display::file_colors::entry_style;
+use crate::ui::display::media::media_lines;
+use crate::ui::display::notebook::notebook_lines;
+use crate::ui::display::pane::convert_bytes;
+#[cfg(feature = "parquet")]
+use crate::ui::display::parquet::parquet_lines;
+#[cfg(feature = "pdf")]
+use crate::ui::display::pdf::pdf_lines;
+use crate::ui::display::zip_index;
+use crate::ui::input::archive::{tar_reader, Format};
+use crate::vfs::{RangeReader, VfsProvider};
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Span, Spans},
+};
+use std::fs::{self, File};
+use std::io::{Read, Seek};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+// archives can hold a lot, the preview only needs the first screens
+const MAX_ENTRIES: usize = 1000;
+// blocks of a remote archive fetched for its index, a megabyte
+const REMOTE_FETCHES: usize = 16;
+// compressed bytes of a tar read for its preview, it has no index and every
+// entry has to be decompressed to get to the next header
+const TAR_BYTES: u64 = 16 * 1024 * 1024;
+
+// a directory's children: whether each is a directory, and its path
+type Children = Result<Vec<(bool, PathBuf)>, String>;
+
+// the last directory previewed, kept until it's modified
+struct Listed {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    show_hidden: bool,
+    children: Children,
+}
+
+static LAST_DIR: Mutex<Option<Listed>> = Mutex::new(None);
+
+// the last notebook or archive previewed, its path, mtime, and the theme and
+// size units it was drawn with
+type Parsed = (
+    PathBuf,
+    Option<SystemTime>,
+    (String, bool),
+    Option<Vec<Spans<'static>>>,
+);
+
+static LAST_PARSED: Mutex<Option<Parsed>> = Mutex::new(None);
+
+// what's previewed, a remote entry only gets the previews that can read it
+// in ranges
+pub enum Source<'a> {
+    Local(&'a Path),
+    Remote {
+        vfs: &'a Arc<dyn VfsProvider>,
+        path: &'a Path,
+        is_dir: bool,
+    },
+}
+
+// everything that isn't shown as plain text goes through here, None falls
+// back to the text preview. `si` is app.si_units, for the sizes in it
+pub fn preview_lines(
+    source: Source,
+    theme: &Theme,
+    show_hidden: bool,
+    si: bool,
+) -> Option<Vec<Spans<'static>>> {
+    let path = match source {
+        Source::Local(path) => path,
+        Source::Remote { vfs, path, is_dir } => {
+            return remote_lines(vfs, path, is_dir, theme, show_hidden, si)
+        }
+    };
+    let name = path.to_string_lossy().to_lowercase();
+
+    if path.is_dir() {
+        return Some(dir_lines(path, theme, show_hidden));
+    }
+
+    if name.ends_with(".ipynb") {
+        return parsed(path, theme, si, || notebook_lines(path, theme));
+    }
+
+    #[cfg(feature = "parquet")]
+    if name.ends_with(".parquet") {
+        return parquet_lines(path, theme);
+    }
+
+    #[cfg(feature = "pdf")]
+    if name.ends_with(".pdf") {
+        return pdf_lines(path, theme);
+    }
+
+    if let Some(lines) = media_lines(path, theme, si) {
+        return Some(lines);
+    }
+
+    match Format::of(&name) {
+        Some(format) => parsed(path, theme, si, || archive_lines(path, format, theme, si)),
+        None => compressed_lines(path, theme, si),
+    }
+}
+
+// drawn every frame, a file is only parsed again once it changed
+fn parsed(
+    path: &Path,
+    theme: &Theme,
+    si: bool,
+    parse: impl FnOnce() -> Option<Vec<Spans<'static>>>,
+) -> Option<Vec<Spans<'static>>> {
+    let modified = path
+        .metadata()
+        .and_then(|metadata| metadata.modified())
+        .ok();
+
+    let mut last = LAST_PARSED.lock().unwrap_or_else(|e| e.into_inner());
+    let drawn = (theme.name.clone(), si);
+    if let Some((last_path, last_modified, last_drawn, lines)) = last.as_ref() {
+        if last_path == path && *last_modified == modified && *last_drawn == drawn {
+            return lines.clone();
+        }
+    }
+
+    let lines = parse();
+    *last = Some((path.to_path_buf(), modified, drawn, lines.clone()));
+    lines
+}
+
+fn archive_lines(
+    path: &Path,
+    format: Format,
+    theme: &Theme,
+    si: bool,
+) -> Option<Vec<Spans<'static>>> {
+    match format {
+        Format::Zip => zip_lines(File::open(path).ok()?, theme, si),
+        #[cfg(feature = "sevenz")]
+        Format::SevenZ => sevenz_lines(path, theme, si),
+        Format::Tar => {
+            let mut archive = tar::Archive::new(File::open(path).ok()?);
+            tar_lines(archive.entries_with_seek().ok()?, theme, si)
+        }
+        format => {
+            // cut off, the listing ends at the entry it was in
+            let file = File::open(path).ok()?.take(TAR_BYTES);
+            let mut archive = tar::Archive::new(tar_reader(format, file).ok()??);
+            tar_lines(archive.entries().ok()?, theme, si)
+        }
+    }
+}
+
+// a remote directory's children, or an archive's index read a block at a
+// time, zip from the central directory at its end and tar a header at once
+fn remote_lines(
+    vfs: &Arc<dyn VfsProvider>,
+    path: &Path,
+    is_dir: bool,
+    theme: &Theme,
+    show_hidden: bool,
+    si: bool,
+) -> Option<Vec<Spans<'static>>> {
+    if is_dir {
+        let children = vfs
+            .read_dir(path)
+            .map(|entries| {
+                let mut children = entries
+                    .into_iter()
+                    .filter(|entry| show_hidden || !entry.name.starts_with('.'))
+                    .map(|entry| (entry.is_dir, PathBuf::from(entry.name)))
+                    .collect::<Vec<_>>();
+                children.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+                children
+            })
+            .map_err(|e| e.to_string());
+        return Some(children_lines(children, theme));
+    }
+
+    let format = Format::of(&path.to_string_lossy().to_lowercase())?;
+    let reader = RangeReader::new(vfs.clone(), path, REMOTE_FETCHES).ok()?;
+
+    match format {
+        Format::Zip => zip_lines(reader, theme, si),
+        Format::Tar => tar_lines(
+            tar::Archive::new(reader).entries_with_seek().ok()?,
+            theme,
+            si,
+        ),
+        format => {
+            let mut archive = tar::Archive::new(tar_reader(format, reader).ok()??);
+            tar_lines(archive.entries().ok()?, theme, si)
+        }
+    }
+}
+
+fn title(text: String, theme: &Theme) -> Spans<'static> {
+    Spans::from(Span::styled(
+        text,
+        Style::default()
+            .fg(theme.border)
+            .add_modifier(Modifier::BOLD),
+    ))
+}
+
+// the children of a directory, directories first like the panes and
+// hidden ones left out unless they're shown there
+fn dir_lines(path: &Path, theme: &Theme, show_hidden: bool) -> Vec<Spans<'static>> {
+    let modified = path
+        .metadata()
+        .and_then(|metadata| metadata.modified())
+        .ok();
+
+    // drawn every frame, the listing is only read again once it changed
+    let mut last = LAST_DIR.lock().unwrap_or_else(|e| e.into_inner());
+    let entries = match last.as_ref() {
+        Some(listed)
+            if listed.path == path
+                && listed.modified == modified
+                && listed.show_hidden == show_hidden =>
+        {
+            listed.children.clone()
+        }
+        _ => {
+            let children = read_children(path, show_hidden);
+            *last = Some(Listed {
+                path: path.to_path_buf(),
+                modified,
+                show_hidden,
+                children: children.clone(),
+            });
+            children
+        }
+    };
+    drop(last);
+
+    children_lines(entries, theme)
+}
+
+fn children_lines(entries: Children, theme: &Theme) -> Vec<Spans<'static>> {
+    let entries = match entries {
+        Ok(entries) => entries,
+        Err(e) => return vec![title(format!("Can't list it: {}", e), theme)],
+    };
+    if entries.is_empty() {
+        return vec![title("Empty directory".to_string(), theme)];
+    }
+
+    let mut lines = vec![title(format!("{} entries", entries.len()), theme)];
+
+    for (is_dir, child) in entries.iter().take(MAX_ENTRIES) {
+        let name = child.file_name().map(display_name).unwrap_or_default();
+        let name = if *is_dir { format!("{}/", name) } else { name };
+
+        let style = entry_style(child.as_os_str(), None, theme);
+        lines.push(Spans::from(Span::styled(name, style)));
+    }
+    if entries.len() > MAX_ENTRIES {
+        lines.push(Spans::from(format!(
+            "... and {} more",
+            entries.len() - MAX_ENTRIES
+        )));
+    }
+
+    lines
+}
+
+fn read_children(path: &Path, show_hidden: bool) -> Children {
+    let mut entries = fs::read_dir(path)
+        .map_err(|e| e.kind().to_string())?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| show_hidden || !is_hidden(entry))
+        .map(|entry| (entry.path().is_dir(), entry.path()))
+        .collect::<Vec<_>>();
+
+    entries.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    Ok(entries)
+}
+
+// what git knows about a highlighted directory, shown above its children
+pub fn git_lines(
+    summary: DirSummary,
+    ignored: bool,
+    last_commit: Option<String>,
+    theme: &Theme,
+) -> Vec<Spans<'static>> {
+    let mut lines = vec![title("git".to_string(), theme)];
+
+    if ignored {
+        lines.push(Spans::from(Span::styled(
+            "ignored",
+            Style::default().fg(theme.dim),
+        )));
+    } else if summary.modified + summary.staged + summary.untracked == 0 {
+        lines.push(Spans::from(Span::styled(
+            "clean",
+            Style::default().fg(theme.success),
+        )));
+    } else {
+        let counts = [
+            (summary.modified, "modified", theme.warning),
+            (summary.staged, "staged", theme.success),
+            (summary.untracked, "untracked", theme.error),
+        ];
+
+        let mut spans = vec![];
+        for (count, label, color) in counts.into_iter().filter(|count| count.0 > 0) {
+            if !spans.is_empty() {
+                spans.push(Span::raw(", "));
+            }
+            spans.push(Span::styled(
+                format!("{} {}", count, label),
+                Style::default().fg(color),
+            ));
+        }
+        lines.push(Spans::from(spans));
+    }
+
+    lines.push(Spans::from(Span::styled(
+        last_commit.unwrap_or_else(|| "no commits touch it".to_string()),
+        Style::default().fg(theme.muted),
+    )));
+    lines.push(Spans::from(""));
+
+    lines
+}
+
+fn entry_line(name: String, size: u64, is_dir: bool, theme: &Theme, si: bool) -> Spans<'static> {
+    if is_dir {
+        Spans::from(Span::styled(name, Style::default().fg(theme.directory)))
+    } else {
+        Spans::from(vec![
+            Span::styled(
+                format!("{:>10}  ", convert_bytes(size, si)),
+                Style::default().fg(theme.muted),
+            ),
+            Span::raw(name),
+        ])
+    }
+}
+
+// zip keeps a central directory at the end, reading it doesn't touch the
+// compressed data
+fn zip_lines<R: Read + Seek>(reader: R, theme: &Theme, si: bool) -> Option<Vec<Spans<'static>>> {
+    let (count, entries) = zip_index::read(reader, MAX_ENTRIES).ok()?;
+    let encrypted = entries.iter().filter(|entry| entry.encrypted).count();
+
+    let mut lines = vec![title(
+        match encrypted {
+            0 => format!("Zip archive: {} entries", count),
+            _ => format!("Zip archive: {} entries, {} encrypted", count, encrypted),
+        },
+        theme,
+    )];
+
+    for entry in &entries {
+        let name = if entry.encrypted {
+            format!("{} (encrypted)", entry.name)
+        } else {
+            entry.name.clone()
+        };
+        lines.push(entry_line(name, entry.size, entry.is_dir, theme, si));
+    }
+
+    if count > entries.len() as u64 {
+        lines.push(Spans::from(format!(
+            "... {} more entries",
+            count - entries.len() as u64
+        )));
+    }
+
+    Some(lines)
+}
+
+// 7z keeps its index in a header at the end, encrypted along with the names
+// when the whole archive is
+#[cfg(feature = "sevenz")]
+fn sevenz_lines(path: &Path, theme: &Theme, si: bool) -> Option<Vec<Spans<'static>>> {
+    let archive = match sevenz_rust::Archive::open(path) {
+        Ok(archive) => archive,
+        Err(sevenz_rust::Error::PasswordRequired) => {
+            return Some(vec![title(
+                "7z archive: encrypted, x asks for the password".to_string(),
+                theme,
+            )])
+        }
+        Err(_) => return None,
+    };
+
+    let mut lines = vec![title(
+        format!("7z archive: {} entries", archive.files.len()),
+        theme,
+    )];
+
+    for entry in archive.files.iter().take(MAX_ENTRIES) {
+        lines.push(entry_line(
+            entry.name().to_string(),
+            entry.size,
+            entry.is_directory,
+            theme,
+            si,
+        ));
+    }
+
+    if archive.files.len() > MAX_ENTRIES {
+        lines.push(Spans::from(format!(
+            "... {} more entries",
+            archive.files.len() - MAX_ENTRIES
+        )));
+    }
+
+    Some(lines)
+}
+
+// tar has no index, walk the headers and skip over the contents. Seeking
+// past them when the reader can, reading as far as it gets when not
+fn tar_lines<R: Read>(
+    entries: tar::Entries<R>,
+    theme: &Theme,
+    si: bool,
+) -> Option<Vec<Spans<'static>>> {
+    let mut lines = vec![];
+    let mut truncated = false;
+
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            // a remote archive stops where its fetches run out, a compressed
+            // one where its bytes do
+            Err(_) => {
+                truncated = !lines.is_empty();
+                break;
+            }
+        };
+
+        if lines.len() >= MAX_ENTRIES {
+            truncated = true;
+            break;
+        }
+
+        let name = entry
+            .path()
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let is_dir = entry.header().entry_type().is_dir();
+
+        lines.push(entry_line(name, entry.size(), is_dir, theme, si));
+    }
+
+    let count = if truncated {
+        format!("Tar archive: first {} entries", lines.len())
+    } else {
+        format!("Tar archive: {} entries", lines.len())
+    };
+    lines.insert(0, title(count, theme));
+
+    Some(lines)
+}
@@ -1,106 +1,331 @@
 use crate::app::app::App;
+use crate::configuration::session;
+use crate::ui::display::inline::InlineBackend;
 use crate::ui::display::*;
+use crate::ui::input::bookmark::read_bookmark;
+use crate::ui::input::nav;
+use crate::ui::input::piped::{load_paths, read_stdin};
+use crate::ui::input::plugins;
+use crate::ui::input::remote;
 use crate::ui::input::run_app::run_app;
+use crate::ui::input::terminal_title;
 use anyhow::Result;
 use crossterm::{
+    cursor::{MoveTo, Show},
     event::{DisableMouseCapture, EnableMouseCapture},
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{
+        disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
 };
 use ratatui::backend::Backend;
 use ratatui::{
     backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Layout},
     terminal::Terminal,
     Frame,
 };
-use std::io;
-use std::time::Duration;
+use std::ffi::OsStr;
+use std::fs::OpenOptions;
+use std::io::{self, IsTerminal, Write};
+use std::panic;
+use std::path::PathBuf;
+use std::process::exit;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
 
-pub fn init() -> Result<()> {
-    enable_raw_mode()?;
+#[derive(Default)]
+pub struct Options {
+    pub start: Option<PathBuf>,
+    pub pick: bool,
+    // --choose-file: only files can be picked, quitting picks nothing
+    pub choose_file: bool,
+    // lines to draw in at the bottom of the terminal, instead of taking the
+    // alternate screen
+    pub inline: Option<u16>,
+    pub choose_dir: Option<PathBuf>,
+    pub resume: bool,
+    pub from_stdin: bool,
+}
+
+// where the UI gets drawn: the terminal on stdout, or, when stdout is a pipe
+// (`cd "$(rt --pick)"`, `rt --from-stdin | xargs ...`), the controlling
+// terminal so the pipe only gets the picked paths
+fn ui_output(tty_fallback: bool) -> Option<Box<dyn Write>> {
+    if io::stdout().is_terminal() {
+        return Some(Box::new(io::stdout()));
+    }
+
+    if !tty_fallback {
+        return None;
+    }
+
+    #[cfg(unix)]
+    let tty = OpenOptions::new().read(true).write(true).open("/dev/tty");
+    #[cfg(windows)]
+    let tty = OpenOptions::new().read(true).write(true).open("CONOUT$");
+
+    tty.ok().map(|tty| Box::new(tty) as Box<dyn Write>)
+}
+
+static RESTORED: AtomicBool = AtomicBool::new(false);
+// the first row of the UI in inline mode, once the lines are reserved
+static INLINE_TOP: OnceLock<u16> = OnceLock::new();
+
+// puts the terminal back the way the shell had it, once, whether traverse
+// quits, bails out with an error or panics
+fn restore() {
+    if RESTORED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let _ = disable_raw_mode();
+
+    let mut output = match ui_output(true) {
+        Some(output) => output,
+        None => return,
+    };
 
-    let stdout = io::stdout();
-    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture,)?;
+    let _ = match INLINE_TOP.get() {
+        Some(top) => execute!(
+            output,
+            MoveTo(0, *top),
+            Clear(ClearType::FromCursorDown),
+            DisableMouseCapture,
+            Show
+        ),
+        None => execute!(output, LeaveAlternateScreen, DisableMouseCapture, Show),
+    };
+    terminal_title::restore(&mut output);
+}
+
+// restores the terminal when dropped, and before the panic message is
+// printed so it lands on a usable screen
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn new() -> TerminalGuard {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            // background jobs panicking don't take the UI down with them
+            if std::thread::current().name() == Some("main") {
+                restore();
+            }
+            // a preview giving up on a malformed file says so in the pane
+            if std::thread::current().name() == Some("preview") {
+                return;
+            }
+            default_hook(info);
+        }));
+
+        TerminalGuard
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+// the picked path for the pipe, as the bytes it has on disk
+#[cfg(unix)]
+fn print_path(path: &OsStr) {
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut stdout = io::stdout().lock();
+    let _ = stdout.write_all(path.as_bytes());
+    let _ = stdout.write_all(b"\n");
+}
+
+#[cfg(windows)]
+fn print_path(path: &OsStr) {
+    println!("{}", path.to_string_lossy());
+}
+
+pub fn init(options: Options) -> Result<()> {
+    // read before anything moves the current directory, relative paths are
+    // relative to where traverse was started
+    let piped = if options.from_stdin {
+        match read_stdin() {
+            Ok(piped) => Some(piped),
+            Err(e) => {
+                eprintln!("rt: {}", e);
+                exit(2);
+            }
+        }
+    } else {
+        None
+    };
+
+    let mut output = match ui_output(options.pick || options.from_stdin) {
+        Some(output) => output,
+        None => {
+            if options.pick {
+                eprintln!("rt: picking needs a terminal to draw on, none was found");
+            } else {
+                eprintln!(
+                    "rt: stdout is not a terminal. To choose a path from a script or an editor, \
+                     run `rt --pick`, it draws on the terminal and prints the picked path."
+                );
+            }
+            exit(1);
+        }
+    };
+
+    let launch_dir = std::env::current_dir().unwrap_or_default();
+
+    // a file to start at means its directory, with the file selected
+    let mut select = None;
+    if let Some(start) = &options.start {
+        let dir = if start.is_file() {
+            select = start.file_name().map(|name| name.to_os_string());
+            start
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+        } else {
+            Some(start.as_path())
+        };
+
+        if let Some(dir) = dir {
+            if let Err(e) = std::env::set_current_dir(dir) {
+                eprintln!("rt: can't open {}: {}", start.display(), e);
+                exit(2);
+            }
+        }
+    }
 
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
     let tick_rate = Duration::from_millis(250);
     let mut app = App::new();
+    if let Some(name) = select {
+        if let Some(idx) = app.files.items.iter().position(|entry| entry.0 == name) {
+            app.files.state.select(Some(idx));
+            app.dirs.state.select(None);
+        }
+    }
+    app.pick = options.pick;
+    app.launch_dir = launch_dir;
+    app.choose_file = options.choose_file;
+    app.choose_dir = options.choose_dir.clone();
     app.op_menu_init();
-    let res = run_app(&mut terminal, app, tick_rate);
+    read_bookmark(&mut app);
+    plugins::load(&mut app);
 
-    disable_raw_mode()?;
+    // an explicit path wins over the config's resume
+    if options.resume
+        || (app.resume && options.start.is_none() && !options.pick && !options.from_stdin)
+    {
+        session::restore(&mut app);
+    }
 
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture,
-    )?;
+    if let Some((paths, missing)) = piped {
+        load_paths(&mut app, paths, missing);
+    }
+
+    enable_raw_mode()?;
+    let guard = TerminalGuard::new();
+
+    let res = match options.inline {
+        Some(height) => {
+            let top = inline::reserve(&mut output, height)?;
+            let _ = INLINE_TOP.set(top);
+            app.inline = Some(top);
+            execute!(output, EnableMouseCapture)?;
+
+            let backend = InlineBackend::new(CrosstermBackend::new(output), top, height);
+            let mut terminal = Terminal::new(backend)?;
+            terminal.clear()?;
+            run_app(&mut terminal, app, tick_rate)
+        }
+        None => {
+            execute!(output, EnterAlternateScreen, EnableMouseCapture,)?;
 
-    terminal.show_cursor()?;
+            let backend = CrosstermBackend::new(output);
+            let mut terminal = Terminal::new(backend)?;
+            run_app(&mut terminal, app, tick_rate)
+        }
+    };
 
-    if let Err(e) = res {
-        eprintln!("{}", e);
+    drop(guard);
+
+    match res {
+        Ok(Some(path)) => print_path(&path),
+        // nothing was picked, scripts can tell from the status
+        Ok(None) if options.pick => exit(130),
+        // quitting, once the terminal is back to how it was
+        Ok(None) => {
+            if !nav::write_chosen_dir(options.choose_dir.as_deref()) {
+                println!(
+                    "To navigate to traverse's last directory: cd {}",
+                    nav::picked_dir().to_string_lossy()
+                );
+            }
+        }
+        Err(e) => eprintln!("{}", e),
     }
 
     Ok(())
 }
 
 pub fn render<B: Backend>(f: &mut Frame<B>, app: &mut App, input: &mut String) {
-    let cur_dir = app.cur_dir.clone();
+    // a remote listing says where it is on which host
+    let cur_dir = remote::title(app).unwrap_or_else(|| app.cur_dir.clone());
     let cur_du = app.cur_du.clone();
 
-    let size = f.size();
-    let fifty_percent = (size.width as f32 * 0.5) as u16;
-    let ninety_percent = (size.height as f32 * 0.9) as u16;
-
-    let chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Length(fifty_percent), Constraint::Min(1)])
-        .split(size);
-
-    let left_chunks = Layout::default()
+    // the last line is the status bar, the panes get the rest
+    let screen = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(ninety_percent), Constraint::Min(1)])
-        .split(chunks[0]);
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(f.size());
+    let size = screen[0];
+    let areas = layout::areas(app, size);
 
-    let right_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage(45),
-            Constraint::Percentage(45),
-            Constraint::Percentage(10),
-        ])
-        .split(chunks[1]);
-
-    let bottom_chunks = bottom_chunks(f);
-
-    contents::render_contents(f, app, &left_chunks);
-    files_dirs::render_files(f, app, &[right_chunks[0]]);
-    files_dirs::render_dirs(f, app, &[right_chunks[1]]);
-    details::render_details(f, app, &bottom_chunks, cur_dir, cur_du);
+    // remembered so mouse events can be matched to a pane
+    app.screen_area = size;
+    app.preview_area = areas.preview;
+    app.files_area = areas.files;
+    app.dirs_area = areas.dirs;
+
+    let started = Instant::now();
+    contents::render_contents(f, app, &[areas.preview]);
+    app.metrics.last_preview = started.elapsed();
+    if app.preview_focused || app.follow.is_some() {
+        contents::render_focus(f, app, areas.preview);
+    }
+    if let Some(parent) = areas.parent.filter(|_| app.remote.is_none()) {
+        files_dirs::render_parent(f, app, parent);
+    }
+    if app.unified() {
+        files_dirs::render_listing(f, app, areas.files);
+    } else {
+        files_dirs::render_files(f, app, &[areas.files]);
+        files_dirs::render_dirs(f, app, &[areas.dirs]);
+    }
+    details::render_details(f, app, &[areas.details], cur_dir, cur_du);
+    status::render_status(f, app, screen[1]);
+    treemap::render_treemap(f, app, size);
+    kinds::render_kinds(f, app, size);
+    du::render_du(f, app, size);
+    diff::render_diff(f, app, size);
+    compress::render_compress(f, app, size);
+    export::render_export(f, app, size);
+    piped::render_piped(f, app, size);
     inputs::render_input(f, app, size, input);
     navs::render_navigator(f, app, size, input);
     navs::render_fzf(f, app, size);
     help::render_help(f, app, size);
     bookmarks::render_bookmark(f, app, size);
     ops::render_ops_menu(f, app, size);
-}
-
-fn bottom_chunks<B: Backend>(f: &mut Frame<B>) -> Vec<Rect> {
-    let size = f.size();
-    let ninety_percent = (size.height as f32 * 0.9) as u16;
-
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Length(ninety_percent), Constraint::Min(1)])
-        .split(size);
-
-    let bottom_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(f.size().width / 2)])
-        .split(chunks[1]);
-
-    (bottom_chunks).to_vec()
+    jumps::render_jumps(f, app, size);
+    mounts::render_mounts(f, app, size);
+    open_with::render_open_with(f, app, size);
+    report::render_report(f, app, size);
+    themes::render_themes(f, app, size);
+    palette::render_palette(f, app, size, input);
+    frecent::render_frecent(f, app, size, input);
+    grep::render_grep(f, app, size, input);
+    pager::render_pager(f, app, size);
+    metrics::render_metrics(f, app, size);
 }
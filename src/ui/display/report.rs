@@ -0,0 +1,58 @@
+use crate::app::app::App;
+use crate::ui::input::popups::{self, Popup};
+use ratatui::backend::Backend;
+use ratatui::layout::Alignment;
+use ratatui::widgets::Clear;
+use ratatui::widgets::ListItem;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    widgets::{Block, Borders, List},
+    Frame,
+};
+
+pub fn render_report<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
+    if app.show_report {
+        let block_width = f.size().width / 2;
+        let block_height = f.size().height / 2;
+        let block_x = (size.width - block_width) / 2;
+        let block_y = (size.height - block_height) / 2;
+
+        let area = popups::area(
+            app,
+            Popup::Report,
+            Rect::new(block_x, block_y, block_width, block_height),
+            size,
+        );
+
+        f.render_widget(Clear, area);
+
+        let report_text = app
+            .report
+            .items
+            .iter()
+            .map(|line| ListItem::new(line.clone()))
+            .collect::<Vec<ListItem>>();
+
+        let report_list = List::new(report_text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(app.report_title.clone())
+                    .border_style(
+                        Style::default()
+                            .fg(app.theme.border)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                    .title_alignment(Alignment::Center),
+            )
+            .highlight_style(
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .fg(app.theme.highlight),
+            )
+            .highlight_symbol("> ");
+
+        f.render_stateful_widget(report_list, area, &mut app.report.state);
+    }
+}
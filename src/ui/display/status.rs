@@ -0,0 +1,89 @@
+use crate::app::app::App;
+use crate::app::messages::Level;
+use crate::ui::input::{jobs, typeahead};
+use ratatui::backend::Backend;
+use ratatui::widgets::Paragraph;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    Frame,
+};
+
+fn mode(app: &App) -> &'static str {
+    if typeahead::active(app) {
+        "JUMP"
+    } else if app.show_fzf || app.show_palette || app.show_frecent || app.show_grep {
+        "SEARCH"
+    } else if app.show_popup || app.show_nav {
+        "INPUT"
+    } else {
+        "NORMAL"
+    }
+}
+
+// one line at the bottom: the mode, then whatever was last reported
+pub fn render_status<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+    let mut spans = vec![
+        Span::styled(
+            format!(" {} ", mode(app)),
+            Style::default()
+                .fg(app.theme.highlight)
+                .add_modifier(Modifier::REVERSED | Modifier::BOLD),
+        ),
+        Span::raw(" "),
+    ];
+
+    // a count or g waiting for its motion
+    let pending = format!(
+        "{}{}",
+        app.pending
+            .count
+            .map(|count| count.to_string())
+            .unwrap_or_default(),
+        if app.pending.g { "g" } else { "" }
+    );
+    if !pending.is_empty() {
+        spans.push(Span::styled(
+            format!("{} ", pending),
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    // background jobs, J lists them
+    let running = jobs::running(app);
+    if running > 0 {
+        spans.push(Span::styled(
+            format!("[{} running] ", running),
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    let (info, error, dim) = (app.theme.success, app.theme.error, app.theme.dim);
+
+    if let Some(type_ahead) = app.type_ahead.as_ref().filter(|_| typeahead::active(app)) {
+        if type_ahead.found {
+            spans.push(Span::raw(format!("Jump to: {}", type_ahead.prefix)));
+        } else {
+            spans.push(Span::styled(
+                format!("Nothing starts with {}", type_ahead.prefix),
+                Style::default().fg(error),
+            ));
+        }
+    } else if let Some((message, fading)) = app.messages.current() {
+        let color = match message.level {
+            Level::Info => info,
+            Level::Error => error,
+        };
+
+        let style = if fading {
+            Style::default().fg(dim)
+        } else {
+            Style::default().fg(color)
+        };
+
+        spans.push(Span::styled(message.text.clone(), style));
+    }
+
+    f.render_widget(Paragraph::new(Spans::from(spans)), area);
+}
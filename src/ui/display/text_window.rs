@@ -0,0 +1,152 @@
+use crate::ui::display::pane::convert_bytes;
+use std::ffi::{OsStr, OsString};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+
+// a line start is remembered every this many lines, scrolling far down
+// seeks to the nearest one instead of reading from the top
+const STEP: usize = 1000;
+// wider than any pane, the rest of a minified file's line is skipped
+const MAX_LINE: usize = 4096;
+
+// how much of a text file the preview reads, and where its lines start
+pub struct TextWindow {
+    // preview_max_bytes=, nothing past this offset is read
+    pub max_bytes: u64,
+    // preview_max_lines=
+    pub max_lines: usize,
+    // the file the offsets are for
+    path: OsString,
+    // offsets[i] is where line i * STEP starts
+    offsets: Vec<u64>,
+}
+
+impl Default for TextWindow {
+    fn default() -> TextWindow {
+        TextWindow {
+            max_bytes: 16 * 1024 * 1024,
+            max_lines: 100_000,
+            path: OsString::new(),
+            offsets: vec![0],
+        }
+    }
+}
+
+// the lines in view, and a note in place of the ones past the limits
+pub struct Window {
+    pub lines: Vec<String>,
+    pub footer: Option<String>,
+    pub at_end: bool,
+}
+
+// the next line without its newline, keeping at most MAX_LINE bytes of it and
+// reading no further than `left` bytes. Returns the bytes consumed
+fn read_line(reader: &mut impl BufRead, line: &mut Vec<u8>, left: u64) -> io::Result<u64> {
+    let mut consumed = 0;
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() || consumed >= left {
+            return Ok(consumed);
+        }
+        let available = &buffer[..buffer.len().min((left - consumed) as usize)];
+
+        let (taken, done) = match available.iter().position(|b| *b == b'\n') {
+            Some(at) => (at + 1, true),
+            None => (available.len(), false),
+        };
+        let keep = MAX_LINE.saturating_sub(line.len()).min(taken);
+        line.extend_from_slice(&available[..keep]);
+        reader.consume(taken);
+        consumed += taken as u64;
+
+        if done {
+            if line.last() == Some(&b'\n') {
+                line.pop();
+            }
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            return Ok(consumed);
+        }
+    }
+}
+
+impl TextWindow {
+    // `count` lines of `file` from line `scroll`, reading only from the
+    // closest remembered line start up to the end of the window. `si` is
+    // for the sizes in the note
+    pub fn read(
+        &mut self,
+        path: &OsStr,
+        file: File,
+        scroll: usize,
+        count: usize,
+        si: bool,
+    ) -> Window {
+        if self.path != path {
+            self.path = path.to_os_string();
+            self.offsets = vec![0];
+        }
+        let size = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+
+        let checkpoint = (scroll / STEP).min(self.offsets.len() - 1);
+        let mut line_number = checkpoint * STEP;
+        let mut offset = self.offsets[checkpoint];
+
+        let mut reader = BufReader::new(file);
+        let mut lines = vec![];
+        if reader.seek(SeekFrom::Start(offset)).is_err() {
+            return Window {
+                lines,
+                footer: None,
+                at_end: true,
+            };
+        }
+
+        let mut line = vec![];
+        let capped = loop {
+            if lines.len() >= count {
+                break false;
+            }
+            if offset >= self.max_bytes || line_number >= self.max_lines {
+                break offset < size;
+            }
+
+            line.clear();
+            let consumed = match read_line(&mut reader, &mut line, self.max_bytes - offset) {
+                Ok(0) | Err(_) => break false,
+                Ok(consumed) => consumed,
+            };
+            offset += consumed;
+            line_number += 1;
+
+            if line_number.is_multiple_of(STEP) && line_number / STEP == self.offsets.len() {
+                self.offsets.push(offset);
+            }
+            if line_number > scroll {
+                lines.push(String::from_utf8_lossy(&line).into_owned());
+            }
+        };
+
+        // the note takes the last row, or waits for one more scroll
+        let limit = if line_number >= self.max_lines {
+            format!("{} lines", self.max_lines)
+        } else {
+            convert_bytes(self.max_bytes, si)
+        };
+        let footer = (capped && lines.len() < count).then(|| {
+            format!(
+                "── {} in all, the preview stops at {}, v opens it in the pager ──",
+                convert_bytes(size, si),
+                limit
+            )
+        });
+        let at_end = lines.len() < count;
+
+        Window {
+            lines,
+            footer,
+            at_end,
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use crate::app::app::App;
+use ratatui::backend::Backend;
+use ratatui::layout::Alignment;
+use ratatui::widgets::Clear;
+use ratatui::widgets::ListItem;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    widgets::{Block, Borders, List},
+    Frame,
+};
+
+pub fn render_themes<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
+    if app.show_themes {
+        let block_width = f.size().width / 3;
+        let block_height = f.size().height / 3;
+        let block_x = (size.width - block_width) / 2;
+        let block_y = (size.height - block_height) / 2;
+
+        let area = Rect::new(block_x, block_y, block_width, block_height);
+
+        let themes_block = Block::default()
+            .style(Style::default().add_modifier(Modifier::BOLD))
+            .border_style(
+                Style::default()
+                    .fg(app.theme.border)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .borders(Borders::ALL)
+            .title_alignment(Alignment::Center);
+
+        f.render_widget(Clear, area);
+        f.render_widget(themes_block, area);
+
+        // the theme in use is marked with '*'
+        let themes_text = app
+            .themes
+            .items
+            .iter()
+            .map(|name| {
+                let marker = if name == &app.theme.name { "*" } else { " " };
+                ListItem::new(format!("{} {}", marker, name))
+            })
+            .collect::<Vec<ListItem>>();
+
+        let themes_list = List::new(themes_text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Themes")
+                    .title_alignment(Alignment::Center),
+            )
+            .highlight_style(
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .fg(app.theme.highlight),
+            )
+            .highlight_symbol("> ");
+
+        let themes_list_area =
+            Rect::new(block_x + 1, block_y + 1, block_width - 2, block_height - 2);
+
+        f.render_stateful_widget(themes_list, themes_list_area, &mut app.themes.state);
+    }
+}
@@ -0,0 +1,186 @@
+use crate::app::app::{display_name, App};
+use crate::ui::display::pane::convert_bytes;
+use ratatui::backend::Backend;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Alignment;
+use ratatui::widgets::{Clear, Paragraph, Widget};
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders},
+    Frame,
+};
+
+struct TreemapCell<'a> {
+    label: &'a str,
+    fill: &'a str,
+    color: Color,
+    label_color: Color,
+    selected: bool,
+}
+
+impl<'a> Widget for TreemapCell<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let color = self.color;
+
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                buf.get_mut(x, y)
+                    .set_symbol(self.fill)
+                    .set_style(Style::default().fg(color));
+            }
+        }
+
+        let mut label_style = Style::default().fg(self.label_color).bg(color);
+        if self.selected {
+            label_style = label_style.add_modifier(Modifier::BOLD);
+        }
+
+        buf.set_stringn(area.x, area.y, self.label, area.width as usize, label_style);
+    }
+}
+
+// split the (size sorted) entries in two halves of roughly equal weight,
+// cutting the longer side of the area, until every entry has a rectangle
+fn layout(items: &[(usize, u64)], area: Rect, out: &mut Vec<(usize, Rect)>) {
+    if items.is_empty() || area.width == 0 || area.height == 0 {
+        return;
+    }
+
+    if items.len() == 1 {
+        out.push((items[0].0, area));
+        return;
+    }
+
+    let total: u64 = items.iter().map(|item| item.1).sum();
+    let mut split = 1;
+    let mut acc = 0;
+
+    for (idx, item) in items.iter().enumerate() {
+        acc += item.1;
+        if acc * 2 >= total {
+            split = (idx + 1).clamp(1, items.len() - 1);
+            break;
+        }
+    }
+
+    let first: u64 = items[..split].iter().map(|item| item.1).sum();
+    let ratio = if total == 0 {
+        split as f64 / items.len() as f64
+    } else {
+        first as f64 / total as f64
+    };
+
+    // terminal cells are about twice as tall as they are wide
+    let (a, b) = if area.width >= area.height * 2 {
+        let width = (area.width as f64 * ratio).round() as u16;
+        (
+            Rect::new(area.x, area.y, width, area.height),
+            Rect::new(area.x + width, area.y, area.width - width, area.height),
+        )
+    } else {
+        let height = (area.height as f64 * ratio).round() as u16;
+        (
+            Rect::new(area.x, area.y, area.width, height),
+            Rect::new(area.x, area.y + height, area.width, area.height - height),
+        )
+    };
+
+    layout(&items[..split], a, out);
+    layout(&items[split..], b, out);
+}
+
+pub fn render_treemap<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
+    if app.show_treemap {
+        let total: u64 = app.treemap.items.iter().map(|entry| entry.1).sum();
+
+        let block = Block::default()
+            .title(format!(
+                "Treemap: {} ({})",
+                app.cur_dir.trim_end_matches('\n'),
+                convert_bytes(total, app.si_units)
+            ))
+            .borders(Borders::ALL)
+            .border_style(
+                Style::default()
+                    .fg(app.theme.border)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .title_alignment(Alignment::Center);
+
+        f.render_widget(Clear, size);
+        f.render_widget(block, size);
+
+        if size.width < 3 || size.height < 4 {
+            return;
+        }
+
+        let map_area = Rect::new(size.x + 1, size.y + 1, size.width - 2, size.height - 3);
+        let footer_area = Rect::new(size.x + 1, size.y + size.height - 2, size.width - 2, 1);
+
+        let items = app
+            .treemap
+            .items
+            .iter()
+            .enumerate()
+            .map(|(idx, entry)| (idx, entry.1))
+            .collect::<Vec<(usize, u64)>>();
+
+        let mut cells = vec![];
+        layout(&items, map_area, &mut cells);
+
+        let selected = app.treemap.state.selected();
+
+        for (idx, area) in cells {
+            let (name, bytes) = &app.treemap.items[idx];
+            let label = format!(
+                "{} {}",
+                display_name(name),
+                convert_bytes(*bytes, app.si_units)
+            );
+            let fill = if std::path::Path::new(name).is_dir() {
+                "▓"
+            } else {
+                "░"
+            };
+
+            let palette = &app.theme.palette;
+            let color = if selected == Some(idx) {
+                app.theme.highlight
+            } else if palette.is_empty() {
+                app.theme.muted
+            } else {
+                palette[idx % palette.len()]
+            };
+
+            let cell = TreemapCell {
+                label: &label,
+                fill,
+                color,
+                label_color: app.theme.inverse,
+                selected: selected == Some(idx),
+            };
+            f.render_widget(cell, area);
+        }
+
+        let footer = match selected.and_then(|idx| app.treemap.items.get(idx)) {
+            Some((name, bytes)) => {
+                let percent = if total == 0 {
+                    0.0
+                } else {
+                    *bytes as f64 / total as f64 * 100.0
+                };
+
+                format!(
+                    "> {}  {}  {:.1}%   (Enter: open, Backspace: up, CTRL + d: delete)",
+                    display_name(name),
+                    convert_bytes(*bytes, app.si_units),
+                    percent
+                )
+            }
+            None => "Nothing to show".to_string(),
+        };
+
+        f.render_widget(Paragraph::new(footer), footer_area);
+    }
+}
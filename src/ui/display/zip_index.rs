@@ -0,0 +1,126 @@
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+
+const END: u32 = 0x0605_4b50;
+const LOCATOR64: u32 = 0x0706_4b50;
+const END64: u32 = 0x0606_4b50;
+const CENTRAL: u32 = 0x0201_4b50;
+// the end record is 22 bytes and can have a comment of up to 64 KiB
+const TAIL: u64 = 22 + 0xffff;
+
+// an entry as the central directory has it
+pub struct ZipEntry {
+    pub name: String,
+    pub size: u64,
+    pub is_dir: bool,
+    pub encrypted: bool,
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap_or_default())
+}
+
+fn u64_at(bytes: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap_or_default())
+}
+
+fn invalid(text: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, text)
+}
+
+// the first `limit` entries of a zip and how many it has, from the central
+// directory at its end. The zip crate reads every entry's local header to
+// open an archive, a remote one would be fetched all over for that
+pub fn read<R: Read + Seek>(mut reader: R, limit: usize) -> io::Result<(u64, Vec<ZipEntry>)> {
+    let len = reader.seek(SeekFrom::End(0))?;
+    let start = len.saturating_sub(TAIL);
+    reader.seek(SeekFrom::Start(start))?;
+    let mut tail = vec![];
+    reader.by_ref().take(TAIL).read_to_end(&mut tail)?;
+
+    let end = (0..tail.len().saturating_sub(21))
+        .rev()
+        .find(|at| u32_at(&tail, *at) == END)
+        .ok_or_else(|| invalid("not a zip archive"))?;
+    let mut count = u16_at(&tail, end + 10) as u64;
+    let mut size = u32_at(&tail, end + 12) as u64;
+    let mut end_at = start + end as u64;
+
+    // zip64 has the real numbers in a record of its own before that
+    if end >= 20 && u32_at(&tail, end - 20) == LOCATOR64 {
+        let at = u64_at(&tail, end - 12);
+        let mut record = [0; 56];
+        reader.seek(SeekFrom::Start(at))?;
+        reader.read_exact(&mut record)?;
+        if u32_at(&record, 0) != END64 {
+            return Err(invalid("a broken zip64 archive"));
+        }
+
+        count = u64_at(&record, 32);
+        size = u64_at(&record, 40);
+        end_at = at;
+    }
+
+    // the directory ends where the end records start, which holds whatever
+    // was put in front of the archive
+    let directory = end_at
+        .checked_sub(size)
+        .ok_or_else(|| invalid("a broken zip archive"))?;
+    reader.seek(SeekFrom::Start(directory))?;
+    let mut reader = BufReader::new(reader);
+
+    let mut entries = vec![];
+    while entries.len() < limit && (entries.len() as u64) < count {
+        match read_entry(&mut reader) {
+            Ok(entry) => entries.push(entry),
+            // what was read before a remote one runs out of fetches
+            Err(_) if !entries.is_empty() => break,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok((count, entries))
+}
+
+fn read_entry<R: Read>(reader: &mut R) -> io::Result<ZipEntry> {
+    let mut header = [0; 46];
+    reader.read_exact(&mut header)?;
+    if u32_at(&header, 0) != CENTRAL {
+        return Err(invalid("a broken zip archive"));
+    }
+
+    let name_len = u16_at(&header, 28) as usize;
+    let extra_len = u16_at(&header, 30) as usize;
+    let comment_len = u16_at(&header, 32) as usize;
+    let mut variable = vec![0; name_len + extra_len + comment_len];
+    reader.read_exact(&mut variable)?;
+
+    let name = String::from_utf8_lossy(&variable[..name_len]).to_string();
+    let size = match u32_at(&header, 24) {
+        0xffff_ffff => zip64_size(&variable[name_len..name_len + extra_len]).unwrap_or(0),
+        size => size as u64,
+    };
+
+    Ok(ZipEntry {
+        is_dir: name.ends_with('/'),
+        encrypted: u16_at(&header, 8) & 1 == 1,
+        name,
+        size,
+    })
+}
+
+// a size too big for the header is first in the zip64 extra field
+fn zip64_size(mut extra: &[u8]) -> Option<u64> {
+    while extra.len() >= 4 {
+        let len = u16_at(extra, 2) as usize;
+        if u16_at(extra, 0) == 1 && len >= 8 && extra.len() >= 12 {
+            return Some(u64_at(extra, 4));
+        }
+        extra = extra.get(4 + len..)?;
+    }
+
+    None
+}
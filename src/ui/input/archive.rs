@@ -0,0 +1,386 @@
+use flate2::read::{GzDecoder, MultiGzDecoder};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use walkdir::WalkDir;
+
+// the archives traverse reads and writes, 7z, xz, zstd and age each come
+// with a feature of their own
+#[derive(Clone, Copy, PartialEq)]
+pub enum Format {
+    Zip,
+    Tar,
+    TarGz,
+    #[cfg(feature = "xz")]
+    TarXz,
+    #[cfg(feature = "zstd")]
+    TarZst,
+    #[cfg(feature = "sevenz")]
+    SevenZ,
+    // a single file encrypted with a passphrase, not an archive of its own
+    #[cfg(feature = "age")]
+    Age,
+}
+
+impl Format {
+    // in the order the compress dialog offers them
+    pub fn all() -> Vec<Format> {
+        let mut formats = vec![Format::Zip, Format::TarGz];
+        #[cfg(feature = "xz")]
+        formats.push(Format::TarXz);
+        #[cfg(feature = "zstd")]
+        formats.push(Format::TarZst);
+        #[cfg(feature = "sevenz")]
+        formats.push(Format::SevenZ);
+        formats.push(Format::Tar);
+        #[cfg(feature = "age")]
+        formats.push(Format::Age);
+
+        formats
+    }
+
+    // the first one is what new archives get
+    fn suffixes(self) -> &'static [&'static str] {
+        match self {
+            Format::Zip => &[".zip"],
+            Format::Tar => &[".tar"],
+            Format::TarGz => &[".tar.gz", ".tgz"],
+            #[cfg(feature = "xz")]
+            Format::TarXz => &[".tar.xz", ".txz"],
+            #[cfg(feature = "zstd")]
+            Format::TarZst => &[".tar.zst", ".tzst"],
+            #[cfg(feature = "sevenz")]
+            Format::SevenZ => &[".7z"],
+            #[cfg(feature = "age")]
+            Format::Age => &[".age"],
+        }
+    }
+
+    pub fn suffix(self) -> &'static str {
+        self.suffixes()[0]
+    }
+
+    // what the compress dialog says about it
+    pub fn description(self) -> &'static str {
+        match self {
+            Format::Zip => "opens anywhere",
+            Format::Tar => "not compressed",
+            Format::TarGz => "the usual on Linux",
+            #[cfg(feature = "xz")]
+            Format::TarXz => "smallest, slowest",
+            #[cfg(feature = "zstd")]
+            Format::TarZst => "small and fast",
+            #[cfg(feature = "sevenz")]
+            Format::SevenZ => "small, for Windows users",
+            #[cfg(feature = "age")]
+            Format::Age => "one file, encrypted",
+        }
+    }
+
+    // takes one file and nothing else
+    pub fn single_file(self) -> bool {
+        #[cfg(feature = "age")]
+        if self == Format::Age {
+            return true;
+        }
+        false
+    }
+
+    // asks for a password before it's written, age can't do without one
+    pub fn encrypts(self) -> bool {
+        match self {
+            Format::Zip => true,
+            #[cfg(feature = "sevenz")]
+            Format::SevenZ => true,
+            #[cfg(feature = "age")]
+            Format::Age => true,
+            _ => false,
+        }
+    }
+
+    pub fn of(name: &str) -> Option<Format> {
+        let name = name.to_lowercase();
+
+        Format::all().into_iter().find(|format| {
+            format
+                .suffixes()
+                .iter()
+                .any(|suffix| name.ends_with(suffix))
+        })
+    }
+}
+
+// the tar stream inside a tar archive, None for the rest
+pub fn tar_reader<R: Read + 'static>(format: Format, file: R) -> io::Result<Option<Box<dyn Read>>> {
+    Ok(match format {
+        Format::Tar => Some(Box::new(file)),
+        Format::TarGz => Some(Box::new(GzDecoder::new(file))),
+        #[cfg(feature = "xz")]
+        Format::TarXz => Some(Box::new(xz2::read::XzDecoder::new(file))),
+        #[cfg(feature = "zstd")]
+        Format::TarZst => Some(Box::new(zstd::Decoder::new(file)?)),
+        _ => None,
+    })
+}
+
+// a single compressed file (foo.log.gz) decompressed as it's read, with what
+// it was compressed with, None for archives and everything else
+pub fn decompressor(path: &Path) -> Option<(&'static str, Box<dyn Read>)> {
+    let name = path.to_string_lossy().to_lowercase();
+    if Format::of(&name).is_some() {
+        return None;
+    }
+
+    if name.ends_with(".gz") {
+        // logrotate and friends append gzip members, read them all
+        let file = File::open(path).ok()?;
+        return Some(("gzip", Box::new(MultiGzDecoder::new(file))));
+    }
+
+    #[cfg(feature = "xz")]
+    if name.ends_with(".xz") {
+        let file = File::open(path).ok()?;
+        return Some((
+            "xz",
+            Box::new(xz2::read::XzDecoder::new_multi_decoder(file)),
+        ));
+    }
+
+    #[cfg(feature = "zstd")]
+    if name.ends_with(".zst") {
+        let file = File::open(path).ok()?;
+        return Some(("zstd", Box::new(zstd::Decoder::new(file).ok()?)));
+    }
+
+    None
+}
+
+// every path below the sources with the name it gets in the archive, which
+// starts at the source's own name, leaving out the archive being written
+fn entries(sources: &[PathBuf], dest: &Path) -> io::Result<Vec<(PathBuf, String)>> {
+    let mut entries = vec![];
+
+    for source in sources {
+        let base = source.parent().unwrap_or(Path::new(""));
+
+        for entry in WalkDir::new(source) {
+            let entry = entry.map_err(io::Error::other)?;
+            if entry.path() == dest {
+                continue;
+            }
+
+            let name = entry
+                .path()
+                .strip_prefix(base)
+                .map_err(io::Error::other)?
+                .components()
+                .map(|part| part.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<String>>()
+                .join("/");
+
+            entries.push((entry.path().to_path_buf(), name));
+        }
+    }
+
+    Ok(entries)
+}
+
+// a killed job stops before the next entry
+fn killed(kill: &AtomicBool) -> io::Result<()> {
+    if kill.load(Ordering::Relaxed) {
+        return Err(io::Error::other("killed"));
+    }
+    Ok(())
+}
+
+fn write_tar<W: Write>(
+    writer: W,
+    sources: &[PathBuf],
+    dest: &Path,
+    kill: &AtomicBool,
+) -> io::Result<W> {
+    let mut builder = tar::Builder::new(writer);
+    // links go in as links, like tar itself does
+    builder.follow_symlinks(false);
+
+    for (path, name) in entries(sources, dest)? {
+        killed(kill)?;
+        builder.append_path_with_name(&path, &name)?;
+    }
+
+    builder.into_inner()
+}
+
+// AES-256 with a password, which 7-Zip, WinRAR and unzip from Info-ZIP 6.1
+// read, Windows Explorer only does the weaker ZipCrypto
+fn write_zip(
+    file: File,
+    sources: &[PathBuf],
+    dest: &Path,
+    password: Option<&str>,
+    kill: &AtomicBool,
+) -> io::Result<()> {
+    let mut zip = zip::ZipWriter::new(file);
+
+    for (path, name) in entries(sources, dest)? {
+        killed(kill)?;
+        let metadata = fs::symlink_metadata(&path)?;
+        let mut options = zip::write::SimpleFileOptions::default();
+        if let Some(password) = password {
+            options = options.with_aes_encryption(zip::AesMode::Aes256, password);
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            options = options.unix_permissions(metadata.permissions().mode() & 0o7777);
+        }
+
+        if metadata.is_symlink() {
+            let target = fs::read_link(&path)?;
+            zip.add_symlink(name, target.to_string_lossy(), options)?;
+        } else if metadata.is_dir() {
+            zip.add_directory(name, options)?;
+        } else {
+            zip.start_file(name, options)?;
+            io::copy(&mut File::open(&path)?, &mut zip)?;
+        }
+    }
+
+    zip.finish()?;
+
+    Ok(())
+}
+
+#[cfg(feature = "sevenz")]
+fn write_7z(
+    file: File,
+    sources: &[PathBuf],
+    dest: &Path,
+    password: Option<&str>,
+    kill: &AtomicBool,
+) -> io::Result<()> {
+    use sevenz_rust::{AesEncoderOptions, SevenZArchiveEntry, SevenZMethod, SevenZWriter};
+
+    let mut writer = SevenZWriter::new(file).map_err(io::Error::other)?;
+    // the names are encrypted along with the data
+    if let Some(password) = password {
+        writer.set_content_methods(vec![
+            AesEncoderOptions::new(password.into()).into(),
+            SevenZMethod::LZMA2.into(),
+        ]);
+    }
+
+    for (path, name) in entries(sources, dest)? {
+        killed(kill)?;
+        let entry = SevenZArchiveEntry::from_path(&path, name);
+
+        let result = if path.is_file() {
+            writer.push_archive_entry(entry, Some(File::open(&path)?))
+        } else {
+            writer.push_archive_entry::<File>(entry, None)
+        };
+        result.map_err(io::Error::other)?;
+    }
+
+    writer.finish()?;
+
+    Ok(())
+}
+
+#[cfg(feature = "age")]
+fn write_age(file: File, sources: &[PathBuf], password: Option<&str>) -> io::Result<()> {
+    let source = match sources {
+        [source] if source.is_file() => source,
+        _ => return Err(io::Error::other("age encrypts a single file")),
+    };
+    let password = password
+        .filter(|password| !password.is_empty())
+        .ok_or_else(|| io::Error::other("age needs a passphrase"))?;
+
+    let encryptor = age::Encryptor::with_user_passphrase(password.to_string().into());
+    let mut writer = encryptor.wrap_output(file)?;
+    io::copy(&mut File::open(source)?, &mut writer)?;
+    writer.finish()?;
+
+    Ok(())
+}
+
+// the file inside `source` written next to it, under its name without .age
+#[cfg(feature = "age")]
+pub fn decrypt_age(source: &Path, password: &str) -> Result<PathBuf, String> {
+    let dest = source.with_extension("");
+    if dest.exists() {
+        return Err(format!("{} already exists", dest.display()));
+    }
+
+    let file = File::open(source).map_err(|err| err.to_string())?;
+    let decryptor =
+        age::Decryptor::new_buffered(io::BufReader::new(file)).map_err(|err| err.to_string())?;
+    if !decryptor.is_scrypt() {
+        return Err("it's encrypted to a key, only passphrases are supported".to_string());
+    }
+
+    let identity = age::scrypt::Identity::new(password.to_string().into());
+    let mut reader = match decryptor.decrypt(std::iter::once(&identity as &dyn age::Identity)) {
+        Ok(reader) => reader,
+        Err(age::DecryptError::DecryptionFailed) => return Err("wrong passphrase".to_string()),
+        Err(err) => return Err(err.to_string()),
+    };
+
+    // a damaged file fails halfway, nothing half decrypted is left behind
+    let result = File::create(&dest).and_then(|mut out| io::copy(&mut reader, &mut out));
+    if let Err(err) = result {
+        let _ = fs::remove_file(&dest);
+        return Err(err.to_string());
+    }
+
+    Ok(dest)
+}
+
+// `dest` holding the sources, encrypted when there's a password, a half
+// written or killed one is removed again
+pub fn create(
+    format: Format,
+    sources: &[PathBuf],
+    dest: &Path,
+    password: Option<&str>,
+    kill: &AtomicBool,
+) -> io::Result<()> {
+    let file = File::create(dest)?;
+
+    let result = match format {
+        Format::Zip => write_zip(file, sources, dest, password, kill),
+        Format::Tar => write_tar(file, sources, dest, kill).map(|_| ()),
+        Format::TarGz => write_tar(
+            GzEncoder::new(file, Compression::default()),
+            sources,
+            dest,
+            kill,
+        )
+        .and_then(|encoder| encoder.finish())
+        .map(|_| ()),
+        #[cfg(feature = "xz")]
+        Format::TarXz => write_tar(xz2::write::XzEncoder::new(file, 6), sources, dest, kill)
+            .and_then(|encoder| encoder.finish())
+            .map(|_| ()),
+        #[cfg(feature = "zstd")]
+        Format::TarZst => zstd::Encoder::new(file, 0)
+            .and_then(|encoder| write_tar(encoder, sources, dest, kill))
+            .and_then(|encoder| encoder.finish())
+            .map(|_| ()),
+        #[cfg(feature = "sevenz")]
+        Format::SevenZ => write_7z(file, sources, dest, password, kill),
+        #[cfg(feature = "age")]
+        Format::Age => write_age(file, sources, password),
+    };
+
+    if result.is_err() {
+        let _ = fs::remove_file(dest);
+    }
+
+    result
+}
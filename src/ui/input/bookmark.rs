@@ -1,8 +1,10 @@
 use super::run_app::Command;
-use crate::app::app::App;
+use crate::app::app::{display_name, App};
+use crate::ui::display::block::block_binds;
 use dirs::config_dir;
-use std::fs::OpenOptions;
-use std::io::prelude::*;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, prelude::*};
+use std::path::PathBuf;
 
 pub fn handle_bookmark(app: &mut App) {
     if app.last_command != Some(Command::Bookmark) {
@@ -12,67 +14,101 @@ pub fn handle_bookmark(app: &mut App) {
     }
 }
 
+// the bookmarks live in traverse's config directory, a system without one
+// can't keep any
+fn bookmarks_file() -> io::Result<PathBuf> {
+    config_dir()
+        .map(|dir| dir.join("traverse/bookmarks.txt"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))
+}
+
 pub fn read_bookmark(app: &mut App) {
-    if !config_dir()
-        .unwrap()
-        .join("traverse/bookmarks.txt")
-        .exists()
-    {
-        return;
+    if let Err(e) = read_bookmarks(app) {
+        app.show_error("Couldn't read the bookmarks", e);
+    }
+
+    if app.bookmarked_dirs.items.len() > 0 {
+        app.bookmarked_dirs.state.select(Some(0));
+    }
+
+    app.bookmarked_dirs.items.sort();
+}
+
+fn read_bookmarks(app: &mut App) -> io::Result<()> {
+    let path = bookmarks_file()?;
+    if !path.exists() {
+        return Ok(());
     }
 
-    let file = std::fs::File::open(config_dir().unwrap().join("traverse/bookmarks.txt")).unwrap();
-    let reader = std::io::BufReader::new(file);
+    let file = File::open(path)?;
+    let reader = io::BufReader::new(file);
 
+    // path, optionally followed by a tab separated label and note
     for line in reader.lines() {
-        let line = line.unwrap();
+        let line = line?;
+        let mut fields = line.split('\t');
+        let path = fields.next().unwrap_or_default().to_string();
+        let label = fields.next().unwrap_or_default().to_string();
+        let note = fields.next().unwrap_or_default().to_string();
 
-        if app.bookmarked_dirs.items.contains(&line) {
+        if path.is_empty() {
+            continue;
+        }
+
+        if !label.is_empty() || !note.is_empty() {
+            app.bookmark_notes.insert(path.clone(), (label, note));
+        }
+
+        if app.bookmarked_dirs.items.contains(&path) {
             continue;
         } else {
-            app.bookmarked_dirs.items.push(line);
+            app.bookmarked_dirs.items.push(path);
         }
     }
 
-    if app.bookmarked_dirs.items.len() > 0 {
-        app.bookmarked_dirs.state.select(Some(0));
+    Ok(())
+}
+
+pub fn add_bookmark(app: &mut App) {
+    match std::env::current_dir() {
+        Ok(path) => bookmark_path(app, path),
+        Err(e) => app.show_error("Couldn't read the current directory", e),
     }
+}
 
-    app.bookmarked_dirs.items.sort();
+// bookmark the highlighted file or directory rather than the cwd
+pub fn add_selected_bookmark(app: &mut App) {
+    if let Some(path) = selected_entry_path(app) {
+        bookmark_path(app, path);
+    }
 }
 
-pub fn add_bookmark(app: &mut App) {
-    let path = std::env::current_dir().unwrap();
+fn bookmark_path(app: &mut App, path: PathBuf) {
     let dirs = app.bookmarked_dirs.items.clone();
 
-    if dirs.contains(&path.to_str().unwrap().to_string()) {
-        return;
-    } else {
-        app.bookmarked_dirs
-            .items
-            .push(path.to_str().unwrap().to_string());
-
-        let mut data = path.to_str().unwrap().to_string();
-        data = format!("{}\n", data);
-
-        if !config_dir()
-            .unwrap()
-            .join("traverse/bookmarks.txt")
-            .exists()
-        {
-            std::fs::create_dir_all(config_dir().unwrap().join("traverse")).unwrap();
-            std::fs::File::create(config_dir().unwrap().join("traverse/bookmarks.txt")).unwrap();
+    // bookmarks are lines of text, a path that isn't can't be one
+    let path = match path.to_str() {
+        Some(path) => path.to_string(),
+        None => {
+            return app.notify_error(format!(
+                "Can't bookmark {}, its name isn't valid UTF-8",
+                display_name(&path)
+            ))
         }
+    };
 
-        let mut file = OpenOptions::new()
-            .append(true)
-            .open(config_dir().unwrap().join("traverse/bookmarks.txt"))
-            .expect("Unable to open file");
+    if dirs.contains(&path) {
+        app.notify_error(format!("{} is already bookmarked", path));
+        return;
+    }
 
-        file.write_all(data.as_bytes())
-            .expect("Unable to write data");
+    if let Err(e) = append_bookmark(&path) {
+        return app.show_error(&format!("Couldn't bookmark {}", path), e);
     }
 
+    app.notify(format!("Bookmarked {}", path));
+    app.bookmarked_dirs.items.push(path);
+
     if app.bookmarked_dirs.items.len() > 0 {
         app.bookmarked_dirs.state.select(Some(0));
     }
@@ -80,30 +116,136 @@ pub fn add_bookmark(app: &mut App) {
     app.update_bookmarks();
 }
 
+fn append_bookmark(path: &str) -> io::Result<()> {
+    let file = bookmarks_file()?;
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut file = OpenOptions::new().append(true).create(true).open(file)?;
+    file.write_all(format!("{}\n", path).as_bytes())
+}
+
 pub fn delete_bookmark(app: &mut App) {
-    let index = app.bookmarked_dirs.state.selected().unwrap();
-    let path = std::env::current_dir().unwrap();
+    let index = match app.bookmarked_dirs.state.selected() {
+        Some(index) if index < app.bookmarked_dirs.items.len() => index,
+        _ => return,
+    };
+    let path = match std::env::current_dir() {
+        Ok(path) => path,
+        Err(e) => return app.show_error("Couldn't read the current directory", e),
+    };
     let dirs = app.bookmarked_dirs.items.clone();
 
-    if dirs.contains(&path.to_str().unwrap().to_string()) {
-        app.bookmarked_dirs.items.remove(index);
+    if dirs.contains(&path.to_string_lossy().to_string()) {
+        let removed = app.bookmarked_dirs.items.remove(index);
+        app.bookmark_notes.remove(&removed);
 
-        let mut file = OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .open(config_dir().unwrap().join("traverse/bookmarks.txt"))
-            .expect("Unable to open file");
+        write_bookmarks(app);
+    }
+
+    app.update_bookmarks();
+}
+
+fn write_bookmarks(app: &mut App) {
+    if let Err(e) = write_bookmarks_file(app) {
+        app.show_error("Couldn't save the bookmarks", e);
+    }
+}
+
+fn write_bookmarks_file(app: &App) -> io::Result<()> {
+    let path = bookmarks_file()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .create(true)
+        .open(path)?;
+
+    for dir in &app.bookmarked_dirs.items {
+        let data = match app.bookmark_notes.get(dir) {
+            Some((label, note)) => format!("{}\t{}\t{}\n", dir, label, note),
+            None => format!("{}\n", dir),
+        };
+
+        file.write_all(data.as_bytes())?;
+    }
+
+    file.sync_all()
+}
+
+pub fn selected_entry_path(app: &App) -> Option<PathBuf> {
+    let name = match app.files.state.selected() {
+        Some(i) => &app.files.items.get(i)?.0,
+        None => &app.dirs.items.get(app.dirs.state.selected()?)?.0,
+    };
+
+    if name == "../" {
+        return None;
+    }
+
+    Some(std::env::current_dir().ok()?.join(name))
+}
 
-        for dir in &app.bookmarked_dirs.items {
-            let mut data = dir.to_string();
-            data = format!("{}\n", data);
+// edit the label and note of the highlighted bookmark, or of the
+// selected entry when that is bookmarked
+pub fn handle_edit_bookmark(app: &mut App, input: &mut String, input_active: &mut bool) {
+    if *input_active {
+        return;
+    }
+
+    let path = if app.show_bookmark {
+        match app.bookmarked_dirs.state.selected() {
+            Some(i) => app.bookmarked_dirs.items[i].clone(),
+            None => return,
+        }
+    } else {
+        if block_binds(app) {
+            return;
+        }
 
-            file.write_all(data.as_bytes())
-                .expect("Unable to write data");
+        match selected_entry_path(app) {
+            Some(path) => path.to_string_lossy().to_string(),
+            None => return,
         }
+    };
 
-        file.sync_all().expect("Unable to sync data");
+    if !app.bookmarked_dirs.items.contains(&path) {
+        return;
     }
 
-    app.update_bookmarks();
+    *input = match app.bookmark_notes.get(&path) {
+        Some((label, note)) => format!("{} | {}", label, note),
+        None => String::new(),
+    };
+
+    app.editing_bookmark = Some(path);
+    app.show_popup = true;
+    app.last_command = Some(Command::EditBookmark);
+    *input_active = true;
+}
+
+// the input is "label | note"
+pub fn save_bookmark_annotation(app: &mut App, input: &str) {
+    let path = match app.editing_bookmark.take() {
+        Some(path) => path,
+        None => return,
+    };
+
+    let input = input.replace('\t', " ");
+    let (label, note) = match input.split_once('|') {
+        Some((label, note)) => (label.trim().to_string(), note.trim().to_string()),
+        None => (input.trim().to_string(), String::new()),
+    };
+
+    if label.is_empty() && note.is_empty() {
+        app.bookmark_notes.remove(&path);
+    } else {
+        app.bookmark_notes.insert(path, (label, note));
+    }
+
+    write_bookmarks(app);
 }
@@ -0,0 +1,406 @@
+use super::bookmark::selected_entry_path;
+use super::clipboard;
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use walkdir::WalkDir;
+
+const MANIFEST: &str = "SHA256SUMS";
+// version control internals aren't what a manifest is for
+const VCS: [&str; 3] = [".git", ".hg", ".svn"];
+
+fn sha256(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+
+    io::copy(&mut file, &mut hasher)?;
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+// (name, hex) of each hash of a file, or why it couldn't be read
+pub type Digests = Result<Vec<(&'static str, String)>, String>;
+
+// MD5, SHA1, SHA256 and BLAKE3 of one file, reading it once
+fn digests(path: &Path) -> io::Result<Vec<(&'static str, String)>> {
+    let mut file = File::open(path)?;
+    let (mut md5, mut sha1, mut sha256) = (Md5::new(), Sha1::new(), Sha256::new());
+    let mut blake3 = blake3::Hasher::new();
+    let mut buf = vec![0; 64 * 1024];
+
+    loop {
+        let read = file.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+
+        md5.update(&buf[..read]);
+        sha1.update(&buf[..read]);
+        sha256.update(&buf[..read]);
+        blake3.update(&buf[..read]);
+    }
+
+    Ok(vec![
+        ("MD5", format!("{:x}", md5.finalize())),
+        ("SHA1", format!("{:x}", sha1.finalize())),
+        ("SHA256", format!("{:x}", sha256.finalize())),
+        ("BLAKE3", blake3.finalize().to_hex().to_string()),
+    ])
+}
+
+// marked files (and everything below marked directories), or the whole
+// current directory when nothing is marked
+fn manifest_roots(app: &App, cur_dir: &Path) -> Vec<PathBuf> {
+    if app.selected_files.is_empty() {
+        vec![cur_dir.to_path_buf()]
+    } else {
+        app.selected_files.iter().map(PathBuf::from).collect()
+    }
+}
+
+// the files below `roots`, walked in the background job since a big tree
+// takes a while
+fn manifest_targets(roots: &[PathBuf], cur_dir: &Path) -> Vec<PathBuf> {
+    let mut targets = roots
+        .iter()
+        .flat_map(|root| {
+            WalkDir::new(root)
+                .into_iter()
+                .filter_entry(|entry| {
+                    entry.depth() == 0 || !VCS.iter().any(|vcs| entry.file_name() == *vcs)
+                })
+                .filter_map(|e| e.ok())
+        })
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+        .filter(|path| path != &cur_dir.join(MANIFEST))
+        .collect::<Vec<PathBuf>>();
+
+    targets.sort();
+    targets.dedup();
+    targets
+}
+
+fn show_running(app: &mut App, title: &str) {
+    app.open_report(title, vec!["Hashing in the background...".to_string()]);
+}
+
+pub fn handle_manifest(app: &mut App) {
+    if block_binds(app) || app.checksum_job.is_some() {
+        return;
+    }
+
+    let cur_dir = match std::env::current_dir() {
+        Ok(dir) => dir,
+        Err(_) => return,
+    };
+    let roots = manifest_roots(app, &cur_dir);
+
+    let (tx, rx) = mpsc::channel();
+    app.checksum_job = Some(rx);
+    show_running(app, "Generate SHA256SUMS");
+
+    thread::spawn(move || {
+        let targets = manifest_targets(&roots, &cur_dir);
+        let mut manifest = String::new();
+        let mut errors = vec![];
+
+        for path in &targets {
+            let name = path.strip_prefix(&cur_dir).unwrap_or(path);
+
+            match sha256(path) {
+                Ok(hash) => manifest.push_str(&manifest_line(&hash, &name.to_string_lossy())),
+                Err(err) => errors.push(format!("unreadable: {} ({})", name.display(), err)),
+            }
+        }
+
+        let mut lines = match fs::write(cur_dir.join(MANIFEST), manifest) {
+            Ok(_) => vec![format!(
+                "Wrote {} checksums to {}",
+                targets.len() - errors.len(),
+                cur_dir.join(MANIFEST).display()
+            )],
+            Err(err) => vec![format!("Failed to write {}: {}", MANIFEST, err)],
+        };
+        lines.extend(errors);
+
+        let _ = tx.send(lines);
+    });
+}
+
+// a name with a backslash or a line break in it is written escaped, with a
+// backslash in front of the line to say so, like sha256sum does
+fn manifest_line(hash: &str, name: &str) -> String {
+    if name.contains(['\\', '\n', '\r']) {
+        let name = name
+            .replace('\\', "\\\\")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r");
+        format!("\\{}  {}\n", hash, name)
+    } else {
+        format!("{}  {}\n", hash, name)
+    }
+}
+
+fn unescape(name: &str) -> Option<String> {
+    let mut unescaped = String::new();
+    let mut chars = name.chars();
+
+    while let Some(c) = chars.next() {
+        unescaped.push(match c {
+            '\\' => match chars.next()? {
+                '\\' => '\\',
+                'n' => '\n',
+                'r' => '\r',
+                _ => return None,
+            },
+            c => c,
+        });
+    }
+
+    Some(unescaped)
+}
+
+// "<hash>  <name>", or "<hash> *<name>" for binary mode entries, as
+// sha256sum writes them: the hash, one space, the mode and then the name as
+// it is, leading spaces and all, unless the line starts with a backslash
+fn manifest_entry(line: &str) -> Option<(&str, String)> {
+    let (escaped, line) = match line.strip_prefix('\\') {
+        Some(line) => (true, line),
+        None => (false, line),
+    };
+
+    let (hash, rest) = line.split_at_checked(64)?;
+    if !hash.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let name = rest.strip_prefix(' ')?.strip_prefix([' ', '*'])?;
+    let name = match escaped {
+        true => unescape(name)?,
+        false => name.to_string(),
+    };
+    (!name.is_empty()).then_some((hash, name))
+}
+
+pub fn handle_verify(app: &mut App) {
+    if block_binds(app) || app.checksum_job.is_some() {
+        return;
+    }
+
+    let cur_dir = match std::env::current_dir() {
+        Ok(dir) => dir,
+        Err(_) => return,
+    };
+
+    let (tx, rx) = mpsc::channel();
+    app.checksum_job = Some(rx);
+    show_running(app, "Verify SHA256SUMS");
+
+    thread::spawn(move || {
+        let manifest = match fs::read_to_string(cur_dir.join(MANIFEST)) {
+            Ok(manifest) => manifest,
+            Err(err) => {
+                let _ = tx.send(vec![format!("Cannot read {}: {}", MANIFEST, err)]);
+                return;
+            }
+        };
+
+        let (mut ok, mut failures) = (0, vec![]);
+
+        for line in manifest.lines().filter(|line| !line.trim().is_empty()) {
+            let (hash, name) = match manifest_entry(line) {
+                Some(entry) => entry,
+                None => {
+                    failures.push(format!("MALFORMED: {}", line));
+                    continue;
+                }
+            };
+
+            match sha256(&cur_dir.join(&name)) {
+                Ok(actual) if actual.eq_ignore_ascii_case(hash) => ok += 1,
+                Ok(_) => failures.push(format!("FAILED: {}", name)),
+                Err(_) => failures.push(format!("MISSING: {}", name)),
+            }
+        }
+
+        let mut lines = vec![format!("{} OK, {} failed", ok, failures.len())];
+        lines.extend(failures);
+
+        let _ = tx.send(lines);
+    });
+}
+
+pub fn handle_hash(app: &mut App) {
+    if block_binds(app) || app.hash_job.is_some() {
+        return;
+    }
+
+    let path = match selected_entry_path(app) {
+        Some(path) if path.is_file() => path,
+        _ => return app.notify_error("Select a file to hash it"),
+    };
+
+    let (tx, rx) = mpsc::channel();
+    app.hash_job = Some(rx);
+    app.open_report(
+        "Checksums",
+        vec![format!("Hashing {} in the background...", path.display())],
+    );
+
+    thread::spawn(move || {
+        let _ = tx.send((path.clone(), digests(&path).map_err(|err| err.to_string())));
+    });
+}
+
+// the four hashes, SHA256 highlighted so Enter copies it unless another one
+// is picked
+pub fn poll_hash_job(app: &mut App) {
+    if block_binds(app) && !app.show_report {
+        return;
+    }
+
+    let (path, digests) = match &app.hash_job {
+        Some(rx) => match rx.try_recv() {
+            Ok(result) => result,
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                (PathBuf::new(), Err("the hashing job stopped".to_string()))
+            }
+        },
+        None => return,
+    };
+
+    app.hash_job = None;
+
+    let digests = match digests {
+        Ok(digests) => digests,
+        Err(err) => return app.show_error(&format!("Couldn't hash {}", path.display()), err),
+    };
+
+    let mut lines = digests
+        .iter()
+        .map(|(name, hash)| format!("{:<8}{}", name, hash))
+        .collect::<Vec<String>>();
+    lines.push(String::new());
+    lines.push("Enter copies the highlighted one to the clipboard.".to_string());
+
+    let title = format!(
+        "Checksums of {}",
+        path.file_name().unwrap_or_default().to_string_lossy()
+    );
+    app.open_report(&title, lines);
+    app.report.state.select(Some(2));
+    app.hashes = Some(digests.into_iter().map(|(_, hash)| hash).collect());
+}
+
+pub fn copy_hash(app: &mut App) {
+    let hash = app
+        .report
+        .state
+        .selected()
+        .and_then(|idx| app.hashes.as_ref()?.get(idx))
+        .cloned();
+
+    let hash = match hash {
+        Some(hash) => hash,
+        None => return app.notify_error("Highlight a checksum to copy it"),
+    };
+
+    match clipboard::copy(&hash) {
+        Ok(_) => {
+            app.show_report = false;
+            app.hashes = None;
+            app.notify(format!("Copied {}", hash));
+        }
+        Err(err) => app.notify_error(format!("Couldn't copy the checksum: {}", err)),
+    }
+}
+
+// show the result once the job is done, unless another popup is in the way
+pub fn poll_checksum_job(app: &mut App) {
+    if block_binds(app) && !app.show_report {
+        return;
+    }
+
+    let lines = match &app.checksum_job {
+        Some(rx) => match rx.try_recv() {
+            Ok(lines) => lines,
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => vec!["Checksum job stopped".to_string()],
+        },
+        None => return,
+    };
+
+    app.checksum_job = None;
+    // the title is still the one the job was started with
+    let title = app.report_title.clone();
+    app.open_report(&title, lines);
+    app.report.state.select(Some(0));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASH: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    #[test]
+    fn reads_plain_entries() {
+        let line = format!("{}  sub/a file", HASH);
+        assert_eq!(
+            manifest_entry(&line),
+            Some((HASH, "sub/a file".to_string()))
+        );
+
+        let line = format!("{} * leading space", HASH);
+        assert_eq!(
+            manifest_entry(&line),
+            Some((HASH, " leading space".to_string()))
+        );
+
+        assert_eq!(manifest_entry(&format!("{} a", HASH)), None);
+        assert_eq!(manifest_entry(&format!("{}  ", HASH)), None);
+        assert_eq!(manifest_entry("not a hash  name"), None);
+    }
+
+    #[test]
+    fn escapes_like_sha256sum() {
+        assert_eq!(manifest_line(HASH, "plain"), format!("{}  plain\n", HASH));
+        assert_eq!(
+            manifest_line(HASH, "a\\b\nc"),
+            format!("\\{}  a\\\\b\\nc\n", HASH)
+        );
+
+        for name in ["plain", "a\\b\nc", "back\\n\\slash", "cr\r"] {
+            let line = manifest_line(HASH, name);
+            assert_eq!(
+                manifest_entry(line.trim_end_matches('\n')),
+                Some((HASH, name.to_string()))
+            );
+        }
+
+        assert_eq!(manifest_entry(&format!("\\{}  bad\\x", HASH)), None);
+    }
+
+    #[test]
+    fn leaves_out_vcs_dirs() {
+        let dir = std::env::temp_dir().join(format!("rt-manifest-{}", std::process::id()));
+        fs::create_dir_all(dir.join(".git/objects")).unwrap();
+        fs::write(dir.join(".git/objects/x"), "").unwrap();
+        fs::write(dir.join("kept"), "").unwrap();
+        fs::write(dir.join(MANIFEST), "").unwrap();
+
+        let targets = manifest_targets(std::slice::from_ref(&dir), &dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(targets, vec![dir.join("kept")]);
+    }
+}
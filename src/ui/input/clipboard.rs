@@ -0,0 +1,418 @@
+use super::bookmark::selected_entry_path;
+use super::conflicts::{self, Then};
+use super::jobs::{self, Job, Update};
+use super::plan::{self, Step};
+use super::preflight;
+use super::wsl::{is_wsl, linux_path, selection};
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use std::fs;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command as SysCommand, Stdio};
+use std::sync::atomic::Ordering;
+
+// what of the selected entry gets copied
+#[derive(Clone, Copy)]
+pub enum PathKind {
+    Path,
+    // relative to the directory traverse was started in
+    Relative,
+    Name,
+}
+
+// the tools that take text on stdin and put it on the system clipboard, in
+// the order they're tried
+fn tools() -> Vec<(&'static str, Vec<&'static str>)> {
+    if cfg!(target_os = "macos") {
+        return vec![("pbcopy", vec![])];
+    }
+
+    if cfg!(windows) || is_wsl() {
+        return vec![("clip.exe", vec![])];
+    }
+
+    let mut tools = vec![];
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        tools.push(("wl-copy", vec![]));
+    }
+    tools.push(("xclip", vec!["-selection", "clipboard"]));
+    tools.push(("xsel", vec!["--clipboard", "--input"]));
+
+    tools
+}
+
+// the same for file references, which file managers and browsers paste as
+// the files themselves
+fn file_tools() -> Vec<(&'static str, Vec<&'static str>)> {
+    let mut tools = vec![];
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        tools.push(("wl-copy", vec!["--type", "text/uri-list"]));
+    }
+    tools.push((
+        "xclip",
+        vec!["-selection", "clipboard", "-t", "text/uri-list"],
+    ));
+
+    tools
+}
+
+fn run(tool: &str, args: &[&str], text: &str) -> std::io::Result<bool> {
+    let mut child = SysCommand::new(tool)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    // they copy once their stdin is closed
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+
+    Ok(child.wait()?.success())
+}
+
+fn first_working(
+    tools: Vec<(&'static str, Vec<&'static str>)>,
+    text: &str,
+) -> Result<&'static str, String> {
+    for (tool, args) in &tools {
+        match run(tool, args, text) {
+            Ok(true) => return Ok(tool),
+            Ok(false) => return Err(format!("{} failed", tool)),
+            // not installed, try the next one
+            Err(_) => continue,
+        }
+    }
+
+    Err(format!(
+        "no clipboard tool found, install {}",
+        tools
+            .iter()
+            .map(|(tool, _)| *tool)
+            .collect::<Vec<&str>>()
+            .join(" or ")
+    ))
+}
+
+// puts `text` on the clipboard, saying which tool did it or why none could
+pub fn copy(text: &str) -> Result<&'static str, String> {
+    first_working(tools(), text)
+}
+
+// file:///path with everything but the unreserved characters and / escaped
+fn uri(path: &Path) -> String {
+    let mut uri = "file://".to_string();
+
+    for byte in path.to_string_lossy().bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+
+    uri
+}
+
+// the buffer (or the selected entry) as files, for pasting into GUI apps
+pub fn copy_files(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    if !cfg!(target_os = "linux") || is_wsl() {
+        return app.notify_error("Copying files to the clipboard needs Wayland or X11");
+    }
+
+    let paths = selection(app);
+    if paths.is_empty() {
+        return app.notify_error("Select an entry or fill the buffer to copy files");
+    }
+
+    // text/uri-list lines end in CRLF
+    let list = paths
+        .iter()
+        .map(|path| format!("{}\r\n", uri(path)))
+        .collect::<String>();
+
+    match first_working(file_tools(), &list) {
+        Ok(_) if paths.len() == 1 => app.notify(format!("Copied {} as a file", paths[0].display())),
+        Ok(_) => app.notify(format!("Copied {} files", paths.len())),
+        Err(err) => app.notify_error(format!("Couldn't copy the files: {}", err)),
+    }
+}
+
+// the tools that print the files on the clipboard, as a text/uri-list or one
+// path per line, in the order they're tried
+fn paste_tools() -> Vec<(&'static str, Vec<&'static str>)> {
+    if cfg!(target_os = "macos") {
+        return vec![(
+            "osascript",
+            vec!["-e", "POSIX path of (the clipboard as «class furl»)"],
+        )];
+    }
+
+    if cfg!(windows) || is_wsl() {
+        return vec![(
+            "powershell.exe",
+            vec![
+                "-NoProfile",
+                "-Command",
+                "Get-Clipboard -Format FileDropList | ForEach-Object { $_.FullName }",
+            ],
+        )];
+    }
+
+    // file managers offer the uri-list, paths copied as text are taken too
+    let mut tools = vec![];
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        tools.push(("wl-paste", vec!["--no-newline", "--type", "text/uri-list"]));
+        tools.push(("wl-paste", vec!["--no-newline"]));
+    }
+    tools.push((
+        "xclip",
+        vec!["-selection", "clipboard", "-o", "-t", "text/uri-list"],
+    ));
+    tools.push(("xclip", vec!["-selection", "clipboard", "-o"]));
+    tools.push(("xsel", vec!["--clipboard", "--output"]));
+
+    tools
+}
+
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = vec![];
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        let escaped = bytes
+            .get(idx + 1..idx + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match escaped {
+            Some(byte) if bytes[idx] == b'%' => {
+                decoded.push(byte);
+                idx += 3;
+            }
+            _ => {
+                decoded.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+// a line of what the clipboard holds as a path, GNOME's "copy"/"cut" line
+// and the uri-list comments aren't one
+fn pasted_path(line: &str) -> Option<PathBuf> {
+    let line = line.trim();
+
+    if let Some(rest) = line.strip_prefix("file://") {
+        let path = rest.strip_prefix("localhost").unwrap_or(rest);
+        return Some(PathBuf::from(decode(path)));
+    }
+
+    // C:\... from the Windows side
+    if is_wsl() && line.get(1..2) == Some(":") {
+        return linux_path(line);
+    }
+
+    Some(PathBuf::from(line)).filter(|path| path.is_absolute())
+}
+
+fn read(tool: &str, args: &[&str]) -> std::io::Result<Option<String>> {
+    let output = SysCommand::new(tool)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()?;
+
+    Ok(Some(String::from_utf8_lossy(&output.stdout).to_string())
+        .filter(|_| output.status.success()))
+}
+
+// the existing files on the clipboard, or why there are none
+fn clipboard_files() -> Result<Vec<PathBuf>, String> {
+    let tools = paste_tools();
+    let mut ran = false;
+
+    for (tool, args) in &tools {
+        let text = match read(tool, args) {
+            Ok(Some(text)) => text,
+            // nothing of that type on the clipboard
+            Ok(None) => {
+                ran = true;
+                continue;
+            }
+            Err(_) => continue,
+        };
+        ran = true;
+
+        let mut paths: Vec<PathBuf> = vec![];
+        for path in text.lines().filter_map(pasted_path) {
+            if fs::symlink_metadata(&path).is_ok() && !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+
+        if !paths.is_empty() {
+            return Ok(paths);
+        }
+    }
+
+    if ran {
+        Err("The clipboard holds no files".to_string())
+    } else {
+        Err(format!(
+            "No clipboard tool found, install {}",
+            tools
+                .iter()
+                .map(|(tool, _)| *tool)
+                .collect::<Vec<&str>>()
+                .join(" or ")
+        ))
+    }
+}
+
+// copies the files a GUI file manager put on the clipboard into the current
+// directory, with the same checks as the buffer but leaving the buffer be
+pub fn paste_files(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    let cur_dir = match std::env::current_dir() {
+        Ok(cur_dir) => cur_dir,
+        Err(e) => return app.show_error("Couldn't read the current directory", e),
+    };
+
+    let sources = match clipboard_files() {
+        Ok(paths) => paths,
+        Err(err) => return app.notify_error(err),
+    };
+
+    let plan = plan::plan(&sources, &cur_dir, false);
+    if !plan.errors.is_empty() {
+        return plan::report_errors(app, &plan, false);
+    }
+
+    if !preflight::check_transfer(app, &sources, &cur_dir, false) {
+        return;
+    }
+
+    if plan.steps.is_empty() {
+        return app.notify("Everything on the clipboard is already here");
+    }
+
+    // names already here are asked about like for the buffer
+    conflicts::check(app, Then::Paste, plan);
+}
+
+// copies what's left of the clipboard once the taken names are settled, as
+// a background job, killing it stops after the current entry
+pub fn paste(app: &mut App, steps: Vec<Step>) {
+    if steps.is_empty() {
+        return app.notify("Everything on the clipboard was skipped");
+    }
+
+    let mut job = Job::start("Paste");
+    let name = match steps.as_slice() {
+        [step] => format!("Paste {}", step.source.display()),
+        steps => format!("Paste {} entries", steps.len()),
+    };
+
+    jobs::spawn(app, name, move |tx, kill| {
+        for step in &steps {
+            let file = step.source.to_string_lossy().to_string();
+            if kill.load(Ordering::Relaxed) {
+                job.failed(&file, "killed before it started");
+                continue;
+            }
+
+            let counted = jobs::measure(&step.source);
+            // a renamed entry is copied to its new name, the rest into the
+            // directory
+            let target = match step.target.parent() {
+                Some(dir) if step.target.file_name() == step.source.file_name() => dir,
+                _ => step.target.as_path(),
+            };
+
+            let _ = tx.send(Update::Line(format!("{} to {}", file, target.display())));
+            let mut cp = SysCommand::new("cp");
+            match jobs::run_child(cp.arg("-r").arg(&step.source).arg(target), &tx, &kill) {
+                Ok((status, _)) if status.success() => job.done(counted),
+                Ok((status, errors)) => match jobs::describe(&status, &kill) {
+                    Some(failed) if errors.trim().is_empty() => job.failed(&file, &failed),
+                    _ => job.failed(&file, errors.trim()),
+                },
+                Err(e) => job.failed(&file, &e.to_string()),
+            }
+        }
+
+        let failed = match job.failures.len() {
+            0 => None,
+            failures => Some(format!("failed for {} of them", failures)),
+        };
+        let _ = tx.send(Update::Finished(
+            failed,
+            Some(Box::new(move |app: &mut App| {
+                app.update_files();
+                app.update_dirs();
+                job.finish(app, true);
+            })),
+        ));
+    });
+}
+
+// `path` as seen from `base`, with as many ../ as it takes
+fn relative(path: &Path, base: &Path) -> PathBuf {
+    let path = path.components().collect::<Vec<Component>>();
+    let base = base.components().collect::<Vec<Component>>();
+
+    let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+
+    let mut relative = PathBuf::new();
+    for _ in common..base.len() {
+        relative.push("..");
+    }
+    relative.extend(&path[common..]);
+
+    if relative.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        relative
+    }
+}
+
+pub fn copy_selected(app: &mut App, what: PathKind) {
+    if block_binds(app) {
+        return;
+    }
+
+    let path = match selected_entry_path(app) {
+        Some(path) => path,
+        None => return app.notify_error("Select an entry to copy its path"),
+    };
+
+    let text = match what {
+        PathKind::Path => path.to_string_lossy().to_string(),
+        PathKind::Relative => relative(&path, &app.launch_dir)
+            .to_string_lossy()
+            .to_string(),
+        PathKind::Name => path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    };
+    let text = text.trim_end_matches('/').to_string();
+
+    match copy(&text) {
+        Ok(_) => app.notify(format!("Copied {}", text)),
+        Err(err) => app.notify_error(format!("Couldn't copy {}: {}", text, err)),
+    }
+}
@@ -0,0 +1,159 @@
+use super::archive::{self, Format};
+use super::jobs::{self, Update};
+use super::run_app::Command;
+use super::secret;
+use super::wsl::selection;
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use std::path::PathBuf;
+
+// the buffer, or the selected entry when it's empty
+fn sources(app: &App) -> Vec<PathBuf> {
+    selection(app)
+        .into_iter()
+        .filter(|path| !path.ends_with(".."))
+        .collect()
+}
+
+// a single entry names the archive, several get the directory's name
+fn default_name(sources: &[PathBuf]) -> String {
+    let named = match sources {
+        [single] => Some(single.clone()),
+        _ => std::env::current_dir().ok(),
+    };
+
+    named
+        .as_deref()
+        .and_then(|path| path.file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "archive".to_string())
+}
+
+pub fn handle_compress(app: &mut App) {
+    if app.show_compress {
+        app.show_compress = false;
+        return;
+    }
+
+    if block_binds(app) {
+        return;
+    }
+
+    if sources(app).is_empty() {
+        return app.notify_error("Select an entry or fill the buffer to compress");
+    }
+
+    let single_file = matches!(sources(app).as_slice(), [single] if single.is_file());
+    app.compress.items = Format::all()
+        .into_iter()
+        .filter(|format| !format.single_file() || single_file)
+        .collect();
+    app.compress.state.select(Some(0));
+    app.show_compress = true;
+}
+
+// the format is picked, the name comes next with the suffix filled in
+pub fn handle_choose_format(app: &mut App, input: &mut String, input_active: &mut bool) {
+    let format = match app
+        .compress
+        .state
+        .selected()
+        .and_then(|idx| app.compress.items.get(idx))
+    {
+        Some(format) => *format,
+        None => return,
+    };
+
+    app.show_compress = false;
+    app.compress_format = Some(format);
+
+    *input = format!("{}{}", default_name(&sources(app)), format.suffix());
+    *input_active = true;
+    app.show_popup = true;
+    app.last_command = Some(Command::Compress);
+}
+
+// a name ending in another known suffix switches to that format, the ones
+// that can be encrypted ask for a password before they're written
+pub fn submit_compress(app: &mut App, name: &str, input: &mut String, input_active: &mut bool) {
+    let (format, name) = match (Format::of(name), app.compress_format.take()) {
+        (Some(format), _) => (format, name.to_string()),
+        (None, Some(format)) => (format, format!("{}{}", name, format.suffix())),
+        (None, None) => return,
+    };
+
+    let dest = match std::env::current_dir() {
+        Ok(cur_dir) => cur_dir.join(&name),
+        Err(e) => return app.show_error("Couldn't read the current directory", e),
+    };
+
+    if dest.exists() {
+        return app.notify_error(format!("{} already exists", name));
+    }
+
+    if format.encrypts() {
+        input.clear();
+        secret::ask(app, input_active, Command::ArchivePassword);
+        app.compress_format = Some(format);
+        app.compress_name = Some(name);
+        return;
+    }
+
+    create(app, format, &name, None);
+}
+
+// an empty password leaves the archive unencrypted
+pub fn submit_password(app: &mut App, password: &str) {
+    let (format, name) = match (app.compress_format.take(), app.compress_name.take()) {
+        (Some(format), Some(name)) => (format, name),
+        _ => return,
+    };
+
+    let password = Some(password).filter(|password| !password.is_empty());
+    create(app, format, &name, password);
+}
+
+// written as a background job, the archive is highlighted once it's there
+// when the panes are still in its directory
+fn create(app: &mut App, format: Format, name: &str, password: Option<&str>) {
+    let cur_dir = match std::env::current_dir() {
+        Ok(cur_dir) => cur_dir,
+        Err(e) => return app.show_error("Couldn't read the current directory", e),
+    };
+
+    let sources = sources(app);
+    let name = name.to_string();
+    let password = password.map(str::to_string);
+
+    jobs::spawn(app, format!("Compress {}", name), move |tx, kill| {
+        let dest = cur_dir.join(&name);
+        let failed = archive::create(format, &sources, &dest, password.as_deref(), &kill)
+            .err()
+            .map(|e| e.to_string());
+        if let Some(failed) = &failed {
+            let _ = tx.send(Update::Line(failed.clone()));
+        }
+
+        let _ = tx.send(Update::Finished(
+            failed.clone(),
+            Some(Box::new(move |app: &mut App| {
+                match failed {
+                    Some(e) => app.notify_error(format!("Couldn't create {}: {}", name, e)),
+                    None if sources.len() == 1 => app.notify(format!("Created {}", name)),
+                    None => app.notify(format!("Created {} from {} entries", name, sources.len())),
+                }
+
+                app.update_files();
+                app.update_dirs();
+
+                if std::env::current_dir().ok().as_ref() != Some(&cur_dir) {
+                    return;
+                }
+                if let Some(idx) = app.files.items.iter().position(|item| item.0 == *name) {
+                    app.files.state.select(Some(idx));
+                    app.dirs.state.select(None);
+                }
+            })),
+        ));
+    });
+}
@@ -0,0 +1,269 @@
+use super::plan::Plan;
+use super::preflight::Confirm;
+use super::run_app::Command;
+use super::{clipboard, file_ops};
+use crate::app::app::App;
+use crate::ui::display::pane::convert_bytes;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// confirm=, what asks before it happens
+#[derive(Clone, Copy, PartialEq)]
+pub enum Policy {
+    // every delete, and every name already taken at the destination
+    Always,
+    // names already taken, deletes only past max_delete_files
+    Overwrites,
+    // copies and moves replace what's there, like cp and mv
+    Never,
+}
+
+impl Policy {
+    pub fn from_config(value: &str) -> Policy {
+        match value.to_ascii_lowercase().as_str() {
+            "always" => Policy::Always,
+            "never" => Policy::Never,
+            _ => Policy::Overwrites,
+        }
+    }
+}
+
+// what the plan goes on with once it's settled
+#[derive(Clone, Copy, PartialEq)]
+pub enum Then {
+    Copy,
+    Move,
+    // the files on the clipboard, the buffer stays as it is
+    Paste,
+}
+
+impl Then {
+    pub fn transfer(is_move: bool) -> Then {
+        if is_move {
+            Then::Move
+        } else {
+            Then::Copy
+        }
+    }
+}
+
+// the plan being settled and the step asked about
+#[derive(Clone)]
+pub struct Asked {
+    pub then: Then,
+    pub plan: Plan,
+    pub at: usize,
+}
+
+// a name free in `dir` and among `taken`: "notes (1).txt", "notes (2).txt", ...
+pub fn free_name(dir: &Path, name: &str, taken: &[PathBuf]) -> String {
+    let is_dir = dir.join(name).is_dir();
+    // backup.tar.gz becomes backup (1).tar.gz
+    let dot = match name.rfind('.') {
+        Some(at) if name[..at].ends_with(".tar") => Some(at - 4),
+        dot => dot,
+    };
+    let (stem, extension) = match dot {
+        // a leading dot is a hidden file's name, not an extension
+        Some(at) if at > 0 && !is_dir => (&name[..at], &name[at..]),
+        _ => (name, ""),
+    };
+
+    (1..)
+        .map(|number| format!("{} ({}){}", stem, number, extension))
+        .find(|candidate| {
+            let path = dir.join(candidate);
+            fs::symlink_metadata(&path).is_err() && !taken.contains(&path)
+        })
+        .unwrap_or_else(|| name.to_string())
+}
+
+// the step from `at` on whose target is already there
+fn next_conflict(plan: &Plan, at: usize) -> Option<usize> {
+    (at..plan.steps.len()).find(|idx| fs::symlink_metadata(&plan.steps[*idx].target).is_ok())
+}
+
+fn describe(path: &Path, si: bool) -> String {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => "a directory".to_string(),
+        Ok(metadata) => convert_bytes(metadata.len(), si),
+        Err(_) => "gone".to_string(),
+    }
+}
+
+fn name_of(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+// where the step at `at` lands, and the first name free there
+fn suggestion(plan: &Plan, at: usize) -> (PathBuf, String) {
+    let target = &plan.steps[at].target;
+    let dir = target.parent().unwrap_or(Path::new(".")).to_path_buf();
+    let taken = plan
+        .steps
+        .iter()
+        .map(|step| step.target.clone())
+        .collect::<Vec<PathBuf>>();
+
+    let name = free_name(&dir, &name_of(target), &taken);
+    (dir, name)
+}
+
+fn go_ahead(app: &mut App, then: Then, plan: Plan) {
+    match then {
+        Then::Copy => file_ops::go_ahead(app, false, plan),
+        Then::Move => file_ops::go_ahead(app, true, plan),
+        Then::Paste => clipboard::paste(app, plan.steps),
+    }
+}
+
+// the step at `at` shown with the choices, or the plan goes on when nothing
+// is left to ask about
+pub fn ask(app: &mut App, then: Then, plan: Plan, at: usize) {
+    let at = match next_conflict(&plan, at) {
+        Some(at) => at,
+        None => return go_ahead(app, then, plan),
+    };
+    let left = (at..plan.steps.len())
+        .filter(|idx| fs::symlink_metadata(&plan.steps[*idx].target).is_ok())
+        .count();
+
+    let step = &plan.steps[at];
+    let (dir, free) = suggestion(&plan, at);
+    let replace = if step.target.is_dir() {
+        "o merges into it"
+    } else {
+        "o overwrites it"
+    };
+    let mut lines = vec![
+        format!("{} is already in {}", name_of(&step.target), dir.display()),
+        format!("  here: {}", describe(&step.target, app.si_units)),
+        format!(
+            "  {}: {}",
+            if then == Then::Move {
+                "moving in"
+            } else {
+                "copying in"
+            },
+            describe(&step.source, app.si_units)
+        ),
+        String::new(),
+        format!(
+            "{}, s skips it, r names it, starting from {}.",
+            replace, free
+        ),
+    ];
+    if left > 1 {
+        lines.push(format!(
+            "O, S or R does the same for all {} names already here, R with names like that.",
+            left
+        ));
+    }
+    lines.push("Esc cancels the whole operation.".to_string());
+
+    let title = match then {
+        Then::Copy => "Copy",
+        Then::Move => "Move",
+        Then::Paste => "Paste",
+    };
+    app.open_report(title, lines);
+    app.pending_confirm = Some(Confirm::Conflict(Asked { then, plan, at }));
+}
+
+// o, s or r while a conflict is asked about, uppercase for all of them
+pub fn takes(app: &App, c: char) -> bool {
+    app.show_report
+        && matches!(app.pending_confirm, Some(Confirm::Conflict(_)))
+        && "osrOSR".contains(c)
+}
+
+pub fn choose(app: &mut App, c: char, input: &mut String, input_active: &mut bool) {
+    let Asked { then, mut plan, at } = match app.pending_confirm.take() {
+        Some(Confirm::Conflict(asked)) => asked,
+        other => {
+            app.pending_confirm = other;
+            return;
+        }
+    };
+    app.show_report = false;
+
+    // the free name to edit before it's used, the question stays pending
+    if c == 'r' {
+        *input = suggestion(&plan, at).1;
+        *input_active = true;
+        app.show_popup = true;
+        app.last_command = Some(Command::ConflictName);
+        app.pending_confirm = Some(Confirm::Conflict(Asked { then, plan, at }));
+        return;
+    }
+
+    let all = c.is_ascii_uppercase();
+    let mut at = Some(at);
+    while let Some(idx) = at {
+        let next = match c.to_ascii_lowercase() {
+            's' => {
+                plan.steps.remove(idx);
+                idx
+            }
+            'r' => {
+                let (dir, name) = suggestion(&plan, idx);
+                plan.steps[idx].target = dir.join(name);
+                idx + 1
+            }
+            _ => idx + 1,
+        };
+
+        if !all {
+            return ask(app, then, plan, next);
+        }
+        at = next_conflict(&plan, next);
+    }
+
+    go_ahead(app, then, plan);
+}
+
+// the name typed after r, asked about again when it's no good either
+pub fn submit_name(app: &mut App, name: &str) {
+    let Asked { then, mut plan, at } = match app.pending_confirm.take() {
+        Some(Confirm::Conflict(asked)) => asked,
+        _ => return,
+    };
+
+    let name = name.trim();
+    let dir = plan.steps[at]
+        .target
+        .parent()
+        .unwrap_or(Path::new("."))
+        .to_path_buf();
+    let target = dir.join(name);
+    let taken = plan
+        .steps
+        .iter()
+        .enumerate()
+        .any(|(idx, step)| idx != at && step.target == target);
+
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+        app.notify_error(format!("{} isn't a name", name));
+        return ask(app, then, plan, at);
+    }
+    if fs::symlink_metadata(&target).is_ok() || taken {
+        app.notify_error(format!("{} is taken too", name));
+        return ask(app, then, plan, at);
+    }
+
+    plan.steps[at].target = target;
+    ask(app, then, plan, at + 1);
+}
+
+// the plan with the overwrites it holds, by the confirm= policy
+pub fn check(app: &mut App, then: Then, plan: Plan) {
+    app.show_ops_menu = false;
+    app.last_command = None;
+
+    if app.confirm == Policy::Never {
+        return go_ahead(app, then, plan);
+    }
+    ask(app, then, plan, 0);
+}
@@ -0,0 +1,146 @@
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use walkdir::WalkDir;
+
+// MAX_PATH on Windows, counting the drive and the terminating nul
+const WINDOWS_PATH: usize = 259;
+// longest name NTFS, FAT32, exFAT and most unix filesystems allow
+const NAME: usize = 255;
+// offenders listed by name, the rest are only counted
+const LISTED: usize = 10;
+
+// Windows counts UTF-16 units, not bytes or chars
+fn width(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+fn offenders(title: String, paths: &[String], lines: &mut Vec<String>) {
+    lines.push(String::new());
+    lines.push(format!("{}: {}", title, paths.len()));
+    lines.extend(paths.iter().take(LISTED).map(|path| format!("  {}", path)));
+
+    if paths.len() > LISTED {
+        lines.push(format!("  ... and {} more", paths.len() - LISTED));
+    }
+}
+
+fn diagnose(root: &Path) -> Vec<String> {
+    let (mut entries, mut dirs) = (0, 0);
+    let mut deepest = (0, String::new());
+    let mut longest_path = (0, String::new());
+    let mut longest_name = (0, String::new());
+    let (mut long_paths, mut long_names) = (vec![], vec![]);
+
+    for entry in WalkDir::new(root)
+        .min_depth(1)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+    {
+        entries += 1;
+        if entry.file_type().is_dir() {
+            dirs += 1;
+        }
+
+        let relative = entry
+            .path()
+            .strip_prefix(root)
+            .unwrap_or(entry.path())
+            .to_string_lossy()
+            .to_string();
+        let name = entry.file_name().to_string_lossy().to_string();
+
+        if entry.depth() > deepest.0 {
+            deepest = (entry.depth(), relative.clone());
+        }
+        if width(&relative) > longest_path.0 {
+            longest_path = (width(&relative), relative.clone());
+        }
+        if width(&name) > longest_name.0 {
+            longest_name = (width(&name), relative.clone());
+        }
+
+        if width(&entry.path().to_string_lossy()) > WINDOWS_PATH {
+            long_paths.push(relative.clone());
+        }
+        if width(&name) > NAME || name.len() > NAME {
+            long_names.push(relative);
+        }
+    }
+
+    if entries == 0 {
+        return vec!["Nothing below here".to_string()];
+    }
+
+    let mut lines = vec![
+        format!("{} entries, {} of them directories", entries, dirs),
+        format!("Deepest:      {} levels, {}", deepest.0, deepest.1),
+        format!(
+            "Longest path: {} characters, {}",
+            longest_path.0, longest_path.1
+        ),
+        format!(
+            "Longest name: {} characters, {}",
+            longest_name.0, longest_name.1
+        ),
+    ];
+
+    offenders(
+        format!(
+            "Paths over the Windows limit of {} characters",
+            WINDOWS_PATH
+        ),
+        &long_paths,
+        &mut lines,
+    );
+    offenders(
+        format!("Names over {} characters (FAT, exFAT, NTFS, ext4)", NAME),
+        &long_names,
+        &mut lines,
+    );
+
+    lines
+}
+
+pub fn handle_diagnostics(app: &mut App) {
+    if block_binds(app) || app.diagnostics_job.is_some() {
+        return;
+    }
+
+    let cur_dir = match std::env::current_dir() {
+        Ok(dir) => dir,
+        Err(_) => return,
+    };
+
+    let (tx, rx) = mpsc::channel();
+    app.diagnostics_job = Some(rx);
+    app.open_report(
+        "Path diagnostics",
+        vec!["Walking the tree in the background...".to_string()],
+    );
+
+    thread::spawn(move || {
+        let _ = tx.send(diagnose(&cur_dir));
+    });
+}
+
+// like the checksum jobs, the result lands in the report once it's ready
+pub fn poll_diagnostics_job(app: &mut App) {
+    if block_binds(app) && !app.show_report {
+        return;
+    }
+
+    let lines = match &app.diagnostics_job {
+        Some(rx) => match rx.try_recv() {
+            Ok(lines) => lines,
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => vec!["Diagnostics stopped".to_string()],
+        },
+        None => return,
+    };
+
+    app.diagnostics_job = None;
+    app.open_report("Path diagnostics", lines);
+}
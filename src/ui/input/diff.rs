@@ -0,0 +1,60 @@
+use super::bookmark::selected_entry_path;
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use std::path::{Path, PathBuf};
+use std::process::Command as SysCommand;
+
+// the two files to compare: both from the buffer, or the one in the buffer
+// and the selected one
+fn pair(app: &App) -> Option<(PathBuf, PathBuf)> {
+    match app.selected_files.as_slice() {
+        [a, b] => Some((PathBuf::from(a), PathBuf::from(b))),
+        [a] => Some((PathBuf::from(a), selected_entry_path(app)?)),
+        _ => None,
+    }
+}
+
+pub fn handle_diff(app: &mut App) {
+    if app.show_diff {
+        app.show_diff = false;
+        return;
+    }
+
+    if block_binds(app) {
+        return;
+    }
+
+    let (a, b) = match pair(app) {
+        Some((a, b)) if a.is_file() && b.is_file() && a != b => (a, b),
+        _ => return app.notify_error("Add two files to the buffer with c to compare them"),
+    };
+
+    // diff exits with 0 for the same contents, 1 for differences, 2 for trouble
+    let output = match SysCommand::new("diff").arg("-u").arg(&a).arg(&b).output() {
+        Ok(output) => output,
+        Err(err) => return app.show_error("Couldn't run diff", err),
+    };
+
+    match output.status.code() {
+        Some(0) => app.notify(format!("{} and {} are the same", name(&a), name(&b))),
+        Some(1) => {
+            app.diff.items = String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(|line| line.replace('\t', "    "))
+                .collect();
+            app.diff.state.select(Some(0));
+            app.diff_title = format!("{} -> {}", a.display(), b.display());
+            app.show_diff = true;
+        }
+        _ => app.show_error(
+            "Couldn't compare the files",
+            String::from_utf8_lossy(&output.stderr).trim(),
+        ),
+    }
+}
+
+fn name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
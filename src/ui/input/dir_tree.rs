@@ -0,0 +1,117 @@
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use std::ffi::{OsStr, OsString};
+use std::path::PathBuf;
+
+// with `dir_tree` the Directories pane is a tree: an expanded directory is
+// followed by its subdirectories, named by their path from the current
+// directory so Enter, the preview and the file operations work on them as is
+
+fn depth(name: &OsStr) -> usize {
+    let bytes = name.as_encoded_bytes();
+    let bytes = bytes.strip_suffix(b"/").unwrap_or(bytes);
+    bytes.iter().filter(|byte| **byte == b'/').count()
+}
+
+// expanded directories are remembered by their full path, they stay open
+// when traverse leaves and comes back
+fn key(name: &OsStr) -> PathBuf {
+    std::env::current_dir().unwrap_or_default().join(name)
+}
+
+fn is_expanded(app: &App, name: &OsStr) -> bool {
+    app.expanded_dirs.contains(&key(name))
+}
+
+fn label(app: &App, name: &OsStr, label: &str) -> String {
+    let marker = if is_expanded(app, name) { "▾" } else { "▸" };
+    format!("{}{} {}", "  ".repeat(depth(name)), marker, label)
+}
+
+fn push_tree(app: &App, items: &mut Vec<(OsString, String)>, entry: (OsString, String)) {
+    let expanded = is_expanded(app, &entry.0);
+    let mut prefix = entry.0.clone();
+    prefix.push("/");
+
+    items.push((entry.0.clone(), label(app, &entry.0, &entry.1)));
+
+    if expanded {
+        for child in app.subdirs(&prefix) {
+            push_tree(app, items, child);
+        }
+    }
+}
+
+// called by update_dirs with the directories listed, adds what's expanded
+pub fn expand(app: &mut App) {
+    let top = std::mem::take(&mut app.dirs.items);
+    let mut items = vec![];
+
+    for entry in top {
+        if entry.0 == "../" {
+            items.push(entry);
+        } else {
+            push_tree(app, &mut items, entry);
+        }
+    }
+
+    app.dirs.items = items;
+}
+
+// the selected directory in the tree, not ../
+fn selected(app: &App) -> Option<(usize, OsString)> {
+    if !app.dir_tree || app.unified() {
+        return None;
+    }
+
+    let idx = app.dirs.state.selected()?;
+    let name = &app.dirs.items.get(idx)?.0;
+
+    if name == "../" {
+        None
+    } else {
+        Some((idx, name.clone()))
+    }
+}
+
+pub fn active(app: &mut App) -> bool {
+    selected(app).is_some() && !block_binds(app)
+}
+
+// l or Right: opens the directory, or goes to its first subdirectory
+pub fn handle_expand(app: &mut App) {
+    let (idx, name) = match selected(app) {
+        Some(selected) => selected,
+        None => return,
+    };
+
+    if !is_expanded(app, &name) {
+        app.expanded_dirs.insert(key(&name));
+        app.update_dirs();
+    } else if app
+        .dirs
+        .items
+        .get(idx + 1)
+        .is_some_and(|(next, _)| depth(next) > depth(&name))
+    {
+        app.dirs.state.select(Some(idx + 1));
+    }
+}
+
+// h or Left: closes the directory, or goes to the one it's in
+pub fn handle_collapse(app: &mut App) {
+    let (idx, name) = match selected(app) {
+        Some(selected) => selected,
+        None => return,
+    };
+
+    if is_expanded(app, &name) {
+        app.expanded_dirs.remove(&key(&name));
+        app.update_dirs();
+    } else if depth(&name) > 0 {
+        let parent = app.dirs.items[..idx]
+            .iter()
+            .rposition(|(entry, _)| depth(entry) < depth(&name));
+        app.dirs.state.select(parent);
+    }
+}
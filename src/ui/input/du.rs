@@ -0,0 +1,199 @@
+use super::jumps;
+use super::treemap::dir_size;
+use crate::app::app::{is_hidden, App};
+use crate::ui::display::block::block_binds;
+use crate::ui::display::pane::get_pwd;
+use std::fs::read_dir;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+// an entry of the current directory, `size` is None until its walk is done
+pub struct Usage {
+    pub name: String,
+    pub is_dir: bool,
+    pub size: Option<u64>,
+}
+
+// directories are walked by a few workers sharing one queue, each sending
+// (name, size) as it finishes, they stop once the receiver is gone
+fn spawn(dirs: Vec<(String, PathBuf)>) -> mpsc::Receiver<(String, u64)> {
+    let queue = Arc::new(Mutex::new(dirs));
+    let (tx, rx) = mpsc::channel();
+
+    let workers = thread::available_parallelism()
+        .map(|workers| workers.get())
+        .unwrap_or(4)
+        .min(8);
+
+    for _ in 0..workers {
+        let queue = Arc::clone(&queue);
+        let tx = tx.clone();
+
+        thread::spawn(move || loop {
+            let next = queue.lock().ok().and_then(|mut queue| queue.pop());
+
+            let (name, path) = match next {
+                Some(next) => next,
+                None => break,
+            };
+
+            if tx.send((name, dir_size(&path))).is_err() {
+                break;
+            }
+        });
+    }
+
+    // only the workers hold a sender now, so the channel closes when they're done
+    drop(tx);
+
+    rx
+}
+
+// biggest first, the ones still being counted at the bottom, keeping the
+// highlighted entry highlighted unless it's the top one, the top stays on top
+fn sort(app: &mut App) {
+    let selected = app
+        .du
+        .state
+        .selected()
+        .filter(|idx| *idx > 0)
+        .and_then(|idx| app.du.items.get(idx))
+        .map(|usage| usage.name.clone());
+
+    app.du
+        .items
+        .sort_by_key(|usage| std::cmp::Reverse(usage.size));
+
+    let idx = selected
+        .and_then(|name| app.du.items.iter().position(|usage| usage.name == name))
+        .or(if app.du.items.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+    app.du.state.select(idx);
+}
+
+// lists the current directory and starts walking its directories
+pub fn build(app: &mut App) {
+    let mut items = vec![];
+    let mut dirs = vec![];
+
+    if let Ok(dir) = read_dir("./") {
+        for entry in dir.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+
+            if is_hidden(&entry) && !app.show_hidden {
+                continue;
+            }
+
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+
+            if metadata.is_dir() {
+                dirs.push((name.clone(), entry.path()));
+            }
+
+            items.push(Usage {
+                name,
+                is_dir: metadata.is_dir(),
+                size: if metadata.is_dir() {
+                    None
+                } else {
+                    Some(metadata.len())
+                },
+            });
+        }
+    }
+
+    app.du.items = items;
+    app.du.state.select(None);
+    app.du_job = if dirs.is_empty() {
+        None
+    } else {
+        Some(spawn(dirs))
+    };
+
+    sort(app);
+}
+
+pub fn handle_du(app: &mut App) {
+    if app.show_du {
+        app.show_du = false;
+        app.du_job = None;
+        return;
+    }
+
+    if block_binds(app) {
+        return;
+    }
+
+    build(app);
+    app.show_du = true;
+}
+
+pub fn poll_du_job(app: &mut App) {
+    let mut finished = vec![];
+    let mut done = false;
+
+    if let Some(rx) = &app.du_job {
+        loop {
+            match rx.try_recv() {
+                Ok(result) => finished.push(result),
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    done = true;
+                    break;
+                }
+            }
+        }
+    }
+
+    if done {
+        app.du_job = None;
+    }
+    if finished.is_empty() {
+        return;
+    }
+
+    for (name, size) in finished {
+        if let Some(usage) = app.du.items.iter_mut().find(|usage| usage.name == name) {
+            usage.size = Some(size);
+        }
+    }
+
+    sort(app);
+}
+
+fn change_dir(app: &mut App, path: &Path) {
+    if !jumps::change_dir(app, path) {
+        return;
+    }
+
+    app.cur_dir = get_pwd();
+    app.update_files();
+    app.update_dirs();
+
+    build(app);
+}
+
+pub fn drill_in(app: &mut App) {
+    let dir = app
+        .du
+        .state
+        .selected()
+        .and_then(|idx| app.du.items.get(idx))
+        .filter(|usage| usage.is_dir)
+        .map(|usage| PathBuf::from(&usage.name));
+
+    if let Some(dir) = dir {
+        change_dir(app, &dir);
+    }
+}
+
+pub fn drill_out(app: &mut App) {
+    change_dir(app, Path::new(".."));
+}
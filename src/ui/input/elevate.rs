@@ -0,0 +1,271 @@
+#[cfg(unix)]
+use super::{run_app::Command, secret};
+use crate::app::app::App;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command as SysCommand, Output, Stdio};
+
+// an operation refused for lack of permission, Enter on the report runs it
+// again as root in a process of its own
+#[derive(Clone)]
+pub enum Elevated {
+    Delete(PathBuf),
+    Rename(PathBuf, PathBuf),
+    CreateFile(PathBuf),
+    CreateDir(PathBuf),
+}
+
+impl Elevated {
+    fn done(&self) -> String {
+        match self {
+            Elevated::Delete(path) => format!("Deleted {} as root", path.display()),
+            Elevated::Rename(from, to) => {
+                format!("Renamed {} to {} as root", from.display(), to.display())
+            }
+            Elevated::CreateFile(path) => format!("Created {} as root", path.display()),
+            Elevated::CreateDir(path) => format!("Created {}/ as root", path.display()),
+        }
+    }
+
+    // the program doing it, run without a shell in between
+    #[cfg(unix)]
+    fn command(&self) -> SysCommand {
+        let mut command;
+        match self {
+            Elevated::Delete(path) => {
+                command = SysCommand::new("rm");
+                command.arg("-rf").arg("--").arg(path);
+            }
+            Elevated::Rename(from, to) => {
+                command = SysCommand::new("mv");
+                command.arg("--").arg(from).arg(to);
+            }
+            Elevated::CreateFile(path) => {
+                command = SysCommand::new("touch");
+                command.arg("--").arg(path);
+            }
+            Elevated::CreateDir(path) => {
+                command = SysCommand::new("mkdir");
+                command.arg("--").arg(path);
+            }
+        }
+        command
+    }
+
+    // the PowerShell that does it, in the window UAC elevates
+    #[cfg(windows)]
+    fn script(&self) -> String {
+        let quote = |path: &std::path::Path| {
+            format!("'{}'", path.display().to_string().replace('\'', "''"))
+        };
+
+        match self {
+            Elevated::Delete(path) => {
+                format!("Remove-Item -LiteralPath {} -Recurse -Force", quote(path))
+            }
+            Elevated::Rename(from, to) => format!(
+                "Move-Item -LiteralPath {} -Destination {}",
+                quote(from),
+                quote(to)
+            ),
+            Elevated::CreateFile(path) => {
+                format!("New-Item -ItemType File -Path {}", quote(path))
+            }
+            Elevated::CreateDir(path) => {
+                format!("New-Item -ItemType Directory -Path {}", quote(path))
+            }
+        }
+    }
+}
+
+// relative names are the current directory's, root's may be another
+pub fn absolute(path: &Path) -> PathBuf {
+    std::env::current_dir()
+        .map(|cur_dir| cur_dir.join(path))
+        .unwrap_or_else(|_| PathBuf::from(path))
+}
+
+pub fn denied(err: &io::Error) -> bool {
+    err.kind() == io::ErrorKind::PermissionDenied
+}
+
+pub fn trash_denied(err: &trash::Error) -> bool {
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if let trash::Error::FileSystem { kind, .. } = err {
+        return *kind == io::ErrorKind::PermissionDenied;
+    }
+    err.to_string().to_lowercase().contains("permission denied")
+}
+
+// the report for an operation the permissions refused, offering to do it as
+// root
+pub fn offer(app: &mut App, title: &str, err: impl std::fmt::Display, elevated: Elevated) {
+    let mut lines = vec![err.to_string()];
+    if let Elevated::Delete(path) = &elevated {
+        lines.push(format!(
+            "As root {} is deleted for good, it can't go to your bin.",
+            path.display()
+        ));
+    }
+    lines.push(if cfg!(windows) {
+        "Press Enter to do it as administrator, Esc to give up.".to_string()
+    } else {
+        "Press Enter to do it as root with sudo, Esc to give up.".to_string()
+    });
+
+    app.open_report(title, lines);
+    app.pending_elevate = Some(elevated);
+}
+
+fn failure(output: &Output) -> String {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    match stderr.lines().rfind(|line| !line.trim().is_empty()) {
+        Some(line) => line.trim().to_string(),
+        None => format!("it exited with {}", output.status),
+    }
+}
+
+fn finish(app: &mut App, elevated: &Elevated, result: Result<(), String>) {
+    match result {
+        Ok(_) => app.notify(elevated.done()),
+        Err(err) => app.notify_error(format!("Couldn't do it as root: {}", err)),
+    }
+    app.update_files();
+    app.update_dirs();
+    app.clamp_selection();
+}
+
+// through sudo when it's there, its password typed into the masked prompt
+// unless it's still cached, and pkexec's own agent otherwise
+#[cfg(unix)]
+pub fn confirm(app: &mut App, input: &mut String, input_active: &mut bool) {
+    app.show_report = false;
+    let elevated = match app.pending_elevate.take() {
+        Some(elevated) => elevated,
+        None => return,
+    };
+
+    let cached = SysCommand::new("sudo")
+        .args(["-n", "true"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+
+    match cached {
+        Ok(status) if status.success() => {
+            run_sudo(app, &elevated, None);
+        }
+        Ok(_) => {
+            input.clear();
+            secret::ask(app, input_active, Command::SudoPassword);
+            app.pending_elevate = Some(elevated);
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            let command = elevated.command();
+            let mut pkexec = SysCommand::new("pkexec");
+            pkexec
+                .arg(command.get_program())
+                .args(command.get_args())
+                .stdin(Stdio::null());
+            let result = match pkexec.output() {
+                Ok(output) if output.status.success() => Ok(()),
+                Ok(output) => Err(failure(&output)),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    Err("neither sudo nor pkexec is installed".to_string())
+                }
+                Err(e) => Err(e.to_string()),
+            };
+            finish(app, &elevated, result);
+        }
+        Err(e) => finish(app, &elevated, Err(e.to_string())),
+    }
+}
+
+#[cfg(windows)]
+pub fn confirm(app: &mut App, _input: &mut String, _input_active: &mut bool) {
+    app.show_report = false;
+    let elevated = match app.pending_elevate.take() {
+        Some(elevated) => elevated,
+        None => return,
+    };
+
+    // Start-Process passes its arguments on as they are, the script is the
+    // rest of the elevated PowerShell's command line
+    let outer = format!(
+        "$p = Start-Process powershell -Verb RunAs -Wait -PassThru -WindowStyle Hidden \
+         -ArgumentList '-NoProfile', '-Command', '{}'; exit $p.ExitCode",
+        elevated.script().replace('\'', "''")
+    );
+    let result = match SysCommand::new("powershell")
+        .args(["-NoProfile", "-Command", &outer])
+        .stdin(Stdio::null())
+        .output()
+    {
+        Ok(output) if output.status.success() => Ok(()),
+        // declining the UAC prompt lands here too
+        Ok(output) => Err(failure(&output)),
+        Err(e) => Err(e.to_string()),
+    };
+    finish(app, &elevated, result);
+}
+
+#[cfg(unix)]
+fn run_sudo(app: &mut App, elevated: &Elevated, password: Option<&str>) -> bool {
+    let command = elevated.command();
+    let mut sudo = SysCommand::new("sudo");
+    // -S reads the password from stdin, -p '' keeps its prompt out of stderr
+    match password {
+        Some(_) => sudo.args(["-S", "-p", ""]),
+        None => sudo.arg("-n"),
+    };
+    sudo.arg("--")
+        .arg(command.get_program())
+        .args(command.get_args())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+
+    let output = sudo.spawn().and_then(|mut child| {
+        use std::io::Write;
+
+        if let (Some(password), Some(mut stdin)) = (password, child.stdin.take()) {
+            writeln!(stdin, "{}", password)?;
+        }
+        child.wait_with_output()
+    });
+
+    let (result, wrong) = match output {
+        Ok(output) if output.status.success() => (Ok(()), false),
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_lowercase();
+            let wrong = stderr.contains("incorrect password") || stderr.contains("try again");
+            (Err(failure(&output)), wrong)
+        }
+        Err(e) => (Err(e.to_string()), false),
+    };
+
+    if wrong {
+        return false;
+    }
+    finish(app, elevated, result);
+    true
+}
+
+// what the masked prompt submits, asked again when sudo turns it down
+#[cfg(unix)]
+pub fn submit_password(app: &mut App, password: &str, input_active: &mut bool) {
+    let elevated = match app.pending_elevate.take() {
+        Some(elevated) => elevated,
+        None => return,
+    };
+
+    if !run_sudo(app, &elevated, Some(password)) {
+        app.notify_error("Wrong password, try again or press Esc");
+        secret::ask(app, input_active, Command::SudoPassword);
+        app.pending_elevate = Some(elevated);
+    }
+}
+
+#[cfg(windows)]
+pub fn submit_password(_app: &mut App, _password: &str, _input_active: &mut bool) {}
@@ -0,0 +1,349 @@
+use super::clipboard;
+use super::run_app::Command;
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use ignore::WalkBuilder;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// a report nobody reads past this, and a walk that could take a while
+const MAX_ENTRIES: usize = 10_000;
+const MAX_DEPTH: usize = 20;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum TreeFormat {
+    // like `tree` prints it
+    Text,
+    Markdown,
+    Html,
+}
+
+impl TreeFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            TreeFormat::Text => "Text",
+            TreeFormat::Markdown => "Markdown",
+            TreeFormat::Html => "HTML",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            TreeFormat::Text => ".txt",
+            TreeFormat::Markdown => ".md",
+            TreeFormat::Html => ".html",
+        }
+    }
+}
+
+struct Entry {
+    name: String,
+    depth: usize,
+    is_dir: bool,
+    // the last one in its directory
+    last: bool,
+}
+
+// what's below `root` down to `depth`, hidden and git ignored entries only
+// when the panes show them too, and whether it stopped at MAX_ENTRIES
+fn entries(app: &App, root: &Path, depth: usize) -> (Vec<Entry>, bool) {
+    let walker = WalkBuilder::new(root)
+        .standard_filters(app.hide_ignored)
+        .hidden(!app.show_hidden)
+        .max_depth(Some(depth))
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+
+    let mut entries = vec![];
+    let mut parents: Vec<PathBuf> = vec![];
+    let mut truncated = false;
+
+    for entry in walker.filter_map(|entry| entry.ok()) {
+        if entry.depth() == 0 {
+            continue;
+        }
+        if entries.len() == MAX_ENTRIES {
+            truncated = true;
+            break;
+        }
+
+        entries.push(Entry {
+            name: entry.file_name().to_string_lossy().to_string(),
+            depth: entry.depth(),
+            is_dir: entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_dir()),
+            last: false,
+        });
+        parents.push(entry.path().parent().unwrap_or(root).to_path_buf());
+    }
+
+    let mut last = HashMap::new();
+    for (idx, parent) in parents.iter().enumerate() {
+        last.insert(parent, idx);
+    }
+    for idx in last.into_values() {
+        entries[idx].last = true;
+    }
+
+    (entries, truncated)
+}
+
+fn summary(entries: &[Entry], truncated: bool) -> String {
+    let dirs = entries.iter().filter(|entry| entry.is_dir).count();
+    let summary = format!("{} directories, {} files", dirs, entries.len() - dirs);
+
+    if truncated {
+        format!("{}, stopped after {} entries", summary, MAX_ENTRIES)
+    } else {
+        summary
+    }
+}
+
+fn display_name(entry: &Entry) -> String {
+    if entry.is_dir {
+        format!("{}/", entry.name)
+    } else {
+        entry.name.clone()
+    }
+}
+
+fn text(root: &str, entries: &[Entry], truncated: bool) -> String {
+    let mut lines = vec![format!("{}/", root)];
+    // whether the directory at each depth above was the last in its own
+    let mut ancestors: Vec<bool> = vec![];
+
+    for entry in entries {
+        ancestors.truncate(entry.depth - 1);
+
+        let mut line = ancestors
+            .iter()
+            .map(|last| if *last { "    " } else { "│   " })
+            .collect::<String>();
+        line.push_str(if entry.last {
+            "└── "
+        } else {
+            "├── "
+        });
+        line.push_str(&display_name(entry));
+
+        lines.push(line);
+        ancestors.push(entry.last);
+    }
+
+    lines.push(String::new());
+    lines.push(summary(entries, truncated));
+
+    lines.join("\n") + "\n"
+}
+
+fn markdown_escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        if "\\`*_[]<>#|".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn markdown(root: &str, entries: &[Entry], truncated: bool) -> String {
+    let mut lines = vec![format!("# {}", markdown_escape(root)), String::new()];
+
+    for entry in entries {
+        let indent = "  ".repeat(entry.depth - 1);
+        let name = markdown_escape(&display_name(entry));
+
+        if entry.is_dir {
+            lines.push(format!("{}- **{}**", indent, name));
+        } else {
+            lines.push(format!("{}- {}", indent, name));
+        }
+    }
+
+    lines.push(String::new());
+    lines.push(summary(entries, truncated));
+
+    lines.join("\n") + "\n"
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn html(root: &str, entries: &[Entry], truncated: bool) -> String {
+    let root = html_escape(root);
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{root}</title>\n\
+         <style>\nbody {{ font-family: sans-serif; }}\n\
+         ul {{ list-style: none; padding-left: 1.5em; }}\n\
+         li {{ font-weight: normal; }}\nli.dir {{ font-weight: bold; }}\n</style>\n\
+         </head>\n<body>\n<h1>{root}/</h1>\n<ul>\n"
+    );
+
+    for (idx, entry) in entries.iter().enumerate() {
+        let class = if entry.is_dir { " class=\"dir\"" } else { "" };
+        html.push_str(&format!(
+            "<li{}>{}",
+            class,
+            html_escape(&display_name(entry))
+        ));
+
+        // children open a list, the end of a directory closes as many as it ends
+        let next = entries.get(idx + 1).map(|next| next.depth).unwrap_or(1);
+        if next > entry.depth {
+            html.push_str("\n<ul>\n");
+        } else {
+            html.push_str("</li>\n");
+            for _ in next..entry.depth {
+                html.push_str("</ul>\n</li>\n");
+            }
+        }
+    }
+
+    html.push_str(&format!(
+        "</ul>\n<p>{}</p>\n</body>\n</html>\n",
+        summary(entries, truncated)
+    ));
+
+    html
+}
+
+// the current directory as a report, and how many entries it has
+fn render(app: &App, format: TreeFormat) -> Result<(String, usize), String> {
+    let cur_dir = std::env::current_dir().map_err(|e| e.to_string())?;
+    let root = cur_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| cur_dir.display().to_string());
+
+    let (entries, truncated) = entries(app, &cur_dir, app.export_depth);
+
+    let report = match format {
+        TreeFormat::Text => text(&root, &entries, truncated),
+        TreeFormat::Markdown => markdown(&root, &entries, truncated),
+        TreeFormat::Html => html(&root, &entries, truncated),
+    };
+
+    Ok((report, entries.len()))
+}
+
+// the export dialog's choices, a format and whether it goes to the clipboard
+fn choices() -> Vec<(TreeFormat, bool)> {
+    let mut choices = vec![];
+    for format in [TreeFormat::Text, TreeFormat::Markdown, TreeFormat::Html] {
+        choices.push((format, false));
+        choices.push((format, true));
+    }
+    choices
+}
+
+pub fn handle_export(app: &mut App) {
+    if app.show_export {
+        app.show_export = false;
+        return;
+    }
+
+    if block_binds(app) {
+        return;
+    }
+
+    app.export.items = choices();
+    app.export.state.select(Some(0));
+    app.show_export = true;
+}
+
+// + and - in the dialog
+pub fn change_depth(app: &mut App, by: isize) {
+    app.export_depth = app
+        .export_depth
+        .saturating_add_signed(by)
+        .clamp(1, MAX_DEPTH);
+}
+
+// the clipboard gets it right away, a file asks for its name first
+pub fn handle_choose_export(app: &mut App, input: &mut String, input_active: &mut bool) {
+    let (format, to_clipboard) = match app
+        .export
+        .state
+        .selected()
+        .and_then(|idx| app.export.items.get(idx))
+    {
+        Some(choice) => *choice,
+        None => return,
+    };
+
+    app.show_export = false;
+
+    if to_clipboard {
+        let (report, count) = match render(app, format) {
+            Ok(report) => report,
+            Err(e) => {
+                return app.notify_error(format!("Couldn't read the current directory: {}", e))
+            }
+        };
+
+        return match clipboard::copy(&report) {
+            Ok(_) => app.notify(format!(
+                "Copied the tree as {}, {} entries",
+                format.name(),
+                count
+            )),
+            Err(err) => app.notify_error(format!("Couldn't copy the tree: {}", err)),
+        };
+    }
+
+    let dir_name = std::env::current_dir()
+        .ok()
+        .and_then(|dir| {
+            dir.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+        })
+        .unwrap_or_else(|| "root".to_string());
+
+    app.export_format = Some(format);
+    *input = format!("{}-tree{}", dir_name, format.extension());
+    *input_active = true;
+    app.show_popup = true;
+    app.last_command = Some(Command::ExportTree);
+}
+
+pub fn submit_export(app: &mut App, name: &str) {
+    let format = match app.export_format.take() {
+        Some(format) => format,
+        None => return,
+    };
+
+    let dest = match std::env::current_dir() {
+        Ok(cur_dir) => cur_dir.join(name),
+        Err(e) => return app.show_error("Couldn't read the current directory", e),
+    };
+
+    if dest.exists() {
+        return app.notify_error(format!("{} already exists", name));
+    }
+
+    let (report, count) = match render(app, format) {
+        Ok(report) => report,
+        Err(e) => return app.show_error("Couldn't read the current directory", e),
+    };
+
+    match fs::write(&dest, report) {
+        Ok(_) => app.notify(format!("Wrote the tree to {}, {} entries", name, count)),
+        Err(e) => return app.show_error(&format!("Couldn't write {}", name), e),
+    }
+
+    app.update_files();
+    app.update_dirs();
+
+    if let Some(idx) = app.files.items.iter().position(|item| item.0 == name) {
+        app.files.state.select(Some(idx));
+        app.dirs.state.select(None);
+    }
+}
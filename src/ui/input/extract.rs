@@ -1,36 +1,307 @@
-use crate::app::app::App;
-use flate2::read::GzDecoder;
-use std::io::Read;
-use std::{fs::File, io::Cursor};
+use super::archive::{tar_reader, Format};
+use super::jobs::{self, Update};
+use crate::app::app::{display_name, App};
+use std::fs;
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use tar::Archive;
+use zip::result::ZipError;
+use zip::ZipArchive;
 
-pub fn extract_tar(app: &mut App, file: &str) -> Result<(), std::io::Error> {
-    let path = std::env::current_dir().unwrap().join(file);
+// the extra field PKWARE's own strong encryption adds, which only their tools
+// can decrypt
+const STRONG_ENCRYPTION: u16 = 0x0017;
 
-    let tar_gz = File::open(path)?;
-    let tar = GzDecoder::new(tar_gz);
-    let mut archive = Archive::new(tar);
-    archive.unpack(".")?;
+// extracts `file` into the current directory as a background job, `work`
+// gets the archive, where it goes and the flag to stop at the next entry
+fn spawn(
+    app: &mut App,
+    file: PathBuf,
+    work: impl FnOnce(&Path, &Path, &AtomicBool) -> Result<(), String> + Send + 'static,
+) {
+    let target_dir = match std::env::current_dir() {
+        Ok(cur_dir) => cur_dir,
+        Err(e) => return app.show_error("Couldn't read the current directory", e),
+    };
+    let name = display_name(&file);
 
-    app.update_files();
-    app.update_dirs();
+    jobs::spawn(app, format!("Extract {}", name), move |tx, kill| {
+        // the panes may be somewhere else by the time it's done
+        let failed = work(&target_dir.join(&file), &target_dir, &kill).err();
+        if let Some(failed) = &failed {
+            let _ = tx.send(Update::Line(failed.clone()));
+        }
 
+        let _ = tx.send(Update::Finished(
+            failed.clone(),
+            Some(Box::new(move |app: &mut App| {
+                match failed {
+                    Some(err) => app.notify_error(format!("Couldn't extract {}: {}", name, err)),
+                    None => app.notify(format!("Extracted {}", name)),
+                }
+                app.update_files();
+                app.update_dirs();
+            })),
+        ));
+    });
+}
+
+fn killed(kill: &AtomicBool) -> Result<(), String> {
+    if kill.load(Ordering::Relaxed) {
+        return Err("killed".to_string());
+    }
     Ok(())
 }
 
-pub fn extract_zip(app: &mut App, file: &str) -> Result<(), std::io::Error> {
-    let target_dir = std::env::current_dir().unwrap();
+pub fn extract_tar(app: &mut App, file: PathBuf, format: Format) {
+    spawn(app, file, move |file, target_dir, kill| {
+        let tar = match tar_reader(format, File::open(file).map_err(|err| err.to_string())?)
+            .map_err(|err| err.to_string())?
+        {
+            Some(tar) => tar,
+            None => return Ok(()),
+        };
+
+        let mut archive = Archive::new(tar);
+        for entry in archive.entries().map_err(|err| err.to_string())? {
+            killed(kill)?;
+            entry
+                .and_then(|mut entry| entry.unpack_in(target_dir))
+                .map_err(|err| err.to_string())?;
+        }
+
+        Ok(())
+    });
+}
+
+// `password` is for the encrypted ones, asked for before this
+pub fn extract_zip(app: &mut App, file: PathBuf, password: Option<String>) {
+    spawn(app, file, move |file, target_dir, kill| {
+        extract_zip_entries(file, password.as_deref(), target_dir, kill)
+    });
+}
+
+// zip only says an entry is encrypted by refusing to open it without a
+// password, which it does before touching the data
+pub fn is_encrypted<R: Read + Seek>(archive: &mut ZipArchive<R>, idx: usize) -> bool {
+    matches!(
+        archive.by_index(idx),
+        Err(ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED))
+    )
+}
+
+// whether any entry needs a password, the names themselves are never
+// encrypted so this doesn't need one
+pub fn zip_encrypted(file: &Path) -> bool {
+    match File::open(file).ok().and_then(|f| ZipArchive::new(f).ok()) {
+        Some(mut archive) => (0..archive.len()).any(|idx| is_encrypted(&mut archive, idx)),
+        None => false,
+    }
+}
+
+fn has_extra_field(extra: &[u8], id: u16) -> bool {
+    let mut rest = extra;
+
+    while rest.len() >= 4 {
+        let field = u16::from_le_bytes([rest[0], rest[1]]);
+        let len = u16::from_le_bytes([rest[2], rest[3]]) as usize;
+        if field == id {
+            return true;
+        }
+        rest = rest.get(4 + len..).unwrap_or_default();
+    }
+
+    false
+}
+
+fn zip_error(err: ZipError) -> String {
+    match err {
+        ZipError::InvalidPassword => "wrong password".to_string(),
+        ZipError::UnsupportedArchive(detail) => {
+            format!(
+                "uses an encryption or compression traverse can't read ({})",
+                detail
+            )
+        }
+        err => err.to_string(),
+    }
+}
+
+// the directory every entry is in, stripped like zip-extract does for the
+// unencrypted ones
+fn toplevel(names: &[PathBuf]) -> Option<PathBuf> {
+    if names.len() < 2 {
+        return None;
+    }
+
+    let first = names[0].components().take(1).collect::<PathBuf>();
+    if names.iter().all(|name| name.starts_with(&first)) {
+        Some(first)
+    } else {
+        None
+    }
+}
+
+fn extract_zip_entries(
+    file: &Path,
+    password: Option<&str>,
+    target_dir: &Path,
+    kill: &AtomicBool,
+) -> Result<(), String> {
+    let mut archive =
+        ZipArchive::new(File::open(file).map_err(|err| err.to_string())?).map_err(zip_error)?;
+
+    let mut names = vec![];
+    for idx in 0..archive.len() {
+        let encrypted = is_encrypted(&mut archive, idx);
+        let entry = archive.by_index_raw(idx).map_err(zip_error)?;
+
+        if encrypted && has_extra_field(entry.extra_data().unwrap_or_default(), STRONG_ENCRYPTION) {
+            return Err(format!(
+                "{} uses PKWARE strong encryption, which isn't supported, only ZipCrypto and AES are",
+                entry.name()
+            ));
+        }
+
+        match entry.enclosed_name() {
+            Some(name) => names.push(name.to_path_buf()),
+            None => return Err(format!("{} points outside the archive", entry.name())),
+        }
+    }
+
+    // every entry is checked against the password before anything is written,
+    // a wrong one leaves nothing half extracted
+    if let Some(password) = password {
+        for idx in 0..archive.len() {
+            archive
+                .by_index_decrypt(idx, password.as_bytes())
+                .map_err(zip_error)?;
+        }
+    }
 
-    let mut file = File::open(file)?;
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)?;
+    let strip = toplevel(&names);
 
-    let reader = Cursor::new(buffer);
+    for (idx, name) in names.iter().enumerate() {
+        killed(kill)?;
+        let relative = match &strip {
+            Some(top) => name.strip_prefix(top).unwrap_or(name),
+            None => name,
+        };
+        if relative.as_os_str().is_empty() {
+            continue;
+        }
+        let target = target_dir.join(relative);
 
-    zip_extract::extract(reader, &target_dir, true).unwrap();
+        let mut entry = match password {
+            Some(password) => archive.by_index_decrypt(idx, password.as_bytes()),
+            None => archive.by_index(idx),
+        }
+        .map_err(zip_error)?;
 
-    app.update_files();
-    app.update_dirs();
+        if entry.is_dir() {
+            fs::create_dir_all(&target).map_err(|err| err.to_string())?;
+            continue;
+        }
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        let mut out = File::create(&target).map_err(|err| err.to_string())?;
+        std::io::copy(&mut entry, &mut out).map_err(|err| format!("{}: {}", entry.name(), err))?;
+
+        #[cfg(unix)]
+        if let Some(mode) = entry.unix_mode() {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = fs::set_permissions(&target, fs::Permissions::from_mode(mode));
+        }
+    }
 
     Ok(())
 }
+
+// its Display is the Debug output, this is for the status bar
+#[cfg(feature = "sevenz")]
+pub fn sevenz_error(err: sevenz_rust::Error) -> String {
+    use sevenz_rust::Error;
+
+    match err {
+        Error::PasswordRequired => "needs a password".to_string(),
+        Error::MaybeBadPassword(_) => "wrong password".to_string(),
+        Error::UnsupportedCompressionMethod(method) => {
+            format!("uses {}, which traverse can't read", method)
+        }
+        Error::Io(err, _) | Error::FileOpen(err, _) => err.to_string(),
+        Error::Other(detail) | Error::Unsupported(detail) => detail.to_string(),
+        err => format!("{:?}", err),
+    }
+}
+
+// whether the names or the data need a password, 7z itself only says so
+// once the data is asked for
+#[cfg(feature = "sevenz")]
+pub fn sevenz_encrypted(file: &Path) -> bool {
+    use sevenz_rust::{Archive, Error, Password, SevenZMethod};
+
+    match Archive::open_with_password(file, &Password::empty()) {
+        Ok(archive) => archive.folders.iter().any(|folder| {
+            folder
+                .coders
+                .iter()
+                .any(|coder| coder.decompression_method_id() == SevenZMethod::ID_AES256SHA256)
+        }),
+        Err(Error::PasswordRequired | Error::MaybeBadPassword(_)) => true,
+        Err(_) => false,
+    }
+}
+
+#[cfg(feature = "sevenz")]
+pub fn extract_7z(app: &mut App, file: PathBuf, password: Option<String>) {
+    use sevenz_rust::{default_entry_extract_fn, Error, Password};
+
+    spawn(app, file, move |file, target_dir, kill| {
+        let password = password
+            .as_deref()
+            .map_or_else(Password::empty, Password::from);
+        let source = File::open(file).map_err(|err| err.to_string())?;
+
+        sevenz_rust::decompress_with_extract_fn_and_password(
+            source,
+            target_dir,
+            password,
+            |entry, reader, dest| {
+                if kill.load(Ordering::Relaxed) {
+                    return Err(Error::other("killed"));
+                }
+                default_entry_extract_fn(entry, reader, dest)
+            },
+        )
+        .map_err(sevenz_error)
+    });
+}
+
+// what the masked prompt submits, for the archive `x` asked it for
+pub fn submit_password(app: &mut App, password: &str) {
+    let file = match app.password_for.take() {
+        Some(file) => file,
+        None => return,
+    };
+
+    match Format::of(&file.to_string_lossy()) {
+        #[cfg(feature = "sevenz")]
+        Some(Format::SevenZ) => extract_7z(app, file, Some(password.to_string())),
+        #[cfg(feature = "age")]
+        Some(Format::Age) => {
+            match super::archive::decrypt_age(&file, password) {
+                Ok(dest) => app.notify(format!("Decrypted {}", dest.display())),
+                Err(err) => {
+                    app.notify_error(format!("Couldn't decrypt {}: {}", display_name(&file), err))
+                }
+            }
+            app.update_files();
+            app.update_dirs();
+        }
+        _ => extract_zip(app, file, Some(password.to_string())),
+    }
+}
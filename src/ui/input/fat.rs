@@ -0,0 +1,395 @@
+use super::file_ops;
+use super::jobs::{self, Job, Update};
+use crate::app::app::{display_name, App};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use sysinfo::{DiskExt, System, SystemExt};
+use walkdir::WalkDir;
+
+// the largest file FAT32 can hold, one byte short of 4 GiB
+const MAX_FILE: u64 = 0xFFFF_FFFF;
+// longest name, in UTF-16 units
+const NAME: usize = 255;
+const ILLEGAL: [char; 9] = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+const RESERVED: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Fat {
+    Fat32,
+    ExFat,
+}
+
+impl Fat {
+    pub fn name(self) -> &'static str {
+        match self {
+            Fat::Fat32 => "FAT32",
+            Fat::ExFat => "exFAT",
+        }
+    }
+}
+
+// the filesystem of the mount `dest` is on, when it's one of the FATs
+pub fn filesystem(dest: &Path) -> Option<Fat> {
+    let dest = fs::canonicalize(dest).ok()?;
+
+    let mut sys = System::new();
+    sys.refresh_disks_list();
+
+    let disk = sys
+        .disks()
+        .iter()
+        .filter(|disk| dest.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())?;
+
+    match String::from_utf8_lossy(disk.file_system())
+        .to_lowercase()
+        .as_str()
+    {
+        "exfat" => Some(Fat::ExFat),
+        "vfat" | "msdos" | "fat" | "fat12" | "fat16" | "fat32" => Some(Fat::Fat32),
+        _ => None,
+    }
+}
+
+// the name with everything FAT refuses replaced, the same name when it's fine
+pub fn sanitize(name: &str) -> String {
+    let mut safe = name
+        .chars()
+        .map(|c| {
+            if ILLEGAL.contains(&c) || c < ' ' {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect::<String>();
+
+    // trailing dots and spaces are silently dropped, which can merge names
+    let trimmed = safe.trim_end_matches(['.', ' ']);
+    if trimmed.len() != safe.len() {
+        safe = format!("{}_", trimmed);
+    }
+
+    let stem = safe.split('.').next().unwrap_or("").to_uppercase();
+    if RESERVED.contains(&stem.as_str()) {
+        safe = format!("_{}", safe);
+    }
+
+    // too long, cut the stem and keep the extension
+    if safe.encode_utf16().count() > NAME {
+        let extension = Path::new(&safe)
+            .extension()
+            .map(|extension| format!(".{}", extension.to_string_lossy()))
+            .filter(|extension| extension.encode_utf16().count() < NAME / 2)
+            .unwrap_or_default();
+
+        let mut stem = String::new();
+        for c in safe.chars() {
+            if stem.encode_utf16().count() + c.len_utf16() + extension.encode_utf16().count() > NAME
+            {
+                break;
+            }
+            stem.push(c);
+        }
+
+        safe = format!("{}{}", stem, extension);
+    }
+
+    safe
+}
+
+// "path: what's wrong" for everything under `sources` that can't be copied
+// to `fat` as it is, and whether any file is too big for it
+pub fn problems(sources: &[PathBuf], fat: Fat) -> (Vec<String>, bool) {
+    let mut problems = vec![];
+    let mut too_big = false;
+    // FAT ignores case, so "a" and "A" in one directory end up as one entry
+    let mut seen: HashMap<(PathBuf, String), String> = HashMap::new();
+
+    for source in sources {
+        let root = source.parent().unwrap_or(Path::new(""));
+
+        for entry in WalkDir::new(source)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+        {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let shown = display_name(entry.path().strip_prefix(root).unwrap_or(entry.path()));
+
+            // FAT keeps names in UTF-16, bytes that aren't text don't fit
+            if entry.file_name().to_str().is_none() || sanitize(&name) != name {
+                problems.push(format!("{}: not a valid {} name", shown, fat.name()));
+            }
+
+            if fat == Fat::Fat32 {
+                if let Ok(metadata) = entry.metadata() {
+                    if metadata.is_file() && metadata.len() > MAX_FILE {
+                        problems.push(format!("{}: over 4 GB", shown));
+                        too_big = true;
+                    }
+                }
+            }
+
+            let parent = entry.path().parent().unwrap_or(root).to_path_buf();
+            let key = (parent, sanitize(&name).to_lowercase());
+            if let Some(other) = seen.get(&key) {
+                problems.push(format!("{}: same name as {} ignoring case", shown, other));
+            } else {
+                seen.insert(key, name);
+            }
+        }
+    }
+
+    (problems, too_big)
+}
+
+// the first of "name", "name (2)", "name (3)", ... that isn't taken
+fn unique(dir: &Path, name: &str) -> PathBuf {
+    let path = Path::new(name);
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+
+    let mut target = dir.join(name);
+    let mut n = 2;
+    while target.exists() {
+        target = dir.join(format!("{} ({}){}", stem, n, extension));
+        n += 1;
+    }
+
+    target
+}
+
+// name.001, name.002, ... of at most MAX_FILE bytes each, put back together
+// with `cat name.0* > name`
+fn split(source: &Path, target: &Path) -> io::Result<()> {
+    let mut reader = File::open(source)?;
+    let parts = reader.metadata()?.len().div_ceil(MAX_FILE);
+
+    for part in 1..=parts {
+        let mut name = target.as_os_str().to_os_string();
+        name.push(format!(".{:03}", part));
+
+        let mut out = File::create(name)?;
+        io::copy(&mut (&mut reader).take(MAX_FILE), &mut out)?;
+    }
+
+    Ok(())
+}
+
+// copies `source` into `dest_dir` under safe names, splitting what's too big,
+// and says where it went when all of it made it. Killing it stops before the
+// next entry
+fn copy_tree(
+    source: &Path,
+    dest_dir: &Path,
+    fat: Fat,
+    job: &mut Job,
+    kill: &AtomicBool,
+) -> Option<PathBuf> {
+    let name = source.file_name()?.to_string_lossy().to_string();
+    let safe = sanitize(&name);
+
+    if kill.load(Ordering::Relaxed) {
+        job.failed(&source.display().to_string(), "killed before it started");
+        return None;
+    }
+
+    let metadata = match fs::metadata(source) {
+        Ok(metadata) => metadata,
+        Err(err) => {
+            job.failed(&source.display().to_string(), &err.to_string());
+            return None;
+        }
+    };
+
+    // FAT ignores case, so a file must not land on top of whatever took its
+    // name, nor a renamed directory, the others merge like with cp
+    let target = if safe == name && metadata.is_dir() {
+        dest_dir.join(&safe)
+    } else {
+        unique(dest_dir, &safe)
+    };
+
+    if metadata.is_dir() {
+        if let Err(err) = fs::create_dir_all(&target) {
+            job.failed(&source.display().to_string(), &err.to_string());
+            return None;
+        }
+
+        let mut children = match fs::read_dir(source) {
+            Ok(children) => children
+                .filter_map(|child| child.ok())
+                .map(|child| child.path())
+                .collect::<Vec<PathBuf>>(),
+            Err(err) => {
+                job.failed(&source.display().to_string(), &err.to_string());
+                return None;
+            }
+        };
+        children.sort();
+
+        let mut complete = true;
+        for child in children {
+            complete &= copy_tree(&child, &target, fat, job, kill).is_some();
+        }
+
+        return if complete { Some(target) } else { None };
+    }
+
+    let copied = if fat == Fat::Fat32 && metadata.len() > MAX_FILE {
+        split(source, &target)
+    } else {
+        fs::copy(source, &target).map(|_| ())
+    };
+
+    match copied {
+        Ok(_) => {
+            job.done((1, metadata.len()));
+            Some(target)
+        }
+        Err(err) => {
+            job.failed(&source.display().to_string(), &err.to_string());
+            None
+        }
+    }
+}
+
+// the buffer copied or moved here with safe names as a background job, a
+// move only removes entries that made it over whole
+pub fn transfer(app: &mut App, is_move: bool, fat: Fat) {
+    let cur_dir = match std::env::current_dir() {
+        Ok(cur_dir) => cur_dir,
+        Err(e) => return app.show_error("Couldn't read the current directory", e),
+    };
+
+    let mut job = if is_move {
+        Job::start("Move")
+    } else {
+        Job::start("Copy")
+    };
+
+    // the buffer is on its way, it can be filled again meanwhile
+    let sources = std::mem::take(&mut app.selected_files);
+    app.selected_dirs = vec![];
+    app.show_ops_menu = false;
+    app.last_command = None;
+
+    let name = match sources.as_slice() {
+        [source] => format!("{} {} to {}", job.kind, source.display(), fat.name()),
+        sources => format!("{} {} entries to {}", job.kind, sources.len(), fat.name()),
+    };
+
+    jobs::spawn(app, name, move |tx, kill| {
+        let mut moved = vec![];
+
+        for source in sources {
+            let _ = tx.send(Update::Line(display_name(&source)));
+            let target = match copy_tree(&source, &cur_dir, fat, &mut job, &kill) {
+                Some(target) => target,
+                None => continue,
+            };
+
+            if !is_move {
+                continue;
+            }
+
+            let removed = if source.is_dir() {
+                fs::remove_dir_all(&source)
+            } else {
+                fs::remove_file(&source)
+            };
+
+            match removed {
+                Ok(_) => moved.push((source, target)),
+                Err(err) => job.failed(
+                    &display_name(&source),
+                    &format!("copied, but not removed: {}", err),
+                ),
+            }
+        }
+
+        let failed = match job.failures.len() {
+            0 => None,
+            failures => Some(format!("failed for {} of them", failures)),
+        };
+        let _ = tx.send(Update::Finished(
+            failed,
+            Some(Box::new(move |app: &mut App| {
+                file_ops::finish_transfer(app, is_move, moved, job)
+            })),
+        ));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_valid_names() {
+        assert_eq!(sanitize("notes.txt"), "notes.txt");
+        assert_eq!(sanitize("console.log"), "console.log");
+        assert_eq!(sanitize("über café.md"), "über café.md");
+    }
+
+    #[test]
+    fn replaces_illegal_characters() {
+        assert_eq!(sanitize("a:b?c*.txt"), "a_b_c_.txt");
+        assert_eq!(sanitize("line\nbreak"), "line_break");
+    }
+
+    #[test]
+    fn marks_trailing_dots_and_spaces() {
+        assert_eq!(sanitize("name."), "name_");
+        assert_eq!(sanitize("name. . "), "name_");
+    }
+
+    #[test]
+    fn prefixes_reserved_names() {
+        assert_eq!(sanitize("CON"), "_CON");
+        assert_eq!(sanitize("con.txt"), "_con.txt");
+        assert_eq!(sanitize("Lpt9.tar.gz"), "_Lpt9.tar.gz");
+    }
+
+    #[test]
+    fn cuts_long_names_in_utf16_units() {
+        // two units each, under the limit in chars but not in units
+        let name = format!("{}.txt", "😀".repeat(200));
+        let safe = sanitize(&name);
+
+        assert!(safe.encode_utf16().count() <= NAME);
+        assert!(safe.ends_with(".txt"));
+        assert_eq!(safe.encode_utf16().count(), 254);
+    }
+
+    #[test]
+    fn finds_problems_below_a_source() {
+        let root = std::env::temp_dir().join(format!("rt-fat-{}", std::process::id()));
+        let source = root.join("source");
+        fs::create_dir_all(&source).unwrap();
+        for name in ["a.txt", "A.TXT", "what?", "fine"] {
+            File::create(source.join(name)).unwrap();
+        }
+
+        let (mut problems, too_big) = problems(&[source], Fat::ExFat);
+        problems.sort();
+        let _ = fs::remove_dir_all(&root);
+
+        assert!(!too_big);
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].contains("same name as"));
+        assert_eq!(problems[1], "source/what?: not a valid exFAT name");
+    }
+}
@@ -1,5 +1,14 @@
-use super::{extract::*, run_app::Command};
-use crate::{app::app::App, ui::display::block::block_binds};
+use super::archive::Format;
+use super::conflicts::{self, Then};
+use super::jobs::{self, Job, Update};
+use super::locking::{self, Retry};
+use super::plan::{self, Plan, Step};
+use super::undo::{self, Operation};
+use super::{extract::*, preflight, run_app::Command, secret};
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
 
 pub fn handle_new_file(app: &mut App, input_active: &mut bool) {
     if app.files.state.selected().is_some() {
@@ -9,6 +18,7 @@ pub fn handle_new_file(app: &mut App, input_active: &mut bool) {
             *input_active = true;
             app.show_popup = true;
             app.last_command = Some(Command::CreateFile);
+            app.use_template = true;
         }
     } else if app.dirs.state.selected().is_some() {
         if (*input_active == false && app.last_command != Some(Command::CreateDir))
@@ -21,37 +31,46 @@ pub fn handle_new_file(app: &mut App, input_active: &mut bool) {
     }
 }
 
-pub fn handle_delete(app: &mut App) {
-    if let Some(selected) = app.files.state.selected() {
-        if selected == 0 && app.files.items.len() == 0 {
-            return;
-        } else {
-            let file = app.files.items[selected].0.clone();
+pub fn handle_new_symlink(app: &mut App, input_active: &mut bool) {
+    if block_binds(app) {
+        return;
+    }
 
-            trash::delete(&file).unwrap();
-            app.update_files();
+    let selected = match app.files.state.selected() {
+        Some(i) => app.files.items.get(i),
+        None => app
+            .dirs
+            .state
+            .selected()
+            .and_then(|i| app.dirs.items.get(i)),
+    };
 
-            if selected >= app.files.items.len() {
-                app.files
-                    .state
-                    .select(Some(app.files.items.len().saturating_sub(1)));
-            }
+    match selected {
+        Some(entry) if entry.0 != "../" => {
+            *input_active = true;
+            app.show_popup = true;
+            app.last_command = Some(Command::CreateSymlink);
         }
-    } else if let Some(selected) = app.dirs.state.selected() {
-        let dir = app.dirs.items[selected].0.clone();
+        _ => {}
+    }
+}
 
-        if dir == "../" {
-            return;
-        } else {
-            trash::delete(&dir).unwrap();
-            app.update_dirs();
+pub fn handle_delete(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
 
-            if selected >= app.dirs.items.len() {
-                app.dirs
-                    .state
-                    .select(Some(app.dirs.items.len().saturating_sub(1)));
+    // the panes are refreshed once the job is done
+    if app.files.state.selected().is_some() {
+        if let Some(file) = app.selected_file().map(PathBuf::from) {
+            if preflight::check_delete(app, &file) {
+                locking::run(app, Retry::Delete(file));
             }
         }
+    } else if let Some(dir) = app.selected_dir().map(PathBuf::from) {
+        if dir.as_os_str() != "../" && preflight::check_delete(app, &dir) {
+            locking::run(app, Retry::Delete(dir));
+        }
     }
 }
 
@@ -60,59 +79,104 @@ pub fn handle_rename(app: &mut App, input: &mut String, input_active: &mut bool)
         return;
     }
 
-    if app.files.state.selected().is_some() {
+    if let Some(file) = app.selected_file() {
         if *input_active == false && app.last_command != Some(Command::RenameFile) {
             *input_active = true;
             app.show_popup = true;
             app.last_command = Some(Command::RenameFile);
 
-            *input = app.files.items[app.files.state.selected().unwrap()]
-                .0
-                .clone();
+            // a name that isn't UTF-8 can only be shown, typing replaces it
+            *input = file.to_string_lossy().to_string();
         }
-    } else if app.dirs.state.selected().is_some() {
-        if app.dirs.items[app.dirs.state.selected().unwrap()].0 == "../" {
+    } else if let Some(dir) = app.selected_dir() {
+        if dir == "../" {
             return;
         } else {
             if *input_active == false && app.last_command != Some(Command::RenameDir) {
+                *input = dir.to_string_lossy().to_string();
                 *input_active = true;
                 app.show_popup = true;
                 app.last_command = Some(Command::RenameDir);
-                *input = app.dirs.items[app.dirs.state.selected().unwrap()].0.clone();
             }
         }
     }
 }
 
-pub fn extract(app: &mut App) {
-    if app.files.state.selected().is_some() {
-        let file = app.files.items[app.files.state.selected().unwrap()]
-            .0
-            .clone();
+pub fn extract(app: &mut App, input: &mut String, input_active: &mut bool) {
+    if let Some(file) = app.selected_file() {
+        let file = PathBuf::from(file);
+
+        let cur_dir = match std::env::current_dir() {
+            Ok(cur_dir) => cur_dir,
+            Err(e) => return app.show_error("Couldn't read the current directory", e),
+        };
+        if !preflight::check_extract(app, &file, &cur_dir) {
+            return;
+        }
+
+        let format = Format::of(&file.to_string_lossy());
+
+        // encrypted zips ask for the password first, submitting it extracts
+        if format == Some(Format::Zip) && zip_encrypted(&file) {
+            return ask_password(app, input, input_active, file);
+        }
+
+        // age files always have one
+        #[cfg(feature = "age")]
+        if format == Some(Format::Age) {
+            return ask_password(app, input, input_active, file);
+        }
+
+        // so do 7z archives with anything encrypted
+        #[cfg(feature = "sevenz")]
+        if format == Some(Format::SevenZ) && sevenz_encrypted(&file) {
+            return ask_password(app, input, input_active, file);
+        }
 
-        if file.ends_with(".tar.gz") {
-            extract_tar(app, &file).expect("Failed to extract tar file");
-        } else if file.ends_with(".zip") {
-            extract_zip(app, &file).expect("Failed to extract zip file");
+        match format {
+            Some(Format::Zip) => extract_zip(app, file, None),
+            #[cfg(feature = "sevenz")]
+            Some(Format::SevenZ) => extract_7z(app, file, None),
+            Some(format) => extract_tar(app, file, format),
+            None => {}
         }
     }
 }
 
+fn ask_password(app: &mut App, input: &mut String, input_active: &mut bool, file: PathBuf) {
+    input.clear();
+    secret::ask(app, input_active, Command::Password);
+    app.password_for = Some(file);
+}
+
 fn add_dir(app: &mut App) {
-    let selected = app.dirs.state.selected().unwrap();
-    let cur_dir = std::env::current_dir().unwrap();
+    let dir = match app.selected_dir() {
+        Some(dir) => dir,
+        None => return,
+    };
+    let cur_dir = match std::env::current_dir() {
+        Ok(cur_dir) => cur_dir,
+        Err(e) => return app.show_error("Couldn't read the current directory", e),
+    };
 
-    app.selected_files.push(format!(
-        "{}/{}",
-        cur_dir.display(),
-        app.dirs.items[selected].0
+    // joined, C:\ and / already end in a separator
+    app.selected_files.push(cur_dir.join(dir));
+    app.notify(format!(
+        "{} in the move/copy buffer",
+        app.selected_files.len()
     ));
 }
 
 fn add_file(app: &mut App) {
-    let selected = app.files.state.selected().unwrap();
-    let cur_dir = std::env::current_dir().unwrap();
-    let selected = format!("{}/{}", cur_dir.display(), app.files.items[selected].0);
+    let file = match app.selected_file() {
+        Some(file) => file,
+        None => return,
+    };
+    let cur_dir = match std::env::current_dir() {
+        Ok(cur_dir) => cur_dir,
+        Err(e) => return app.show_error("Couldn't read the current directory", e),
+    };
+    let selected = cur_dir.join(file);
 
     for file in app.selected_files.clone() {
         if file == selected {
@@ -121,6 +185,10 @@ fn add_file(app: &mut App) {
     }
 
     app.selected_files.push(selected);
+    app.notify(format!(
+        "{} in the move/copy buffer",
+        app.selected_files.len()
+    ));
 }
 
 pub fn add_to_selected(app: &mut App) {
@@ -142,69 +210,8 @@ pub fn handle_paste_or_move(app: &mut App) {
     }
 
     if let Some(selected) = app.ops_menu.state.selected() {
-        let mut cur_dir = std::env::current_dir().unwrap();
         match selected {
-            0 => {
-                // copy
-                for file in app.selected_files.clone() {
-                    for cur_files in app.files.items.clone() {
-                        if file == cur_files.0 {
-                            continue;
-                        }
-
-                        std::process::Command::new("cp")
-                            .arg("-r")
-                            .arg(&file)
-                            .arg(&cur_dir)
-                            .spawn()
-                            .expect("Failed to copy file");
-
-                        app.show_ops_menu = false;
-                        app.last_command = None;
-                        app.selected_files = vec![];
-                        app.selected_dirs = vec![];
-
-                        app.update_files();
-                        app.update_dirs();
-
-                        app.files
-                            .state
-                            .select(Some(app.files.items.len().saturating_sub(1)));
-                    }
-
-                    cur_dir = std::env::current_dir().unwrap();
-                }
-            }
-            1 => {
-                // move
-                for file in app.selected_files.clone() {
-                    for cur_files in app.files.items.clone() {
-                        if file == cur_files.0 {
-                            continue;
-                        }
-
-                        std::process::Command::new("mv")
-                            .arg(&file)
-                            .arg(&cur_dir)
-                            .spawn()
-                            .expect("Failed to move file");
-
-                        app.show_ops_menu = false;
-                        app.last_command = None;
-                        app.selected_files = vec![];
-                        app.selected_dirs = vec![];
-
-                        app.update_files();
-                        app.update_dirs();
-
-                        app.files
-                            .state
-                            .select(Some(app.files.items.len().saturating_sub(1)));
-
-                        cur_dir = std::env::current_dir().unwrap();
-                    }
-                }
-            }
+            0 | 1 => transfer(app, selected == 1, false),
             2 => {
                 // clear selection
                 app.last_command = None;
@@ -220,3 +227,162 @@ pub fn handle_paste_or_move(app: &mut App) {
         }
     }
 }
+
+// copy or move the buffer here, `confirmed` once the user went past the caps
+pub fn transfer(app: &mut App, is_move: bool, confirmed: bool) {
+    let cur_dir = match std::env::current_dir() {
+        Ok(cur_dir) => cur_dir,
+        Err(e) => return app.show_error("Couldn't read the current directory", e),
+    };
+
+    let sources = app.selected_files.clone();
+
+    // fail before touching anything rather than halfway with ENOSPC, on
+    // entries clashing with each other or on a name the destination can't
+    // hold, the space check walks everything so the entry cap goes first
+    if !confirmed && !preflight::check_entries(app, &sources, is_move) {
+        app.show_ops_menu = false;
+        app.last_command = None;
+        return;
+    }
+
+    let plan = plan::plan(&sources, &cur_dir, is_move);
+    if !plan.errors.is_empty() {
+        plan::report_errors(app, &plan, is_move);
+    }
+
+    if !plan.errors.is_empty()
+        || !preflight::check_transfer(app, &sources, &cur_dir, is_move)
+        || !preflight::check_names(app, &sources, &cur_dir, is_move)
+    {
+        app.show_ops_menu = false;
+        app.last_command = None;
+        return;
+    }
+
+    if plan.steps.is_empty() {
+        app.show_ops_menu = false;
+        app.last_command = None;
+        return app.notify("Everything in the buffer is already here");
+    }
+
+    conflicts::check(app, Then::transfer(is_move), plan);
+}
+
+// the plan once the names already taken are settled, shown first when it
+// isn't the buffer as it is
+pub fn go_ahead(app: &mut App, is_move: bool, plan: Plan) {
+    app.show_ops_menu = false;
+    app.last_command = None;
+
+    if plan.steps.is_empty() {
+        return app.notify("Everything in the buffer was skipped");
+    }
+    if plan.reordered || !plan.skipped.is_empty() {
+        return plan::hold(app, plan, is_move);
+    }
+
+    run(app, is_move, plan.steps);
+}
+
+// copy or move as a background job, one step after the other and waiting
+// for each so the summary adds up, killing it stops after the current step
+pub fn run(app: &mut App, is_move: bool, steps: Vec<Step>) {
+    let mut job = if !is_move {
+        Job::start("Copy")
+    } else {
+        Job::start("Move")
+    };
+
+    // the buffer is on its way, it can be filled again meanwhile
+    app.show_ops_menu = false;
+    app.last_command = None;
+    app.selected_files = vec![];
+    app.selected_dirs = vec![];
+
+    let name = match steps.as_slice() {
+        [step] => format!("{} {}", job.kind, step.source.display()),
+        steps => format!("{} {} entries", job.kind, steps.len()),
+    };
+
+    jobs::spawn(app, name, move |tx, kill| {
+        let mut moved = vec![];
+
+        for step in steps {
+            let file = step.source.to_string_lossy().to_string();
+            let dest = match step.target.parent() {
+                Some(dest) => dest,
+                None => continue,
+            };
+            if kill.load(Ordering::Relaxed) {
+                job.failed(&file, "killed before it started");
+                continue;
+            }
+            let counted = jobs::measure(&step.source);
+
+            let mut command = if !is_move {
+                let mut cp = std::process::Command::new("cp");
+                cp.arg("-r");
+                cp
+            } else {
+                std::process::Command::new("mv")
+            };
+
+            // a renamed entry is copied to its new name, the rest into the
+            // directory
+            let target = if step.target.file_name() == step.source.file_name() {
+                dest
+            } else {
+                step.target.as_path()
+            };
+
+            let _ = tx.send(Update::Line(format!("{} to {}", file, target.display())));
+            match jobs::run_child(command.arg(&step.source).arg(target), &tx, &kill) {
+                Ok((status, _)) if status.success() => {
+                    job.done(counted);
+                    moved.push((step.source.clone(), step.target.clone()));
+                }
+                Ok((status, errors)) => match jobs::describe(&status, &kill) {
+                    Some(failed) if errors.trim().is_empty() => job.failed(&file, &failed),
+                    _ => job.failed(&file, errors.trim()),
+                },
+                Err(e) => job.failed(&file, &e.to_string()),
+            }
+        }
+
+        let failed = match job.failures.len() {
+            0 => None,
+            failures => Some(format!("failed for {} of them", failures)),
+        };
+        let _ = tx.send(Update::Finished(
+            failed,
+            Some(Box::new(move |app: &mut App| {
+                finish_transfer(app, is_move, moved, job)
+            })),
+        ));
+    });
+}
+
+// back to a clean buffer once the entries are where they're going
+pub fn finish_transfer(app: &mut App, is_move: bool, moved: Vec<(PathBuf, PathBuf)>, job: Job) {
+    app.show_ops_menu = false;
+    app.last_command = None;
+    app.selected_files = vec![];
+    app.selected_dirs = vec![];
+
+    app.update_files();
+    app.update_dirs();
+
+    app.files
+        .state
+        .select(Some(app.files.items.len().saturating_sub(1)));
+
+    // copies only add files, there's nothing to take back
+    if is_move && !moved.is_empty() {
+        undo::record(app, Operation::Move(moved));
+    }
+
+    // a transfer done in the background doesn't pop up over something else
+    let show = !block_binds(app);
+    job.finish(app, show);
+}
@@ -0,0 +1,85 @@
+use super::run_app::Command;
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+// what the Files pane is restricted to, as typed
+pub struct FileFilter {
+    pub pattern: String,
+    globs: GlobSet,
+}
+
+impl FileFilter {
+    // names in the flat listing are paths, `*` goes past their slashes
+    pub fn matches(&self, name: &str) -> bool {
+        self.globs.is_match(name)
+    }
+}
+
+// `rs` and `.rs` are short for `*.rs`
+fn glob(token: &str) -> String {
+    if token.contains(['*', '?', '[', '{', '/']) {
+        token.to_string()
+    } else {
+        format!("*.{}", token.trim_start_matches('.'))
+    }
+}
+
+// several globs separated by spaces, any of them matching is enough
+fn parse(pattern: &str) -> Result<GlobSet, globset::Error> {
+    let mut builder = GlobSetBuilder::new();
+
+    for token in pattern.split_whitespace() {
+        builder.add(
+            GlobBuilder::new(&glob(token))
+                .case_insensitive(true)
+                .build()?,
+        );
+    }
+
+    builder.build()
+}
+
+// *: asks for the globs, starting from the ones in use
+pub fn handle_filter(app: &mut App, input: &mut String, input_active: &mut bool) {
+    if block_binds(app) || *input_active {
+        return;
+    }
+
+    *input = app
+        .file_filter
+        .as_ref()
+        .map(|filter| filter.pattern.clone())
+        .unwrap_or_default();
+    *input_active = true;
+    app.show_popup = true;
+    app.last_command = Some(Command::FilterFiles);
+}
+
+// an empty pattern clears the filter
+pub fn submit_filter(app: &mut App, pattern: &str) {
+    let pattern = pattern.trim();
+    if pattern.is_empty() {
+        return clear(app);
+    }
+
+    match parse(pattern) {
+        Ok(globs) => {
+            app.file_filter = Some(FileFilter {
+                pattern: pattern.to_string(),
+                globs,
+            });
+            app.update_files();
+            app.clamp_selection();
+        }
+        Err(e) => app.notify_error(format!("Not a glob: {}", e)),
+    }
+}
+
+pub fn clear(app: &mut App) {
+    if app.file_filter.take().is_some() {
+        app.notify("Showing all files");
+        app.update_files();
+        app.clamp_selection();
+    }
+}
@@ -0,0 +1,158 @@
+use crate::app::app::{display_name, App};
+use crate::ui::display::block::block_binds;
+use ignore::WalkBuilder;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+// sent this many at a time, so a big tree fills the pane as it's walked
+const BATCH: usize = 500;
+
+// the walk behind the flat listing, dropping it stops the thread
+pub struct FlatJob {
+    rx: Receiver<Vec<OsString>>,
+    found: Vec<(OsString, String)>,
+    // the first walk in a directory shows what it has so far, a refresh keeps
+    // the old list until the new one is complete
+    streaming: bool,
+}
+
+// every file below `root` by its path from there, hidden and git ignored
+// ones only when the panes show them too, stopping at `cap` (0 for none)
+fn spawn(
+    root: PathBuf,
+    show_hidden: bool,
+    hide_ignored: bool,
+    cap: usize,
+) -> Receiver<Vec<OsString>> {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let walker = WalkBuilder::new(&root)
+            .standard_filters(hide_ignored)
+            .hidden(!show_hidden)
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .filter_entry(|entry| entry.file_name() != ".git")
+            .build();
+
+        let mut batch = vec![];
+        let mut count = 0;
+
+        for entry in walker.filter_map(|entry| entry.ok()) {
+            // links to directories too, they'd list like one
+            if entry.path().is_dir() {
+                continue;
+            }
+
+            let name = match entry.path().strip_prefix(&root) {
+                Ok(name) => name.as_os_str().to_os_string(),
+                Err(_) => continue,
+            };
+            batch.push(name);
+            count += 1;
+
+            if batch.len() == BATCH && tx.send(std::mem::take(&mut batch)).is_err() {
+                return;
+            }
+            if count == cap {
+                break;
+            }
+        }
+
+        let _ = tx.send(batch);
+    });
+
+    rx
+}
+
+// F: every file below the current directory in the Files pane, or back to
+// the ones in it
+pub fn handle_flatten(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    app.flatten = !app.flatten;
+    app.flat_job = None;
+    app.flat_dir = None;
+    app.update_files();
+    app.clamp_selection();
+}
+
+// called by update_files in the flat listing, walks again in the background
+pub fn list(app: &mut App) {
+    let cur_dir = match std::env::current_dir() {
+        Ok(cur_dir) => cur_dir,
+        Err(_) => return app.files.items.clear(),
+    };
+
+    let streaming = app.flat_dir.as_ref() != Some(&cur_dir);
+    if streaming {
+        app.files.items.clear();
+        app.flat_dir = Some(cur_dir.clone());
+    }
+
+    app.flat_job = Some(FlatJob {
+        rx: spawn(cur_dir, app.show_hidden, app.hide_ignored, app.max_entries),
+        found: vec![],
+        streaming,
+    });
+}
+
+pub fn poll_flatten_job(app: &mut App) {
+    let job = match app.flat_job.as_mut() {
+        Some(job) => job,
+        None => return,
+    };
+
+    let mut done = false;
+    loop {
+        match job.rx.try_recv() {
+            Ok(batch) => job.found.extend(
+                batch
+                    .into_iter()
+                    .filter(|name| {
+                        app.file_filter
+                            .as_ref()
+                            .is_none_or(|filter| filter.matches(&name.to_string_lossy()))
+                    })
+                    .map(|name| {
+                        let label = display_name(&name);
+                        (name, label)
+                    }),
+            ),
+            Err(mpsc::TryRecvError::Empty) => break,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                done = true;
+                break;
+            }
+        }
+    }
+
+    if job.streaming {
+        app.files.items.append(&mut job.found);
+    } else if done {
+        app.files.items = std::mem::take(&mut job.found);
+    }
+
+    if done {
+        app.flat_job = None;
+        app.clamp_selection();
+    }
+}
+
+// the Files pane's title
+pub fn title(app: &App) -> String {
+    if !app.flatten {
+        return match &app.file_pages {
+            Some(pages) if pages.streaming() => format!("Files, {} so far", app.files.items.len()),
+            _ => "Files".to_string(),
+        };
+    }
+
+    match &app.flat_job {
+        Some(job) if job.streaming => format!("All files below, {} so far", app.files.items.len()),
+        _ => format!("All files below, {}", app.files.items.len()),
+    }
+}
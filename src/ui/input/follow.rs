@@ -0,0 +1,150 @@
+use crate::app::app::{display_name, App};
+use crate::ui::display::block::block_binds;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+
+// the end of the file that's read again on every change, plenty for a
+// preview and cheap even when a log grows fast
+const TAIL_BYTES: u64 = 256 * 1024;
+const MAX_LINES: usize = 1000;
+
+// the preview pinned to the end of a file, like `tail -f`
+pub struct Follow {
+    pub file: OsString,
+    path: PathBuf,
+    // watches the file's directory rather than the file, so a log that's
+    // rotated (moved away and created again) is picked up again
+    _watcher: Option<RecommendedWatcher>,
+    rx: Option<Receiver<notify::Result<Event>>>,
+    // what the file looked like when last read, for when there's no watcher
+    len: u64,
+    pub lines: Vec<String>,
+}
+
+// the last lines of the file, the first one dropped when it's cut off
+fn tail(path: &Path) -> io::Result<(u64, Vec<String>)> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(TAIL_BYTES);
+
+    file.seek(SeekFrom::Start(start))?;
+    let mut bytes = vec![];
+    file.take(TAIL_BYTES).read_to_end(&mut bytes)?;
+
+    let text = String::from_utf8_lossy(&bytes);
+    let mut lines = text.lines().map(str::to_string).collect::<Vec<String>>();
+    if start > 0 && !lines.is_empty() {
+        lines.remove(0);
+    }
+    if lines.len() > MAX_LINES {
+        lines.drain(..lines.len() - MAX_LINES);
+    }
+
+    Ok((len, lines))
+}
+
+fn watch(path: &Path) -> Option<(RecommendedWatcher, Receiver<notify::Result<Event>>)> {
+    let (tx, rx) = channel();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = tx.send(event);
+    })
+    .ok()?;
+
+    watcher
+        .watch(path.parent()?, RecursiveMode::NonRecursive)
+        .ok()?;
+
+    Some((watcher, rx))
+}
+
+// F in the focused preview, starts or stops following the previewed file
+pub fn handle_follow(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    if app.follow.take().is_some() {
+        app.notify("Stopped following");
+        return;
+    }
+
+    let file = app.preview_file.clone();
+    let path = match std::env::current_dir() {
+        Ok(cur_dir) => cur_dir.join(&file),
+        Err(e) => return app.show_error("Couldn't read the current directory", e),
+    };
+    if file.is_empty() || !path.is_file() {
+        return app.notify_error("Only files can be followed");
+    }
+
+    let (len, lines) = match tail(&path) {
+        Ok(tail) => tail,
+        Err(e) => return app.show_error(&format!("Couldn't read {}", display_name(&file)), e),
+    };
+    if lines.iter().any(|line| line.contains('\0')) {
+        return app.notify_error("Only text files can be followed");
+    }
+
+    let (watcher, rx) = match watch(&path) {
+        Some((watcher, rx)) => (Some(watcher), Some(rx)),
+        None => (None, None),
+    };
+
+    app.notify(format!("Following {}", display_name(&file)));
+    app.follow = Some(Follow {
+        file,
+        path,
+        _watcher: watcher,
+        rx,
+        len,
+        lines,
+    });
+}
+
+// every tick: reads the end again when the file changed, and stops when the
+// preview moved on to another entry
+pub fn poll_follow(app: &mut App) {
+    let follow = match app.follow.as_mut() {
+        Some(follow) => follow,
+        None => return,
+    };
+
+    if follow.file != app.preview_file {
+        app.follow = None;
+        return;
+    }
+
+    let changed = match &follow.rx {
+        Some(rx) => {
+            let mut changed = false;
+            while let Ok(event) = rx.try_recv() {
+                if let Ok(event) = event {
+                    changed |= !event.kind.is_access() && event.paths.contains(&follow.path);
+                }
+            }
+            changed
+        }
+        // without a watcher the length has to do
+        None => std::fs::metadata(&follow.path).is_ok_and(|metadata| metadata.len() != follow.len),
+    };
+
+    if !changed {
+        return;
+    }
+
+    // gone for now, a rotated log comes back
+    if let Ok((len, lines)) = tail(&follow.path) {
+        follow.len = len;
+        follow.lines = lines;
+    }
+}
+
+pub fn active(app: &App) -> Option<&Follow> {
+    app.follow
+        .as_ref()
+        .filter(|follow| follow.file == app.preview_file)
+}
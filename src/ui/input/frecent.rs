@@ -0,0 +1,139 @@
+use super::jumps;
+use super::run_app::Command;
+use crate::app::app::App;
+use crate::configuration::frecency;
+use crate::ui::display::block::block_binds;
+use crate::ui::display::pane::get_pwd;
+use std::path::{Path, PathBuf};
+use sublime_fuzzy::best_match;
+
+// every directory traverse ends up in counts as a visit, however it got there
+pub fn track(app: &mut App) {
+    let cwd = match std::env::current_dir() {
+        Ok(cwd) => cwd,
+        Err(_) => return,
+    };
+
+    if app.last_visit.as_ref() == Some(&cwd) {
+        return;
+    }
+
+    if let Some(db) = app.jump_db {
+        db.add(&cwd);
+    }
+    frecency::visit(&mut app.frecency, &cwd.to_string_lossy());
+    frecency::save(&app.frecency);
+    app.last_visit = Some(cwd);
+}
+
+// the most frecent first, among the ones matching `query`, leaving out where
+// we are and anything that's gone
+fn entries(app: &App, query: &str) -> Vec<String> {
+    let here = |path: &str| app.last_visit.as_deref() == Some(Path::new(path));
+
+    // zoxide and autojump rank them already
+    if let Some(dirs) = &app.jump_db_dirs {
+        return dirs
+            .iter()
+            .filter(|path| !here(path))
+            .filter(|path| Path::new(path).is_dir())
+            .filter(|path| query.is_empty() || best_match(query, path).is_some())
+            .cloned()
+            .collect();
+    }
+
+    let now = frecency::now();
+
+    let mut entries = app
+        .frecency
+        .iter()
+        .filter(|visit| !here(&visit.path))
+        .filter(|visit| Path::new(&visit.path).is_dir())
+        .filter(|visit| query.is_empty() || best_match(query, &visit.path).is_some())
+        .map(|visit| (visit.score(now), visit.path.clone()))
+        .collect::<Vec<(f64, String)>>();
+
+    entries.sort_by(|a, b| b.0.total_cmp(&a.0));
+    entries.into_iter().map(|(_, path)| path).collect()
+}
+
+pub fn handle_frecent(app: &mut App, input: &mut String, input_active: &mut bool) {
+    if block_binds(app) {
+        return;
+    }
+
+    app.show_frecent = true;
+    app.last_command = Some(Command::ShowFrecent);
+
+    // asked once per popup, typing filters what it said
+    app.jump_db_dirs = None;
+    if let Some(db) = app.jump_db {
+        match db.query() {
+            Ok(dirs) => app.jump_db_dirs = Some(dirs),
+            Err(e) => app.notify_error(format!(
+                "Couldn't ask {}, showing traverse's own: {}",
+                db.name(),
+                e
+            )),
+        }
+    }
+
+    input.clear();
+    *input_active = true;
+
+    app.frecent_query = input.clone();
+    app.frecent.items = entries(app, input);
+    app.frecent.state.select(if app.frecent.items.is_empty() {
+        None
+    } else {
+        Some(0)
+    });
+}
+
+pub fn filter(app: &mut App, input: &str) {
+    if app.frecent_query == input {
+        return;
+    }
+
+    app.frecent.items = entries(app, input);
+    app.frecent.state.select(if app.frecent.items.is_empty() {
+        None
+    } else {
+        Some(0)
+    });
+    app.frecent_query = input.to_string();
+}
+
+pub fn handle_open_frecent(app: &mut App, input: &mut String, input_active: &mut bool) {
+    let path = app
+        .frecent
+        .state
+        .selected()
+        .and_then(|idx| app.frecent.items.get(idx))
+        .cloned();
+
+    app.show_frecent = false;
+    app.last_command = None;
+    input.clear();
+    *input_active = false;
+
+    let path = match path {
+        Some(path) => path,
+        None => return,
+    };
+
+    // a directory that can't be opened anymore isn't worth suggesting again
+    if !jumps::change_dir(app, &PathBuf::from(&path)) {
+        frecency::forget(&mut app.frecency, &path);
+        frecency::save(&app.frecency);
+        return;
+    }
+
+    app.update_files();
+    app.update_dirs();
+
+    app.files.state.select(Some(0));
+    app.dirs.state.select(None);
+
+    app.cur_dir = get_pwd();
+}
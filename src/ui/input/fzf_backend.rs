@@ -0,0 +1,217 @@
+use crate::app::app::App;
+use std::io::{self, BufRead, BufReader};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+// lines are handed over in batches, a giant repo lists millions
+const BATCH: usize = 512;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Backend {
+    // the ignore crate walking in-process
+    Builtin,
+    Fd,
+    Rg,
+}
+
+impl Backend {
+    pub fn from_config(value: &str) -> Backend {
+        match value.to_ascii_lowercase().as_str() {
+            "fd" | "fdfind" => Backend::Fd,
+            "rg" | "ripgrep" => Backend::Rg,
+            _ => Backend::Builtin,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Backend::Builtin => "builtin",
+            Backend::Fd => "fd",
+            Backend::Rg => "rg",
+        }
+    }
+
+    // Debian and Ubuntu ship fd as fdfind
+    fn programs(self) -> &'static [&'static str] {
+        match self {
+            Backend::Builtin => &[],
+            Backend::Fd => &["fd", "fdfind"],
+            Backend::Rg => &["rg"],
+        }
+    }
+
+    // every file under `dir`, one per line, prefixed with `dir` like the
+    // builtin walk's paths
+    fn command(self, program: &str, dir: &str, respect_ignore: bool, show_hidden: bool) -> Command {
+        let mut command = Command::new(program);
+        match self {
+            Backend::Fd | Backend::Builtin => {
+                command.args(["--type", "f", "--color", "never", "--exclude", ".git"]);
+                if show_hidden {
+                    command.arg("--hidden");
+                }
+                if !respect_ignore {
+                    command.arg("--no-ignore");
+                }
+                command.arg(".").arg(dir);
+            }
+            Backend::Rg => {
+                command.args(["--files", "--color", "never", "--glob", "!.git"]);
+                if show_hidden {
+                    command.arg("--hidden");
+                }
+                if !respect_ignore {
+                    command.arg("--no-ignore");
+                }
+                command.arg(dir);
+            }
+        }
+        command
+    }
+}
+
+// the listing an external backend streams in, kept while the popup is open
+// so typing only filters it
+pub struct FzfBackend {
+    pub backend: Backend,
+    // the directory and toggles it was started for
+    key: Option<(String, bool, bool)>,
+    pub files: Vec<PathBuf>,
+    lines: Option<Receiver<Vec<String>>>,
+    child: Option<Child>,
+}
+
+impl Default for FzfBackend {
+    fn default() -> FzfBackend {
+        FzfBackend {
+            backend: Backend::Builtin,
+            key: None,
+            files: vec![],
+            lines: None,
+            child: None,
+        }
+    }
+}
+
+impl FzfBackend {
+    fn stop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        self.lines = None;
+        self.files.clear();
+        self.key = None;
+    }
+}
+
+fn start(app: &mut App, key: (String, bool, bool)) -> bool {
+    let backend = app.fzf_backend.backend;
+    let mut spawned = Err(io::Error::from(io::ErrorKind::NotFound));
+    for program in backend.programs() {
+        spawned = backend
+            .command(program, &key.0, key.1, key.2)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn();
+        if !matches!(&spawned, Err(e) if e.kind() == io::ErrorKind::NotFound) {
+            break;
+        }
+    }
+
+    let mut child = match spawned {
+        Ok(child) => child,
+        // not installed: the builtin walk takes over for the session
+        Err(e) => {
+            app.fzf_backend.backend = Backend::Builtin;
+            app.notify_error(format!(
+                "Couldn't run {}, searching without it: {}",
+                backend.name(),
+                e
+            ));
+            return false;
+        }
+    };
+
+    let stdout = match child.stdout.take() {
+        Some(stdout) => stdout,
+        None => return false,
+    };
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut batch = Vec::with_capacity(BATCH);
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            batch.push(line);
+            if batch.len() == BATCH && tx.send(std::mem::take(&mut batch)).is_err() {
+                return;
+            }
+        }
+        let _ = tx.send(batch);
+    });
+
+    app.fzf_backend.key = Some(key);
+    app.fzf_backend.child = Some(child);
+    app.fzf_backend.lines = Some(rx);
+    true
+}
+
+// takes what the backend printed since the last call, true when there was
+// anything new
+pub fn drain(app: &mut App) -> bool {
+    let mut got = false;
+    let mut finished = false;
+
+    if let Some(lines) = &app.fzf_backend.lines {
+        loop {
+            match lines.try_recv() {
+                Ok(batch) => {
+                    got |= !batch.is_empty();
+                    app.fzf_backend
+                        .files
+                        .extend(batch.into_iter().map(PathBuf::from));
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    finished = true;
+                    break;
+                }
+            }
+        }
+    }
+
+    if finished {
+        app.fzf_backend.lines = None;
+        if let Some(mut child) = app.fzf_backend.child.take() {
+            let _ = child.wait();
+        }
+    }
+    got
+}
+
+// gets the files under `dir` listed into app.fzf_backend.files, false when
+// the builtin walk should do it. `fresh` starts over, for a popup that was
+// just opened
+pub fn list(app: &mut App, dir: &str, fresh: bool) -> bool {
+    if app.fzf_backend.backend == Backend::Builtin {
+        return false;
+    }
+
+    let key = (dir.to_string(), app.fzf_respect_ignore, app.show_hidden);
+    if fresh || app.fzf_backend.key.as_ref() != Some(&key) {
+        app.fzf_backend.stop();
+        if !start(app, key) {
+            return false;
+        }
+    }
+
+    drain(app);
+    true
+}
+
+// still listing, the popup's title says so
+pub fn streaming(app: &App) -> bool {
+    app.fzf_backend.lines.is_some()
+}
@@ -0,0 +1,439 @@
+use super::jumps;
+use super::run_app::Command;
+use super::stateful_list::StatefulList;
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use crate::ui::display::pane::get_pwd;
+use crate::ui::input::archive::decompressor;
+use ignore::WalkBuilder;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command as Process, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+// a common word in a big tree matches millions of lines, the search stops
+// once the popup has this many
+const MAX_HITS: usize = 5000;
+// hits are handed over in batches
+const BATCH: usize = 256;
+// a line is cut here, minified files are one long line
+const MAX_TEXT: usize = 300;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum GrepBackend {
+    // the ignore crate walking and searching in-process
+    Builtin,
+    Rg,
+}
+
+impl GrepBackend {
+    // `grep_backend=` in the config
+    pub fn from_config(value: &str) -> GrepBackend {
+        match value.to_ascii_lowercase().as_str() {
+            "rg" | "ripgrep" => GrepBackend::Rg,
+            _ => GrepBackend::Builtin,
+        }
+    }
+}
+
+// a line that matched, the path relative to where the search ran
+pub struct Hit {
+    pub path: PathBuf,
+    pub line: usize,
+    pub text: String,
+}
+
+// the content search popup, the search streams its hits in while it runs
+pub struct Grep {
+    pub backend: GrepBackend,
+    // look inside .gz, .xz and .zst files, `grep_compressed=true`
+    pub compressed: bool,
+    // bigger files are skipped, compressed ones are also decompressed no
+    // further than this, `grep_max_size=` in MiB
+    pub max_size: u64,
+    pub hits: StatefulList<Hit>,
+    // what the hits are for and where they were searched
+    pub searched: Option<(String, PathBuf)>,
+    rx: Option<Receiver<Vec<Hit>>>,
+    child: Option<Child>,
+    kill: Arc<AtomicBool>,
+}
+
+impl Default for Grep {
+    fn default() -> Grep {
+        Grep {
+            backend: GrepBackend::Builtin,
+            compressed: false,
+            max_size: 16 * 1024 * 1024,
+            hits: StatefulList::with_items(vec![]),
+            searched: None,
+            rx: None,
+            child: None,
+            kill: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+impl Grep {
+    // still searching, the popup's title says so
+    pub fn searching(&self) -> bool {
+        self.rx.is_some()
+    }
+
+    fn stop(&mut self) {
+        self.kill.store(true, Ordering::Relaxed);
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        self.rx = None;
+    }
+}
+
+pub fn handle_grep(app: &mut App, input: &mut String, input_active: &mut bool) {
+    if block_binds(app) {
+        return;
+    }
+
+    app.show_grep = true;
+    app.last_command = Some(Command::Grep);
+
+    // the last search comes back with its hits, to pick another one
+    *input = app
+        .grep
+        .searched
+        .as_ref()
+        .map(|(query, _)| query.clone())
+        .unwrap_or_default();
+    *input_active = true;
+}
+
+pub fn close(app: &mut App) {
+    app.grep.stop();
+    app.show_grep = false;
+}
+
+// Enter searches for what's typed, or goes to the highlighted hit when
+// that's what the hits are for
+pub fn handle_submit(app: &mut App, input: &mut String, input_active: &mut bool) {
+    if input.is_empty() {
+        return;
+    }
+
+    let cwd = match std::env::current_dir() {
+        Ok(cwd) => cwd,
+        Err(e) => return app.show_error("Couldn't read the current directory", e),
+    };
+
+    let searched = app.grep.searched.as_ref() == Some(&(input.clone(), cwd.clone()));
+    if searched && app.grep.hits.state.selected().is_some() {
+        return open_hit(app, input, input_active);
+    }
+
+    start(app, input.clone(), cwd);
+}
+
+fn start(app: &mut App, query: String, dir: PathBuf) {
+    app.grep.stop();
+    app.grep.hits = StatefulList::with_items(vec![]);
+    app.grep.kill = Arc::new(AtomicBool::new(false));
+    app.grep.searched = Some((query.clone(), dir.clone()));
+
+    let (tx, rx) = mpsc::channel();
+    app.grep.rx = Some(rx);
+
+    if app.grep.backend == GrepBackend::Rg {
+        match spawn_rg(app, &query, &dir, tx.clone()) {
+            Ok(child) => {
+                app.grep.child = Some(child);
+                return;
+            }
+            // not installed: the builtin search takes over for the session
+            Err(e) => {
+                app.grep.backend = GrepBackend::Builtin;
+                app.notify_error(format!("Couldn't run rg, searching without it: {}", e));
+            }
+        }
+    }
+
+    let (kill, show_hidden) = (app.grep.kill.clone(), app.show_hidden);
+    let (compressed, max_size) = (app.grep.compressed, app.grep.max_size);
+    thread::spawn(move || search(&query, &dir, show_hidden, compressed, max_size, &kill, &tx));
+}
+
+// `rg -z` decompresses on its own, the size limit applies to the file on
+// disk
+fn spawn_rg(app: &App, query: &str, dir: &Path, tx: Sender<Vec<Hit>>) -> io::Result<Child> {
+    let mut command = Process::new("rg");
+    command
+        .args([
+            "--line-number",
+            "--no-heading",
+            "--null",
+            "--color",
+            "never",
+        ])
+        .args(["--fixed-strings", "--smart-case", "--glob", "!.git"])
+        .arg("--max-filesize")
+        .arg(app.grep.max_size.to_string());
+    if app.grep.compressed {
+        command.arg("--search-zip");
+    }
+    if app.show_hidden {
+        command.arg("--hidden");
+    }
+    command
+        .arg("--regexp")
+        .arg(query)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+
+    let mut child = command.spawn()?;
+    let stdout = match child.stdout.take() {
+        Some(stdout) => stdout,
+        None => return Err(io::Error::other("no output")),
+    };
+
+    // path\0line:text, the path can hold anything but the NUL
+    thread::spawn(move || {
+        let mut batch = Vec::with_capacity(BATCH);
+        for line in BufReader::new(stdout).split(b'\n').map_while(Result::ok) {
+            let (path, rest) = match line.iter().position(|byte| *byte == 0) {
+                Some(at) => (&line[..at], &line[at + 1..]),
+                None => continue,
+            };
+            let rest = String::from_utf8_lossy(rest);
+            let (number, text) = match rest.split_once(':') {
+                Some(pair) => pair,
+                None => continue,
+            };
+
+            batch.push(Hit {
+                path: path_from_bytes(path),
+                line: number.parse().unwrap_or(0),
+                text: clip(text),
+            });
+            if batch.len() == BATCH && tx.send(std::mem::take(&mut batch)).is_err() {
+                return;
+            }
+        }
+        let _ = tx.send(batch);
+    });
+
+    Ok(child)
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+
+    PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).to_string())
+}
+
+fn clip(text: &str) -> String {
+    let text = text.trim_end_matches(['\n', '\r']).replace('\t', "    ");
+    match text.char_indices().nth(MAX_TEXT) {
+        Some((at, _)) => text[..at].to_string(),
+        None => text,
+    }
+}
+
+// a fixed string, ignoring case unless it has capitals, like rg's
+// --smart-case
+fn search(
+    query: &str,
+    dir: &Path,
+    show_hidden: bool,
+    compressed: bool,
+    max_size: u64,
+    kill: &AtomicBool,
+    tx: &Sender<Vec<Hit>>,
+) {
+    let ignore_case = !query.chars().any(char::is_uppercase);
+    let needle = if ignore_case {
+        query.to_lowercase()
+    } else {
+        query.to_string()
+    };
+
+    let walker = WalkBuilder::new(dir)
+        .hidden(!show_hidden)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+    let mut batch = vec![];
+
+    for entry in walker.filter_map(|entry| entry.ok()) {
+        if kill.load(Ordering::Relaxed) {
+            return;
+        }
+        if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+            continue;
+        }
+        if entry
+            .metadata()
+            .map_or(true, |metadata| metadata.len() > max_size)
+        {
+            continue;
+        }
+
+        let reader: Box<dyn Read> = match decompressor(entry.path()) {
+            Some(_) if !compressed => continue,
+            Some((_, reader)) => reader,
+            None => match File::open(entry.path()) {
+                Ok(file) => Box::new(file),
+                Err(_) => continue,
+            },
+        };
+        let path = entry
+            .path()
+            .strip_prefix(dir)
+            .unwrap_or(entry.path())
+            .to_path_buf();
+
+        search_file(
+            reader.take(max_size),
+            &path,
+            &needle,
+            ignore_case,
+            &mut batch,
+        );
+
+        if batch.len() >= BATCH && tx.send(std::mem::take(&mut batch)).is_err() {
+            return;
+        }
+    }
+
+    let _ = tx.send(batch);
+}
+
+// binary files are skipped, at the first NUL
+fn search_file(
+    reader: impl Read,
+    path: &Path,
+    needle: &str,
+    ignore_case: bool,
+    hits: &mut Vec<Hit>,
+) {
+    let mut reader = BufReader::new(reader);
+    let mut buffer = vec![];
+    let mut found = vec![];
+    let mut number = 0;
+
+    loop {
+        buffer.clear();
+        match reader.read_until(b'\n', &mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => number += 1,
+        }
+
+        if buffer.contains(&0) {
+            return;
+        }
+
+        let line = String::from_utf8_lossy(&buffer);
+        let matched = if ignore_case {
+            line.to_lowercase().contains(needle)
+        } else {
+            line.contains(needle)
+        };
+
+        if matched {
+            found.push(Hit {
+                path: path.to_path_buf(),
+                line: number,
+                text: clip(&line),
+            });
+            if found.len() >= MAX_HITS {
+                break;
+            }
+        }
+    }
+
+    hits.append(&mut found);
+}
+
+// every tick: the hits found since are added, up to MAX_HITS
+pub fn poll_grep(app: &mut App) {
+    let rx = match &app.grep.rx {
+        Some(rx) => rx,
+        None => return,
+    };
+
+    let mut got = vec![];
+    let mut finished = false;
+    loop {
+        match rx.try_recv() {
+            Ok(mut batch) => got.append(&mut batch),
+            Err(TryRecvError::Empty) => break,
+            Err(TryRecvError::Disconnected) => {
+                finished = true;
+                break;
+            }
+        }
+    }
+
+    let hits = &mut app.grep.hits;
+    let room = MAX_HITS.saturating_sub(hits.items.len());
+    got.truncate(room);
+    hits.items.append(&mut got);
+    if hits.state.selected().is_none() && !hits.items.is_empty() {
+        hits.state.select(Some(0));
+    }
+
+    if finished || hits.items.len() >= MAX_HITS {
+        app.grep.stop();
+    }
+}
+
+// the hit's directory, with its file highlighted
+fn open_hit(app: &mut App, input: &mut String, input_active: &mut bool) {
+    let (_, dir) = match &app.grep.searched {
+        Some(searched) => searched.clone(),
+        None => return,
+    };
+    let path = match app
+        .grep
+        .hits
+        .state
+        .selected()
+        .and_then(|idx| app.grep.hits.items.get(idx))
+    {
+        Some(hit) => dir.join(&hit.path),
+        None => return,
+    };
+
+    close(app);
+    app.last_command = None;
+    input.clear();
+    *input_active = false;
+
+    let (parent, name) = match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => (parent.to_path_buf(), name.to_os_string()),
+        _ => return,
+    };
+
+    if !jumps::change_dir(app, &parent) {
+        return;
+    }
+
+    app.update_files();
+    app.update_dirs();
+
+    if let Some(idx) = app.files.items.iter().position(|item| item.0 == name) {
+        app.files.state.select(Some(idx));
+        app.dirs.state.select(None);
+    }
+
+    app.cur_dir = get_pwd();
+}
@@ -0,0 +1,164 @@
+use super::shell::shell_command;
+use crate::app::app::App;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Stdio};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Event {
+    EnterDir,
+    Open,
+    Quit,
+}
+
+impl Event {
+    fn parse(name: &str) -> Option<Event> {
+        match name.trim().trim_start_matches("on_") {
+            "enter_dir" | "cd" => Some(Event::EnterDir),
+            "open" => Some(Event::Open),
+            "quit" => Some(Event::Quit),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Event::EnterDir => "enter_dir",
+            Event::Open => "open",
+            Event::Quit => "quit",
+        }
+    }
+}
+
+// a hook running in the background, its errors are read once it's done
+struct Running {
+    command: String,
+    child: Child,
+}
+
+// the hook= lines from the config and what they left running
+#[derive(Default)]
+pub struct Hooks {
+    hooks: Vec<(Event, String)>,
+    // the directory enter_dir last fired for
+    last_dir: Option<PathBuf>,
+    running: Vec<Running>,
+}
+
+// hook=enter_dir: tmux rename-window "$(basename "$TRAVERSE_DIR")"
+fn parse(spec: &str) -> Option<(Event, String)> {
+    let (event, command) = spec.split_once(':')?;
+    let command = command.trim();
+    if command.is_empty() {
+        return None;
+    }
+
+    Some((Event::parse(event)?, command.to_string()))
+}
+
+// called by read_config with every hook= line
+pub fn configure(app: &mut App, specs: Vec<String>) {
+    app.hooks.hooks = specs.iter().filter_map(|spec| parse(spec)).collect();
+}
+
+// the hooks see what happened through TRAVERSE_* variables, run in the
+// current directory. Their output goes to the terminal, for escape
+// sequences like OSC 7, their errors come back here
+fn start(app: &mut App, event: Event, file: Option<&Path>, previous: Option<&Path>, wait: bool) {
+    let commands = app
+        .hooks
+        .hooks
+        .iter()
+        .filter(|(hooked, _)| *hooked == event)
+        .map(|(_, command)| command.clone())
+        .collect::<Vec<String>>();
+    if commands.is_empty() {
+        return;
+    }
+
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let selected = app
+        .selected_entry()
+        .filter(|name| name != "..")
+        .map(|name| cwd.join(name));
+
+    for command in commands {
+        let mut shell = shell_command(&command);
+        shell
+            .env("TRAVERSE_EVENT", event.name())
+            .env("TRAVERSE_DIR", &cwd)
+            .env("TRAVERSE_PID", std::process::id().to_string())
+            .stdin(Stdio::null())
+            .stderr(Stdio::piped());
+        if let Some(selected) = &selected {
+            shell.env("TRAVERSE_SELECTED", selected);
+        }
+        if let Some(file) = file {
+            shell.env("TRAVERSE_FILE", file);
+        }
+        if let Some(previous) = previous {
+            shell.env("TRAVERSE_PREVIOUS_DIR", previous);
+        }
+
+        match shell.spawn() {
+            // on quit there's no later tick to hear back on
+            Ok(mut child) if wait => {
+                let _ = child.wait();
+            }
+            Ok(child) => app.hooks.running.push(Running { command, child }),
+            Err(e) => app.notify_error(format!("Couldn't run the {} hook: {}", event.name(), e)),
+        }
+    }
+}
+
+// every tick: enter_dir when the directory changed, and the hooks that are
+// done reaped, the failed ones said
+pub fn track(app: &mut App) {
+    if !app.hooks.running.is_empty() {
+        let mut failed = vec![];
+        app.hooks
+            .running
+            .retain_mut(|running| match running.child.try_wait() {
+                Ok(Some(status)) if !status.success() => {
+                    let mut errors = String::new();
+                    if let Some(stderr) = running.child.stderr.as_mut() {
+                        let _ = stderr.read_to_string(&mut errors);
+                    }
+                    let errors = errors.lines().last().unwrap_or_default().to_string();
+                    failed.push(format!("Hook `{}` failed: {}", running.command, errors));
+                    false
+                }
+                Ok(Some(_)) | Err(_) => false,
+                Ok(None) => true,
+            });
+
+        for failed in failed {
+            app.notify_error(failed);
+        }
+    }
+
+    if app.hooks.hooks.is_empty() || app.remote.is_some() {
+        return;
+    }
+
+    let cwd = match std::env::current_dir() {
+        Ok(cwd) => cwd,
+        Err(_) => return,
+    };
+    if app.hooks.last_dir.as_ref() == Some(&cwd) {
+        return;
+    }
+
+    let previous = app.hooks.last_dir.replace(cwd);
+    start(app, Event::EnterDir, None, previous.as_deref(), false);
+}
+
+// before O runs a program on `file`
+pub fn on_open(app: &mut App, file: &Path) {
+    start(app, Event::Open, Some(file), None, false);
+}
+
+// on the way out, waited for so it's done before the shell is back
+pub fn on_quit(app: &mut App) {
+    start(app, Event::Quit, None, None, true);
+}
@@ -0,0 +1,528 @@
+use super::schedule;
+use super::shell::shell_command;
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use crate::ui::display::pane::convert_bytes;
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+use std::process::{Child, Command as SysCommand, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use walkdir::WalkDir;
+
+// how many finished jobs the history keeps
+const HISTORY: usize = 20;
+// lines of output kept for each background job
+const TAIL: usize = 200;
+// characters of a job's name shown in the list
+const NAME: usize = 32;
+// how often a running command is checked on
+const POLL: Duration = Duration::from_millis(50);
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+// what's left to do on the app once a background job is done
+pub type AfterJob = Box<dyn FnOnce(&mut App) + Send>;
+
+// what the thread behind a background job sends back
+pub enum Update {
+    Line(String),
+    // None when it went fine, otherwise what went wrong
+    Finished(Option<String>, Option<AfterJob>),
+}
+
+// a command or a copy/move running off the UI thread, listed under J
+pub struct BackgroundJob {
+    pub id: usize,
+    pub name: String,
+    started: Instant,
+    ended: Option<Instant>,
+    failed: Option<String>,
+    rx: Receiver<Update>,
+    pub tail: VecDeque<String>,
+    kill: Arc<AtomicBool>,
+}
+
+// what a finished copy, move or delete did
+pub struct JobSummary {
+    pub kind: &'static str,
+    pub files: usize,
+    pub bytes: u64,
+    pub elapsed: Duration,
+    pub failures: Vec<String>,
+    pub finished: Instant,
+}
+
+pub struct Job {
+    pub kind: &'static str,
+    started: Instant,
+    files: usize,
+    bytes: u64,
+    pub failures: Vec<String>,
+}
+
+// files and bytes under path, taken before the operation since a move or a
+// delete leaves nothing behind to measure
+pub fn measure(path: &Path) -> (usize, u64) {
+    measure_until(path, &AtomicBool::new(false)).unwrap_or_default()
+}
+
+// the same, giving up with None once the job is killed
+pub fn measure_until(path: &Path, kill: &AtomicBool) -> Option<(usize, u64)> {
+    let mut counted = (0, 0);
+
+    for entry in WalkDir::new(path).into_iter().flatten() {
+        if kill.load(Ordering::Relaxed) {
+            return None;
+        }
+        if let Ok(metadata) = entry.metadata() {
+            if metadata.is_file() {
+                counted = (counted.0 + 1, counted.1 + metadata.len());
+            }
+        }
+    }
+
+    Some(counted)
+}
+
+pub fn format_elapsed(elapsed: Duration) -> String {
+    if elapsed < Duration::from_secs(1) {
+        format!("{}ms", elapsed.as_millis())
+    } else if elapsed < Duration::from_secs(60) {
+        format!("{:.1}s", elapsed.as_secs_f64())
+    } else {
+        format!("{}m {}s", elapsed.as_secs() / 60, elapsed.as_secs() % 60)
+    }
+}
+
+fn ago(elapsed: Duration) -> String {
+    match elapsed.as_secs() {
+        secs if secs < 60 => format!("{}s ago", secs),
+        secs if secs < 3600 => format!("{}m ago", secs / 60),
+        secs => format!("{}h ago", secs / 3600),
+    }
+}
+
+impl Job {
+    pub fn start(kind: &'static str) -> Job {
+        Job {
+            kind,
+            started: Instant::now(),
+            files: 0,
+            bytes: 0,
+            failures: vec![],
+        }
+    }
+
+    pub fn done(&mut self, (files, bytes): (usize, u64)) {
+        self.files += files;
+        self.bytes += bytes;
+    }
+
+    pub fn failed(&mut self, path: &str, err: &str) {
+        self.failures.push(format!("{}: {}", path, err));
+    }
+
+    // into the history, and on screen when `show` is set
+    pub fn finish(self, app: &mut App, show: bool) {
+        let summary = JobSummary {
+            kind: self.kind,
+            files: self.files,
+            bytes: self.bytes,
+            elapsed: self.started.elapsed(),
+            failures: self.failures,
+            finished: Instant::now(),
+        };
+
+        if show {
+            app.notify(format!(
+                "{} finished: {} files, {}, {} failed",
+                summary.kind,
+                summary.files,
+                convert_bytes(summary.bytes, app.si_units),
+                summary.failures.len()
+            ));
+            let lines = summary.lines(app.si_units);
+            app.open_report(&format!("{} finished", summary.kind), lines);
+        }
+
+        app.job_history.push(summary);
+        if app.job_history.len() > HISTORY {
+            app.job_history.remove(0);
+        }
+    }
+}
+
+impl JobSummary {
+    fn throughput(&self, si: bool) -> String {
+        let secs = self.elapsed.as_secs_f64();
+
+        if secs > 0.0 {
+            format!("{}/s", convert_bytes((self.bytes as f64 / secs) as u64, si))
+        } else {
+            "-".to_string()
+        }
+    }
+
+    pub fn lines(&self, si: bool) -> Vec<String> {
+        let mut lines = vec![
+            format!("Files:      {}", self.files),
+            format!("Bytes:      {}", convert_bytes(self.bytes, si)),
+            format!("Elapsed:    {}", format_elapsed(self.elapsed)),
+            format!("Throughput: {}", self.throughput(si)),
+            format!("Failures:   {}", self.failures.len()),
+        ];
+
+        lines.extend(self.failures.iter().map(|failure| format!("  {}", failure)));
+        lines
+    }
+
+    // one line for the history, newest first
+    fn line(&self, si: bool) -> String {
+        format!(
+            "{}  {}  {} files  {}  {}  {}  {} failed",
+            ago(self.finished.elapsed()),
+            self.kind,
+            self.files,
+            convert_bytes(self.bytes, si),
+            format_elapsed(self.elapsed),
+            self.throughput(si),
+            self.failures.len()
+        )
+    }
+}
+
+// the schedule, the background jobs and the copies, moves and deletes
+fn history_lines(app: &App) -> Vec<String> {
+    let mut lines = schedule::lines(app);
+
+    if !app.background_jobs.is_empty() {
+        lines.push("Background jobs (Enter shows the output, CTRL + d kills):".to_string());
+        lines.extend(
+            app.background_jobs
+                .iter()
+                .rev()
+                .map(|job| format!("  {}", job.line())),
+        );
+        lines.push(String::new());
+    }
+
+    let history = app
+        .job_history
+        .iter()
+        .rev()
+        .map(|summary| summary.line(app.si_units))
+        .collect::<Vec<String>>();
+
+    if history.is_empty() {
+        lines.push("No copies, moves or deletes yet.".to_string());
+    }
+    lines.extend(history);
+
+    lines
+}
+
+pub fn show_history(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    let lines = history_lines(app);
+    app.open_report("Job history", lines);
+    app.report_jobs = true;
+}
+
+// starts `work` on its own thread as a background job, it sends its output
+// and how it ended through the sender, and checks the flag to stop early
+pub fn spawn(
+    app: &mut App,
+    name: String,
+    work: impl FnOnce(Sender<Update>, Arc<AtomicBool>) + Send + 'static,
+) {
+    let (tx, rx) = mpsc::channel();
+    let kill = Arc::new(AtomicBool::new(false));
+
+    let flag = kill.clone();
+    thread::spawn(move || work(tx, flag));
+
+    app.background_jobs.push(BackgroundJob {
+        id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        name,
+        started: Instant::now(),
+        ended: None,
+        failed: None,
+        rx,
+        tail: VecDeque::new(),
+        kill,
+    });
+}
+
+fn forward(
+    stream: impl Read + Send + 'static,
+    tx: Sender<Update>,
+    keep: bool,
+) -> JoinHandle<String> {
+    thread::spawn(move || {
+        let mut kept = String::new();
+
+        for line in BufReader::new(stream).lines().map_while(Result::ok) {
+            if keep {
+                kept.push_str(&line);
+                kept.push('\n');
+            }
+            let _ = tx.send(Update::Line(line));
+        }
+
+        kept
+    })
+}
+
+// the job's own process group, so whatever its shell started goes too
+#[cfg(unix)]
+fn kill_child(child: &mut Child) {
+    let _ = SysCommand::new("kill")
+        .arg("--")
+        .arg(format!("-{}", child.id()))
+        .status();
+    let _ = child.kill();
+}
+
+#[cfg(windows)]
+fn kill_child(child: &mut Child) {
+    let _ = child.kill();
+}
+
+// runs `command` to its end, or until the job is killed, sending what it
+// prints along, the error output comes back too for summaries
+pub fn run_child(
+    command: &mut SysCommand,
+    tx: &Sender<Update>,
+    kill: &AtomicBool,
+) -> io::Result<(ExitStatus, String)> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(command, 0);
+
+    let mut child = command.spawn()?;
+    if let Some(stdout) = child.stdout.take() {
+        forward(stdout, tx.clone(), false);
+    }
+    let stderr = child
+        .stderr
+        .take()
+        .map(|stderr| forward(stderr, tx.clone(), true));
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if kill.load(Ordering::Relaxed) {
+            kill_child(&mut child);
+            break child.wait()?;
+        }
+        thread::sleep(POLL);
+    };
+
+    let errors = stderr
+        .and_then(|reader| reader.join().ok())
+        .unwrap_or_default();
+    Ok((status, errors))
+}
+
+// None when it went fine, otherwise how it ended
+pub fn describe(status: &ExitStatus, kill: &AtomicBool) -> Option<String> {
+    if status.success() {
+        None
+    } else if kill.load(Ordering::Relaxed) {
+        Some("killed".to_string())
+    } else {
+        Some(match status.code() {
+            Some(code) => format!("exited with {}", code),
+            None => "was killed".to_string(),
+        })
+    }
+}
+
+// a shell command in the background, `!` with a trailing & and programs
+// opened in the background
+pub fn spawn_command(app: &mut App, command: &str) {
+    let mut shell = shell_command(command);
+
+    spawn(app, command.to_string(), move |tx, kill| {
+        let failed = match run_child(&mut shell, &tx, &kill) {
+            Ok((status, _)) => describe(&status, &kill),
+            Err(e) => Some(format!("couldn't start: {}", e)),
+        };
+        let _ = tx.send(Update::Finished(failed, None));
+    });
+
+    app.notify(format!("Started {} in the background, J lists it", command));
+}
+
+impl BackgroundJob {
+    // one line for the job history
+    fn line(&self) -> String {
+        let state = match (self.ended, &self.failed) {
+            (None, _) => format!("running {}", format_elapsed(self.started.elapsed())),
+            (Some(ended), None) => format!("done {}", ago(ended.elapsed())),
+            (Some(ended), Some(failed)) => format!("{} {}", failed, ago(ended.elapsed())),
+        };
+
+        // long commands would hide the output
+        let name = match self.name.char_indices().nth(NAME) {
+            Some((idx, _)) => format!("{}...", &self.name[..idx]),
+            None => self.name.clone(),
+        };
+
+        match self.tail.back() {
+            Some(last) => format!("{}  {}  | {}", state, name, last),
+            None => format!("{}  {}", state, name),
+        }
+    }
+}
+
+pub fn running(app: &App) -> usize {
+    app.background_jobs
+        .iter()
+        .filter(|job| job.ended.is_none())
+        .count()
+}
+
+// collects output and finished jobs every tick
+pub fn poll_background_jobs(app: &mut App) {
+    let mut finished = vec![];
+
+    for job in &mut app.background_jobs {
+        loop {
+            match job.rx.try_recv() {
+                Ok(Update::Line(line)) => {
+                    job.tail.push_back(line);
+                    if job.tail.len() > TAIL {
+                        job.tail.pop_front();
+                    }
+                }
+                Ok(Update::Finished(failed, after)) => {
+                    job.ended = Some(Instant::now());
+                    job.failed = failed.clone();
+                    finished.push((job.name.clone(), failed, after));
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    // the thread went away without saying how it ended
+                    if job.ended.is_none() {
+                        job.ended = Some(Instant::now());
+                        job.failed = Some("stopped".to_string());
+                    }
+                    break;
+                }
+            }
+        }
+    }
+
+    // what's done afterwards says how it went itself
+    for (name, failed, after) in finished {
+        match (failed, after) {
+            (_, Some(after)) => after(app),
+            (None, None) => app.notify(format!("Finished {}", name)),
+            (Some(failed), None) => app.notify_error(format!("{} {}", name, failed)),
+        }
+    }
+
+    // the oldest finished ones make room
+    while app.background_jobs.len() > HISTORY {
+        match app
+            .background_jobs
+            .iter()
+            .position(|job| job.ended.is_some())
+        {
+            Some(idx) => app.background_jobs.remove(idx),
+            None => break,
+        };
+    }
+
+    // the open history, with the schedule counting down, and the output keep up
+    if app.show_report && app.report_jobs {
+        app.report.items = history_lines(app);
+        if let Some(idx) = app.report.state.selected() {
+            let last = app.report.items.len().saturating_sub(1);
+            app.report.state.select(Some(idx.min(last)));
+        }
+    } else if let (true, Some(id)) = (app.show_report, app.report_output) {
+        let tail = match app.background_jobs.iter().find(|job| job.id == id) {
+            Some(job) => job.tail.iter().cloned().collect::<Vec<String>>(),
+            None => return,
+        };
+
+        // following along unless scrolled up
+        let at_end = app
+            .report
+            .state
+            .selected()
+            .is_none_or(|idx| idx + 1 >= app.report.items.len());
+        app.report.items = tail;
+        if at_end && !app.report.items.is_empty() {
+            app.report.state.select(Some(app.report.items.len() - 1));
+        }
+    }
+}
+
+// the background job on the selected line of the job history, they come
+// right after the schedule, newest first
+fn selected(app: &App) -> Option<usize> {
+    let first = schedule::lines(app).len() + 1;
+    let idx = app.report.state.selected()?.checked_sub(first)?;
+
+    app.background_jobs.len().checked_sub(idx + 1)
+}
+
+// CTRL + d in the job history: kills the selected job, or cancels the
+// selected scheduled one
+pub fn kill_selected(app: &mut App) {
+    let schedule = schedule::lines(app).len();
+    if app
+        .report
+        .state
+        .selected()
+        .is_some_and(|idx| idx < schedule)
+    {
+        return schedule::cancel_selected(app);
+    }
+
+    let job = match selected(app).map(|idx| &app.background_jobs[idx]) {
+        Some(job) if job.ended.is_none() => job,
+        Some(_) => return app.notify_error("That job is already done"),
+        None => return app.notify_error("Select a running job to kill it"),
+    };
+
+    job.kill.store(true, Ordering::Relaxed);
+    let name = job.name.clone();
+    app.notify(format!("Killing {}", name));
+}
+
+// Enter in the job history: the output of the selected job
+pub fn show_output(app: &mut App) {
+    let job = match selected(app).map(|idx| &app.background_jobs[idx]) {
+        Some(job) => job,
+        None => {
+            app.show_report = false;
+            return;
+        }
+    };
+
+    let (id, title) = (job.id, format!("Output of {}", job.name));
+    let mut lines = job.tail.iter().cloned().collect::<Vec<String>>();
+    if lines.is_empty() {
+        lines.push("No output yet".to_string());
+    }
+
+    app.open_report(&title, lines);
+    app.report.state.select(Some(app.report.items.len() - 1));
+    app.report_output = Some(id);
+}
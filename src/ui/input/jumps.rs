@@ -0,0 +1,106 @@
+use super::run_app::Command;
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use crate::ui::display::pane::get_pwd;
+use std::path::{Path, PathBuf};
+
+// go to `path`, remembering where we were once we got there, a jump that
+// failed isn't one
+pub fn change_dir(app: &mut App, path: &Path) -> bool {
+    let cur = get_pwd().trim_end_matches('\n').to_string();
+
+    if !app.change_dir(path) {
+        return false;
+    }
+
+    record_jump(app, cur);
+    true
+}
+
+// remember where we were before a jump, dropping anything "ahead" of us
+fn record_jump(app: &mut App, cur: String) {
+    // nowhere to come back to
+    if cur.is_empty() {
+        return;
+    }
+
+    app.jump_list.items.truncate(app.jump_index);
+
+    if app.jump_list.items.last() != Some(&cur) {
+        app.jump_list.items.push(cur);
+    }
+
+    app.jump_index = app.jump_list.items.len();
+}
+
+pub fn jump_back(app: &mut App) {
+    if block_binds(app) || app.jump_index == 0 {
+        return;
+    }
+
+    // like vim, store the current location so ctrl+i can come back to it
+    if app.jump_index == app.jump_list.items.len() {
+        let cur = get_pwd().trim_end_matches('\n').to_string();
+
+        if !cur.is_empty() && app.jump_list.items.last() != Some(&cur) {
+            app.jump_list.items.push(cur);
+        }
+    }
+
+    app.jump_index -= 1;
+    go_to_jump(app);
+}
+
+pub fn jump_forward(app: &mut App) {
+    if block_binds(app) || app.jump_index + 1 >= app.jump_list.items.len() {
+        return;
+    }
+
+    app.jump_index += 1;
+    go_to_jump(app);
+}
+
+pub fn handle_jumps(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    if app.last_command != Some(Command::ShowJumps) {
+        if !app.jump_list.items.is_empty() {
+            let selected = app.jump_index.min(app.jump_list.items.len() - 1);
+            app.jump_list.state.select(Some(selected));
+        }
+
+        app.show_jumps = true;
+        app.last_command = Some(Command::ShowJumps);
+    }
+}
+
+pub fn handle_open_jump(app: &mut App) {
+    if let Some(selected) = app.jump_list.state.selected() {
+        app.jump_index = selected;
+        go_to_jump(app);
+    }
+
+    app.show_jumps = false;
+    app.last_command = None;
+}
+
+fn go_to_jump(app: &mut App) {
+    let path = match app.jump_list.items.get(app.jump_index) {
+        Some(path) => PathBuf::from(path),
+        None => return,
+    };
+
+    if !app.change_dir(&path) {
+        return;
+    }
+
+    app.update_files();
+    app.update_dirs();
+
+    app.files.state.select(Some(0));
+    app.dirs.state.select(None);
+
+    app.cur_dir = get_pwd();
+}
@@ -0,0 +1,142 @@
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use crate::ui::display::file_colors::{ARCHIVES, IMAGES};
+use crate::ui::display::icons::{AUDIO, VIDEO};
+use crate::ui::display::pane::convert_bytes;
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use walkdir::WalkDir;
+
+const CODE: [&str; 30] = [
+    "rs", "py", "js", "mjs", "cjs", "ts", "tsx", "jsx", "go", "java", "kt", "c", "h", "cpp", "cc",
+    "hpp", "cs", "rb", "php", "swift", "lua", "sh", "bash", "zsh", "fish", "html", "css", "scss",
+    "sql", "ipynb",
+];
+const DOCUMENTS: [&str; 14] = [
+    "md", "txt", "pdf", "doc", "docx", "odt", "rtf", "xls", "xlsx", "ods", "csv", "ppt", "pptx",
+    "epub",
+];
+const CONFIG: [&str; 8] = ["toml", "yaml", "yml", "json", "ini", "conf", "xml", "lock"];
+
+fn kind(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let extension = extension.as_str();
+
+    if IMAGES.contains(&extension) {
+        "images"
+    } else if VIDEO.contains(&extension) {
+        "videos"
+    } else if AUDIO.contains(&extension) {
+        "audio files"
+    } else if ARCHIVES.contains(&extension) {
+        "archives"
+    } else if CODE.contains(&extension) {
+        "code files"
+    } else if DOCUMENTS.contains(&extension) {
+        "documents"
+    } else if CONFIG.contains(&extension) {
+        "config files"
+    } else {
+        "other files"
+    }
+}
+
+// "N kind totaling X" for everything below `dir`, biggest first
+fn summarize(dir: &Path, si: bool) -> Vec<String> {
+    let mut kinds: Vec<(&str, usize, u64)> = vec![];
+
+    for entry in WalkDir::new(dir).into_iter().filter_map(|entry| entry.ok()) {
+        let size = match entry.metadata() {
+            Ok(metadata) if metadata.is_file() => metadata.len(),
+            _ => continue,
+        };
+
+        let kind = kind(entry.path());
+        match kinds.iter_mut().find(|(name, ..)| *name == kind) {
+            Some((_, count, bytes)) => {
+                *count += 1;
+                *bytes += size;
+            }
+            None => kinds.push((kind, 1, size)),
+        }
+    }
+
+    if kinds.is_empty() {
+        return vec!["No files below here".to_string()];
+    }
+
+    kinds.sort_by_key(|kind| std::cmp::Reverse(kind.2));
+
+    let (files, bytes) = kinds.iter().fold((0, 0), |(files, bytes), kind| {
+        (files + kind.1, bytes + kind.2)
+    });
+
+    let mut lines = kinds
+        .iter()
+        .map(|(kind, count, bytes)| {
+            format!(
+                "{:>7} {:<14} {:>10}",
+                count,
+                kind,
+                convert_bytes(*bytes, si)
+            )
+        })
+        .collect::<Vec<String>>();
+
+    lines.push(String::new());
+    lines.push(format!(
+        "{:>7} {:<14} {:>10}",
+        files,
+        "files in all",
+        convert_bytes(bytes, si)
+    ));
+
+    lines
+}
+
+// walking a big tree takes a while, so it runs in the background and the
+// panel says so until it's done
+pub fn handle_kinds(app: &mut App) {
+    if app.show_kinds {
+        app.show_kinds = false;
+        return;
+    }
+
+    if block_binds(app) {
+        return;
+    }
+
+    let cur_dir = match std::env::current_dir() {
+        Ok(dir) => dir,
+        Err(_) => return,
+    };
+
+    let (tx, rx) = mpsc::channel();
+    app.kinds_job = Some(rx);
+    app.kinds.items = vec!["Counting...".to_string()];
+    app.kinds.state.select(None);
+    app.show_kinds = true;
+
+    let si = app.si_units;
+    thread::spawn(move || {
+        let _ = tx.send(summarize(&cur_dir, si));
+    });
+}
+
+pub fn poll_kinds_job(app: &mut App) {
+    let lines = match &app.kinds_job {
+        Some(rx) => match rx.try_recv() {
+            Ok(lines) => lines,
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => vec!["Counting stopped".to_string()],
+        },
+        None => return,
+    };
+
+    app.kinds_job = None;
+    app.kinds.items = lines;
+}
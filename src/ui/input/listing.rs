@@ -0,0 +1,107 @@
+use super::stateful_list::stepped;
+use super::submit;
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use std::path::Path;
+
+// with `unified_listing` the directories, ../ first, and then the files are
+// one list, a row in it is the selection in dirs or in files so everything
+// that reads those keeps working
+
+pub fn len(app: &App) -> usize {
+    app.dirs.items.len() + app.files.items.len()
+}
+
+pub fn selected(app: &App) -> Option<usize> {
+    match (app.dirs.state.selected(), app.files.state.selected()) {
+        (_, Some(idx)) => Some(app.dirs.items.len() + idx),
+        (Some(idx), None) => Some(idx),
+        (None, None) => None,
+    }
+}
+
+pub fn select(app: &mut App, idx: usize) {
+    let dirs = app.dirs.items.len();
+
+    if idx < dirs {
+        app.dirs.state.select(Some(idx));
+        app.files.state.select(None);
+    } else if idx - dirs < app.files.items.len() {
+        app.files.state.select(Some(idx - dirs));
+        app.dirs.state.select(None);
+    }
+}
+
+// j and k
+pub fn step(app: &mut App, by: isize) {
+    let len = len(app);
+    if len == 0 {
+        return;
+    }
+
+    let idx = match selected(app) {
+        Some(idx) => stepped(idx, by, len, app.wrap_lists),
+        None => 0,
+    };
+    select(app, idx);
+}
+
+// the wheel and counted motions stop at either end
+pub fn move_by(app: &mut App, by: isize) {
+    let len = len(app);
+    if len == 0 {
+        return;
+    }
+
+    let idx = selected(app).unwrap_or(0) as isize + by;
+    select(app, idx.clamp(0, len as isize - 1) as usize);
+}
+
+// counted from 1, past the end is the last one
+pub fn select_line(app: &mut App, line: usize) {
+    let len = len(app);
+    if len > 0 {
+        select(app, line.saturating_sub(1).min(len - 1));
+    }
+}
+
+// keeps the rows in step with the entries before drawing or matching a click
+pub fn sync(app: &mut App, height: usize) {
+    let len = len(app);
+    app.listing.items.resize(len, ());
+    app.listing.state.select(selected(app));
+    app.listing.update_offset(height);
+}
+
+// h: up to the parent, with the directory just left selected
+pub fn handle_up(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    let cur_dir = std::env::current_dir().ok();
+    if !app.change_dir(Path::new("..")) {
+        return;
+    }
+    app.update_files();
+    app.update_dirs();
+
+    let left = cur_dir.as_deref().and_then(Path::file_name);
+    let idx = app
+        .dirs
+        .items
+        .iter()
+        .position(|(name, _)| Some(name.as_os_str()) == left);
+
+    app.dirs.state.select(Some(idx.unwrap_or(0)));
+    app.files.state.select(None);
+}
+
+// l: into the selected directory, same as Enter on it
+pub fn handle_open(app: &mut App) {
+    if block_binds(app) || app.dirs.state.selected().is_none() {
+        return;
+    }
+
+    submit::handle_submit(app, &mut String::new(), &mut false);
+}
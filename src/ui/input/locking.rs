@@ -0,0 +1,239 @@
+use super::elevate::{self, Elevated};
+use super::jobs::{self, Job, Update};
+use super::undo;
+use crate::app::app::{display_name, App};
+use std::path::{Path, PathBuf};
+
+// a delete or rename that failed and can be tried again from the report
+#[derive(Clone)]
+pub enum Retry {
+    Delete(PathBuf),
+    Rename(PathBuf, PathBuf),
+}
+
+// the error, and whether it was the permissions that refused it
+fn attempt(retry: &Retry) -> Result<(), (String, bool)> {
+    match retry {
+        Retry::Delete(path) => {
+            trash::delete(path).map_err(|err| (err.to_string(), elevate::trash_denied(&err)))
+        }
+        Retry::Rename(from, to) => {
+            std::fs::rename(from, to).map_err(|err| (err.to_string(), elevate::denied(&err)))
+        }
+    }
+}
+
+// run the operation, on failure say why and who holds the file instead of
+// panicking, enter on the report tries again
+pub fn run(app: &mut App, retry: Retry) {
+    match retry {
+        Retry::Delete(path) => delete(app, path),
+        Retry::Rename(..) => {
+            let result = attempt(&retry);
+            settle(app, retry, result);
+        }
+    }
+}
+
+// moves to the bin as a background job so a big tree doesn't hold up the
+// ui, it goes into the job history without a summary popping up after every
+// single one
+fn delete(app: &mut App, path: PathBuf) {
+    let shown = display_name(&path);
+    // the cwd follows the panes, the job shouldn't
+    let path = elevate::absolute(&path);
+    let mut job = Job::start("Delete");
+
+    jobs::spawn(app, format!("Delete {}", shown), move |tx, kill| {
+        let _ = tx.send(Update::Line(format!("Measuring {}", shown)));
+        let result = match jobs::measure_until(&path, &kill) {
+            Some(counted) => {
+                let _ = tx.send(Update::Line(format!("Moving {} to the bin", shown)));
+                let result = attempt(&Retry::Delete(path.clone()));
+                match &result {
+                    Ok(_) => job.done(counted),
+                    Err((err, _)) => job.failed(&shown, err),
+                }
+                Some(result)
+            }
+            None => {
+                job.failed(&shown, "killed before it started");
+                None
+            }
+        };
+
+        let failed = match &result {
+            Some(Ok(_)) => None,
+            Some(Err(_)) => Some("failed".to_string()),
+            None => Some("killed".to_string()),
+        };
+        let _ = tx.send(Update::Finished(
+            failed,
+            Some(Box::new(move |app: &mut App| {
+                job.finish(app, false);
+                if let Some(result) = result {
+                    settle(app, Retry::Delete(path), result);
+                }
+            })),
+        ));
+    });
+}
+
+// what's left once the operation ran: the undo entry and the panes, or the
+// report to try again from
+fn settle(app: &mut App, retry: Retry, result: Result<(), (String, bool)>) {
+    let err = match result {
+        Ok(_) => {
+            match &retry {
+                Retry::Delete(path) => undo::record_delete(app, path),
+                Retry::Rename(from, to) => undo::record_rename(app, from, to),
+            }
+            app.notify(match &retry {
+                Retry::Delete(path) => format!("Moved {} to the bin", display_name(path)),
+                Retry::Rename(from, to) => {
+                    format!("Renamed {} to {}", display_name(from), display_name(to))
+                }
+            });
+            app.pending_retry = None;
+            app.update_files();
+            app.update_dirs();
+            app.clamp_selection();
+            return;
+        }
+        Err(err) => err,
+    };
+
+    let (title, path) = match &retry {
+        Retry::Delete(path) => ("Delete failed", path),
+        Retry::Rename(from, _) => ("Rename failed", from),
+    };
+
+    // trying again won't help, root might
+    let (err, denied) = err;
+    if denied {
+        app.pending_retry = None;
+        let elevated = match &retry {
+            Retry::Delete(path) => Elevated::Delete(elevate::absolute(path)),
+            Retry::Rename(from, to) => {
+                Elevated::Rename(elevate::absolute(from), elevate::absolute(to))
+            }
+        };
+        return elevate::offer(
+            app,
+            title,
+            format!("{}: {}", display_name(path), err),
+            elevated,
+        );
+    }
+
+    let mut lines = vec![format!("{}: {}", display_name(path), err)];
+    let holders = holders(path);
+
+    if holders.is_empty() {
+        lines.push("Press Enter to try again, Esc to give up.".to_string());
+    } else {
+        lines.push("The file is in use by:".to_string());
+        lines.extend(holders.iter().map(|holder| format!("  {}", holder)));
+        lines.push("Close it, then press Enter to try again, Esc to give up.".to_string());
+    }
+
+    app.open_report(title, lines);
+    app.pending_retry = Some(retry);
+}
+
+pub fn retry(app: &mut App) {
+    app.show_report = false;
+
+    if let Some(retry) = app.pending_retry.take() {
+        run(app, retry);
+    }
+}
+
+// the processes that have the file open, as "name (pid)", asked from the
+// restart manager
+#[cfg(windows)]
+fn holders(path: &Path) -> Vec<String> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::System::RestartManager::{
+        RmEndSession, RmGetList, RmRegisterResources, RmStartSession, CCH_RM_SESSION_KEY,
+        RM_PROCESS_INFO,
+    };
+
+    const ERROR_MORE_DATA: u32 = 234;
+
+    let full = std::fs::canonicalize(path).unwrap_or_else(|_| path.into());
+    let wide = full
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect::<Vec<u16>>();
+
+    let mut session = 0u32;
+    let mut key = [0u16; CCH_RM_SESSION_KEY as usize + 1];
+    let mut holders = vec![];
+
+    unsafe {
+        if RmStartSession(&mut session, 0, key.as_mut_ptr()) != 0 {
+            return holders;
+        }
+
+        let files = [wide.as_ptr()];
+
+        if RmRegisterResources(
+            session,
+            1,
+            files.as_ptr(),
+            0,
+            std::ptr::null(),
+            0,
+            std::ptr::null(),
+        ) == 0
+        {
+            let mut needed = 0u32;
+            let mut count = 0u32;
+            let mut reasons = 0u32;
+
+            let result = RmGetList(
+                session,
+                &mut needed,
+                &mut count,
+                std::ptr::null_mut(),
+                &mut reasons,
+            );
+
+            if result == ERROR_MORE_DATA && needed > 0 {
+                let mut infos: Vec<RM_PROCESS_INFO> = vec![std::mem::zeroed(); needed as usize];
+                count = needed;
+
+                if RmGetList(
+                    session,
+                    &mut needed,
+                    &mut count,
+                    infos.as_mut_ptr(),
+                    &mut reasons,
+                ) == 0
+                {
+                    for info in infos.iter().take(count as usize) {
+                        let len = info
+                            .strAppName
+                            .iter()
+                            .position(|c| *c == 0)
+                            .unwrap_or(info.strAppName.len());
+                        let name = String::from_utf16_lossy(&info.strAppName[..len]);
+
+                        holders.push(format!("{} ({})", name, info.Process.dwProcessId));
+                    }
+                }
+            }
+        }
+
+        RmEndSession(session);
+    }
+
+    holders
+}
+
+#[cfg(not(windows))]
+fn holders(_path: &Path) -> Vec<String> {
+    vec![]
+}
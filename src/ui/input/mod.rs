@@ -1,9 +1,59 @@
+pub mod archive;
 pub mod bookmark;
+pub mod checksum;
+pub mod clipboard;
+pub mod compress;
+pub mod conflicts;
+pub mod diagnostics;
+pub mod diff;
+pub mod dir_tree;
+pub mod du;
+pub mod elevate;
+pub mod export;
 pub mod extract;
+pub mod fat;
 pub mod file_ops;
+pub mod filter;
+pub mod flatten;
+pub mod follow;
+pub mod frecent;
+pub mod fzf_backend;
+pub mod grep;
 pub mod help;
+pub mod hooks;
+pub mod jobs;
+pub mod jumps;
+pub mod kinds;
+pub mod listing;
+pub mod locking;
+pub mod motion;
+pub mod mounts;
+pub mod mouse;
 pub mod movement;
 pub mod nav;
+pub mod open_with;
+pub mod pager;
+pub mod paging;
+pub mod palette;
+pub mod pins;
+pub mod piped;
+pub mod plan;
+pub mod plugins;
+pub mod popups;
+pub mod preflight;
+pub mod preview_search;
+pub mod previewers;
+pub mod properties;
+pub mod remote;
 pub mod run_app;
+pub mod schedule;
+pub mod secret;
+pub mod shell;
 pub mod stateful_list;
 pub mod submit;
+pub mod terminal_title;
+pub mod themes;
+pub mod treemap;
+pub mod typeahead;
+pub mod undo;
+pub mod wsl;
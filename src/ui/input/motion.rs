@@ -0,0 +1,265 @@
+use super::listing;
+use super::movement;
+use super::preview_search;
+use super::stateful_list::StatefulList;
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::ffi::OsString;
+
+// what's been typed towards a vim style motion: a count (10j) and the first
+// g of gg
+#[derive(Default)]
+pub struct Pending {
+    pub count: Option<usize>,
+    pub g: bool,
+    // the panes' selections and the preview's focus before 1, 2 or 3
+    // switched them, put back when the digit turns out to start a count
+    panes: Option<(Option<usize>, Option<usize>, bool)>,
+}
+
+// the keys that use or add to what's pending, the rest drop it
+pub fn continues(key: &KeyEvent) -> bool {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+    match key.code {
+        KeyCode::Char(c) if c.is_ascii_digit() => !ctrl,
+        KeyCode::Char('j' | 'k' | 'g' | 'G') => !ctrl,
+        KeyCode::Char('u') => ctrl,
+        KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown => true,
+        _ => false,
+    }
+}
+
+pub fn clear(app: &mut App) {
+    app.pending = Pending::default();
+}
+
+// 1, 2 and 3 still switch panes right away, the switch is undone when
+// another digit or a motion follows
+pub fn digit(app: &mut App, digit: usize) {
+    if block_binds(app) {
+        return;
+    }
+
+    match app.pending.count {
+        Some(count) => {
+            restore_panes(app);
+            app.pending.count = Some(count.saturating_mul(10).saturating_add(digit));
+        }
+        None if (1..=3).contains(&digit) => {
+            app.pending.panes = Some((
+                app.files.state.selected(),
+                app.dirs.state.selected(),
+                app.preview_focused,
+            ));
+            if digit == 3 {
+                preview_search::focus(app);
+            } else {
+                movement::handle_pane_switching(app, digit as u8);
+            }
+            app.pending.count = Some(digit);
+        }
+        None if digit > 0 => app.pending.count = Some(digit),
+        None => {}
+    }
+    app.pending.g = false;
+}
+
+// with neither pane selected before, the switch stays
+fn restore_panes(app: &mut App) {
+    if let Some((files, dirs, preview)) = app
+        .pending
+        .panes
+        .take()
+        .filter(|(files, dirs, _)| files.is_some() || dirs.is_some())
+    {
+        app.files.state.select(files);
+        app.dirs.state.select(dirs);
+        app.preview_focused = preview;
+    }
+}
+
+// the count for the motion being done, if one was typed
+fn take_count(app: &mut App) -> Option<usize> {
+    let count = app.pending.count;
+    if count.is_some() {
+        restore_panes(app);
+    }
+    clear(app);
+
+    count
+}
+
+fn move_by<T>(list: &mut StatefulList<T>, by: isize) {
+    if list.items.is_empty() {
+        return;
+    }
+
+    let selected = list.state.selected().unwrap_or(0) as isize;
+    let last = list.items.len() as isize - 1;
+    list.state
+        .select(Some((selected + by).clamp(0, last) as usize));
+}
+
+// counted from 1, past the end is the last one
+fn select_line<T>(list: &mut StatefulList<T>, line: usize) {
+    if !list.items.is_empty() {
+        let idx = line.saturating_sub(1).min(list.items.len() - 1);
+        list.state.select(Some(idx));
+    }
+}
+
+// the files or directories list with the selection
+fn focused(app: &mut App) -> Option<&mut StatefulList<(OsString, String)>> {
+    if app.files.state.selected().is_some() {
+        Some(&mut app.files)
+    } else if app.dirs.state.selected().is_some() {
+        Some(&mut app.dirs)
+    } else {
+        None
+    }
+}
+
+// the rows of text the preview shows
+fn preview_height(app: &App) -> usize {
+    app.preview_area.height.saturating_sub(2) as usize
+}
+
+fn select_focused_line(app: &mut App, line: usize) {
+    if app.preview_focused {
+        // the preview doesn't know where it ends, only the top is a line
+        if line == 1 {
+            app.preview_scroll = 0;
+        }
+    } else if app.unified() {
+        listing::select_line(app, line);
+    } else if let Some(list) = focused(app) {
+        select_line(list, line);
+    }
+}
+
+// j and k, taking a count, a single step wraps around like it always did
+pub fn step(app: &mut App, key: char) {
+    let count = take_count(app);
+    if block_binds(app) {
+        return;
+    }
+
+    if app.preview_focused {
+        let count = count.unwrap_or(1) as isize;
+        return preview_search::scroll(app, if key == 'j' { count } else { -count });
+    }
+
+    let count = match count {
+        Some(count) if count > 1 => count as isize,
+        _ => return movement::handle_movement(app, key),
+    };
+
+    let by = if key == 'j' { count } else { -count };
+    if app.unified() {
+        listing::move_by(app, by);
+    } else if let Some(list) = focused(app) {
+        move_by(list, by);
+    }
+}
+
+// gg goes to the top, 5gg to the fifth entry
+pub fn handle_g(app: &mut App) {
+    if block_binds(app) {
+        return clear(app);
+    }
+
+    if !app.pending.g {
+        app.pending.g = true;
+        return;
+    }
+
+    let line = take_count(app).unwrap_or(1);
+    if app.preview_focused {
+        app.preview_scroll = line - 1;
+        return;
+    }
+    select_focused_line(app, line);
+}
+
+// G goes to the bottom, 5G to the fifth entry
+pub fn handle_bottom(app: &mut App) {
+    let count = take_count(app);
+    if block_binds(app) {
+        return;
+    }
+
+    select_focused_line(app, count.unwrap_or(usize::MAX));
+}
+
+// CTRL + u and CTRL + d, by half the rows the list shows, times the count
+pub fn half_page(app: &mut App, down: bool) {
+    let count = take_count(app).unwrap_or(1) as isize;
+    let by =
+        |list_height: usize| (list_height / 2).max(1) as isize * if down { count } else { -count };
+
+    if app.show_fzf {
+        let by = by(app.fzf_results.height);
+        return move_by(&mut app.fzf_results, by);
+    }
+
+    if block_binds(app) {
+        return;
+    }
+
+    if app.preview_focused {
+        let by = by(preview_height(app));
+        return preview_search::scroll(app, by);
+    }
+
+    if app.unified() {
+        let by = by(app.listing.height);
+        listing::move_by(app, by);
+    } else if let Some(list) = focused(app) {
+        let by = by(list.height);
+        move_by(list, by);
+    }
+}
+
+// PageUp and PageDown, times the count
+pub fn page(app: &mut App, down: bool) {
+    let count = take_count(app).unwrap_or(1) as isize;
+    let pages = if down { count } else { -count };
+
+    if app.show_fzf {
+        return app.fzf_results.page(pages);
+    }
+
+    if block_binds(app) {
+        return;
+    }
+
+    if app.preview_focused {
+        let by = preview_height(app).max(1) as isize * pages;
+        return preview_search::scroll(app, by);
+    }
+
+    if app.unified() {
+        let by = app.listing.height.max(1) as isize * pages;
+        listing::move_by(app, by);
+    } else if let Some(list) = focused(app) {
+        list.page(pages);
+    }
+}
+
+// Home and End
+pub fn edge(app: &mut App, top: bool) {
+    clear(app);
+    let line = if top { 1 } else { usize::MAX };
+
+    if app.show_fzf {
+        return select_line(&mut app.fzf_results, line);
+    }
+
+    if block_binds(app) {
+        return;
+    }
+
+    select_focused_line(app, line);
+}
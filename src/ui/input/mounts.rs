@@ -0,0 +1,321 @@
+use super::jobs::{self, Update};
+use super::jumps;
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use crate::ui::display::pane::{convert_bytes, get_pwd};
+use std::path::{Path, PathBuf};
+use std::process::Command as SysCommand;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::{DiskExt, System, SystemExt};
+
+// how often the mounts are looked at for media plugged in since
+const CHECK: Duration = Duration::from_secs(2);
+
+// a mounted filesystem, and whether it can be unplugged
+struct Mount {
+    point: String,
+    line: String,
+    removable: bool,
+}
+
+// what's known about removable media between checks
+#[derive(Default)]
+pub struct MountWatch {
+    // None until the first check, media mounted before traverse started
+    // aren't news
+    known: Option<Vec<String>>,
+    checked: Option<Instant>,
+    scan: Option<Receiver<Vec<String>>>,
+    // the mount to eject once the running jobs are done
+    pending_eject: Option<String>,
+}
+
+// USB sticks and cards the desktop mounted, where sysinfo doesn't know
+fn under_media(point: &str) -> bool {
+    ["/media/", "/run/media/", "/Volumes/"]
+        .iter()
+        .any(|dir| point.starts_with(dir))
+}
+
+// phones and cameras over MTP or PTP, which gvfs mounts under the runtime
+// directory instead of the mount table
+fn gvfs_mounts() -> Vec<Mount> {
+    let dir = match std::env::var("XDG_RUNTIME_DIR") {
+        Ok(dir) => PathBuf::from(dir).join("gvfs"),
+        Err(_) => return vec![],
+    };
+
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| {
+                    let point = entry.path().to_string_lossy().to_string();
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    Mount {
+                        line: format!(
+                            "{:<28} {:>29}  gvfs {}  (removable, e ejects)",
+                            point, "", name
+                        ),
+                        point,
+                        removable: true,
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// every mounted filesystem once, `si` for the sizes in its line
+fn mounts(si: bool) -> Vec<Mount> {
+    let mut sys = System::new();
+    sys.refresh_disks_list();
+
+    let mut mounts = sys
+        .disks()
+        .iter()
+        .map(|disk| {
+            let point = disk.mount_point().to_string_lossy().to_string();
+            let removable = disk.is_removable() || under_media(&point);
+            let eject = if removable {
+                "  (removable, e ejects)"
+            } else {
+                ""
+            };
+            let line = format!(
+                "{:<28} {:>10} free of {:>10}  {} {}{}",
+                point,
+                convert_bytes(disk.available_space(), si),
+                convert_bytes(disk.total_space(), si),
+                String::from_utf8_lossy(disk.file_system()),
+                disk.name().to_string_lossy(),
+                eject,
+            );
+
+            Mount {
+                point,
+                line,
+                removable,
+            }
+        })
+        .collect::<Vec<Mount>>();
+    mounts.extend(gvfs_mounts());
+
+    mounts.sort_by(|a, b| a.point.cmp(&b.point));
+    mounts.dedup_by(|a, b| a.point == b.point);
+    mounts
+}
+
+// only the mount points, the lines aren't shown
+fn removable() -> Vec<String> {
+    mounts(false)
+        .into_iter()
+        .filter(|mount| mount.removable)
+        .map(|mount| mount.point)
+        .collect()
+}
+
+pub fn handle_mounts(app: &mut App) {
+    if app.show_mounts {
+        app.show_mounts = false;
+        return;
+    }
+
+    if block_binds(app) {
+        return;
+    }
+
+    app.mounts.items = mounts(app.si_units)
+        .into_iter()
+        .map(|mount| (mount.point, mount.line))
+        .collect();
+
+    // start on the filesystem we're on, the longest mount point above us
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let current = app
+        .mounts
+        .items
+        .iter()
+        .enumerate()
+        .filter(|(_, (mount, _))| cwd.starts_with(mount))
+        .max_by_key(|(_, (mount, _))| mount.len())
+        .map(|(idx, _)| idx);
+
+    app.mounts
+        .state
+        .select(current.or(if app.mounts.items.is_empty() {
+            None
+        } else {
+            Some(0)
+        }));
+    app.show_mounts = true;
+}
+
+pub fn handle_open_mount(app: &mut App) {
+    let mount = app
+        .mounts
+        .state
+        .selected()
+        .and_then(|idx| app.mounts.items.get(idx))
+        .map(|(mount, _)| PathBuf::from(mount));
+
+    app.show_mounts = false;
+
+    let mount = match mount {
+        Some(mount) => mount,
+        None => return,
+    };
+
+    if !jumps::change_dir(app, Path::new(&mount)) {
+        return;
+    }
+
+    app.update_files();
+    app.update_dirs();
+
+    app.files.state.select(Some(0));
+    app.dirs.state.select(None);
+
+    app.cur_dir = get_pwd();
+}
+
+// e in the mounts list: unmounts the highlighted removable medium and powers
+// it off, once the jobs that could be writing to it are done
+pub fn handle_eject(app: &mut App) {
+    let point = match app
+        .mounts
+        .state
+        .selected()
+        .and_then(|idx| app.mounts.items.get(idx))
+    {
+        Some((point, _)) => point.clone(),
+        None => return,
+    };
+
+    if !removable().contains(&point) {
+        return app.notify_error(format!("{} isn't removable, only media are ejected", point));
+    }
+
+    app.show_mounts = false;
+
+    let running = jobs::running(app);
+    if running > 0 {
+        app.notify(format!(
+            "Ejecting {} once the {} running jobs are done",
+            point, running
+        ));
+        app.mount_watch.pending_eject = Some(point);
+        return;
+    }
+
+    eject(app, point);
+}
+
+// the commands that let go of a mount, the first one there is runs
+fn eject_command(point: &str) -> SysCommand {
+    let mut command;
+
+    if cfg!(target_os = "macos") {
+        command = SysCommand::new("diskutil");
+        command.arg("eject").arg(point);
+    } else if point.contains("/gvfs/") {
+        command = SysCommand::new("gio");
+        command.arg("mount").arg("--unmount").arg(point);
+    } else {
+        // udisks powers the stick off too, so it's safe to pull
+        command = SysCommand::new("sh");
+        command.arg("-c").arg(
+            "dev=$(findmnt -n -o SOURCE --target \"$1\") && \
+             if command -v udisksctl >/dev/null; then \
+               udisksctl unmount -b \"$dev\" && udisksctl power-off -b \"$dev\"; \
+             else umount \"$1\"; fi",
+        );
+        command.arg("sh").arg(point);
+    }
+
+    command
+}
+
+fn eject(app: &mut App, point: String) {
+    // a shell in the mount would keep it busy, and so would traverse
+    let cwd = std::env::current_dir().unwrap_or_default();
+    if cwd.starts_with(&point) {
+        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("/"));
+        if jumps::change_dir(app, &home) {
+            app.update_files();
+            app.update_dirs();
+            app.clamp_selection();
+        }
+    }
+
+    let mut command = eject_command(&point);
+    jobs::spawn(app, format!("Eject {}", point), move |tx, kill| {
+        let (failed, errors) = match jobs::run_child(&mut command, &tx, &kill) {
+            Ok((status, errors)) => (jobs::describe(&status, &kill), errors),
+            Err(e) => (Some(format!("couldn't start: {}", e)), String::new()),
+        };
+
+        let _ = tx.send(Update::Finished(
+            failed.clone(),
+            Some(Box::new(move |app: &mut App| match failed {
+                Some(failed) => app.notify_error(format!(
+                    "Couldn't eject {}: {} {}",
+                    point,
+                    failed,
+                    errors.trim()
+                )),
+                None => app.notify(format!("Ejected {}, it's safe to remove", point)),
+            })),
+        ));
+    });
+}
+
+// every tick: notices media plugged in since the last look, and ejects the
+// one waiting on jobs once they're done
+pub fn poll_mounts(app: &mut App) {
+    if app.mount_watch.pending_eject.is_some() && jobs::running(app) == 0 {
+        if let Some(point) = app.mount_watch.pending_eject.take() {
+            eject(app, point);
+        }
+    }
+
+    let watch = &mut app.mount_watch;
+    if let Some(rx) = &watch.scan {
+        let found = match rx.try_recv() {
+            Ok(found) => found,
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => vec![],
+        };
+        watch.scan = None;
+
+        let added = match &watch.known {
+            Some(known) => found
+                .iter()
+                .filter(|point| !known.contains(point))
+                .cloned()
+                .collect(),
+            None => vec![],
+        };
+        watch.known = Some(found);
+
+        for point in added {
+            app.notify(format!("{} was mounted, D lists it", point));
+        }
+        return;
+    }
+
+    // a hung network mount holds up the thread, not the UI
+    if watch
+        .checked
+        .is_none_or(|checked| checked.elapsed() >= CHECK)
+    {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = tx.send(removable());
+        });
+        watch.scan = Some(rx);
+        watch.checked = Some(Instant::now());
+    }
+}
@@ -0,0 +1,234 @@
+use super::stateful_list::StatefulList;
+use super::{listing, movement, pager, preview_search, submit};
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use crate::ui::display::layout::PaneLayout;
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Rect;
+use std::time::{Duration, Instant};
+
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+const SCROLL_LINES: usize = 3;
+const RESIZE_STEP: u16 = 5;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum PaneBorder {
+    Preview,
+    Lists,
+}
+
+fn contains(area: Rect, column: u16, row: u16) -> bool {
+    column > area.x
+        && column < area.x + area.width.saturating_sub(1)
+        && row > area.y
+        && row < area.y + area.height.saturating_sub(1)
+}
+
+pub fn handle_mouse(app: &mut App, mut mouse: MouseEvent) {
+    // inline mode draws lower down than where the terminal counts from
+    if let Some(top) = app.inline {
+        if mouse.row < top {
+            return;
+        }
+        mouse.row -= top;
+    }
+
+    // nothing under the pager can be clicked
+    if app.pager.is_some() {
+        match mouse.kind {
+            MouseEventKind::ScrollDown => pager::scroll(app, SCROLL_LINES as isize),
+            MouseEventKind::ScrollUp => pager::scroll(app, -(SCROLL_LINES as isize)),
+            _ => {}
+        }
+        return;
+    }
+
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            app.dragging = border_at(app, mouse.column, mouse.row);
+
+            if app.dragging.is_none() {
+                handle_click(app, mouse.column, mouse.row);
+            }
+        }
+        MouseEventKind::Drag(MouseButton::Left) => handle_drag(app, mouse.column, mouse.row),
+        MouseEventKind::Up(MouseButton::Left) => app.dragging = None,
+        MouseEventKind::ScrollDown => handle_scroll(app, mouse.column, mouse.row, 1),
+        MouseEventKind::ScrollUp => handle_scroll(app, mouse.column, mouse.row, -1),
+        _ => {}
+    }
+}
+
+// grow or shrink the preview against the file and directory lists
+pub fn resize_preview(app: &mut App, grow: bool) {
+    if block_binds(app) {
+        return;
+    }
+
+    app.preview_width = if grow {
+        app.preview_width + RESIZE_STEP
+    } else {
+        app.preview_width.saturating_sub(RESIZE_STEP)
+    }
+    .clamp(20, 80);
+}
+
+fn border_at(app: &mut App, column: u16, row: u16) -> Option<PaneBorder> {
+    if block_binds(app) {
+        return None;
+    }
+
+    let lists_x = app.files_area.x;
+    let lists_end = app.dirs_area.y + app.dirs_area.height;
+    // in the three column layout the preview is on the right
+    let preview_x = match app.layout {
+        PaneLayout::Split => lists_x,
+        PaneLayout::Miller => app.preview_area.x,
+    };
+
+    // the border between preview and lists is two columns wide, one per block
+    if (column + 1 == preview_x || column == preview_x) && row < lists_end {
+        Some(PaneBorder::Preview)
+    } else if column > lists_x
+        && !app.unified()
+        && (row + 1 == app.dirs_area.y || row == app.dirs_area.y)
+        && app.dirs_area.y > 0
+    {
+        Some(PaneBorder::Lists)
+    } else {
+        None
+    }
+}
+
+fn handle_drag(app: &mut App, column: u16, row: u16) {
+    let screen = app.screen_area;
+
+    match app.dragging {
+        Some(PaneBorder::Preview) if screen.width > 0 => {
+            let width = match app.layout {
+                PaneLayout::Split => column,
+                PaneLayout::Miller => screen.width.saturating_sub(column),
+            };
+            let percent = (width as u32 * 100 / screen.width as u32) as u16;
+            app.preview_width = percent.clamp(20, 80);
+        }
+        Some(PaneBorder::Lists) if screen.height > 0 => {
+            let percent = (row as u32 * 100 / screen.height as u32) as u16;
+            app.files_height = percent.clamp(10, 80);
+        }
+        _ => {}
+    }
+}
+
+fn handle_click(app: &mut App, column: u16, row: u16) {
+    if block_binds(app) {
+        return;
+    }
+
+    let double_click = match app.last_click {
+        Some((at, x, y)) => x == column && y == row && at.elapsed() < DOUBLE_CLICK,
+        None => false,
+    };
+    app.last_click = Some((Instant::now(), column, row));
+
+    if contains(app.preview_area, column, row) {
+        return preview_search::focus(app);
+    } else if contains(app.files_area, column, row) || contains(app.dirs_area, column, row) {
+        preview_search::unfocus(app);
+    }
+
+    if app.unified() && contains(app.files_area, column, row) {
+        let row = (row - app.files_area.y - 1) as usize;
+
+        if let Some(index) = app.listing.index_at(row) {
+            listing::select(app, index);
+
+            if double_click && app.dirs.state.selected().is_some() {
+                submit::handle_submit(app, &mut String::new(), &mut false);
+                app.last_click = None;
+            }
+        }
+    } else if contains(app.files_area, column, row) {
+        let row = (row - app.files_area.y - 1) as usize;
+
+        if let Some(index) = app.files.index_at(row) {
+            app.files.state.select(Some(index));
+            app.dirs.state.select(None);
+        }
+    } else if contains(app.dirs_area, column, row) {
+        let row = (row - app.dirs_area.y - 1) as usize;
+
+        if let Some(index) = app.dirs.index_at(row) {
+            app.dirs.state.select(Some(index));
+            app.files.state.select(None);
+
+            if double_click {
+                // same as pressing enter on the directory
+                submit::handle_submit(app, &mut String::new(), &mut false);
+                app.last_click = None;
+            }
+        }
+    }
+}
+
+fn handle_scroll(app: &mut App, column: u16, row: u16, direction: isize) {
+    if app.show_fzf {
+        movement::handle_fzf_movement(app, direction);
+        return;
+    } else if app.show_bookmark {
+        movement::handle_bookmark_movement(app, direction);
+        return;
+    } else if app.show_jumps {
+        movement::handle_jump_movement(app, direction);
+        return;
+    } else if app.show_treemap {
+        movement::handle_treemap_movement(app, direction);
+        return;
+    } else if app.show_report {
+        movement::handle_report_movement(app, direction);
+        return;
+    } else if app.show_themes {
+        movement::handle_theme_movement(app, direction);
+        return;
+    } else if app.show_palette {
+        movement::handle_palette_movement(app, direction);
+        return;
+    } else if block_binds(app) {
+        return;
+    }
+
+    if contains(app.preview_area, column, row) {
+        if direction > 0 {
+            if !app.preview_at_end {
+                app.preview_scroll += SCROLL_LINES;
+            }
+        } else {
+            app.preview_scroll = app.preview_scroll.saturating_sub(SCROLL_LINES);
+        }
+    } else if app.unified() && contains(app.files_area, column, row) {
+        if listing::selected(app).is_none() {
+            listing::select_line(app, 1);
+        } else {
+            listing::move_by(app, direction);
+        }
+    } else if contains(app.files_area, column, row) {
+        if !app.files.items.is_empty() {
+            scroll_list(&mut app.files, direction);
+            app.dirs.state.select(None);
+        }
+    } else if contains(app.dirs_area, column, row) {
+        scroll_list(&mut app.dirs, direction);
+        app.files.state.select(None);
+    }
+}
+
+// unlike j/k the wheel stops at either end instead of wrapping around
+fn scroll_list<T>(list: &mut StatefulList<T>, direction: isize) {
+    let last = list.items.len().saturating_sub(1) as isize;
+    let selected = match list.state.selected() {
+        Some(selected) => selected as isize + direction,
+        None => 0,
+    };
+
+    list.state.select(Some(selected.clamp(0, last) as usize));
+}
@@ -1,3 +1,6 @@
+use super::listing;
+use super::preview_search;
+use super::stateful_list::stepped;
 use crate::{app::app::App, ui::display::block::block_binds};
 
 pub fn handle_movement(app: &mut App, key: char) {
@@ -5,20 +8,24 @@ pub fn handle_movement(app: &mut App, key: char) {
         return;
     }
 
+    if app.unified() {
+        return listing::step(app, if key == 'j' { 1 } else { -1 });
+    }
+
     if app.files.state.selected().is_some() {
         if app.files.items.len() > 1 {
             if key == 'j' {
-                app.files.next();
+                app.files.next(app.wrap_lists);
             } else {
-                app.files.previous();
+                app.files.previous(app.wrap_lists);
             }
         }
     } else if app.dirs.state.selected().is_some() {
         if app.dirs.items.len() > 1 {
             if key == 'j' {
-                app.dirs.next();
+                app.dirs.next(app.wrap_lists);
             } else {
-                app.dirs.previous();
+                app.dirs.previous(app.wrap_lists);
             }
         }
     }
@@ -32,7 +39,7 @@ pub fn handle_fzf_movement(app: &mut App, idx: isize) {
             app.fzf_results.state.select(Some(0));
         } else {
             let selected = app.fzf_results.state.selected().unwrap() as isize;
-            let new_selected = (selected + idx).rem_euclid(results as isize) as usize;
+            let new_selected = stepped(selected as usize, idx, results, app.wrap_lists);
 
             app.fzf_results.state.select(Some(new_selected));
         }
@@ -47,7 +54,7 @@ pub fn handle_bookmark_movement(app: &mut App, idx: isize) {
             app.bookmarked_dirs.state.select(Some(0));
         } else {
             let selected = app.bookmarked_dirs.state.selected().unwrap() as isize;
-            let new_selected = (selected + idx).rem_euclid(results as isize) as usize;
+            let new_selected = stepped(selected as usize, idx, results, app.wrap_lists);
 
             app.bookmarked_dirs.state.select(Some(new_selected));
         }
@@ -59,6 +66,7 @@ pub fn handle_pane_switching(app: &mut App, key: u8) {
         return;
     }
 
+    preview_search::unfocus(app);
     if key == 1 {
         app.files.state.select(Some(0));
         app.dirs.state.select(None);
@@ -76,9 +84,218 @@ pub fn handle_ops_menu_movement(app: &mut App, idx: isize) {
             app.ops_menu.state.select(Some(0));
         } else {
             let selected = app.ops_menu.state.selected().unwrap() as isize;
-            let new_selected = (selected + idx).rem_euclid(results as isize) as usize;
+            let new_selected = stepped(selected as usize, idx, results, app.wrap_lists);
 
             app.ops_menu.state.select(Some(new_selected));
         }
     }
 }
+
+pub fn handle_jump_movement(app: &mut App, idx: isize) {
+    let results = app.jump_list.items.len();
+
+    if results > 0 {
+        if app.jump_list.state.selected().is_none() {
+            app.jump_list.state.select(Some(0));
+        } else {
+            let selected = app.jump_list.state.selected().unwrap() as isize;
+            let new_selected = stepped(selected as usize, idx, results, app.wrap_lists);
+
+            app.jump_list.state.select(Some(new_selected));
+        }
+    }
+}
+
+pub fn handle_report_movement(app: &mut App, idx: isize) {
+    let results = app.report.items.len();
+
+    if results > 0 {
+        if app.report.state.selected().is_none() {
+            app.report.state.select(Some(0));
+        } else {
+            let selected = app.report.state.selected().unwrap() as isize;
+            let new_selected = stepped(selected as usize, idx, results, app.wrap_lists);
+
+            app.report.state.select(Some(new_selected));
+        }
+    }
+}
+
+pub fn handle_theme_movement(app: &mut App, idx: isize) {
+    let results = app.themes.items.len();
+
+    if results > 0 {
+        if app.themes.state.selected().is_none() {
+            app.themes.state.select(Some(0));
+        } else {
+            let selected = app.themes.state.selected().unwrap() as isize;
+            let new_selected = stepped(selected as usize, idx, results, app.wrap_lists);
+
+            app.themes.state.select(Some(new_selected));
+        }
+    }
+}
+
+pub fn handle_palette_movement(app: &mut App, idx: isize) {
+    let results = app.palette.items.len();
+
+    if results > 0 {
+        if app.palette.state.selected().is_none() {
+            app.palette.state.select(Some(0));
+        } else {
+            let selected = app.palette.state.selected().unwrap() as isize;
+            let new_selected = stepped(selected as usize, idx, results, app.wrap_lists);
+
+            app.palette.state.select(Some(new_selected));
+        }
+    }
+}
+
+pub fn handle_du_movement(app: &mut App, idx: isize) {
+    let results = app.du.items.len();
+
+    if results > 0 {
+        if app.du.state.selected().is_none() {
+            app.du.state.select(Some(0));
+        } else {
+            let selected = app.du.state.selected().unwrap() as isize;
+            let new_selected = stepped(selected as usize, idx, results, app.wrap_lists);
+
+            app.du.state.select(Some(new_selected));
+        }
+    }
+}
+
+pub fn handle_piped_movement(app: &mut App, idx: isize) {
+    let results = app.piped.items.len();
+
+    if results > 0 {
+        if app.piped.state.selected().is_none() {
+            app.piped.state.select(Some(0));
+        } else {
+            let selected = app.piped.state.selected().unwrap() as isize;
+            let new_selected = stepped(selected as usize, idx, results, app.wrap_lists);
+
+            app.piped.state.select(Some(new_selected));
+        }
+    }
+}
+
+pub fn handle_diff_movement(app: &mut App, idx: isize) {
+    let results = app.diff.items.len();
+
+    if results > 0 {
+        if app.diff.state.selected().is_none() {
+            app.diff.state.select(Some(0));
+        } else {
+            let selected = app.diff.state.selected().unwrap() as isize;
+            let new_selected = stepped(selected as usize, idx, results, app.wrap_lists);
+
+            app.diff.state.select(Some(new_selected));
+        }
+    }
+}
+
+pub fn handle_compress_movement(app: &mut App, idx: isize) {
+    let results = app.compress.items.len();
+
+    if results > 0 {
+        if app.compress.state.selected().is_none() {
+            app.compress.state.select(Some(0));
+        } else {
+            let selected = app.compress.state.selected().unwrap() as isize;
+            let new_selected = stepped(selected as usize, idx, results, app.wrap_lists);
+
+            app.compress.state.select(Some(new_selected));
+        }
+    }
+}
+
+pub fn handle_export_movement(app: &mut App, idx: isize) {
+    let results = app.export.items.len();
+
+    if results > 0 {
+        if app.export.state.selected().is_none() {
+            app.export.state.select(Some(0));
+        } else {
+            let selected = app.export.state.selected().unwrap() as isize;
+            let new_selected = stepped(selected as usize, idx, results, app.wrap_lists);
+
+            app.export.state.select(Some(new_selected));
+        }
+    }
+}
+
+pub fn handle_mounts_movement(app: &mut App, idx: isize) {
+    let results = app.mounts.items.len();
+
+    if results > 0 {
+        if app.mounts.state.selected().is_none() {
+            app.mounts.state.select(Some(0));
+        } else {
+            let selected = app.mounts.state.selected().unwrap() as isize;
+            let new_selected = stepped(selected as usize, idx, results, app.wrap_lists);
+
+            app.mounts.state.select(Some(new_selected));
+        }
+    }
+}
+
+pub fn handle_open_with_movement(app: &mut App, idx: isize) {
+    let results = app.open_with.items.len();
+
+    if results > 0 {
+        if app.open_with.state.selected().is_none() {
+            app.open_with.state.select(Some(0));
+        } else {
+            let selected = app.open_with.state.selected().unwrap() as isize;
+            let new_selected = stepped(selected as usize, idx, results, app.wrap_lists);
+
+            app.open_with.state.select(Some(new_selected));
+        }
+    }
+}
+
+pub fn handle_frecent_movement(app: &mut App, idx: isize) {
+    let results = app.frecent.items.len();
+
+    if results > 0 {
+        if app.frecent.state.selected().is_none() {
+            app.frecent.state.select(Some(0));
+        } else {
+            let selected = app.frecent.state.selected().unwrap() as isize;
+            let new_selected = stepped(selected as usize, idx, results, app.wrap_lists);
+
+            app.frecent.state.select(Some(new_selected));
+        }
+    }
+}
+
+pub fn handle_grep_movement(app: &mut App, idx: isize) {
+    let wrap = app.wrap_lists;
+    let hits = &mut app.grep.hits;
+    let results = hits.items.len();
+
+    if results > 0 {
+        let new_selected = match hits.state.selected() {
+            Some(selected) => stepped(selected, idx, results, wrap),
+            None => 0,
+        };
+        hits.state.select(Some(new_selected));
+    }
+}
+
+pub fn handle_treemap_movement(app: &mut App, idx: isize) {
+    let results = app.treemap.items.len();
+
+    if results > 0 {
+        if app.treemap.state.selected().is_none() {
+            app.treemap.state.select(Some(0));
+        } else {
+            let selected = app.treemap.state.selected().unwrap() as isize;
+            let new_selected = stepped(selected as usize, idx, results, app.wrap_lists);
+
+            app.treemap.state.select(Some(new_selected));
+        }
+    }
+}
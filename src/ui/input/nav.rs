@@ -1,89 +1,267 @@
 use super::stateful_list::StatefulList;
 use super::*;
-use crate::app::app::App;
-use crate::ui::display::pane::get_pwd;
-use crossterm::{
-    cursor::MoveTo, cursor::Show, execute, style::Print, style::ResetColor, terminal::Clear,
-    terminal::ClearType,
-};
+use crate::app::app::{is_hidden, App};
+use ignore::WalkBuilder;
 use run_app::Command;
-use std::io::stdout;
-use std::io::Write;
-use std::path::PathBuf;
-use std::process::exit;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{is_separator, Path, PathBuf, MAIN_SEPARATOR};
 use sublime_fuzzy::best_match;
-use walkdir::WalkDir;
 
 pub fn handle_nav(app: &mut App, input_active: &mut bool) {
     if !*input_active {
         app.show_nav = true;
         *input_active = true;
         app.last_command = Some(Command::ShowNav);
+        update_completions(app, "");
     }
 }
 
-fn fzf(app: &mut App, input: &mut String) -> Vec<PathBuf> {
-    let query = input.clone();
-    let dir = app.cur_dir.clone();
-    let dir = dir.trim_end_matches('\n');
+// C: alone, which Windows takes as wherever that drive was left
+fn is_drive(input: &str) -> bool {
+    let bytes = input.as_bytes();
+    cfg!(windows) && bytes.len() == 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':'
+}
 
-    let mut result = Vec::new();
+// the typed path with ~ and $VARS (or ${VARS}) filled in, unknown variables
+// are left as they are. On Windows C: is the drive's root and \\server\share
+// a share, both as typed or with forward slashes
+pub fn expand(input: &str) -> PathBuf {
+    let mut expanded = String::new();
+    let mut rest = input;
 
-    for entry in WalkDir::new(dir) {
-        let entry = entry.unwrap();
+    if is_drive(rest) {
+        return PathBuf::from(format!("{}\\", rest));
+    }
 
-        if entry.file_type().is_file() {
-            let mut should_exclude = false;
+    if rest == "~" || rest.starts_with("~/") || (cfg!(windows) && rest.starts_with("~\\")) {
+        if let Some(home) = dirs::home_dir() {
+            expanded.push_str(&home.to_string_lossy());
+            rest = &rest[1..];
+        }
+    }
 
-            for dir in &app.excluded_directories {
-                if entry.path().to_str().unwrap().contains(dir) {
-                    should_exclude = true;
-                    break;
-                }
-            }
+    let mut chars = rest.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            expanded.push(c);
+            continue;
+        }
 
-            if should_exclude {
-                continue;
-            }
+        let braced = chars.next_if_eq(&'{').is_some();
+        let mut name = String::new();
+        while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+            name.push(c);
+        }
+        let closed = braced && chars.next_if_eq(&'}').is_some();
 
-            if entry.path().to_str().unwrap().contains(".git") || !app.show_hidden {
-                if !app.show_hidden {
-                    if entry.file_name().to_str().unwrap().starts_with('.') {
-                        continue;
-                    }
-                } else {
-                    continue;
+        match std::env::var(&name) {
+            Ok(value) if !name.is_empty() && braced == closed => expanded.push_str(&value),
+            _ => {
+                expanded.push('$');
+                if braced {
+                    expanded.push('{');
+                }
+                expanded.push_str(&name);
+                if closed {
+                    expanded.push('}');
                 }
             }
+        }
+    }
 
-            let filename = entry.file_name().to_str().unwrap().to_string();
+    PathBuf::from(expanded)
+}
 
-            if let Some(matched) = best_match(&query, &filename) {
-                if matched.score() > 0 {
-                    result.push(entry.path().to_path_buf());
-                }
-            }
+// what the input can become, the directories whose names start with its last
+// component, written the way the rest of it was typed
+fn completions(input: &str, show_hidden: bool) -> Vec<String> {
+    // ~, $HOME and C: complete to themselves with a slash
+    let bare = input == "~" || (input.starts_with('$') && !input.contains(is_separator));
+    if (bare || is_drive(input)) && expand(input).is_dir() {
+        return vec![format!("{}{}", input, MAIN_SEPARATOR)];
+    }
+
+    let (dir, prefix) = match input.rfind(is_separator) {
+        Some(idx) => input.split_at(idx + 1),
+        None => ("", input),
+    };
+    // the separator the rest was typed with, \ or / on Windows
+    let separator = dir
+        .chars()
+        .last()
+        .filter(|c| is_separator(*c))
+        .unwrap_or(MAIN_SEPARATOR);
+    let read_from = if dir.is_empty() {
+        PathBuf::from(".")
+    } else {
+        expand(dir)
+    };
+
+    let entries = match fs::read_dir(read_from) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    let mut names = entries
+        .filter_map(|entry| entry.ok())
+        // links to directories count
+        .filter(|entry| entry.path().is_dir())
+        .filter(|entry| show_hidden || prefix.starts_with('.') || !is_hidden(entry))
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| name.starts_with(prefix))
+        .collect::<Vec<String>>();
+    names.sort_by_key(|name| name.to_lowercase());
+
+    names
+        .into_iter()
+        .map(|name| format!("{}{}{}", dir, name, separator))
+        .collect()
+}
+
+pub fn update_completions(app: &mut App, input: &str) {
+    app.nav_completions = completions(input, app.show_hidden);
+}
+
+// Tab: the one match, or as much as all of them share
+pub fn complete(app: &mut App, input: &mut String) {
+    let mut common = match app.nav_completions.first() {
+        Some(first) => first.clone(),
+        None => return,
+    };
+
+    for completion in &app.nav_completions[1..] {
+        let shared = common
+            .char_indices()
+            .zip(completion.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map(|((idx, c), _)| idx + c.len_utf8())
+            .unwrap_or(0);
+        common.truncate(shared);
+    }
+
+    if common.len() > input.len() {
+        *input = common;
+        update_completions(app, input);
+    }
+}
+
+// outside the excluded directories, and a name matching the query
+fn fzf_match(app: &App, query: &str, path: &Path) -> bool {
+    let full = path.to_string_lossy();
+    if app
+        .excluded_directories
+        .iter()
+        .any(|dir| full.contains(dir.as_str()))
+    {
+        return false;
+    }
+
+    let filename = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    best_match(query, &filename).is_some_and(|matched| matched.score() > 0)
+}
+
+fn fzf(app: &mut App, query: &str, fresh: bool) -> Vec<PathBuf> {
+    let dir = app.cur_dir.clone();
+    let dir = dir.trim_end_matches('\n');
+
+    // fd or rg list the files, they're matched the same way
+    if fzf_backend::list(app, dir, fresh) {
+        return app
+            .fzf_backend
+            .files
+            .iter()
+            .filter(|path| fzf_match(app, query, path))
+            .cloned()
+            .collect();
+    }
+
+    let mut result = Vec::new();
+
+    // hidden files follow show_hidden, gitignored ones the fzf toggle
+    let walker = WalkBuilder::new(dir)
+        .standard_filters(app.fzf_respect_ignore)
+        .hidden(!app.show_hidden)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+
+    for entry in walker {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(_) => continue,
+        };
+
+        if entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_file())
+            && fzf_match(app, query, entry.path())
+        {
+            result.push(entry.path().to_path_buf());
         }
     }
 
     result
 }
 
+// CTRL + g, only while the fuzzy finder is open
+pub fn toggle_fzf_ignore(app: &mut App, input: &mut String, input_active: &mut bool) {
+    if !app.show_fzf {
+        return;
+    }
+
+    app.fzf_respect_ignore = !app.fzf_respect_ignore;
+    handle_fzf(app, input, input_active);
+}
+
 pub fn handle_fzf(app: &mut App, input: &mut String, input_active: &mut bool) {
+    let fresh = !app.show_fzf;
     app.show_fzf = true;
     app.show_popup = true;
     app.last_command = Some(Command::ShowFzf);
 
     *input_active = true;
 
-    let result = fzf(app, input);
+    let result = fzf(app, input, fresh);
 
-    app.fzf_results = StatefulList::with_items(
-        result
-            .iter()
-            .map(|x| x.to_str().unwrap().to_string())
-            .collect(),
-    );
+    app.fzf_results = StatefulList::with_items(result);
+}
+
+// every tick: the files fd or rg printed since are matched in, the
+// highlighted result stays
+pub fn poll_fzf(app: &mut App, input: &str) {
+    if !fzf_backend::drain(app) || !app.show_fzf {
+        return;
+    }
+
+    let selected = app.fzf_results.state.selected();
+    let result = fzf(app, input, false);
+    app.fzf_results.items = result;
+    if app.fzf_results.items.is_empty() {
+        app.fzf_results.state.select(None);
+    } else {
+        app.fzf_results.state.select(selected);
+    }
+}
+
+// reopen the results left behind by jumping to one of them
+pub fn resume_fzf(app: &mut App, input: &mut String, input_active: &mut bool) -> bool {
+    if !app.fzf_resume {
+        return false;
+    }
+
+    app.fzf_resume = false;
+    app.show_fzf = true;
+    app.show_popup = true;
+    app.last_command = Some(Command::ShowFzf);
+
+    *input = app.fzf_query.clone();
+    *input_active = true;
+
+    true
 }
 
 pub fn abbreviate_path(path: &str) -> String {
@@ -103,24 +281,45 @@ pub fn abbreviate_path(path: &str) -> String {
     }
 }
 
-pub fn output_cur_dir() {
-    crossterm::terminal::disable_raw_mode().unwrap();
+// --pick: ESC and q pick the directory traverse is in, --choose-file
+// only takes files
+pub fn picked(app: &App) -> Option<OsString> {
+    if app.choose_file {
+        None
+    } else {
+        Some(picked_dir())
+    }
+}
+
+pub fn picked_dir() -> OsString {
+    std::env::current_dir()
+        .map(PathBuf::into_os_string)
+        .unwrap_or_default()
+}
+
+// --pick: Enter on a file picks it, as an absolute path
+pub fn picked_file(app: &App) -> Option<OsString> {
+    if !app.pick {
+        return None;
+    }
+
+    let (name, _) = app.files.items.get(app.files.state.selected()?)?;
+    let dir = std::env::current_dir().ok()?;
 
-    let dir = get_pwd();
+    Some(dir.join(name).into_os_string())
+}
 
-    execute!(
-        stdout(),
-        Clear(ClearType::All),
-        ResetColor,
-        Show,
-        MoveTo(0, 0),
-        Print(format!(
-            "To navigate to traverse's last directory: cd {}",
-            dir
-        ))
-    )
-    .unwrap();
+// --choose-dir: the shell wrapper reads the last directory from the file,
+// false when there is no file to write to
+pub fn write_chosen_dir(file: Option<&Path>) -> bool {
+    let file = match file {
+        Some(file) => file,
+        None => return false,
+    };
+
+    if let Err(e) = fs::write(file, picked_dir().as_encoded_bytes()) {
+        eprintln!("Couldn't write {}: {}", file.display(), e);
+    }
 
-    stdout().flush().unwrap();
-    exit(0);
+    true
 }
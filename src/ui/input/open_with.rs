@@ -0,0 +1,196 @@
+use super::hooks;
+use super::jobs;
+use super::plugins;
+use super::run_app::Command;
+use super::shell::{redirect, shell_command, substitute, suspend, wait_for_enter};
+use crate::app::app::{display_name, App};
+use crate::ui::display::block::block_binds;
+use crate::ui::display::pane::get_pwd;
+use anyhow::Result;
+use ratatui::backend::Backend;
+use ratatui::terminal::Terminal;
+use std::ffi::OsStr;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command as SysCommand;
+
+// one open_with= line from the config:
+//   open_with=mp4,mkv,video/*: mpv {} &
+// extensions, mime types (with type/* for all of a kind) or * for anything,
+// then the command, {} is the entry and {files} the move/copy buffer like in
+// ! commands, a trailing & runs it in the background
+#[derive(Clone)]
+pub struct Association {
+    pub patterns: Vec<String>,
+    pub command: String,
+    pub detached: bool,
+}
+
+fn parse(spec: &str) -> Option<Association> {
+    let (patterns, command) = spec.split_once(':')?;
+
+    let patterns = patterns
+        .split(',')
+        .map(|pattern| pattern.trim().trim_start_matches('.').to_lowercase())
+        .filter(|pattern| !pattern.is_empty())
+        .collect::<Vec<String>>();
+
+    let command = command.trim();
+    let (command, detached) = match command.strip_suffix('&') {
+        Some(command) => (command.trim_end(), true),
+        None => (command, false),
+    };
+
+    if patterns.is_empty() || command.is_empty() {
+        return None;
+    }
+
+    Some(Association {
+        patterns,
+        command: command.to_string(),
+        detached,
+    })
+}
+
+// called by read_config with every open_with= line
+pub fn configure(app: &mut App, specs: Vec<String>) {
+    app.associations = specs.iter().filter_map(|spec| parse(spec)).collect();
+}
+
+// asks `file`, only when some association needs it
+fn mime_type(path: &OsStr) -> Option<String> {
+    let output = SysCommand::new("file")
+        .arg("--brief")
+        .arg("--mime-type")
+        .arg(path)
+        .output()
+        .ok()?;
+
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .to_lowercase(),
+    )
+}
+
+fn matches(pattern: &str, extension: &str, mime: Option<&str>) -> bool {
+    if pattern == "*" {
+        return true;
+    }
+
+    match (pattern.split_once('/'), mime) {
+        (Some((kind, "*")), Some(mime)) => mime.split('/').next() == Some(kind),
+        (Some(_), Some(mime)) => pattern == mime,
+        (Some(_), None) => false,
+        (None, _) => pattern == extension,
+    }
+}
+
+// the associations for `entry`, in the order they're in the config
+fn matching(app: &App, entry: &OsStr) -> Vec<Association> {
+    let extension = Path::new(entry)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    let needs_mime = app
+        .associations
+        .iter()
+        .flat_map(|association| &association.patterns)
+        .any(|pattern| pattern.contains('/'));
+    let mime = if needs_mime { mime_type(entry) } else { None };
+
+    app.associations
+        .iter()
+        .filter(|association| {
+            association
+                .patterns
+                .iter()
+                .any(|pattern| matches(pattern, &extension, mime.as_deref()))
+        })
+        .cloned()
+        .collect()
+}
+
+// O: the commands the selected entry can be opened with
+pub fn handle_open_with(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    let entry = match app.selected_entry() {
+        Some(entry) if entry != ".." => entry,
+        _ => return app.notify_error("Nothing selected to open"),
+    };
+
+    let found = matching(app, &entry);
+    if found.is_empty() {
+        return app.notify_error(format!(
+            "Nothing to open {} with, add open_with= lines to the config",
+            display_name(&entry)
+        ));
+    }
+
+    app.open_with.items = found;
+    app.open_with.state.select(Some(0));
+    app.open_with_entry = entry;
+    app.show_open_with = true;
+    app.last_command = Some(Command::OpenWith);
+}
+
+// Enter in the menu, a foreground command gets the terminal until it exits
+pub fn run<B: Backend + Write>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()> {
+    let association = app
+        .open_with
+        .state
+        .selected()
+        .and_then(|idx| app.open_with.items.get(idx))
+        .cloned();
+
+    app.show_open_with = false;
+    app.last_command = None;
+
+    let association = match association {
+        Some(association) => association,
+        None => return Ok(()),
+    };
+    plugins::on_open(app);
+    let file = std::env::current_dir()
+        .unwrap_or_default()
+        .join(&app.open_with_entry);
+    hooks::on_open(app, &file);
+    let command = substitute(app, &association.command);
+
+    if association.detached {
+        jobs::spawn_command(app, &command);
+        return Ok(());
+    }
+
+    suspend(terminal, app, || {
+        let mut shell = shell_command(&command);
+        redirect(&mut shell, app);
+
+        // a viewer that exits fine goes straight back, errors stay readable
+        let failed = match shell.status() {
+            Ok(status) if status.success() => None,
+            Ok(status) => Some(match status.code() {
+                Some(code) => format!("exited with {}", code),
+                None => "was killed".to_string(),
+            }),
+            Err(e) => Some(format!("couldn't start: {}", e)),
+        };
+
+        if let Some(failed) = failed {
+            eprint!("\n[{}] Press Enter to return to traverse", failed);
+            wait_for_enter(app);
+        }
+    })?;
+
+    app.git_refreshed = None;
+    app.update_files();
+    app.update_dirs();
+    app.clamp_selection();
+    app.cur_dir = get_pwd();
+
+    Ok(())
+}
@@ -0,0 +1,265 @@
+use super::preview_search::{self, PreviewSearch};
+use crate::app::app::{display_name, App};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::ffi::OsString;
+use std::fs;
+
+// read whole into memory, bigger files go to $PAGER
+const MAX_SIZE: u64 = 64 * 1024 * 1024;
+// columns a Left or Right moves
+const SIDE_STEP: usize = 8;
+// lines shown above the match jumped to
+const CONTEXT: usize = 2;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Prompt {
+    Search,
+    Line,
+}
+
+// v: the selected file on the whole screen
+pub struct Pager {
+    pub path: OsString,
+    pub lines: Vec<String>,
+    // the first line and column shown
+    pub top: usize,
+    pub left: usize,
+    // rows for text, set when it's drawn
+    pub height: usize,
+    pub search: Option<PreviewSearch>,
+    // / or : being typed at the bottom
+    pub prompt: Option<(Prompt, String)>,
+}
+
+impl Pager {
+    fn last_top(&self) -> usize {
+        self.lines.len().saturating_sub(self.height.max(1))
+    }
+
+    fn scroll(&mut self, by: isize) {
+        self.top = self.top.saturating_add_signed(by).min(self.last_top());
+    }
+
+    fn page(&self) -> isize {
+        self.height.saturating_sub(1).max(1) as isize
+    }
+
+    // how far down the last line in view is, in percent
+    pub fn percent(&self) -> usize {
+        if self.lines.is_empty() {
+            return 100;
+        }
+        let bottom = (self.top + self.height).min(self.lines.len());
+        bottom * 100 / self.lines.len()
+    }
+}
+
+// reads the selected file into the pager, false when it's too big and
+// $PAGER should have it instead
+pub fn open(app: &mut App) -> bool {
+    let file = match app.files.state.selected().and(app.selected_entry()) {
+        Some(file) => file,
+        None => return true,
+    };
+
+    let contents = match fs::metadata(&file) {
+        Ok(metadata) if metadata.len() > MAX_SIZE => return false,
+        Ok(metadata) if metadata.is_file() => fs::read(&file),
+        Ok(_) => {
+            app.notify_error("Only files can be viewed");
+            return true;
+        }
+        Err(e) => Err(e),
+    };
+    let contents = match contents {
+        Ok(contents) if !contents.iter().take(1024).any(|byte| *byte == 0) => contents,
+        Ok(_) => {
+            app.notify_error("Not a text file");
+            return true;
+        }
+        Err(e) => {
+            app.show_error(&format!("Couldn't read {}", display_name(&file)), e);
+            return true;
+        }
+    };
+
+    // a tab is as wide as the terminal likes, four columns here
+    let lines = String::from_utf8_lossy(&contents)
+        .lines()
+        .map(|line| line.replace('\t', "    "))
+        .collect();
+
+    app.pager = Some(Pager {
+        path: file,
+        lines,
+        top: 0,
+        left: 0,
+        height: 0,
+        search: None,
+        prompt: None,
+    });
+    true
+}
+
+fn submit_search(app: &mut App, query: String) {
+    let pager = match app.pager.as_mut() {
+        Some(pager) => pager,
+        None => return,
+    };
+    pager.search = None;
+    if query.is_empty() {
+        return;
+    }
+
+    let mut search = PreviewSearch {
+        query,
+        file: pager.path.clone(),
+        matches: vec![],
+        current: 0,
+    };
+    let ignore_case = search.ignore_case();
+    search.matches = pager
+        .lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !preview_search::find(line, &search.query, ignore_case).is_empty())
+        .map(|(idx, _)| idx)
+        .collect();
+
+    if search.matches.is_empty() {
+        return app.notify_error(format!("No {} in the file", search.query));
+    }
+
+    // the first one from where the pager is
+    let from = pager.top + CONTEXT;
+    search.current = search
+        .matches
+        .iter()
+        .position(|line| *line >= from)
+        .unwrap_or(0);
+    pager.search = Some(search);
+    jump(app);
+}
+
+// n and N
+fn next(app: &mut App, forward: bool) {
+    let search = match app.pager.as_mut().and_then(|pager| pager.search.as_mut()) {
+        Some(search) => search,
+        None => return,
+    };
+
+    let len = search.matches.len();
+    search.current = if forward {
+        (search.current + 1) % len
+    } else {
+        (search.current + len - 1) % len
+    };
+    jump(app);
+}
+
+fn jump(app: &mut App) {
+    let pager = match app.pager.as_mut() {
+        Some(pager) => pager,
+        None => return,
+    };
+    let (line, current, len) = match &pager.search {
+        Some(search) => (
+            search.matches[search.current],
+            search.current + 1,
+            search.matches.len(),
+        ),
+        None => return,
+    };
+
+    pager.top = line.saturating_sub(CONTEXT).min(pager.last_top());
+    pager.left = 0;
+    app.notify(format!(
+        "Match {} of {}, on line {}",
+        current,
+        len,
+        line + 1
+    ));
+}
+
+fn go_to_line(app: &mut App, line: &str) {
+    let pager = match app.pager.as_mut() {
+        Some(pager) => pager,
+        None => return,
+    };
+    match line.trim().parse::<usize>() {
+        Ok(line) => pager.top = line.saturating_sub(1).min(pager.last_top()),
+        Err(_) if line.trim().is_empty() => {}
+        Err(_) => app.notify_error(format!("{} isn't a line number", line.trim())),
+    }
+}
+
+// typing after / or :
+fn handle_prompt(app: &mut App, key: &KeyEvent) {
+    let pager = match app.pager.as_mut() {
+        Some(pager) => pager,
+        None => return,
+    };
+    let (prompt, text) = match pager.prompt.as_mut() {
+        Some(prompt) => prompt,
+        None => return,
+    };
+
+    match key.code {
+        KeyCode::Esc => pager.prompt = None,
+        // backspacing past the start closes it, like less
+        KeyCode::Backspace if text.is_empty() => pager.prompt = None,
+        KeyCode::Backspace => {
+            text.pop();
+        }
+        KeyCode::Char(c) => text.push(c),
+        KeyCode::Enter => {
+            let (prompt, text) = (*prompt, std::mem::take(text));
+            pager.prompt = None;
+            match prompt {
+                Prompt::Search => submit_search(app, text),
+                Prompt::Line => go_to_line(app, &text),
+            }
+        }
+        _ => {}
+    }
+}
+
+// every key while the pager is open
+pub fn handle_key(app: &mut App, key: &KeyEvent) {
+    let pager = match app.pager.as_mut() {
+        Some(pager) => pager,
+        None => return,
+    };
+    if pager.prompt.is_some() {
+        return handle_prompt(app, key);
+    }
+
+    let control = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('v') => app.pager = None,
+        KeyCode::Char('c') if control => app.pager = None,
+        KeyCode::Char('d') | KeyCode::Char('f') if control => pager.scroll(pager.page()),
+        KeyCode::Char('u') | KeyCode::Char('b') if control => pager.scroll(-pager.page()),
+        KeyCode::Char('j') | KeyCode::Down | KeyCode::Enter => pager.scroll(1),
+        KeyCode::Char('k') | KeyCode::Up => pager.scroll(-1),
+        KeyCode::Char(' ') | KeyCode::PageDown => pager.scroll(pager.page()),
+        KeyCode::Char('b') | KeyCode::PageUp => pager.scroll(-pager.page()),
+        KeyCode::Char('g') | KeyCode::Home => pager.top = 0,
+        KeyCode::Char('G') | KeyCode::End => pager.top = pager.last_top(),
+        KeyCode::Char('l') | KeyCode::Right => pager.left += SIDE_STEP,
+        KeyCode::Char('h') | KeyCode::Left => pager.left = pager.left.saturating_sub(SIDE_STEP),
+        KeyCode::Char('0') => pager.left = 0,
+        KeyCode::Char('/') => pager.prompt = Some((Prompt::Search, String::new())),
+        KeyCode::Char(':') => pager.prompt = Some((Prompt::Line, String::new())),
+        KeyCode::Char('n') => next(app, true),
+        KeyCode::Char('N') => next(app, false),
+        _ => {}
+    }
+}
+
+// the mouse wheel
+pub fn scroll(app: &mut App, by: isize) {
+    if let Some(pager) = app.pager.as_mut() {
+        pager.scroll(by);
+    }
+}
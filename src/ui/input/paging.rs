@@ -0,0 +1,239 @@
+use super::stateful_list::StatefulList;
+use crate::app::app::{sort_dirs, sort_files, App};
+use crate::app::dir_cache::{Listed, Rest};
+use std::ffi::{OsStr, OsString};
+use std::fs::{DirEntry, ReadDir};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::SystemTime;
+
+// entries read at once, a directory with more lists the first ones right
+// away and reads the rest in the background a page at a time
+pub const PAGE: usize = 2000;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Pane {
+    Files,
+    Dirs,
+}
+
+// how far a pane's listing is read, a directory too big for one page
+// gets the rest in the background
+pub struct Pages {
+    // the directory read, a refresh of it keeps the old list until the new
+    // one is complete
+    dir: PathBuf,
+    rx: Option<Receiver<Vec<Listed>>>,
+    found: Vec<(OsString, String)>,
+    // every entry so far, for the cache once they're all in. Only one of
+    // the panes reading a directory keeps them
+    read: Option<Vec<Listed>>,
+    modified: Option<SystemTime>,
+    // entries in front that aren't sorted with the rest, ../
+    fixed: usize,
+    streaming: bool,
+}
+
+impl Pages {
+    pub fn reading(&self) -> bool {
+        self.rx.is_some()
+    }
+
+    // the pane shows what was read so far
+    pub fn streaming(&self) -> bool {
+        self.reading() && self.streaming
+    }
+}
+
+// the first page of `entries`, and the rest of them when there may be more
+pub fn first_page(mut entries: ReadDir) -> (Vec<DirEntry>, Option<ReadDir>) {
+    let page = entries
+        .by_ref()
+        .filter_map(|entry| entry.ok())
+        .take(PAGE)
+        .collect::<Vec<DirEntry>>();
+
+    if page.len() == PAGE {
+        (page, Some(entries))
+    } else {
+        (page, None)
+    }
+}
+
+// the rest of the entries, read once for both panes. Dropping both
+// receivers stops the thread
+pub fn spawn(entries: ReadDir) -> (Receiver<Vec<Listed>>, Receiver<Vec<Listed>>) {
+    let (files_tx, files_rx) = mpsc::channel();
+    let (dirs_tx, dirs_rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let mut senders = vec![files_tx, dirs_tx];
+        let mut batch = vec![];
+
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            batch.push(Listed::read(&entry));
+            if batch.len() == PAGE {
+                senders.retain(|tx| tx.send(batch.clone()).is_ok());
+                batch.clear();
+                if senders.is_empty() {
+                    return;
+                }
+            }
+        }
+
+        for tx in senders {
+            let _ = tx.send(batch.clone());
+        }
+    });
+
+    (files_rx, dirs_rx)
+}
+
+fn parts(app: &mut App, pane: Pane) -> (&mut StatefulList<(OsString, String)>, &mut Option<Pages>) {
+    match pane {
+        Pane::Files => (&mut app.files, &mut app.file_pages),
+        Pane::Dirs => (&mut app.dirs, &mut app.dir_pages),
+    }
+}
+
+// the pane gets `head` and the sorted first page, and reads on when `rest`
+// may have more
+pub fn list(
+    app: &mut App,
+    pane: Pane,
+    head: Vec<(OsString, String)>,
+    found: Vec<(OsString, String)>,
+    rest: Option<Rest>,
+) {
+    let dir = std::env::current_dir().unwrap_or_default();
+    let (list, pages) = parts(app, pane);
+    let fixed = head.len();
+
+    let rest = match rest {
+        Some(rest) => rest,
+        None => {
+            list.items = head;
+            list.items.extend(found);
+            *pages = Some(Pages {
+                dir,
+                rx: None,
+                found: vec![],
+                read: None,
+                modified: None,
+                fixed,
+                streaming: false,
+            });
+            return;
+        }
+    };
+
+    let streaming = !pages
+        .as_ref()
+        .is_some_and(|pages| pages.dir == dir && !pages.streaming());
+
+    let found = if streaming {
+        list.items = head;
+        list.items.extend(found);
+        vec![]
+    } else {
+        found
+    };
+
+    *pages = Some(Pages {
+        dir,
+        rx: Some(rest.rx),
+        found,
+        read: rest.read,
+        modified: rest.modified,
+        fixed,
+        streaming,
+    });
+}
+
+pub fn poll_pages(app: &mut App) {
+    poll(app, Pane::Files);
+    poll(app, Pane::Dirs);
+}
+
+fn poll(app: &mut App, pane: Pane) {
+    let rx = match parts(app, pane).1 {
+        Some(Pages { rx: Some(rx), .. }) => rx,
+        _ => return,
+    };
+
+    let mut read = vec![];
+    let mut done = false;
+    loop {
+        match rx.try_recv() {
+            Ok(mut batch) => read.append(&mut batch),
+            Err(mpsc::TryRecvError::Empty) => break,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                done = true;
+                break;
+            }
+        }
+    }
+
+    if read.is_empty() && !done {
+        return;
+    }
+
+    let mut entries = read
+        .iter()
+        .filter_map(|entry| match pane {
+            Pane::Files if entry.is_file => app.file_entry(entry),
+            Pane::Dirs if entry.is_dir => app.dir_entry(OsStr::new(""), entry),
+            _ => None,
+        })
+        .collect::<Vec<(OsString, String)>>();
+    let (pinned, sort_by) = (app.pinned.clone(), app.sort);
+    let sort = |entries: &mut [(OsString, String)]| match pane {
+        Pane::Files => sort_files(entries, &pinned, sort_by),
+        Pane::Dirs => sort_dirs(entries, sort_by),
+    };
+
+    let (list, pages) = parts(app, pane);
+    let pages = match pages {
+        Some(pages) => pages,
+        None => return,
+    };
+
+    // the highlighted entry stays highlighted while others are sorted in
+    // around it
+    let selected = list
+        .state
+        .selected()
+        .and_then(|idx| list.items.get(idx))
+        .map(|entry| entry.0.clone());
+
+    if let Some(all) = pages.read.as_mut() {
+        all.append(&mut read);
+    }
+    if pages.streaming {
+        list.items.append(&mut entries);
+        sort(&mut list.items[pages.fixed..]);
+    } else {
+        pages.found.append(&mut entries);
+        if done {
+            sort(&mut pages.found);
+            list.items.truncate(pages.fixed);
+            list.items.append(&mut pages.found);
+        }
+    }
+
+    if let Some(selected) = selected {
+        if let Some(idx) = list.items.iter().position(|entry| entry.0 == selected) {
+            list.state.select(Some(idx));
+        }
+    }
+
+    if done {
+        pages.rx = None;
+        let (dir, modified) = (pages.dir.clone(), pages.modified);
+        if let Some(read) = pages.read.take() {
+            app.dir_cache.insert(dir, modified, read);
+        }
+        app.clamp_selection();
+    }
+}
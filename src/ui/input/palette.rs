@@ -0,0 +1,419 @@
+use super::run_app::Command;
+use super::*;
+use crate::app::app::App;
+use crate::configuration::configuration::write_config;
+use crate::ui::display::block::block_binds;
+use crate::ui::display::layout::PaneLayout;
+use sublime_fuzzy::best_match;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Action {
+    NewEntry,
+    NewSymlink,
+    Rename,
+    Pin,
+    Properties,
+    Delete,
+    Extract,
+    Compress,
+    ExportTree,
+    AddToBuffer,
+    OpenBuffer,
+    ToggleHidden,
+    ToggleIgnored,
+    ChangeSort,
+    ToggleListing,
+    SwitchLayout,
+    ToggleDirTree,
+    Flatten,
+    FilterFiles,
+    ClearFilter,
+    Fzf,
+    Grep,
+    Navigator,
+    TypeAhead,
+    Bookmarks,
+    BookmarkDir,
+    BookmarkSelected,
+    EditBookmark,
+    GoToBookmark(usize),
+    Plugin(usize),
+    CopyWindowsPath,
+    OpenInWindows,
+    JumpList,
+    JumpBack,
+    JumpForward,
+    Frecent,
+    Mounts,
+    Treemap,
+    Kinds,
+    DiskUsage,
+    Diagnostics,
+    Diff,
+    Piped,
+    JobHistory,
+    Undo,
+    Redo,
+    Hash,
+    CopyPath,
+    CopyRelative,
+    CopyName,
+    CopyFiles,
+    PasteFiles,
+    Manifest,
+    Verify,
+    Themes,
+    GrowPreview,
+    ShrinkPreview,
+    ShellCommand,
+    OpenWith,
+    Connect,
+    Disconnect,
+    Shell,
+    Help,
+}
+
+// name and key of every action, the key is shown as a reminder
+const ACTIONS: [(&str, &str, Action); 59] = [
+    ("New file or directory", "n", Action::NewEntry),
+    ("New symlink", "L", Action::NewSymlink),
+    ("Rename", "r", Action::Rename),
+    ("Pin or unpin file", "P", Action::Pin),
+    ("Copy the absolute path", "y", Action::CopyPath),
+    (
+        "Copy the path relative to the start directory",
+        "CTRL + y",
+        Action::CopyRelative,
+    ),
+    ("Copy the file name", "Y", Action::CopyName),
+    (
+        "Copy files for pasting into GUI apps",
+        "C",
+        Action::CopyFiles,
+    ),
+    (
+        "Paste files copied in a GUI file manager",
+        "CTRL + v",
+        Action::PasteFiles,
+    ),
+    ("Properties and data streams", "i", Action::Properties),
+    ("Delete", "Delete", Action::Delete),
+    ("Undo the last file operation", "u", Action::Undo),
+    (
+        "Redo the last undone file operation",
+        "CTRL + r",
+        Action::Redo,
+    ),
+    ("Extract archive", "x", Action::Extract),
+    ("Compress into an archive", "X", Action::Compress),
+    (
+        "Export the tree as text, Markdown or HTML",
+        "R",
+        Action::ExportTree,
+    ),
+    ("Add to move/copy buffer", "c", Action::AddToBuffer),
+    ("Open move/copy buffer", "p", Action::OpenBuffer),
+    ("Toggle hidden files", "", Action::ToggleHidden),
+    ("Toggle git ignored files", "I", Action::ToggleIgnored),
+    (
+        "Change the sort order (name, size, modified)",
+        "",
+        Action::ChangeSort,
+    ),
+    (
+        "Toggle one list for files and directories",
+        "",
+        Action::ToggleListing,
+    ),
+    (
+        "Switch between two and three columns",
+        "",
+        Action::SwitchLayout,
+    ),
+    ("Toggle the directory tree", "", Action::ToggleDirTree),
+    (
+        "List every file below the current directory",
+        "F",
+        Action::Flatten,
+    ),
+    (
+        "Filter the files by glob or extension",
+        "*",
+        Action::FilterFiles,
+    ),
+    ("Clear the file filter", "", Action::ClearFilter),
+    ("Fuzzy finder", "w", Action::Fzf),
+    ("Search file contents", "", Action::Grep),
+    ("Navigate to a path", "f", Action::Navigator),
+    (
+        "Jump to an entry by typing its name",
+        "/",
+        Action::TypeAhead,
+    ),
+    ("Bookmarks", "b", Action::Bookmarks),
+    ("Bookmark current directory", "z", Action::BookmarkDir),
+    ("Bookmark selected entry", "Z", Action::BookmarkSelected),
+    ("Edit bookmark label and note", "e", Action::EditBookmark),
+    ("Jump list", "'", Action::JumpList),
+    ("Jump back", "CTRL + o", Action::JumpBack),
+    ("Jump forward", "CTRL + i", Action::JumpForward),
+    ("Frequent directories", "CTRL + j", Action::Frecent),
+    ("Drives and mount points", "D", Action::Mounts),
+    ("Treemap", "T", Action::Treemap),
+    ("File kinds below here", "K", Action::Kinds),
+    ("Disk usage", "U", Action::DiskUsage),
+    (
+        "Path diagnostics: depth, longest path, path limits",
+        "",
+        Action::Diagnostics,
+    ),
+    ("Compare the two buffered files", "=", Action::Diff),
+    ("Paths piped in with --from-stdin", "V", Action::Piped),
+    ("Jobs and copy/move/delete history", "J", Action::JobHistory),
+    (
+        "Checksums of the selected file: MD5, SHA1, SHA256, BLAKE3",
+        "H",
+        Action::Hash,
+    ),
+    ("Write SHA256SUMS manifest", "m", Action::Manifest),
+    ("Verify SHA256SUMS manifest", "M", Action::Verify),
+    ("Switch theme", "t", Action::Themes),
+    ("Grow preview", ">", Action::GrowPreview),
+    ("Shrink preview", "<", Action::ShrinkPreview),
+    ("Run shell command", "!", Action::ShellCommand),
+    ("Open with...", "O", Action::OpenWith),
+    (
+        "Connect over SFTP, FTP, WebDAV or S3: connect user@host, ftp://host, davs://host, s3://bucket",
+        "",
+        Action::Connect,
+    ),
+    ("Disconnect from the remote host", "", Action::Disconnect),
+    ("Drop to a shell here", "S", Action::Shell),
+    ("Help", "?", Action::Help),
+];
+
+fn entries(app: &App) -> Vec<(String, Action)> {
+    let mut entries = ACTIONS
+        .iter()
+        .map(|(name, key, action)| {
+            if key.is_empty() {
+                (name.to_string(), *action)
+            } else {
+                (format!("{}  ({})", name, key), *action)
+            }
+        })
+        .collect::<Vec<(String, Action)>>();
+
+    for (idx, path) in app.bookmarked_dirs.items.iter().enumerate() {
+        let name = match app.bookmark_notes.get(path) {
+            Some((label, _)) if !label.is_empty() => format!("Go to bookmark: {} {}", label, path),
+            _ => format!("Go to bookmark: {}", path),
+        };
+
+        entries.push((name, Action::GoToBookmark(idx)));
+    }
+
+    for (name, idx) in plugins::commands(app) {
+        entries.push((name, Action::Plugin(idx)));
+    }
+
+    if wsl::is_wsl() {
+        entries.push((
+            "Copy Windows path  (W)".to_string(),
+            Action::CopyWindowsPath,
+        ));
+        entries.push((
+            "Open with Windows app  (E)".to_string(),
+            Action::OpenInWindows,
+        ));
+    }
+
+    entries
+}
+
+pub fn handle_palette(app: &mut App, input: &mut String, input_active: &mut bool) {
+    if block_binds(app) {
+        return;
+    }
+
+    app.show_palette = true;
+    app.last_command = Some(Command::ShowPalette);
+
+    input.clear();
+    *input_active = true;
+
+    app.palette_query = input.clone();
+    app.palette.items = entries(app);
+    app.palette.state.select(Some(0));
+}
+
+// best matches first, the full list in its own order while nothing is typed
+pub fn filter(app: &mut App, input: &str) {
+    if app.palette_query == input {
+        return;
+    }
+
+    let mut matches = entries(app)
+        .into_iter()
+        .filter_map(|entry| {
+            if input.is_empty() {
+                return Some((0, entry));
+            }
+
+            best_match(input, &entry.0).map(|found| (found.score(), entry))
+        })
+        .collect::<Vec<(isize, (String, Action))>>();
+
+    matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+
+    app.palette.items = matches.into_iter().map(|(_, entry)| entry).collect();
+    app.palette.state.select(if app.palette.items.is_empty() {
+        None
+    } else {
+        Some(0)
+    });
+    app.palette_query = input.to_string();
+}
+
+// actions that take over the terminal are handed back to run_app
+pub fn execute(app: &mut App, input: &mut String, input_active: &mut bool) -> Option<Action> {
+    let action = app
+        .palette
+        .state
+        .selected()
+        .and_then(|idx| app.palette.items.get(idx))
+        .map(|entry| entry.1);
+    let typed = std::mem::take(input);
+
+    app.show_palette = false;
+    app.last_command = None;
+    *input_active = false;
+
+    // the commands that take an argument, typed out
+    if let Some(target) = typed.trim().strip_prefix("connect ") {
+        remote::connect(app, input_active, target.trim(), None);
+        return None;
+    }
+
+    let action = action?;
+    if app.remote.is_some() && !remote::allows(action) {
+        app.notify_error("Not available on a remote listing, :disconnect first");
+        return None;
+    }
+
+    match action {
+        Action::NewEntry => file_ops::handle_new_file(app, input_active),
+        Action::NewSymlink => file_ops::handle_new_symlink(app, input_active),
+        Action::Rename => file_ops::handle_rename(app, input, input_active),
+        Action::Pin => pins::toggle_pin(app),
+        Action::Properties => properties::handle_properties(app),
+        Action::CopyWindowsPath => wsl::copy_windows_path(app),
+        Action::OpenInWindows => wsl::open_in_windows(app),
+        Action::Delete => file_ops::handle_delete(app),
+        Action::Extract => file_ops::extract(app, input, input_active),
+        Action::Compress => compress::handle_compress(app),
+        Action::ExportTree => export::handle_export(app),
+        Action::AddToBuffer => file_ops::add_to_selected(app),
+        Action::OpenBuffer => {
+            if app.files.state.selected().is_some() || app.dirs.state.selected().is_some() {
+                app.show_ops_menu = true;
+            }
+        }
+        Action::ToggleHidden => {
+            // the config is read on every refresh, so it has to hold the new value
+            app.show_hidden = !app.show_hidden;
+            write_config("show_hidden", &app.show_hidden.to_string());
+            app.notify(if app.show_hidden {
+                "Showing hidden files"
+            } else {
+                "Hiding hidden files"
+            });
+            app.update_files();
+            app.update_dirs();
+        }
+        Action::ToggleIgnored => {
+            app.hide_ignored = !app.hide_ignored;
+            app.update_files();
+            app.update_dirs();
+        }
+        Action::ChangeSort => {
+            // kept in the config like the hidden files, which is read on
+            // every refresh
+            app.sort = app.sort.next();
+            write_config("sort", app.sort.config_name());
+            app.notify(format!("Sorting by {}", app.sort.config_name()));
+            app.update_files();
+            app.update_dirs();
+        }
+        Action::ToggleListing => {
+            app.unified_listing = !app.unified_listing;
+            write_config("unified_listing", &app.unified_listing.to_string());
+        }
+        Action::SwitchLayout => {
+            app.layout = match app.layout {
+                PaneLayout::Split => PaneLayout::Miller,
+                PaneLayout::Miller => PaneLayout::Split,
+            };
+            write_config("layout", app.layout.config_name());
+        }
+        Action::ToggleDirTree => {
+            app.dir_tree = !app.dir_tree;
+            write_config("dir_tree", &app.dir_tree.to_string());
+            app.update_dirs();
+            app.clamp_selection();
+        }
+        Action::Flatten => flatten::handle_flatten(app),
+        Action::FilterFiles => filter::handle_filter(app, input, input_active),
+        Action::ClearFilter => filter::clear(app),
+        Action::Fzf => {
+            if !nav::resume_fzf(app, input, input_active) {
+                nav::handle_fzf(app, input, input_active);
+            }
+        }
+        Action::Navigator => nav::handle_nav(app, input_active),
+        Action::TypeAhead => typeahead::handle_type_ahead(app),
+        Action::Bookmarks => bookmark::handle_bookmark(app),
+        Action::BookmarkDir => bookmark::add_bookmark(app),
+        Action::BookmarkSelected => bookmark::add_selected_bookmark(app),
+        Action::EditBookmark => bookmark::handle_edit_bookmark(app, input, input_active),
+        Action::GoToBookmark(idx) => {
+            app.bookmarked_dirs.state.select(Some(idx));
+            submit::handle_open_bookmark(app);
+        }
+        Action::Plugin(idx) => plugins::run_command(app, idx),
+        Action::JumpList => jumps::handle_jumps(app),
+        Action::JumpBack => jumps::jump_back(app),
+        Action::JumpForward => jumps::jump_forward(app),
+        Action::Frecent => frecent::handle_frecent(app, input, input_active),
+        Action::Grep => grep::handle_grep(app, input, input_active),
+        Action::Mounts => mounts::handle_mounts(app),
+        Action::Treemap => treemap::handle_treemap(app),
+        Action::Kinds => kinds::handle_kinds(app),
+        Action::DiskUsage => du::handle_du(app),
+        Action::Diagnostics => diagnostics::handle_diagnostics(app),
+        Action::Diff => diff::handle_diff(app),
+        Action::Piped => piped::handle_piped(app),
+        Action::Hash => checksum::handle_hash(app),
+        Action::CopyPath => clipboard::copy_selected(app, clipboard::PathKind::Path),
+        Action::CopyRelative => clipboard::copy_selected(app, clipboard::PathKind::Relative),
+        Action::CopyName => clipboard::copy_selected(app, clipboard::PathKind::Name),
+        Action::CopyFiles => clipboard::copy_files(app),
+        Action::PasteFiles => clipboard::paste_files(app),
+        Action::Manifest => checksum::handle_manifest(app),
+        Action::Verify => checksum::handle_verify(app),
+        Action::Themes => themes::handle_themes(app),
+        Action::GrowPreview => mouse::resize_preview(app, true),
+        Action::ShrinkPreview => mouse::resize_preview(app, false),
+        Action::ShellCommand => shell::handle_shell_command(app, input_active),
+        Action::OpenWith => open_with::handle_open_with(app),
+        Action::Connect => remote::handle_connect(app, input, input_active),
+        Action::Disconnect => remote::disconnect(app),
+        Action::JobHistory => jobs::show_history(app),
+        Action::Undo => undo::undo(app),
+        Action::Redo => undo::redo(app),
+        Action::Shell => return Some(action),
+        Action::Help => help::handle_help(app),
+    }
+
+    None
+}
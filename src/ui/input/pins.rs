@@ -0,0 +1,48 @@
+use crate::app::app::App;
+use crate::configuration::directories::set_dir_values;
+use crate::ui::display::block::block_binds;
+
+// pins the selected file to the top of the Files pane, or unpins it
+pub fn toggle_pin(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    let name = match app.files.state.selected() {
+        Some(idx) => match app.files.items.get(idx) {
+            Some(entry) => entry.0.clone(),
+            None => return,
+        },
+        None => return,
+    };
+    // pins are kept as text in the config
+    let name = match name.into_string() {
+        Ok(name) => name,
+        Err(_) => return app.notify_error("Only names in UTF-8 can be pinned"),
+    };
+
+    let cwd = match std::env::current_dir() {
+        Ok(cwd) => cwd,
+        Err(_) => return,
+    };
+
+    let mut pinned = app.pinned.clone();
+    match pinned.iter().position(|pin| *pin == name) {
+        Some(idx) => {
+            pinned.remove(idx);
+            app.notify(format!("Unpinned {}", name));
+        }
+        None => {
+            pinned.push(name.clone());
+            app.notify(format!("Pinned {}", name));
+        }
+    }
+
+    set_dir_values(&cwd, "pin", &pinned);
+    app.update_files();
+
+    // keep the selection on the file that moved
+    if let Some(idx) = app.files.items.iter().position(|entry| entry.0 == *name) {
+        app.files.state.select(Some(idx));
+    }
+}
@@ -0,0 +1,190 @@
+use super::jumps;
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use crate::ui::display::pane::get_pwd;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io::{self, BufRead, IsTerminal};
+use std::path::{Path, PathBuf};
+
+// absolute, with the directories resolved but not the entry itself, so a
+// piped symlink stays the symlink
+fn absolute(path: &Path) -> Option<PathBuf> {
+    fs::symlink_metadata(path).ok()?;
+
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) if !parent.as_os_str().is_empty() => {
+            Some(fs::canonicalize(parent).ok()?.join(name))
+        }
+        (_, Some(name)) => Some(std::env::current_dir().ok()?.join(name)),
+        _ => fs::canonicalize(path).ok(),
+    }
+}
+
+// --from-stdin: the paths piped in, one per line, resolved against the
+// directory traverse was started in, and how many of them don't exist
+pub fn read_stdin() -> Result<(Vec<PathBuf>, usize), String> {
+    let stdin = io::stdin();
+
+    if stdin.is_terminal() {
+        return Err(
+            "--from-stdin needs paths piped in, e.g. find . -name '*.log' | rt --from-stdin"
+                .to_string(),
+        );
+    }
+
+    let mut paths: Vec<PathBuf> = vec![];
+    let mut missing = 0;
+
+    // split on bytes, a name that isn't UTF-8 is still a path
+    for line in stdin.lock().split(b'\n') {
+        let line = line.map_err(|err| format!("couldn't read stdin: {}", err))?;
+        let line = line.strip_suffix(b"\r").unwrap_or(&line);
+
+        if line.is_empty() {
+            continue;
+        }
+
+        match absolute(&path_from_bytes(line)) {
+            Some(path) => {
+                if !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+            None => missing += 1,
+        }
+    }
+
+    Ok((paths, missing))
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+
+    PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+
+// Windows hands over text, there's nothing but UTF-8 to expect
+#[cfg(windows)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).to_string())
+}
+
+// the piped paths make up their own listing and start out marked, which is
+// the move/copy buffer, so everything that works on the buffer works on them
+pub fn load_paths(app: &mut App, paths: Vec<PathBuf>, missing: usize) {
+    app.from_stdin = true;
+
+    for path in &paths {
+        if !app.selected_files.contains(path) {
+            app.selected_files.push(path.clone());
+        }
+    }
+
+    app.piped.items = paths;
+    app.piped.state.select(if app.piped.items.is_empty() {
+        None
+    } else {
+        Some(0)
+    });
+    app.show_piped = true;
+
+    match missing {
+        0 => app.notify(format!("{} paths piped in", app.piped.items.len())),
+        _ => app.notify_error(format!(
+            "{} paths piped in, {} skipped that don't exist",
+            app.piped.items.len(),
+            missing
+        )),
+    }
+}
+
+pub fn handle_piped(app: &mut App) {
+    if app.show_piped {
+        app.show_piped = false;
+        return;
+    }
+
+    if block_binds(app) {
+        return;
+    }
+
+    if !app.from_stdin {
+        return app.notify_error("Nothing was piped in, start with --from-stdin");
+    }
+
+    app.show_piped = true;
+}
+
+pub fn toggle_mark(app: &mut App) {
+    let path = match app
+        .piped
+        .state
+        .selected()
+        .and_then(|idx| app.piped.items.get(idx))
+    {
+        Some(path) => path.clone(),
+        None => return,
+    };
+
+    match app.selected_files.iter().position(|file| *file == path) {
+        Some(idx) => {
+            app.selected_files.remove(idx);
+        }
+        None => app.selected_files.push(path),
+    }
+}
+
+// jumps to the directory of the highlighted path with it selected, the
+// listing stays a key away
+pub fn handle_open_piped(app: &mut App) {
+    let path = match app
+        .piped
+        .state
+        .selected()
+        .and_then(|idx| app.piped.items.get(idx))
+    {
+        Some(path) => path.clone(),
+        None => return,
+    };
+
+    let (dir, name) = match (path.parent(), path.file_name()) {
+        (Some(dir), Some(name)) => (dir.to_path_buf(), name.to_os_string()),
+        _ => return,
+    };
+
+    if !jumps::change_dir(app, &dir) {
+        return;
+    }
+
+    app.show_piped = false;
+    app.update_files();
+    app.update_dirs();
+
+    if let Some(idx) = app.files.items.iter().position(|item| item.0 == name) {
+        app.files.state.select(Some(idx));
+        app.dirs.state.select(None);
+    } else if let Some(idx) = app.dirs.items.iter().position(|item| item.0 == name) {
+        app.dirs.state.select(Some(idx));
+        app.files.state.select(None);
+    }
+
+    app.cur_dir = get_pwd();
+}
+
+// what quitting prints, one marked path per line, for the next command in
+// the pipeline
+pub fn picked(app: &App) -> Option<OsString> {
+    if app.selected_files.is_empty() {
+        None
+    } else {
+        Some(
+            app.selected_files
+                .iter()
+                .map(|file| file.as_os_str())
+                .collect::<Vec<&OsStr>>()
+                .join(OsStr::new("\n")),
+        )
+    }
+}
@@ -0,0 +1,398 @@
+use super::preflight::Confirm;
+use crate::app::app::{display_name, App};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// steps and problems listed in a report, the rest are only counted
+const LISTED: usize = 15;
+
+// one entry of the buffer and where it lands
+#[derive(Clone)]
+pub struct Step {
+    pub source: PathBuf,
+    pub target: PathBuf,
+}
+
+// the order the buffer goes in, what's left out and what can't go at all
+#[derive(Clone)]
+pub struct Plan {
+    pub steps: Vec<Step>,
+    pub skipped: Vec<String>,
+    pub errors: Vec<String>,
+    pub reordered: bool,
+}
+
+// whether `dir` takes "a" and "A" for one name, found out by looking up one
+// of the names already in it with its case flipped. Nothing is written there,
+// and with no name to try it's what the platform usually does
+fn ignores_case(dir: &Path, here: &[OsString]) -> bool {
+    for name in here.iter().filter_map(|name| name.to_str()) {
+        let flipped = name
+            .chars()
+            .flat_map(|c| {
+                if c.is_lowercase() {
+                    c.to_uppercase().collect::<Vec<char>>()
+                } else {
+                    c.to_lowercase().collect::<Vec<char>>()
+                }
+            })
+            .collect::<String>();
+        if flipped == name {
+            continue;
+        }
+
+        // both names listed, so they're two entries
+        if here.iter().any(|other| *other == *flipped) {
+            return false;
+        }
+        return fs::symlink_metadata(dir.join(flipped)).is_ok();
+    }
+
+    cfg!(any(windows, target_os = "macos"))
+}
+
+fn kind(is_dir: bool) -> &'static str {
+    if is_dir {
+        "directory"
+    } else {
+        "file"
+    }
+}
+
+// everything that would fail or lose data halfway through is an error up
+// front, moves out of a buffered directory go before the directory itself
+pub fn plan(sources: &[PathBuf], dest: &Path, is_move: bool) -> Plan {
+    let verb = if is_move { "move" } else { "copy" };
+    let dest = fs::canonicalize(dest).unwrap_or_else(|_| dest.to_path_buf());
+
+    // what's already there, by lowercase name, for clashes that only show on
+    // filesystems ignoring case
+    let listing = fs::read_dir(&dest)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name())
+                .collect::<Vec<OsString>>()
+        })
+        .unwrap_or_default();
+    let existing = listing
+        .iter()
+        .map(|name| (name.to_string_lossy().to_lowercase(), name.clone()))
+        .collect::<HashMap<String, OsString>>();
+    let mut dest_ignores_case = None;
+
+    let mut plan = Plan {
+        steps: vec![],
+        skipped: vec![],
+        errors: vec![],
+        reordered: false,
+    };
+    // lowercase name -> source, among the buffer
+    let mut names: HashMap<String, &PathBuf> = HashMap::new();
+
+    for path in sources {
+        let source = display_name(path);
+
+        let name = match path.file_name() {
+            Some(name) => name,
+            None => {
+                plan.errors
+                    .push(format!("{}: has no name to {} it by", source, verb));
+                continue;
+            }
+        };
+
+        let metadata = match fs::symlink_metadata(path) {
+            Ok(metadata) => metadata,
+            Err(_) => {
+                plan.errors.push(format!("{}: no longer exists", source));
+                continue;
+            }
+        };
+
+        // directories are compared with their symlinks resolved, the entry
+        // itself as it is, so a symlink to a parent can still be moved
+        let resolved = path
+            .parent()
+            .and_then(|parent| fs::canonicalize(parent).ok())
+            .map(|parent| parent.join(name))
+            .unwrap_or_else(|| path.to_path_buf());
+
+        if resolved.parent() == Some(dest.as_path()) {
+            plan.skipped.push(format!("{}: already here", source));
+            continue;
+        }
+
+        if metadata.is_dir() && dest.starts_with(&resolved) {
+            plan.errors.push(format!(
+                "{}: can't {} a directory into itself",
+                source, verb
+            ));
+            continue;
+        }
+
+        let key = name.to_string_lossy().to_lowercase();
+        if let Some(other) = names.get(&key) {
+            if other.file_name() == path.file_name() {
+                plan.errors.push(format!(
+                    "{}: same name as {}, one would replace the other",
+                    source,
+                    display_name(other)
+                ));
+                continue;
+            }
+
+            if *dest_ignores_case.get_or_insert_with(|| ignores_case(&dest, &listing)) {
+                plan.errors.push(format!(
+                    "{}: differs from {} only in case, which {} ignores",
+                    source,
+                    display_name(other),
+                    dest.display()
+                ));
+                continue;
+            }
+        }
+        names.insert(key.clone(), path);
+
+        let target = dest.join(name);
+
+        if let Some(here) = existing.get(&key) {
+            if sources.iter().any(|other| *other == dest.join(here)) {
+                plan.errors.push(format!(
+                    "{}: would replace {}, which is in the buffer too",
+                    source,
+                    display_name(here)
+                ));
+                continue;
+            }
+
+            if *here != name {
+                if *dest_ignores_case.get_or_insert_with(|| ignores_case(&dest, &listing)) {
+                    plan.errors.push(format!(
+                        "{}: would replace {}, the names differ only in case, which {} ignores",
+                        source,
+                        display_name(here),
+                        dest.display()
+                    ));
+                    continue;
+                }
+            } else if let Ok(there) = fs::symlink_metadata(&target) {
+                // a file over a file is replaced like mv and cp do, anything
+                // else fails halfway through
+                if there.is_dir() != metadata.is_dir() {
+                    plan.errors.push(format!(
+                        "{}: a {} of that name is already here",
+                        source,
+                        kind(there.is_dir())
+                    ));
+                    continue;
+                }
+
+                let empty = fs::read_dir(&target)
+                    .map(|mut entries| entries.next().is_none())
+                    .unwrap_or(false);
+                if is_move && there.is_dir() && !empty {
+                    plan.errors.push(format!(
+                        "{}: a directory of that name is already here and isn't empty",
+                        source
+                    ));
+                    continue;
+                }
+            }
+        }
+
+        plan.steps.push(Step {
+            source: resolved,
+            target,
+        });
+    }
+
+    // whatever is inside another buffered directory leaves it first, the
+    // sort is stable so the rest keeps the buffer's order
+    if is_move {
+        let inside = |step: &Step| {
+            plan.steps
+                .iter()
+                .filter(|other| {
+                    other.source != step.source && other.source.starts_with(&step.source)
+                })
+                .count()
+        };
+        let counts = plan.steps.iter().map(inside).collect::<Vec<usize>>();
+
+        if counts.iter().any(|count| *count > 0) {
+            let mut order = (0..plan.steps.len()).collect::<Vec<usize>>();
+            order.sort_by_key(|idx| counts[*idx]);
+
+            plan.reordered = order.iter().enumerate().any(|(at, idx)| at != *idx);
+            plan.steps = order
+                .into_iter()
+                .map(|idx| plan.steps[idx].clone())
+                .collect();
+        }
+    }
+
+    plan
+}
+
+fn listed(items: &[String], lines: &mut Vec<String>) {
+    lines.extend(items.iter().take(LISTED).map(|item| format!("  {}", item)));
+
+    if items.len() > LISTED {
+        lines.push(format!("  ... and {} more", items.len() - LISTED));
+    }
+}
+
+pub fn report_errors(app: &mut App, plan: &Plan, is_move: bool) {
+    let mut lines = vec![format!(
+        "{} of the buffered entries can't be {} here:",
+        plan.errors.len(),
+        if is_move { "moved" } else { "copied" }
+    )];
+    listed(&plan.errors, &mut lines);
+    lines.push(String::new());
+    lines.push("Nothing was changed.".to_string());
+
+    app.open_report(if is_move { "Move plan" } else { "Copy plan" }, lines);
+}
+
+// a plan that isn't the buffer as it is gets shown before it runs
+pub fn hold(app: &mut App, plan: Plan, is_move: bool) {
+    let mut lines = vec![format!(
+        "{} {} entries in this order:",
+        if is_move { "Moving" } else { "Copying" },
+        plan.steps.len()
+    )];
+    let steps = plan
+        .steps
+        .iter()
+        .enumerate()
+        .map(|(idx, step)| {
+            format!(
+                "{}. {} -> {}",
+                idx + 1,
+                display_name(&step.source),
+                display_name(&step.target)
+            )
+        })
+        .collect::<Vec<String>>();
+    listed(&steps, &mut lines);
+
+    if !plan.skipped.is_empty() {
+        lines.push(String::new());
+        lines.push("Left out:".to_string());
+        listed(&plan.skipped, &mut lines);
+    }
+
+    lines.push(String::new());
+    lines.push("Press Enter to go ahead, Esc to cancel.".to_string());
+
+    app.open_report(if is_move { "Move plan" } else { "Copy plan" }, lines);
+    app.pending_confirm = Some(Confirm::Plan {
+        is_move,
+        steps: plan.steps,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a fresh directory under the temp dir, gone again when dropped
+    struct Scratch(PathBuf);
+
+    impl Scratch {
+        fn new(name: &str) -> Scratch {
+            let dir = std::env::temp_dir().join(format!("rt-plan-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Scratch(fs::canonicalize(dir).unwrap())
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn moves_out_of_a_buffered_directory_first() {
+        let scratch = Scratch::new("order");
+        let (dir, dest) = (scratch.0.join("dir"), scratch.0.join("dest"));
+        fs::create_dir_all(dir.join("inner")).unwrap();
+        fs::create_dir_all(&dest).unwrap();
+        fs::write(dir.join("inner").join("file"), "").unwrap();
+
+        let sources = [
+            dir.clone(),
+            dir.join("inner"),
+            dir.join("inner").join("file"),
+        ];
+        let plan = plan(&sources, &dest, true);
+
+        assert!(plan.errors.is_empty());
+        assert!(plan.reordered);
+        let order = plan
+            .steps
+            .iter()
+            .map(|step| step.source.clone())
+            .collect::<Vec<PathBuf>>();
+        assert_eq!(order, [sources[2].clone(), sources[1].clone(), dir]);
+    }
+
+    #[test]
+    fn keeps_the_buffer_order_for_copies() {
+        let scratch = Scratch::new("copy");
+        let (dir, dest) = (scratch.0.join("dir"), scratch.0.join("dest"));
+        fs::create_dir_all(dir.join("inner")).unwrap();
+        fs::create_dir_all(&dest).unwrap();
+
+        let plan = plan(&[dir.clone(), dir.join("inner")], &dest, false);
+
+        assert!(!plan.reordered);
+        assert_eq!(plan.steps[0].source, dir);
+    }
+
+    #[test]
+    fn refuses_a_directory_into_itself() {
+        let scratch = Scratch::new("itself");
+        let dir = scratch.0.join("dir");
+        fs::create_dir_all(dir.join("inner")).unwrap();
+
+        let plan = plan(&[dir.clone()], &dir.join("inner"), true);
+
+        assert!(plan.steps.is_empty());
+        assert_eq!(plan.errors.len(), 1);
+        assert!(plan.errors[0].contains("into itself"));
+    }
+
+    #[test]
+    fn skips_what_is_already_here() {
+        let scratch = Scratch::new("here");
+        fs::write(scratch.0.join("file"), "").unwrap();
+
+        let plan = plan(&[scratch.0.join("file")], &scratch.0, false);
+
+        assert!(plan.steps.is_empty());
+        assert_eq!(plan.skipped.len(), 1);
+    }
+
+    #[test]
+    fn tells_case_by_an_existing_name() {
+        let scratch = Scratch::new("case");
+        fs::write(scratch.0.join("Name"), "").unwrap();
+        let listing = [OsString::from("Name")];
+
+        // whatever the filesystem does, flipping the case asks it
+        let ignores = fs::symlink_metadata(scratch.0.join("nAME")).is_ok();
+        assert_eq!(ignores_case(&scratch.0, &listing), ignores);
+        // two entries differing only in case settle it without asking
+        let both = [OsString::from("a"), OsString::from("A")];
+        assert!(!ignores_case(&scratch.0, &both));
+        // nothing written there to find out
+        assert_eq!(fs::read_dir(&scratch.0).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,601 @@
+// without the feature nothing registers, the rest stays for the calls
+#![cfg_attr(not(feature = "plugins"), allow(dead_code))]
+
+use super::jobs;
+use super::jumps;
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use dirs::config_dir;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// what a plugin asked traverse to do while it ran, done once it's back
+enum Effect {
+    Notify(String),
+    Error(String),
+    Cd(PathBuf),
+    Spawn(String),
+    Refresh,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Hook {
+    Select,
+    EnterDir,
+    Open,
+}
+
+impl Hook {
+    fn parse(name: &str) -> Option<Hook> {
+        match name.trim_start_matches("on_") {
+            "select" => Some(Hook::Select),
+            "enter_dir" => Some(Hook::EnterDir),
+            "open" => Some(Hook::Open),
+            _ => None,
+        }
+    }
+}
+
+// a key as plugins write it: K, ctrl+k, alt+x or f5
+struct Key {
+    code: KeyCode,
+    control: bool,
+    alt: bool,
+}
+
+impl Key {
+    fn parse(text: &str) -> Option<Key> {
+        let mut key = Key {
+            code: KeyCode::Null,
+            control: false,
+            alt: false,
+        };
+        let mut rest = text.trim();
+
+        loop {
+            let lower = rest.to_lowercase();
+            if lower.starts_with("ctrl+") {
+                key.control = true;
+            } else if lower.starts_with("alt+") {
+                key.alt = true;
+            } else {
+                break;
+            }
+            rest = &rest[rest.find('+')? + 1..];
+        }
+
+        let mut chars = rest.chars();
+        key.code = match (chars.next()?, chars.next()) {
+            (c, None) => KeyCode::Char(c),
+            _ => match rest.to_lowercase().strip_prefix('f')?.parse() {
+                Ok(number @ 1..=12) => KeyCode::F(number),
+                _ => return None,
+            },
+        };
+        Some(key)
+    }
+
+    // shift is in the character already
+    fn matches(&self, event: &KeyEvent) -> bool {
+        let code = match (event.code, self.control) {
+            (KeyCode::Char(c), true) => KeyCode::Char(c.to_ascii_lowercase()),
+            (code, _) => code,
+        };
+        let wanted = match (self.code, self.control) {
+            (KeyCode::Char(c), true) => KeyCode::Char(c.to_ascii_lowercase()),
+            (code, _) => code,
+        };
+
+        code == wanted
+            && event.modifiers.contains(KeyModifiers::CONTROL) == self.control
+            && event.modifiers.contains(KeyModifiers::ALT) == self.alt
+    }
+}
+
+// an entry in the palette, run with the function's index in the handlers
+pub struct PluginCommand {
+    pub name: String,
+    pub key: Option<String>,
+    handler: usize,
+}
+
+// what the scripts registered and asked for while they ran
+#[derive(Default)]
+struct Registered {
+    commands: Vec<PluginCommand>,
+    bindings: Vec<(Key, usize)>,
+    // extensions, or * for any file
+    previewers: Vec<(Vec<String>, usize)>,
+    hooks: Vec<(Hook, usize)>,
+    effects: Vec<Effect>,
+}
+
+impl Registered {
+    fn merge(&mut self, mut other: Registered) -> Vec<Effect> {
+        self.commands.append(&mut other.commands);
+        self.bindings.append(&mut other.bindings);
+        self.previewers.append(&mut other.previewers);
+        self.hooks.append(&mut other.hooks);
+        other.effects
+    }
+}
+
+// what a plugin function is given: the context, or the path to preview
+enum Arg {
+    Context {
+        cwd: String,
+        selected: Option<String>,
+        buffer: Vec<String>,
+    },
+    Path(String),
+}
+
+#[derive(Default)]
+pub struct Plugins {
+    #[cfg(feature = "plugins")]
+    lua: Option<mlua::Lua>,
+    registered: Registered,
+    // where the hooks last ran, so they run on a change only
+    last_dir: Option<PathBuf>,
+    last_selected: Option<PathBuf>,
+    // the file a plugin last previewed, when it was changed and the lines
+    preview: Option<(PathBuf, Option<SystemTime>, Option<Vec<String>>)>,
+}
+
+// the scripts, run in name order
+fn plugin_dir() -> Option<PathBuf> {
+    Some(config_dir()?.join("traverse/plugins"))
+}
+
+#[cfg(feature = "plugins")]
+mod engine {
+    use super::{Arg, Effect, Hook, Key, PluginCommand, Registered};
+    use mlua::{FromLua, Function, Lua, Table, Value};
+    use std::path::PathBuf;
+
+    const HANDLERS: &str = "traverse_handlers";
+
+    // keeps `function` in the handlers table, returns its index
+    fn keep(lua: &Lua, function: Function) -> mlua::Result<usize> {
+        let handlers: Table = lua.named_registry_value(HANDLERS)?;
+        let idx = handlers.raw_len() + 1;
+        handlers.raw_set(idx, function)?;
+        Ok(idx)
+    }
+
+    fn registered(lua: &Lua) -> mlua::Result<mlua::AppDataRefMut<'_, Registered>> {
+        lua.app_data_mut::<Registered>()
+            .ok_or_else(|| mlua::Error::runtime("traverse isn't listening"))
+    }
+
+    fn effect(lua: &Lua, effect: Effect) -> mlua::Result<()> {
+        registered(lua)?.effects.push(effect);
+        Ok(())
+    }
+
+    // the `traverse` table the scripts call into
+    fn api(lua: &Lua) -> mlua::Result<Table<'_>> {
+        let api = lua.create_table()?;
+
+        api.set(
+            "command",
+            lua.create_function(
+                |lua, (name, function, key): (String, Function, Option<String>)| {
+                    let handler = keep(lua, function)?;
+                    let mut registered = registered(lua)?;
+                    if let Some(key) = &key {
+                        let parsed = Key::parse(key)
+                            .ok_or_else(|| mlua::Error::runtime(format!("no such key {}", key)))?;
+                        registered.bindings.push((parsed, handler));
+                    }
+                    registered
+                        .commands
+                        .push(PluginCommand { name, key, handler });
+                    Ok(())
+                },
+            )?,
+        )?;
+
+        api.set(
+            "bind",
+            lua.create_function(|lua, (key, function): (String, Function)| {
+                let parsed = Key::parse(&key)
+                    .ok_or_else(|| mlua::Error::runtime(format!("no such key {}", key)))?;
+                let handler = keep(lua, function)?;
+                registered(lua)?.bindings.push((parsed, handler));
+                Ok(())
+            })?,
+        )?;
+
+        api.set(
+            "previewer",
+            lua.create_function(|lua, (patterns, function): (Value, Function)| {
+                let patterns = match patterns {
+                    Value::Table(table) => table
+                        .sequence_values::<String>()
+                        .collect::<mlua::Result<Vec<String>>>()?,
+                    value => vec![String::from_lua(value, lua)?],
+                };
+                let patterns = patterns
+                    .iter()
+                    .map(|pattern| pattern.trim_start_matches('.').to_lowercase())
+                    .collect();
+                let handler = keep(lua, function)?;
+                registered(lua)?.previewers.push((patterns, handler));
+                Ok(())
+            })?,
+        )?;
+
+        api.set(
+            "on",
+            lua.create_function(|lua, (event, function): (String, Function)| {
+                let hook = Hook::parse(&event).ok_or_else(|| {
+                    mlua::Error::runtime(format!(
+                        "no event {}, there's select, enter_dir and open",
+                        event
+                    ))
+                })?;
+                let handler = keep(lua, function)?;
+                registered(lua)?.hooks.push((hook, handler));
+                Ok(())
+            })?,
+        )?;
+
+        api.set(
+            "notify",
+            lua.create_function(|lua, text: String| effect(lua, Effect::Notify(text)))?,
+        )?;
+        api.set(
+            "error",
+            lua.create_function(|lua, text: String| effect(lua, Effect::Error(text)))?,
+        )?;
+        api.set(
+            "cd",
+            lua.create_function(|lua, path: String| effect(lua, Effect::Cd(PathBuf::from(path))))?,
+        )?;
+        api.set(
+            "spawn",
+            lua.create_function(|lua, command: String| effect(lua, Effect::Spawn(command)))?,
+        )?;
+        api.set(
+            "refresh",
+            lua.create_function(|lua, ()| effect(lua, Effect::Refresh))?,
+        )?;
+
+        Ok(api)
+    }
+
+    // runs every script in `scripts`, a broken one doesn't stop the rest
+    pub fn load(scripts: &[PathBuf]) -> (Option<Lua>, Registered, Vec<String>) {
+        let lua = Lua::new();
+        let mut errors = vec![];
+
+        let setup = lua
+            .create_table()
+            .and_then(|handlers| lua.set_named_registry_value(HANDLERS, handlers))
+            .and_then(|_| lua.globals().set("traverse", api(&lua)?));
+        if let Err(e) = setup {
+            return (None, Registered::default(), vec![e.to_string()]);
+        }
+
+        lua.set_app_data(Registered::default());
+        for script in scripts {
+            let name = script.file_name().unwrap_or_default().to_string_lossy();
+            let ran = std::fs::read_to_string(script)
+                .map_err(|e| e.to_string())
+                .and_then(|source| {
+                    lua.load(source)
+                        .set_name(name.to_string())
+                        .exec()
+                        .map_err(|e| e.to_string())
+                });
+            if let Err(e) = ran {
+                errors.push(format!("{}: {}", name, e));
+            }
+        }
+
+        let registered = lua.remove_app_data::<Registered>().unwrap_or_default();
+        (Some(lua), registered, errors)
+    }
+
+    fn to_lua<'lua>(lua: &'lua Lua, arg: Arg) -> mlua::Result<Value<'lua>> {
+        match arg {
+            Arg::Path(path) => Ok(Value::String(lua.create_string(&path)?)),
+            Arg::Context {
+                cwd,
+                selected,
+                buffer,
+            } => {
+                let table = lua.create_table()?;
+                table.set("cwd", cwd)?;
+                table.set("selected", selected)?;
+                table.set("buffer", buffer)?;
+                Ok(Value::Table(table))
+            }
+        }
+    }
+
+    // runs the function kept at `handler`, returns what it gave back as
+    // lines and what it registered and asked for
+    pub fn call(
+        lua: &Lua,
+        handler: usize,
+        arg: Arg,
+    ) -> (Result<Option<Vec<String>>, String>, Registered) {
+        lua.set_app_data(Registered::default());
+
+        let result = (|| {
+            let handlers: Table = lua.named_registry_value(HANDLERS)?;
+            let function: Function = handlers.raw_get(handler)?;
+            let lines = match function.call::<_, Value>(to_lua(lua, arg)?)? {
+                Value::String(text) => Some(text.to_str()?.lines().map(str::to_string).collect()),
+                Value::Table(table) => Some(
+                    table
+                        .sequence_values::<String>()
+                        .collect::<mlua::Result<Vec<String>>>()?,
+                ),
+                _ => None,
+            };
+            Ok::<_, mlua::Error>(lines)
+        })();
+
+        let registered = lua.remove_app_data::<Registered>().unwrap_or_default();
+        (result.map_err(|e| e.to_string()), registered)
+    }
+}
+
+// reads the scripts in the plugins directory, called once at startup
+#[cfg(feature = "plugins")]
+pub fn load(app: &mut App) {
+    let dir = match plugin_dir() {
+        Some(dir) => dir,
+        None => return,
+    };
+    let mut scripts = std::fs::read_dir(&dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "lua"))
+                .collect::<Vec<PathBuf>>()
+        })
+        .unwrap_or_default();
+    if scripts.is_empty() {
+        return;
+    }
+    scripts.sort();
+
+    let (lua, mut registered, errors) = engine::load(&scripts);
+    let effects = std::mem::take(&mut registered.effects);
+    app.plugins.lua = lua;
+    app.plugins.registered = registered;
+
+    for error in errors {
+        app.notify_error(format!("Plugin {}", error));
+    }
+    apply(app, effects);
+}
+
+// without the feature, the scripts are only pointed out
+#[cfg(not(feature = "plugins"))]
+pub fn load(app: &mut App) {
+    let has_scripts = plugin_dir()
+        .and_then(|dir| std::fs::read_dir(dir).ok())
+        .is_some_and(|mut entries| {
+            entries.any(|entry| {
+                entry.is_ok_and(|entry| entry.path().extension().is_some_and(|ext| ext == "lua"))
+            })
+        });
+
+    if has_scripts {
+        app.notify_error(
+            "This traverse was built without plugins, build it with --features plugins",
+        );
+    }
+}
+
+#[cfg(feature = "plugins")]
+fn run(app: &mut App, handler: usize, arg: Arg) -> Result<Option<Vec<String>>, String> {
+    let lua = match app.plugins.lua.as_ref() {
+        Some(lua) => lua,
+        None => return Ok(None),
+    };
+
+    let (result, registered) = engine::call(lua, handler, arg);
+    let effects = app.plugins.registered.merge(registered);
+    apply(app, effects);
+    result
+}
+
+#[cfg(not(feature = "plugins"))]
+fn run(_app: &mut App, _handler: usize, _arg: Arg) -> Result<Option<Vec<String>>, String> {
+    Ok(None)
+}
+
+fn context(app: &App) -> Arg {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let selected = app
+        .selected_entry()
+        .filter(|name| name != "..")
+        .map(|name| cwd.join(name).to_string_lossy().to_string());
+
+    Arg::Context {
+        cwd: cwd.to_string_lossy().to_string(),
+        selected,
+        buffer: app
+            .selected_files
+            .iter()
+            .chain(app.selected_dirs.iter())
+            .map(|path| path.to_string_lossy().to_string())
+            .collect(),
+    }
+}
+
+fn apply(app: &mut App, effects: Vec<Effect>) {
+    for effect in effects {
+        match effect {
+            Effect::Notify(text) => app.notify(text),
+            Effect::Error(text) => app.notify_error(text),
+            Effect::Cd(path) => {
+                if jumps::change_dir(app, &path) {
+                    app.update_files();
+                    app.update_dirs();
+                    app.files.state.select(None);
+                    app.dirs.state.select(Some(0));
+                }
+            }
+            Effect::Spawn(command) => jobs::spawn_command(app, &command),
+            Effect::Refresh => {
+                app.update_files();
+                app.update_dirs();
+                app.clamp_selection();
+            }
+        }
+    }
+}
+
+// the palette entries of the plugin commands, with their keys
+pub fn commands(app: &App) -> Vec<(String, usize)> {
+    app.plugins
+        .registered
+        .commands
+        .iter()
+        .enumerate()
+        .map(|(idx, command)| match &command.key {
+            Some(key) => (format!("{}  ({})", command.name, key), idx),
+            None => (command.name.clone(), idx),
+        })
+        .collect()
+}
+
+// a command from the palette or its key, what it returns is shown
+pub fn run_command(app: &mut App, idx: usize) {
+    let (name, handler) = match app.plugins.registered.commands.get(idx) {
+        Some(command) => (command.name.clone(), command.handler),
+        None => return,
+    };
+
+    let arg = context(app);
+    match run(app, handler, arg) {
+        Ok(Some(lines)) if !lines.is_empty() => app.notify(lines.join(" ")),
+        Ok(_) => {}
+        Err(e) => app.notify_error(format!("{} failed: {}", name, e)),
+    }
+}
+
+// the keys plugins bound go before traverse's own
+pub fn handle_key(app: &mut App, key: &KeyEvent) -> bool {
+    if app.plugins.registered.bindings.is_empty() || block_binds(app) || app.remote.is_some() {
+        return false;
+    }
+
+    let handler = match app
+        .plugins
+        .registered
+        .bindings
+        .iter()
+        .find(|(bound, _)| bound.matches(key))
+    {
+        Some((_, handler)) => *handler,
+        None => return false,
+    };
+
+    let arg = context(app);
+    match run(app, handler, arg) {
+        Ok(Some(lines)) if !lines.is_empty() => app.notify(lines.join(" ")),
+        Ok(_) => {}
+        Err(e) => app.notify_error(format!("Plugin key failed: {}", e)),
+    }
+    true
+}
+
+fn run_hooks(app: &mut App, hook: Hook) {
+    let handlers = app
+        .plugins
+        .registered
+        .hooks
+        .iter()
+        .filter(|(registered, _)| *registered == hook)
+        .map(|(_, handler)| *handler)
+        .collect::<Vec<usize>>();
+
+    for handler in handlers {
+        let arg = context(app);
+        if let Err(e) = run(app, handler, arg) {
+            app.notify_error(format!("Plugin hook failed: {}", e));
+        }
+    }
+}
+
+// every tick: the enter_dir and select hooks, when the directory or the
+// entry under the cursor changed since
+pub fn track(app: &mut App) {
+    if app.plugins.registered.hooks.is_empty() || app.remote.is_some() {
+        return;
+    }
+
+    let cwd = std::env::current_dir().ok();
+    if app.plugins.last_dir != cwd {
+        app.plugins.last_dir = cwd.clone();
+        run_hooks(app, Hook::EnterDir);
+    }
+
+    let selected = cwd
+        .zip(app.selected_entry())
+        .map(|(cwd, name)| cwd.join(name));
+    if app.plugins.last_selected != selected {
+        app.plugins.last_selected = selected;
+        run_hooks(app, Hook::Select);
+    }
+}
+
+// the open hooks, before O runs a program on the entry
+pub fn on_open(app: &mut App) {
+    run_hooks(app, Hook::Open);
+}
+
+// what a plugin previewer makes of `path`, None when none takes it. Read
+// again only when the file changes, the preview is drawn every frame
+pub fn preview(app: &mut App, path: &Path) -> Option<Vec<String>> {
+    if app.plugins.registered.previewers.is_empty() {
+        return None;
+    }
+
+    let modified = path
+        .metadata()
+        .and_then(|metadata| metadata.modified())
+        .ok();
+    if let Some((previewed, at, lines)) = &app.plugins.preview {
+        if previewed == path && *at == modified {
+            return lines.clone();
+        }
+    }
+
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let handler = app
+        .plugins
+        .registered
+        .previewers
+        .iter()
+        .find(|(patterns, _)| {
+            patterns
+                .iter()
+                .any(|pattern| pattern == "*" || *pattern == extension)
+        })
+        .map(|(_, handler)| *handler);
+
+    let lines = match handler {
+        Some(handler) => match run(app, handler, Arg::Path(path.to_string_lossy().to_string())) {
+            Ok(lines) => lines,
+            Err(e) => Some(vec![format!("The plugin previewer failed: {}", e)]),
+        },
+        None => None,
+    };
+
+    app.plugins.preview = Some((path.to_path_buf(), modified, lines.clone()));
+    lines
+}
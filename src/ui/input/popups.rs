@@ -0,0 +1,133 @@
+use crate::app::app::App;
+use crossterm::event::KeyCode;
+use ratatui::layout::Rect;
+
+// popups that can be moved and resized with ALT + arrows
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Popup {
+    Fzf,
+    Bookmarks,
+    Report,
+}
+
+impl Popup {
+    // how the session file names it
+    pub fn name(self) -> &'static str {
+        match self {
+            Popup::Fzf => "fzf",
+            Popup::Bookmarks => "bookmarks",
+            Popup::Report => "report",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Popup> {
+        match name {
+            "fzf" => Some(Popup::Fzf),
+            "bookmarks" => Some(Popup::Bookmarks),
+            "report" => Some(Popup::Report),
+            _ => None,
+        }
+    }
+}
+
+// how far a popup was moved and resized from where it would be drawn by
+// default, so it still follows the terminal when that's resized
+#[derive(Clone, Copy, Default)]
+pub struct Geometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+const MIN_WIDTH: u16 = 20;
+const MIN_HEIGHT: u16 = 5;
+
+// the popup keys act on the one on top
+pub fn focused(app: &App) -> Option<Popup> {
+    if app.show_report {
+        Some(Popup::Report)
+    } else if app.show_bookmark {
+        Some(Popup::Bookmarks)
+    } else if app.show_fzf {
+        Some(Popup::Fzf)
+    } else {
+        None
+    }
+}
+
+// ALT + arrows move, ALT + SHIFT + arrows resize, ALT + 0 puts it back
+pub fn adjust(app: &mut App, code: KeyCode, resize: bool) {
+    let popup = match focused(app) {
+        Some(popup) => popup,
+        None => return,
+    };
+
+    if code == KeyCode::Char('0') {
+        app.popup_geometry.remove(&popup);
+        return;
+    }
+
+    let (dx, dy) = match code {
+        KeyCode::Left => (-2, 0),
+        KeyCode::Right => (2, 0),
+        KeyCode::Up => (0, -1),
+        KeyCode::Down => (0, 1),
+        _ => return,
+    };
+
+    let geometry = app.popup_geometry.entry(popup).or_default();
+    if resize {
+        geometry.width += dx;
+        geometry.height += dy;
+    } else {
+        geometry.x += dx;
+        geometry.y += dy;
+    }
+}
+
+// where to draw `popup`, its default area with the adjustments applied and
+// kept on screen, the adjustments are trimmed to what could be applied so
+// pushing against an edge doesn't pile up
+pub fn area(app: &mut App, popup: Popup, default: Rect, screen: Rect) -> Rect {
+    let geometry = match app.popup_geometry.get(&popup) {
+        Some(geometry) => *geometry,
+        None => return default,
+    };
+
+    let clamp =
+        |value: i32, min: u16, max: u16| value.clamp(min as i32, max.max(min) as i32) as u16;
+
+    let width = clamp(
+        default.width as i32 + geometry.width,
+        MIN_WIDTH.min(screen.width),
+        screen.width,
+    );
+    let height = clamp(
+        default.height as i32 + geometry.height,
+        MIN_HEIGHT.min(screen.height),
+        screen.height,
+    );
+    let x = clamp(
+        default.x as i32 + geometry.x,
+        screen.x,
+        screen.x + screen.width - width,
+    );
+    let y = clamp(
+        default.y as i32 + geometry.y,
+        screen.y,
+        screen.y + screen.height - height,
+    );
+
+    app.popup_geometry.insert(
+        popup,
+        Geometry {
+            x: x as i32 - default.x as i32,
+            y: y as i32 - default.y as i32,
+            width: width as i32 - default.width as i32,
+            height: height as i32 - default.height as i32,
+        },
+    );
+
+    Rect::new(x, y, width, height)
+}
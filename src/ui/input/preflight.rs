@@ -0,0 +1,259 @@
+use super::archive::{tar_reader, Format};
+use super::conflicts::{self, Asked, Policy};
+use super::fat::{self, Fat};
+use super::file_ops;
+use super::locking::{self, Retry};
+use super::plan::Step;
+use super::treemap::dir_size;
+use crate::app::app::{display_name, App};
+use crate::ui::display::pane::convert_bytes;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+// problems listed in a report, the rest are only counted
+const LISTED: usize = 15;
+
+// an operation held back by a cap or for a look, Enter on the report runs it
+#[derive(Clone)]
+pub enum Confirm {
+    Delete(PathBuf),
+    Transfer { is_move: bool },
+    // onto a FAT volume with names it can hold
+    Sanitize { is_move: bool, fat: Fat },
+    // the buffer reordered or with entries left out
+    Plan { is_move: bool, steps: Vec<Step> },
+    // a step lands on a name that's taken, o/s/r decide
+    Conflict(Asked),
+}
+
+#[cfg(unix)]
+fn same_device(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (std::fs::metadata(a), std::fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev(),
+        _ => false,
+    }
+}
+
+#[cfg(windows)]
+fn same_device(a: &Path, b: &Path) -> bool {
+    a.components().next() == b.components().next()
+}
+
+// what the unpacked archive will take, from the zip index or the tar headers
+fn extracted_size(file: &Path) -> Option<u64> {
+    let format = Format::of(&file.to_string_lossy())?;
+
+    if format == Format::Zip {
+        let mut archive = zip::ZipArchive::new(File::open(file).ok()?).ok()?;
+
+        return Some(
+            (0..archive.len())
+                .filter_map(|idx| archive.by_index_raw(idx).ok().map(|entry| entry.size()))
+                .sum(),
+        );
+    }
+
+    #[cfg(feature = "sevenz")]
+    if format == Format::SevenZ {
+        let archive = sevenz_rust::Archive::open(file).ok()?;
+
+        return Some(archive.files.iter().map(|entry| entry.size).sum());
+    }
+
+    // the decrypted file is a little smaller than the encrypted one
+    #[cfg(feature = "age")]
+    if format == Format::Age {
+        return Some(file.metadata().ok()?.len());
+    }
+
+    if let Some(tar) = tar_reader(format, File::open(file).ok()?).ok()? {
+        let mut archive = tar::Archive::new(tar);
+
+        return Some(
+            archive
+                .entries()
+                .ok()?
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.size())
+                .sum(),
+        );
+    }
+
+    None
+}
+
+fn check(app: &mut App, required: u64, dest: &Path) -> bool {
+    let free = match fs2::available_space(dest) {
+        Ok(free) => free,
+        // nothing to compare against, let the operation try
+        Err(_) => return true,
+    };
+
+    if required <= free {
+        return true;
+    }
+
+    app.open_report(
+        "Not enough space",
+        vec![
+            format!(
+                "Needs {}, only {} free on {}",
+                convert_bytes(required, app.si_units),
+                convert_bytes(free, app.si_units),
+                dest.display()
+            ),
+            "Nothing was changed.".to_string(),
+        ],
+    );
+
+    false
+}
+
+// copies always need room for everything, moves only when they cross disks
+pub fn check_transfer(app: &mut App, sources: &[PathBuf], dest: &Path, is_move: bool) -> bool {
+    let required = sources
+        .iter()
+        .map(PathBuf::as_path)
+        .filter(|source| !is_move || !same_device(source, dest))
+        .map(dir_size)
+        .sum();
+
+    check(app, required, dest)
+}
+
+pub fn check_extract(app: &mut App, file: &Path, dest: &Path) -> bool {
+    match extracted_size(file) {
+        Some(required) => check(app, required, dest),
+        None => true,
+    }
+}
+
+// counting stops just past the cap, so a runaway tree costs no more than the
+// cap itself
+fn count_up_to(paths: &[&Path], files_only: bool, cap: usize) -> usize {
+    paths
+        .iter()
+        .flat_map(|path| {
+            WalkDir::new(path)
+                .into_iter()
+                .filter_map(|entry| entry.ok())
+        })
+        .filter(|entry| !files_only || !entry.file_type().is_dir())
+        .take(cap + 1)
+        .count()
+}
+
+fn hold(app: &mut App, title: &str, reason: String, confirm: Confirm) {
+    app.open_report(
+        title,
+        vec![
+            reason,
+            "Press Enter to go ahead anyway, Esc to cancel.".to_string(),
+        ],
+    );
+    app.pending_confirm = Some(confirm);
+}
+
+// sanity caps from the config, 0 turns one off, and confirm=always
+pub fn check_delete(app: &mut App, path: &Path) -> bool {
+    if app.confirm == Policy::Always {
+        app.open_report(
+            "Delete",
+            vec![
+                format!("Move {} to the bin?", display_name(path)),
+                "Press Enter to delete, Esc to cancel.".to_string(),
+            ],
+        );
+        app.pending_confirm = Some(Confirm::Delete(path.to_path_buf()));
+        return false;
+    }
+
+    let cap = app.max_delete_files;
+    if cap == 0 || count_up_to(&[path], true, cap) <= cap {
+        return true;
+    }
+
+    hold(
+        app,
+        "Large delete",
+        format!("{} holds more than {} files", display_name(path), cap),
+        Confirm::Delete(path.to_path_buf()),
+    );
+    false
+}
+
+pub fn check_entries(app: &mut App, sources: &[PathBuf], is_move: bool) -> bool {
+    let cap = app.max_entries;
+    let paths = sources.iter().map(PathBuf::as_path).collect::<Vec<&Path>>();
+    if cap == 0 || count_up_to(&paths, false, cap) <= cap {
+        return true;
+    }
+
+    hold(
+        app,
+        if is_move { "Large move" } else { "Large copy" },
+        format!("The move/copy buffer holds more than {} entries", cap),
+        Confirm::Transfer { is_move },
+    );
+    false
+}
+
+// names FAT can't hold would fail halfway, or worse get mangled by the driver
+pub fn check_names(app: &mut App, sources: &[PathBuf], dest: &Path, is_move: bool) -> bool {
+    let fat = match fat::filesystem(dest) {
+        Some(fat) => fat,
+        None => return true,
+    };
+
+    let (problems, too_big) = fat::problems(sources, fat);
+    if problems.is_empty() {
+        return true;
+    }
+
+    let mut lines = vec![format!(
+        "{} is on {} and {} entries can't go there as they are:",
+        dest.display(),
+        fat.name(),
+        problems.len()
+    )];
+    lines.extend(
+        problems
+            .iter()
+            .take(LISTED)
+            .map(|problem| format!("  {}", problem)),
+    );
+    if problems.len() > LISTED {
+        lines.push(format!("  ... and {} more", problems.len() - LISTED));
+    }
+
+    lines.push(String::new());
+    lines.push(format!(
+        "Enter {} with safe names: refused characters become _, clashes get a number.",
+        if is_move { "moves" } else { "copies" }
+    ));
+    if too_big {
+        lines.push("Files over 4 GB are split into name.001, name.002, ...".to_string());
+    }
+    lines.push("Esc cancels.".to_string());
+
+    app.open_report("Names on FAT", lines);
+    app.pending_confirm = Some(Confirm::Sanitize { is_move, fat });
+    false
+}
+
+pub fn confirm(app: &mut App) {
+    app.show_report = false;
+
+    match app.pending_confirm.take() {
+        Some(Confirm::Delete(path)) => locking::run(app, Retry::Delete(path)),
+        Some(Confirm::Transfer { is_move }) => file_ops::transfer(app, is_move, true),
+        Some(Confirm::Sanitize { is_move, fat }) => fat::transfer(app, is_move, fat),
+        Some(Confirm::Plan { is_move, steps }) => file_ops::run(app, is_move, steps),
+        // Enter isn't one of the choices, the question stays
+        Some(Confirm::Conflict(asked)) => conflicts::ask(app, asked.then, asked.plan, asked.at),
+        _ => {}
+    }
+}
@@ -0,0 +1,233 @@
+use super::follow;
+use super::run_app::Command;
+use crate::app::app::{display_name, App};
+use crate::configuration::theme::Theme;
+use crate::ui::display::block::block_binds;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Span, Spans};
+use std::ffi::OsString;
+use std::fs;
+
+// bigger files are for an editor, or grep
+const MAX_SIZE: u64 = 16 * 1024 * 1024;
+// lines shown above the match jumped to
+const CONTEXT: usize = 2;
+
+pub struct PreviewSearch {
+    pub query: String,
+    // the file it was searched in, other previews leave it be
+    pub file: OsString,
+    // the lines with a match, from 0
+    pub matches: Vec<usize>,
+    pub current: usize,
+}
+
+impl PreviewSearch {
+    // lowercase searches ignore case, like smartcase in vim
+    pub fn ignore_case(&self) -> bool {
+        !self.query.chars().any(char::is_uppercase)
+    }
+}
+
+// 3 or a click on the preview, j/k and the other motions then scroll it
+pub fn focus(app: &mut App) {
+    app.preview_focused = true;
+}
+
+pub fn unfocus(app: &mut App) {
+    app.preview_focused = false;
+    app.preview_search = None;
+}
+
+pub fn scroll(app: &mut App, by: isize) {
+    if by > 0 && app.preview_at_end {
+        return;
+    }
+    app.preview_scroll = app.preview_scroll.saturating_add_signed(by);
+}
+
+// the byte length of `query` at the start of `text`, if it's there
+fn matches_at(text: &str, query: &str, ignore_case: bool) -> Option<usize> {
+    let mut chars = text.char_indices();
+
+    for q in query.chars() {
+        let (_, c) = chars.next()?;
+        let same = if ignore_case {
+            c.to_lowercase().eq(q.to_lowercase())
+        } else {
+            c == q
+        };
+        if !same {
+            return None;
+        }
+    }
+
+    Some(chars.next().map_or(text.len(), |(idx, _)| idx))
+}
+
+// where `query` is in `line`, as byte ranges
+pub fn find(line: &str, query: &str, ignore_case: bool) -> Vec<(usize, usize)> {
+    let mut found = vec![];
+    let mut start = 0;
+
+    while start < line.len() {
+        match matches_at(&line[start..], query, ignore_case) {
+            Some(len) => {
+                found.push((start, start + len));
+                start += len;
+            }
+            None => start += line[start..].chars().next().map_or(1, char::len_utf8),
+        }
+    }
+
+    found
+}
+
+// a previewed line with the matches standing out
+pub fn highlight(line: &str, search: &PreviewSearch, theme: &Theme) -> Spans<'static> {
+    let style = Style::default()
+        .fg(theme.highlight)
+        .add_modifier(Modifier::REVERSED | Modifier::BOLD);
+
+    let mut spans = vec![];
+    let mut last = 0;
+
+    for (start, end) in find(line, &search.query, search.ignore_case()) {
+        spans.push(Span::raw(line[last..start].to_string()));
+        spans.push(Span::styled(line[start..end].to_string(), style));
+        last = end;
+    }
+    spans.push(Span::raw(line[last..].to_string()));
+
+    Spans::from(spans)
+}
+
+// the search on the previewed file, if there is one
+pub fn active(app: &App) -> Option<&PreviewSearch> {
+    app.preview_search
+        .as_ref()
+        .filter(|search| search.file == app.preview_file)
+}
+
+// / in the focused preview
+pub fn handle_search(app: &mut App, input: &mut String, input_active: &mut bool) {
+    if block_binds(app) || *input_active {
+        return;
+    }
+
+    *input = app
+        .preview_search
+        .as_ref()
+        .map(|search| search.query.clone())
+        .unwrap_or_default();
+    *input_active = true;
+    app.show_popup = true;
+    app.last_command = Some(Command::PreviewSearch);
+}
+
+pub fn submit_search(app: &mut App, query: &str) {
+    app.preview_search = None;
+    if query.is_empty() {
+        return;
+    }
+
+    let file = app.preview_file.clone();
+    let contents = match fs::metadata(&file) {
+        Ok(metadata) if metadata.is_file() && metadata.len() <= MAX_SIZE => fs::read(&file),
+        Ok(metadata) if metadata.is_file() => {
+            return app.notify_error("Too big to search in the preview");
+        }
+        _ => return app.notify_error("Only files can be searched"),
+    };
+    let contents = match contents {
+        Ok(contents) if !contents.iter().take(1024).any(|byte| *byte == 0) => contents,
+        Ok(_) => return app.notify_error("Only text files can be searched"),
+        Err(e) => return app.show_error(&format!("Couldn't read {}", display_name(&file)), e),
+    };
+
+    let mut search = PreviewSearch {
+        query: query.to_string(),
+        file,
+        matches: vec![],
+        current: 0,
+    };
+    let ignore_case = search.ignore_case();
+    search.matches = String::from_utf8_lossy(&contents)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !find(line, query, ignore_case).is_empty())
+        .map(|(idx, _)| idx)
+        .collect();
+
+    if search.matches.is_empty() {
+        return app.notify_error(format!("No {} in the file", query));
+    }
+
+    // the first one from where the preview is
+    let from = app.preview_scroll + CONTEXT;
+    search.current = search
+        .matches
+        .iter()
+        .position(|line| *line >= from)
+        .unwrap_or(0);
+
+    app.preview_search = Some(search);
+    jump(app);
+}
+
+// n and N
+pub fn next(app: &mut App, forward: bool) {
+    let search = match app.preview_search.as_mut() {
+        Some(search) => search,
+        None => return,
+    };
+
+    let len = search.matches.len();
+    search.current = if forward {
+        (search.current + 1) % len
+    } else {
+        (search.current + len - 1) % len
+    };
+
+    jump(app);
+}
+
+fn jump(app: &mut App) {
+    let (line, current, len) = match &app.preview_search {
+        Some(search) => (
+            search.matches[search.current],
+            search.current + 1,
+            search.matches.len(),
+        ),
+        None => return,
+    };
+
+    app.preview_scroll = line.saturating_sub(CONTEXT);
+    app.notify(format!(
+        "Match {} of {}, on line {}",
+        current,
+        len,
+        line + 1
+    ));
+}
+
+// the preview's title while it's focused or following
+pub fn title(app: &App) -> String {
+    let following = if follow::active(app).is_some() {
+        ", following (F stops)"
+    } else {
+        ""
+    };
+
+    match active(app) {
+        Some(search) => format!(
+            "Preview{}, {} of {} for {} (n/N)",
+            following,
+            search.current + 1,
+            search.matches.len(),
+            search.query
+        ),
+        None if app.preview_focused => format!("Preview{} (/ to search)", following),
+        None => format!("Preview{}", following),
+    }
+}
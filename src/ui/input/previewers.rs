@@ -0,0 +1,285 @@
+use super::shell::{quote, shell_command};
+use crate::app::app::App;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+// more than a preview pane shows, less than a runaway command can print
+const MAX_OUTPUT: u64 = 1024 * 1024;
+// previews kept, oldest dropped first
+const CACHED: usize = 64;
+
+// one previewer= line from the config:
+//   previewer=pdf: pdftotext -l 3 {} -
+// extensions (or * for anything) then the command, {} is the file, it's
+// appended when the command doesn't say where
+pub struct Previewer {
+    pub extensions: Vec<String>,
+    pub command: String,
+}
+
+// a previewer running in the background for `path`
+struct Running {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    command: String,
+    started: Instant,
+    result: Receiver<Vec<String>>,
+    cancel: Arc<AtomicBool>,
+}
+
+pub struct Previewers {
+    pub previewers: Vec<Previewer>,
+    // how long a command gets before it's killed, preview_timeout=<seconds>
+    pub timeout: Duration,
+    cache: HashMap<PathBuf, (Option<SystemTime>, Instant, Vec<String>)>,
+    running: Option<Running>,
+}
+
+impl Default for Previewers {
+    fn default() -> Previewers {
+        Previewers {
+            previewers: vec![],
+            timeout: Duration::from_secs(5),
+            cache: HashMap::new(),
+            running: None,
+        }
+    }
+}
+
+fn parse(spec: &str) -> Option<Previewer> {
+    let (extensions, command) = spec.split_once(':')?;
+
+    let extensions = extensions
+        .split(',')
+        .map(|ext| ext.trim().trim_start_matches('.').to_lowercase())
+        .filter(|ext| !ext.is_empty())
+        .collect::<Vec<String>>();
+    let command = command.trim();
+    if extensions.is_empty() || command.is_empty() {
+        return None;
+    }
+
+    Some(Previewer {
+        extensions,
+        command: command.to_string(),
+    })
+}
+
+// called by read_config with every previewer= line
+pub fn configure(app: &mut App, specs: Vec<String>) {
+    app.previewers.previewers = specs.iter().filter_map(|spec| parse(spec)).collect();
+}
+
+// the command line for `path`, from the first previewer taking its extension
+fn command_for(app: &App, path: &Path) -> Option<String> {
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let previewer = app.previewers.previewers.iter().find(|previewer| {
+        previewer
+            .extensions
+            .iter()
+            .any(|ext| ext == "*" || *ext == extension)
+    })?;
+
+    let file = quote(path.as_os_str());
+    Some(if previewer.command.contains("{}") {
+        previewer.command.replace("{}", &file)
+    } else {
+        format!("{} {}", previewer.command, file)
+    })
+}
+
+// colors and cursor movement, a pane can't show them
+fn strip_escapes(line: &str) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            if c == '\t' || !c.is_control() {
+                stripped.push(c);
+            }
+            continue;
+        }
+        if chars.next_if_eq(&'[').is_some() {
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    stripped
+}
+
+// runs `command` off the UI thread, killed once `timeout` is up or the
+// selection moved on
+fn run(command: &str, timeout: Duration, cancel: Arc<AtomicBool>) -> Receiver<Vec<String>> {
+    let (tx, rx) = mpsc::channel();
+    let mut shell = shell_command(command);
+    shell
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    thread::spawn(move || {
+        let mut child = match shell.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                let _ = tx.send(vec![format!("The previewer couldn't start: {}", e)]);
+                return;
+            }
+        };
+
+        // read aside so a chatty command doesn't block on a full pipe
+        let stdout = child.stdout.take();
+        let reader = thread::spawn(move || {
+            let mut output = vec![];
+            if let Some(stdout) = stdout {
+                let _ = stdout.take(MAX_OUTPUT).read_to_end(&mut output);
+            }
+            output
+        });
+        let stderr = child.stderr.take();
+        let errors = thread::spawn(move || {
+            let mut errors = vec![];
+            if let Some(stderr) = stderr {
+                let _ = stderr.take(MAX_OUTPUT).read_to_end(&mut errors);
+            }
+            String::from_utf8_lossy(&errors).to_string()
+        });
+
+        let started = Instant::now();
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break Some(status),
+                Ok(None) if cancel.load(Ordering::SeqCst) || started.elapsed() >= timeout => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    break None;
+                }
+                Ok(None) => thread::sleep(Duration::from_millis(20)),
+                Err(_) => break None,
+            }
+        };
+        if cancel.load(Ordering::SeqCst) {
+            return;
+        }
+        // what the shell started may still hold the pipe, it isn't waited for
+        let status = match status {
+            Some(status) => status,
+            None => {
+                let _ = tx.send(vec![format!("(timed out after {}s)", timeout.as_secs())]);
+                return;
+            }
+        };
+
+        let output = reader.join().unwrap_or_default();
+        let mut lines = String::from_utf8_lossy(&output)
+            .lines()
+            .map(strip_escapes)
+            .collect::<Vec<String>>();
+
+        if !status.success() && lines.is_empty() {
+            let errors = errors.join().unwrap_or_default();
+            lines.push(format!("The previewer failed ({})", status));
+            lines.extend(errors.lines().map(strip_escapes));
+        }
+        let _ = tx.send(lines);
+    });
+
+    rx
+}
+
+// what a configured previewer made of `path`, None when no previewer takes
+// it. The first call starts the command and says so, the output shows up
+// once poll_previewers has it
+pub fn preview(app: &mut App, path: &Path) -> Option<Vec<String>> {
+    if app.previewers.previewers.is_empty() {
+        return None;
+    }
+    let command = command_for(app, path)?;
+
+    let modified = path
+        .metadata()
+        .and_then(|metadata| metadata.modified())
+        .ok();
+    if let Some((at, _, lines)) = app.previewers.cache.get(&path.to_path_buf()) {
+        if *at == modified {
+            return Some(lines.clone());
+        }
+    }
+
+    if let Some(running) = &app.previewers.running {
+        if running.path == path && running.modified == modified {
+            return Some(vec![format!(
+                "Running `{}`... {}s",
+                running.command,
+                running.started.elapsed().as_secs()
+            )]);
+        }
+    }
+
+    cancel(app);
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    let result = run(&command, app.previewers.timeout, cancel.clone());
+    app.previewers.running = Some(Running {
+        path: path.to_path_buf(),
+        modified,
+        command: command.clone(),
+        started: Instant::now(),
+        result,
+        cancel,
+    });
+
+    Some(vec![format!("Running `{}`...", command)])
+}
+
+// whatever ran for the last selection isn't wanted anymore
+pub fn cancel(app: &mut App) {
+    if let Some(running) = app.previewers.running.take() {
+        running.cancel.store(true, Ordering::SeqCst);
+    }
+}
+
+// every tick: a finished previewer's output goes in the cache, where the
+// next frame finds it
+pub fn poll_previewers(app: &mut App) {
+    let lines = match &app.previewers.running {
+        Some(running) => match running.result.try_recv() {
+            Ok(lines) => lines,
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                app.previewers.running = None;
+                return;
+            }
+        },
+        None => return,
+    };
+
+    let running = match app.previewers.running.take() {
+        Some(running) => running,
+        None => return,
+    };
+
+    let cache = &mut app.previewers.cache;
+    if cache.len() >= CACHED {
+        let oldest = cache
+            .iter()
+            .min_by_key(|(_, (_, cached, _))| *cached)
+            .map(|(path, _)| path.clone());
+        if let Some(oldest) = oldest {
+            cache.remove(&oldest);
+        }
+    }
+    cache.insert(running.path, (running.modified, Instant::now(), lines));
+}
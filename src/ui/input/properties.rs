@@ -0,0 +1,281 @@
+use super::bookmark::selected_entry_path;
+use crate::app::app::App;
+use crate::git::ago;
+use crate::ui::display::block::block_binds;
+use crate::ui::display::pane::convert_bytes;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+// data hidden next to the main contents of a file, alternate data streams on
+// NTFS and the resource fork on macOS
+pub struct Stream {
+    pub name: String,
+    pub size: u64,
+}
+
+// what the properties popup was opened for, `first_line` is the report line
+// of the first stream so a highlighted line maps back to its stream
+pub struct Properties {
+    pub path: PathBuf,
+    pub streams: Vec<Stream>,
+    pub first_line: usize,
+}
+
+// what the platform calls them and the ones `path` has, None where there's
+// no such thing
+#[cfg(windows)]
+fn streams(path: &Path) -> Option<(&'static str, Vec<Stream>)> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Foundation::INVALID_HANDLE_VALUE;
+    use windows_sys::Win32::Storage::FileSystem::{
+        FindClose, FindFirstStreamW, FindNextStreamW, FindStreamInfoStandard,
+        WIN32_FIND_STREAM_DATA,
+    };
+
+    let wide = path
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect::<Vec<u16>>();
+
+    let mut streams = vec![];
+
+    unsafe {
+        let mut data: WIN32_FIND_STREAM_DATA = std::mem::zeroed();
+        let handle = FindFirstStreamW(
+            wide.as_ptr(),
+            FindStreamInfoStandard,
+            &mut data as *mut WIN32_FIND_STREAM_DATA as *mut _,
+            0,
+        );
+
+        if handle == INVALID_HANDLE_VALUE {
+            return Some(("Alternate data streams", streams));
+        }
+
+        loop {
+            let len = data
+                .cStreamName
+                .iter()
+                .position(|c| *c == 0)
+                .unwrap_or(data.cStreamName.len());
+            let name = String::from_utf16_lossy(&data.cStreamName[..len]);
+
+            // ":name:$DATA", the unnamed one is the file itself
+            let name = name.trim_start_matches(':').trim_end_matches(":$DATA");
+            if !name.is_empty() {
+                streams.push(Stream {
+                    name: name.to_string(),
+                    size: data.StreamSize.max(0) as u64,
+                });
+            }
+
+            if FindNextStreamW(handle, &mut data as *mut WIN32_FIND_STREAM_DATA as *mut _) == 0 {
+                break;
+            }
+        }
+
+        FindClose(handle);
+    }
+
+    Some(("Alternate data streams", streams))
+}
+
+#[cfg(windows)]
+fn remove_stream(path: &Path, stream: &Stream) -> Result<(), String> {
+    let mut target = path.as_os_str().to_os_string();
+    target.push(format!(":{}", stream.name));
+
+    fs::remove_file(target).map_err(|err| err.to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn streams(path: &Path) -> Option<(&'static str, Vec<Stream>)> {
+    let streams = match fs::metadata(path.join("..namedfork/rsrc")) {
+        Ok(metadata) if metadata.len() > 0 => vec![Stream {
+            name: "rsrc".to_string(),
+            size: metadata.len(),
+        }],
+        _ => vec![],
+    };
+
+    Some(("Resource fork", streams))
+}
+
+#[cfg(target_os = "macos")]
+fn remove_stream(path: &Path, _stream: &Stream) -> Result<(), String> {
+    let output = std::process::Command::new("xattr")
+        .arg("-d")
+        .arg("com.apple.ResourceFork")
+        .arg(path)
+        .output()
+        .map_err(|err| err.to_string())?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn streams(_path: &Path) -> Option<(&'static str, Vec<Stream>)> {
+    None
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn remove_stream(_path: &Path, _stream: &Stream) -> Result<(), String> {
+    Err("there are no streams on this platform".to_string())
+}
+
+fn kind(path: &Path) -> String {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_symlink() => match fs::read_link(path) {
+            Ok(target) => format!("symlink to {}", target.display()),
+            Err(_) => "symlink".to_string(),
+        },
+        Ok(metadata) if metadata.is_dir() => "directory".to_string(),
+        Ok(_) => "file".to_string(),
+        Err(_) => "unknown".to_string(),
+    }
+}
+
+#[cfg(unix)]
+fn permissions(metadata: &fs::Metadata) -> String {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = metadata.permissions().mode();
+    let bits = ['r', 'w', 'x'];
+
+    (0..9)
+        .map(|bit| {
+            if mode & (0o400 >> bit) != 0 {
+                bits[bit % 3]
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+#[cfg(windows)]
+fn permissions(metadata: &fs::Metadata) -> String {
+    if metadata.permissions().readonly() {
+        "read only".to_string()
+    } else {
+        "read and write".to_string()
+    }
+}
+
+fn lines(path: &Path, si: bool) -> Vec<String> {
+    let mut lines = vec![
+        format!(
+            "Name:        {}",
+            path.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default()
+        ),
+        format!("Path:        {}", path.display()),
+        format!("Type:        {}", kind(path)),
+    ];
+
+    if let Ok(metadata) = fs::metadata(path) {
+        if metadata.is_file() {
+            lines.push(format!(
+                "Size:        {}",
+                convert_bytes(metadata.len(), si)
+            ));
+        }
+        if let Ok(modified) = metadata.modified() {
+            if let Ok(since) = modified.duration_since(UNIX_EPOCH) {
+                lines.push(format!("Modified:    {}", ago(since.as_secs() as i64)));
+            }
+        }
+        lines.push(format!("Permissions: {}", permissions(&metadata)));
+    }
+
+    lines
+}
+
+// the stream section, where the platform has streams at all
+fn stream_lines(path: &Path, lines: &mut Vec<String>, si: bool) -> (Vec<Stream>, usize) {
+    let (title, streams) = match streams(path) {
+        Some(streams) => streams,
+        None => return (vec![], lines.len()),
+    };
+
+    lines.push(String::new());
+
+    if streams.is_empty() {
+        lines.push(format!("{}: none", title));
+        return (streams, lines.len());
+    }
+
+    lines.push(format!("{}:", title));
+    let first_line = lines.len();
+
+    lines.extend(streams.iter().map(|stream| {
+        format!(
+            "  {:<24} {:>10}",
+            stream.name,
+            convert_bytes(stream.size, si)
+        )
+    }));
+    lines.push("CTRL + d removes the highlighted one.".to_string());
+
+    (streams, first_line)
+}
+
+fn open(app: &mut App, path: PathBuf) {
+    let mut lines = lines(&path, app.si_units);
+    let (streams, first_line) = stream_lines(&path, &mut lines, app.si_units);
+
+    app.open_report("Properties", lines);
+    app.properties = Some(Properties {
+        path,
+        streams,
+        first_line,
+    });
+}
+
+pub fn handle_properties(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    if let Some(path) = selected_entry_path(app) {
+        open(app, path);
+    }
+}
+
+// removes the stream on the highlighted line and shows what's left
+pub fn remove_selected_stream(app: &mut App) {
+    let properties = match &app.properties {
+        Some(properties) => properties,
+        None => return,
+    };
+
+    let stream = app
+        .report
+        .state
+        .selected()
+        .and_then(|idx| idx.checked_sub(properties.first_line))
+        .and_then(|idx| properties.streams.get(idx));
+
+    let stream = match stream {
+        Some(stream) => stream,
+        None => return app.notify_error("Highlight a stream to remove it"),
+    };
+
+    let path = properties.path.clone();
+    let name = stream.name.clone();
+
+    match remove_stream(&path, stream) {
+        Ok(_) => {
+            open(app, path);
+            app.notify(format!("Removed the {} stream", name));
+        }
+        Err(err) => app.show_error(&format!("Couldn't remove the {} stream", name), err),
+    }
+}
@@ -0,0 +1,364 @@
+use super::jobs::{self, Update};
+use super::palette::Action;
+use super::run_app::Command;
+use super::{secret, typeahead};
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use crate::ui::display::preview::{self, Source};
+use crate::vfs::{self, ConnectError, Local, VfsProvider};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::text::Spans;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// enough of a remote file to preview, without fetching all of it
+const PREVIEW_BYTES: u64 = 64 * 1024;
+
+// browsing another machine, the panes list `dir` on it instead of the
+// current directory
+pub struct Remote {
+    pub vfs: Arc<dyn VfsProvider>,
+    pub dir: PathBuf,
+    // where downloads go, the directory traverse was in when connecting
+    pub local_dir: PathBuf,
+    // the previewed entry and its lines, fetched once
+    pub preview: Option<(String, Vec<Spans<'static>>)>,
+}
+
+// the target asked for while a password is typed
+pub struct PendingConnect {
+    pub target: String,
+}
+
+pub fn title(app: &App) -> Option<String> {
+    let remote = app.remote.as_ref()?;
+    let name = remote.vfs.name();
+
+    // ftp://host/dir, but user@host:/dir
+    if name.contains("://") {
+        Some(format!("{}{}", name, remote.dir.display()))
+    } else {
+        Some(format!("{}:{}", name, remote.dir.display()))
+    }
+}
+
+// the keys that work on a remote listing, everything else would act on the
+// local directory behind it
+pub fn blocks(app: &mut App, key: &KeyEvent, input_active: bool) -> bool {
+    if app.remote.is_none() || input_active || block_binds(app) || typeahead::active(app) {
+        return false;
+    }
+
+    let control = key.modifiers.contains(KeyModifiers::CONTROL);
+    let allowed = match key.code {
+        KeyCode::Char('c' | 'u' | 'n' | 'p') if control => true,
+        KeyCode::Char('d') if control => app.preview_focused,
+        // searching the preview reads the file locally
+        KeyCode::Char('/') => !app.preview_focused,
+        KeyCode::Char(c) => c.is_ascii_digit() || "jkgGpqJ?:<>".contains(c),
+        KeyCode::Up
+        | KeyCode::Down
+        | KeyCode::PageUp
+        | KeyCode::PageDown
+        | KeyCode::Home
+        | KeyCode::End
+        | KeyCode::Enter
+        | KeyCode::Esc
+        | KeyCode::F(12) => true,
+        _ => false,
+    };
+
+    !allowed
+}
+
+// the palette actions that don't touch the local directory
+pub fn allows(action: Action) -> bool {
+    matches!(
+        action,
+        Action::Connect
+            | Action::Disconnect
+            | Action::ToggleHidden
+            | Action::Themes
+            | Action::GrowPreview
+            | Action::ShrinkPreview
+            | Action::JobHistory
+            | Action::Help
+    )
+}
+
+// `:connect user@host[:port]`, or the connect action
+pub fn handle_connect(app: &mut App, input: &mut String, input_active: &mut bool) {
+    if block_binds(app) || *input_active {
+        return;
+    }
+
+    input.clear();
+    *input_active = true;
+    app.show_popup = true;
+    app.last_command = Some(Command::Connect);
+}
+
+// `user@host`, or sftp://, ftp://, dav://, davs:// and s3:// targets
+pub fn connect(app: &mut App, input_active: &mut bool, target: &str, password: Option<&str>) {
+    let (vfs, dir) = match vfs::connect(target, password) {
+        Ok(connected) => connected,
+        // no key or anonymous login got in, ask for the password and come back
+        Err(ConnectError::NeedsPassword) if password.is_none() => {
+            app.pending_connect = Some(PendingConnect {
+                target: target.to_string(),
+            });
+            secret::ask(app, input_active, Command::RemotePassword);
+            return;
+        }
+        Err(ConnectError::NeedsPassword) => {
+            return app.notify_error(format!("Couldn't log in to {}", target))
+        }
+        Err(ConnectError::Failed(err)) => {
+            return app.notify_error(format!("Couldn't connect to {}: {}", target, err))
+        }
+    };
+
+    open(app, vfs, dir);
+}
+
+// the password typed after the login without one didn't get in
+pub fn submit_password(app: &mut App, input_active: &mut bool, password: &str) {
+    if let Some(pending) = app.pending_connect.take() {
+        connect(app, input_active, &pending.target, Some(password));
+    }
+}
+
+// starts browsing `dir` on `vfs`
+fn open(app: &mut App, vfs: Arc<dyn VfsProvider>, dir: PathBuf) {
+    app.notify(format!("Connected to {}", vfs.name()));
+    app.remote = Some(Remote {
+        vfs,
+        dir,
+        local_dir: std::env::current_dir().unwrap_or_default(),
+        preview: None,
+    });
+    app.flatten = false;
+    app.flat_job = None;
+    app.update_files();
+    app.update_dirs();
+    app.files.state.select(None);
+    app.dirs.state.select(Some(0));
+}
+
+pub fn disconnect(app: &mut App) {
+    let remote = match app.remote.take() {
+        Some(remote) => remote,
+        None => return app.notify_error("Not connected anywhere"),
+    };
+
+    app.notify(format!("Disconnected from {}", remote.vfs.name()));
+    app.update_files();
+    app.update_dirs();
+    app.clamp_selection();
+}
+
+// called by update_files in place of reading the current directory, fills
+// both panes
+pub fn list(app: &mut App) {
+    let remote = match app.remote.as_mut() {
+        Some(remote) => remote,
+        None => return,
+    };
+    remote.preview = None;
+
+    let entries = match remote.vfs.read_dir(&remote.dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            let dir = remote.dir.display().to_string();
+            return app.notify_error(format!("Couldn't list {}: {}", dir, e));
+        }
+    };
+
+    let show_hidden = app.show_hidden;
+    let mut files = vec![];
+    let mut dirs = vec![("../".into(), "../".to_string())];
+
+    for entry in entries {
+        if entry.name.starts_with('.') && !show_hidden {
+            continue;
+        }
+        if entry.is_dir {
+            dirs.push((entry.name.clone().into(), entry.name));
+        } else {
+            files.push((entry.name.clone().into(), entry.name));
+        }
+    }
+
+    files.sort();
+    dirs[1..].sort();
+    app.files.items = files;
+    app.dirs.items = dirs;
+}
+
+// the remote path of a listed entry
+fn path_of(remote: &Remote, name: &str) -> PathBuf {
+    match name.trim_end_matches('/') {
+        ".." => remote.dir.parent().unwrap_or(&remote.dir).to_path_buf(),
+        name => remote.vfs.join(&remote.dir, name),
+    }
+}
+
+// Enter: into a directory, or a file downloaded next to where traverse was
+pub fn handle_enter(app: &mut App) {
+    let (name, is_dir) = match (app.files.state.selected(), app.dirs.state.selected()) {
+        (Some(idx), _) => match app.files.items.get(idx) {
+            Some(entry) => (entry.0.clone(), false),
+            None => return,
+        },
+        (None, Some(idx)) => match app.dirs.items.get(idx) {
+            Some(entry) => (entry.0.clone(), true),
+            None => return,
+        },
+        _ => return,
+    };
+
+    let remote = match app.remote.as_mut() {
+        Some(remote) => remote,
+        None => return,
+    };
+    let path = path_of(remote, &name.to_string_lossy());
+
+    if is_dir {
+        let left = remote
+            .dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string());
+        let up = name == "../";
+        remote.dir = path;
+        app.update_files();
+        app.update_dirs();
+
+        // back on the directory just left when going up
+        let idx = left
+            .filter(|_| up)
+            .and_then(|left| app.dirs.items.iter().position(|(name, _)| *name == *left));
+        app.files.state.select(None);
+        app.dirs.state.select(Some(idx.unwrap_or(0)));
+        return;
+    }
+
+    let target = remote.local_dir.join(&name);
+    transfer(app, path, target, false);
+}
+
+// p: the move/copy buffer, local files, uploaded into the remote directory
+pub fn upload_buffer(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    let remote = match app.remote.as_ref() {
+        Some(remote) => remote,
+        None => return,
+    };
+
+    if app.selected_files.is_empty() {
+        return app
+            .notify_error("Buffer local files with c before connecting, p uploads them here");
+    }
+
+    let uploads = app
+        .selected_files
+        .iter()
+        .filter_map(|file| {
+            let name = Path::new(file).file_name()?.to_string_lossy().to_string();
+            Some((PathBuf::from(file), remote.vfs.join(&remote.dir, &name)))
+        })
+        .collect::<Vec<(PathBuf, PathBuf)>>();
+
+    app.selected_files = vec![];
+    app.selected_dirs = vec![];
+
+    for (source, target) in uploads {
+        transfer(app, source, target, true);
+    }
+}
+
+// a download or an upload as a background job, the remote listing is read
+// again once an upload is done
+fn transfer(app: &mut App, source: PathBuf, target: PathBuf, upload: bool) {
+    let remote = match app.remote.as_ref() {
+        Some(remote) => remote,
+        None => return,
+    };
+    let vfs = remote.vfs.clone();
+
+    let name = if upload {
+        format!("Upload {} to {}", source.display(), vfs.name())
+    } else {
+        format!("Download {} to {}", source.display(), target.display())
+    };
+
+    jobs::spawn(app, name, move |tx, _| {
+        let copied = if upload {
+            vfs::copy(&Local, &source, vfs.as_ref(), &target)
+        } else {
+            vfs::copy(vfs.as_ref(), &source, &Local, &target)
+        };
+
+        let (failed, line) = match copied {
+            Ok((files, bytes)) => (None, format!("{} files, {} bytes", files, bytes)),
+            Err(e) => (Some(e.to_string()), e.to_string()),
+        };
+        let _ = tx.send(Update::Line(line));
+        let _ = tx.send(Update::Finished(
+            failed.clone(),
+            Some(Box::new(move |app: &mut App| {
+                match failed {
+                    Some(failed) => {
+                        app.notify_error(format!("Couldn't copy {}: {}", source.display(), failed))
+                    }
+                    None => app.notify(format!(
+                        "Copied {} to {}",
+                        source.display(),
+                        target.display()
+                    )),
+                }
+                app.update_files();
+                app.update_dirs();
+                app.clamp_selection();
+            })),
+        ));
+    });
+}
+
+// the names in a directory, an archive's index or the start of any other
+// file, read once per entry
+pub fn preview_lines(app: &mut App, selected: &str) -> Option<Vec<Spans<'static>>> {
+    let remote = app.remote.as_mut().filter(|_| !selected.is_empty())?;
+
+    if let Some((name, lines)) = &remote.preview {
+        if name == selected {
+            return Some(lines.clone());
+        }
+    }
+
+    let path = path_of(remote, selected);
+    let source = Source::Remote {
+        vfs: &remote.vfs,
+        path: &path,
+        is_dir: app.files.state.selected().is_none(),
+    };
+
+    let lines = match preview::preview_lines(source, &app.theme, app.show_hidden, app.si_units) {
+        Some(lines) => lines,
+        None => match vfs::read_start(remote.vfs.as_ref(), &path, PREVIEW_BYTES) {
+            Ok(bytes) if bytes.iter().take(1024).any(|byte| *byte == 0) => {
+                vec![Spans::from("Binary file, Enter downloads it")]
+            }
+            Ok(bytes) => String::from_utf8_lossy(&bytes)
+                .lines()
+                .map(|line| Spans::from(line.to_string()))
+                .collect(),
+            Err(e) => vec![Spans::from(format!("Couldn't read it: {}", e))],
+        },
+    };
+
+    remote.preview = Some((selected.to_string(), lines.clone()));
+    Some(lines)
+}
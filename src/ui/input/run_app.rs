@@ -1,64 +1,210 @@
 use super::*;
 use crate::app::app::App;
+use crate::configuration::session;
 use crate::ui::display::block::block_binds;
+use crate::ui::display::contents;
+use crate::ui::display::layout::PaneLayout;
 use crate::ui::display::render::render;
 use anyhow::Result;
 use crossterm::event::{self, Event, KeyCode, KeyEventKind};
 use ratatui::backend::Backend;
 use ratatui::terminal::Terminal;
-use std::process::Command as SysCommand;
-use std::time::Duration;
+use std::ffi::OsString;
+use std::io::Write;
+use std::time::{Duration, Instant};
 
 #[derive(PartialEq)]
 pub enum Command {
     CreateFile,
     CreateDir,
+    CreateSymlink,
     RenameFile,
     RenameDir,
     ShowNav,
     ShowFzf,
     ShowHelp,
     Bookmark,
+    EditBookmark,
+    ShowJumps,
+    ShowTreemap,
+    ShowThemes,
+    OpenWith,
+    Connect,
+    RemotePassword,
+    ShowPalette,
+    ShowFrecent,
+    Shell,
+    Password,
+    Compress,
+    ArchivePassword,
+    SudoPassword,
+    ExportTree,
+    FilterFiles,
+    PreviewSearch,
+    ConflictName,
+    Grep,
 }
 
-pub fn run_app<B: Backend>(
+pub fn run_app<B: Backend + Write>(
     terminal: &mut Terminal<B>,
     mut app: App,
     tick_rate: Duration,
-) -> Result<()> {
+) -> Result<Option<OsString>> {
     let mut last_tick = std::time::Instant::now();
     let mut input = String::new();
     let mut input_active = false;
 
     loop {
+        app.poll_watcher();
+        checksum::poll_checksum_job(&mut app);
+        checksum::poll_hash_job(&mut app);
+        diagnostics::poll_diagnostics_job(&mut app);
+        kinds::poll_kinds_job(&mut app);
+        du::poll_du_job(&mut app);
+        flatten::poll_flatten_job(&mut app);
+        paging::poll_pages(&mut app);
+        jobs::poll_background_jobs(&mut app);
+        follow::poll_follow(&mut app);
+        schedule::poll_schedule(&mut app);
+        mounts::poll_mounts(&mut app);
+        frecent::track(&mut app);
+        plugins::track(&mut app);
+        hooks::track(&mut app);
+        previewers::poll_previewers(&mut app);
+        nav::poll_fzf(&mut app, &input);
+        grep::poll_grep(&mut app);
+        terminal_title::track(&mut app, terminal.backend_mut());
+        let started = Instant::now();
         terminal.draw(|f| render(f, &mut app, &mut input))?;
+        app.metrics.frame(started);
+        let queued = crossterm::event::poll(Duration::ZERO)?;
 
         let timeout = tick_rate
             .checked_sub(last_tick.elapsed())
             .unwrap_or_else(|| Duration::from_secs(0));
+        let timeout = match contents::settles_in(&app) {
+            Some(left) => timeout.min(left),
+            None => timeout,
+        };
 
         if crossterm::event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
+            let event = event::read()?;
+            app.metrics.event(queued);
+
+            if let Event::Mouse(mouse) = event {
+                mouse::handle_mouse(&mut app, mouse);
+            }
+
+            if let Event::Key(key) = event {
                 if key.kind == KeyEventKind::Press {
+                    // a type-ahead takes the typing, any other key ends it
+                    // and then does what it does, but Esc only ends it
+                    let type_ahead = typeahead::active(&app);
+                    if !type_ahead || !typeahead::takes(&key) {
+                        typeahead::stop(&mut app);
+                    }
+                    if !motion::continues(&key) {
+                        motion::clear(&mut app);
+                    }
+
                     match key.code {
+                        KeyCode::Esc if type_ahead => {}
+                        // the pager takes every key until it's closed
+                        _ if app.pager.is_some() => pager::handle_key(&mut app, &key),
+                        // overwrite, skip or rename an entry whose name is taken
+                        KeyCode::Char(c) if !input_active && conflicts::takes(&app, c) => {
+                            conflicts::choose(&mut app, c, &mut input, &mut input_active);
+                        }
+                        // a remote listing only browses and transfers
+                        _ if remote::blocks(&mut app, &key, input_active) => {
+                            app.notify_error(
+                                "Not available on a remote listing, :disconnect first",
+                            );
+                        }
+                        // the focused preview
+                        KeyCode::Esc
+                            if app.preview_focused && !input_active && !block_binds(&mut app) =>
+                        {
+                            preview_search::unfocus(&mut app);
+                        }
+                        KeyCode::Char('/') if app.preview_focused && !input_active => {
+                            preview_search::handle_search(&mut app, &mut input, &mut input_active);
+                        }
+                        KeyCode::Char('F') if app.preview_focused && !input_active => {
+                            follow::handle_follow(&mut app);
+                        }
+                        KeyCode::Char(c @ ('n' | 'N'))
+                            if app.preview_focused
+                                && !input_active
+                                && preview_search::active(&app).is_some()
+                                && !block_binds(&mut app) =>
+                        {
+                            preview_search::next(&mut app, c == 'n');
+                        }
+                        _ if type_ahead && typeahead::takes(&key) => {
+                            typeahead::handle_key(&mut app, key.code);
+                        }
+                        // keys bound by plugins
+                        _ if !input_active && plugins::handle_key(&mut app, &key) => {}
+                        // move or resize the popup on top
+                        KeyCode::Left
+                        | KeyCode::Right
+                        | KeyCode::Up
+                        | KeyCode::Down
+                        | KeyCode::Char('0')
+                            if key.modifiers.contains(event::KeyModifiers::ALT)
+                                && popups::focused(&app).is_some() =>
+                        {
+                            popups::adjust(
+                                &mut app,
+                                key.code,
+                                key.modifiers.contains(event::KeyModifiers::SHIFT),
+                            );
+                        }
+                        // DEBUG
+                        KeyCode::F(12) => app.show_metrics = !app.show_metrics,
                         // EXIT
                         KeyCode::Char('c')
                             if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
                         {
-                            SysCommand::new("reset").status().unwrap_or_else(|_| {
-                                panic!("Failed to reset terminal");
-                            });
-                            nav::output_cur_dir();
-
-                            return Ok(());
+                            // the terminal is restored on the way out, then
+                            // the last directory is handed over
+                            session::save(&app);
+                            hooks::on_quit(&mut app);
+                            return Ok(None);
+                        }
+                        KeyCode::Char('r')
+                            if key.modifiers.contains(event::KeyModifiers::CONTROL)
+                                && !input_active =>
+                        {
+                            undo::redo(&mut app);
                         }
                         KeyCode::Esc => {
+                            if app.show_fzf {
+                                app.fzf_resume = false;
+                            }
+
                             if app.show_popup
                                 || app.show_nav
                                 || app.show_fzf
                                 || app.show_bookmark
                                 || app.show_help
                                 || app.show_ops_menu
+                                || app.show_jumps
+                                || app.show_mounts
+                                || app.show_open_with
+                                || app.show_treemap
+                                || app.show_kinds
+                                || app.show_du
+                                || app.show_diff
+                                || app.show_compress
+                                || app.show_export
+                                || app.show_piped
+                                || app.show_report
+                                || app.show_themes
+                                || app.show_palette
+                                || app.show_frecent
+                                || app.show_grep
                             {
                                 input_active = false;
                                 app.show_popup = false;
@@ -68,14 +214,33 @@ pub fn run_app<B: Backend>(
                                 app.show_bookmark = false;
                                 app.show_help = false;
                                 app.show_ops_menu = false;
+                                app.show_jumps = false;
+                                app.show_mounts = false;
+                                app.show_open_with = false;
+                                app.show_treemap = false;
+                                app.show_kinds = false;
+                                app.show_du = false;
+                                app.show_diff = false;
+                                app.show_compress = false;
+                                app.show_export = false;
+                                app.show_piped = false;
+                                app.show_report = false;
+                                app.show_themes = false;
+                                app.show_palette = false;
+                                app.show_frecent = false;
+                                grep::close(&mut app);
+                                app.pending_retry = None;
+                                app.pending_confirm = None;
+                                app.pending_elevate = None;
                                 input.clear();
+                            } else if app.pick {
+                                return Ok(nav::picked(&app));
+                            } else if app.from_stdin {
+                                return Ok(piped::picked(&app));
                             } else {
-                                SysCommand::new("reset").status().unwrap_or_else(|_| {
-                                    panic!("Failed to reset terminal");
-                                });
-                                nav::output_cur_dir();
-
-                                return Ok(());
+                                session::save(&app);
+                                hooks::on_quit(&mut app);
+                                return Ok(None);
                             }
                         }
                         KeyCode::Char('q') => {
@@ -88,6 +253,21 @@ pub fn run_app<B: Backend>(
                                     || app.show_bookmark
                                     || app.show_help
                                     || app.show_ops_menu
+                                    || app.show_jumps
+                                    || app.show_mounts
+                                    || app.show_open_with
+                                    || app.show_treemap
+                                    || app.show_kinds
+                                    || app.show_du
+                                    || app.show_diff
+                                    || app.show_compress
+                                    || app.show_export
+                                    || app.show_piped
+                                    || app.show_report
+                                    || app.show_themes
+                                    || app.show_palette
+                                    || app.show_frecent
+                                    || app.show_grep
                                 {
                                     input_active = false;
                                     app.show_popup = false;
@@ -97,14 +277,33 @@ pub fn run_app<B: Backend>(
                                     app.show_bookmark = false;
                                     app.show_help = false;
                                     app.show_ops_menu = false;
+                                    app.show_jumps = false;
+                                    app.show_mounts = false;
+                                    app.show_open_with = false;
+                                    app.show_treemap = false;
+                                    app.show_kinds = false;
+                                    app.show_du = false;
+                                    app.show_diff = false;
+                                    app.show_compress = false;
+                                    app.show_export = false;
+                                    app.show_piped = false;
+                                    app.show_report = false;
+                                    app.show_themes = false;
+                                    app.show_palette = false;
+                                    app.show_frecent = false;
+                                    grep::close(&mut app);
+                                    app.pending_retry = None;
+                                    app.pending_confirm = None;
+                                    app.pending_elevate = None;
                                     input.clear();
+                                } else if app.pick {
+                                    return Ok(nav::picked(&app));
+                                } else if app.from_stdin {
+                                    return Ok(piped::picked(&app));
                                 } else {
-                                    SysCommand::new("reset").status().unwrap_or_else(|_| {
-                                        panic!("Failed to reset terminal");
-                                    });
-
-                                    nav::output_cur_dir();
-                                    return Ok(());
+                                    session::save(&app);
+                                    hooks::on_quit(&mut app);
+                                    return Ok(None);
                                 }
                             }
                         }
@@ -114,14 +313,28 @@ pub fn run_app<B: Backend>(
                             if input_active {
                                 input.push('1');
                             } else {
-                                movement::handle_pane_switching(&mut app, 1);
+                                motion::digit(&mut app, 1);
                             }
                         }
                         KeyCode::Char('2') => {
                             if input_active {
                                 input.push('2');
                             } else {
-                                movement::handle_pane_switching(&mut app, 2);
+                                motion::digit(&mut app, 2);
+                            }
+                        }
+
+                        // FREQUENT DIRECTORIES
+                        KeyCode::Char('j')
+                            if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
+                        {
+                            if app.show_frecent {
+                                app.show_frecent = false;
+                                app.last_command = None;
+                                input_active = false;
+                                input.clear();
+                            } else {
+                                frecent::handle_frecent(&mut app, &mut input, &mut input_active);
                             }
                         }
 
@@ -129,15 +342,55 @@ pub fn run_app<B: Backend>(
                         KeyCode::Char('j') | KeyCode::Down => {
                             if input_active {
                                 input.push('j');
+                            } else if app.show_diff {
+                                movement::handle_diff_movement(&mut app, 1);
+                            } else if app.show_compress {
+                                movement::handle_compress_movement(&mut app, 1);
+                            } else if app.show_export {
+                                movement::handle_export_movement(&mut app, 1);
+                            } else if app.show_piped {
+                                movement::handle_piped_movement(&mut app, 1);
+                            } else if app.show_du {
+                                movement::handle_du_movement(&mut app, 1);
+                            } else if app.show_mounts {
+                                movement::handle_mounts_movement(&mut app, 1);
+                            } else if app.show_open_with {
+                                movement::handle_open_with_movement(&mut app, 1);
+                            } else if app.show_treemap {
+                                movement::handle_treemap_movement(&mut app, 1);
+                            } else if app.show_report {
+                                movement::handle_report_movement(&mut app, 1);
+                            } else if app.show_themes {
+                                movement::handle_theme_movement(&mut app, 1);
                             } else {
-                                movement::handle_movement(&mut app, 'j');
+                                motion::step(&mut app, 'j');
                             }
                         }
                         KeyCode::Char('k') | KeyCode::Up => {
                             if input_active {
                                 input.push('k');
+                            } else if app.show_diff {
+                                movement::handle_diff_movement(&mut app, -1);
+                            } else if app.show_compress {
+                                movement::handle_compress_movement(&mut app, -1);
+                            } else if app.show_export {
+                                movement::handle_export_movement(&mut app, -1);
+                            } else if app.show_piped {
+                                movement::handle_piped_movement(&mut app, -1);
+                            } else if app.show_du {
+                                movement::handle_du_movement(&mut app, -1);
+                            } else if app.show_mounts {
+                                movement::handle_mounts_movement(&mut app, -1);
+                            } else if app.show_open_with {
+                                movement::handle_open_with_movement(&mut app, -1);
+                            } else if app.show_treemap {
+                                movement::handle_treemap_movement(&mut app, -1);
+                            } else if app.show_report {
+                                movement::handle_report_movement(&mut app, -1);
+                            } else if app.show_themes {
+                                movement::handle_theme_movement(&mut app, -1);
                             } else {
-                                movement::handle_movement(&mut app, 'k');
+                                motion::step(&mut app, 'k');
                             }
                         }
                         KeyCode::Char('n')
@@ -149,6 +402,34 @@ pub fn run_app<B: Backend>(
                                 movement::handle_bookmark_movement(&mut app, 1);
                             } else if app.show_ops_menu {
                                 movement::handle_ops_menu_movement(&mut app, 1);
+                            } else if app.show_jumps {
+                                movement::handle_jump_movement(&mut app, 1);
+                            } else if app.show_mounts {
+                                movement::handle_mounts_movement(&mut app, 1);
+                            } else if app.show_open_with {
+                                movement::handle_open_with_movement(&mut app, 1);
+                            } else if app.show_diff {
+                                movement::handle_diff_movement(&mut app, 1);
+                            } else if app.show_compress {
+                                movement::handle_compress_movement(&mut app, 1);
+                            } else if app.show_export {
+                                movement::handle_export_movement(&mut app, 1);
+                            } else if app.show_piped {
+                                movement::handle_piped_movement(&mut app, 1);
+                            } else if app.show_du {
+                                movement::handle_du_movement(&mut app, 1);
+                            } else if app.show_treemap {
+                                movement::handle_treemap_movement(&mut app, 1);
+                            } else if app.show_report {
+                                movement::handle_report_movement(&mut app, 1);
+                            } else if app.show_themes {
+                                movement::handle_theme_movement(&mut app, 1);
+                            } else if app.show_palette {
+                                movement::handle_palette_movement(&mut app, 1);
+                            } else if app.show_frecent {
+                                movement::handle_frecent_movement(&mut app, 1);
+                            } else if app.show_grep {
+                                movement::handle_grep_movement(&mut app, 1);
                             }
                         }
                         KeyCode::Char('p')
@@ -160,9 +441,68 @@ pub fn run_app<B: Backend>(
                                 movement::handle_bookmark_movement(&mut app, -1);
                             } else if app.show_ops_menu {
                                 movement::handle_ops_menu_movement(&mut app, -1);
+                            } else if app.show_jumps {
+                                movement::handle_jump_movement(&mut app, -1);
+                            } else if app.show_mounts {
+                                movement::handle_mounts_movement(&mut app, -1);
+                            } else if app.show_open_with {
+                                movement::handle_open_with_movement(&mut app, -1);
+                            } else if app.show_diff {
+                                movement::handle_diff_movement(&mut app, -1);
+                            } else if app.show_compress {
+                                movement::handle_compress_movement(&mut app, -1);
+                            } else if app.show_export {
+                                movement::handle_export_movement(&mut app, -1);
+                            } else if app.show_piped {
+                                movement::handle_piped_movement(&mut app, -1);
+                            } else if app.show_du {
+                                movement::handle_du_movement(&mut app, -1);
+                            } else if app.show_treemap {
+                                movement::handle_treemap_movement(&mut app, -1);
+                            } else if app.show_report {
+                                movement::handle_report_movement(&mut app, -1);
+                            } else if app.show_themes {
+                                movement::handle_theme_movement(&mut app, -1);
+                            } else if app.show_palette {
+                                movement::handle_palette_movement(&mut app, -1);
+                            } else if app.show_frecent {
+                                movement::handle_frecent_movement(&mut app, -1);
+                            } else if app.show_grep {
+                                movement::handle_grep_movement(&mut app, -1);
                             }
                         }
 
+                        KeyCode::Char('g')
+                            if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
+                        {
+                            nav::toggle_fzf_ignore(&mut app, &mut input, &mut input_active);
+                        }
+                        KeyCode::Char('g') => {
+                            if input_active {
+                                input.push('g');
+                            } else {
+                                motion::handle_g(&mut app);
+                            }
+                        }
+                        KeyCode::Char('G') => {
+                            if input_active {
+                                input.push('G');
+                            } else {
+                                motion::handle_bottom(&mut app);
+                            }
+                        }
+                        // half a page, CTRL + d is further down since the popups
+                        // remove entries with it
+                        KeyCode::Char('u')
+                            if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
+                        {
+                            motion::half_page(&mut app, false);
+                        }
+                        KeyCode::PageUp => motion::page(&mut app, false),
+                        KeyCode::PageDown => motion::page(&mut app, true),
+                        KeyCode::Home => motion::edge(&mut app, true),
+                        KeyCode::End => motion::edge(&mut app, false),
+
                         // BOOKMARKS
                         KeyCode::Char('z') => {
                             if input_active {
@@ -171,6 +511,27 @@ pub fn run_app<B: Backend>(
                                 bookmark::add_bookmark(&mut app);
                             }
                         }
+                        KeyCode::Char('Z') => {
+                            if input_active {
+                                input.push('Z');
+                            } else if !block_binds(&mut app) {
+                                bookmark::add_selected_bookmark(&mut app);
+                            }
+                        }
+                        KeyCode::Char('e') if app.show_mounts && !input_active => {
+                            mounts::handle_eject(&mut app);
+                        }
+                        KeyCode::Char('e') => {
+                            if input_active {
+                                input.push('e');
+                            } else {
+                                bookmark::handle_edit_bookmark(
+                                    &mut app,
+                                    &mut input,
+                                    &mut input_active,
+                                );
+                            }
+                        }
                         KeyCode::Char('b') => {
                             if input_active {
                                 input.push('b');
@@ -179,6 +540,59 @@ pub fn run_app<B: Backend>(
                             }
                         }
 
+                        // JUMP LIST
+                        KeyCode::Char('o')
+                            if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
+                        {
+                            jumps::jump_back(&mut app);
+                        }
+                        // terminals send ctrl+i as a tab
+                        KeyCode::Tab
+                            if input_active && secret::is_secret(app.last_command.as_ref()) =>
+                        {
+                            app.reveal_input = !app.reveal_input;
+                        }
+                        KeyCode::Tab
+                            if input_active && app.last_command == Some(Command::CreateFile) =>
+                        {
+                            app.use_template = !app.use_template;
+                        }
+                        KeyCode::Tab if input_active && app.show_nav => {
+                            nav::complete(&mut app, &mut input);
+                        }
+                        KeyCode::Tab if !input_active => {
+                            jumps::jump_forward(&mut app);
+                        }
+                        KeyCode::Char('i')
+                            if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
+                        {
+                            jumps::jump_forward(&mut app);
+                        }
+                        KeyCode::Char('i') => {
+                            if input_active {
+                                input.push('i');
+                            } else {
+                                properties::handle_properties(&mut app);
+                            }
+                        }
+                        KeyCode::Char('D') => {
+                            if input_active {
+                                input.push('D');
+                            } else {
+                                mounts::handle_mounts(&mut app);
+                            }
+                        }
+                        KeyCode::Char('\'') => {
+                            if input_active {
+                                input.push('\'');
+                            } else if app.show_jumps {
+                                app.show_jumps = false;
+                                app.last_command = None;
+                            } else {
+                                jumps::handle_jumps(&mut app);
+                            }
+                        }
+
                         // FILE OPS
                         KeyCode::Char('n') => {
                             if input_active {
@@ -187,15 +601,148 @@ pub fn run_app<B: Backend>(
                                 file_ops::handle_new_file(&mut app, &mut input_active);
                             }
                         }
+                        KeyCode::Char('!') => {
+                            if input_active {
+                                input.push('!');
+                            } else {
+                                shell::handle_shell_command(&mut app, &mut input_active);
+                            }
+                        }
+                        KeyCode::Char('S') => {
+                            if input_active {
+                                input.push('S');
+                            } else if !block_binds(&mut app) {
+                                shell::drop_to_shell(terminal, &mut app)?;
+                            }
+                        }
+                        KeyCode::Char('P') => {
+                            if input_active {
+                                input.push('P');
+                            } else {
+                                pins::toggle_pin(&mut app);
+                            }
+                        }
+                        KeyCode::Char('u') => {
+                            if input_active {
+                                input.push('u');
+                            } else {
+                                undo::undo(&mut app);
+                            }
+                        }
+                        KeyCode::Char('K') => {
+                            if input_active {
+                                input.push('K');
+                            } else {
+                                kinds::handle_kinds(&mut app);
+                            }
+                        }
+                        KeyCode::Char('J') => {
+                            if input_active {
+                                input.push('J');
+                            } else {
+                                jobs::show_history(&mut app);
+                            }
+                        }
+                        // CLIPBOARD
+                        KeyCode::Char('y')
+                            if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
+                        {
+                            clipboard::copy_selected(&mut app, clipboard::PathKind::Relative);
+                        }
+                        KeyCode::Char('y') => {
+                            if input_active {
+                                input.push('y');
+                            } else {
+                                clipboard::copy_selected(&mut app, clipboard::PathKind::Path);
+                            }
+                        }
+                        KeyCode::Char('v')
+                            if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
+                        {
+                            clipboard::paste_files(&mut app);
+                        }
+                        // the pager opens what it can in its own view, the rest goes
+                        // to $PAGER
+                        KeyCode::Char('v')
+                            if !input_active
+                                && !block_binds(&mut app)
+                                && app.remote.is_none()
+                                && !pager::open(&mut app) =>
+                        {
+                            shell::page(terminal, &mut app)?;
+                        }
+                        KeyCode::Char('C') => {
+                            if input_active {
+                                input.push('C');
+                            } else {
+                                clipboard::copy_files(&mut app);
+                            }
+                        }
+                        KeyCode::Char('Y') => {
+                            if input_active {
+                                input.push('Y');
+                            } else {
+                                clipboard::copy_selected(&mut app, clipboard::PathKind::Name);
+                            }
+                        }
+                        KeyCode::Char('W') => {
+                            if input_active {
+                                input.push('W');
+                            } else {
+                                wsl::copy_windows_path(&mut app);
+                            }
+                        }
+                        KeyCode::Char('*') => {
+                            if input_active {
+                                input.push('*');
+                            } else {
+                                filter::handle_filter(&mut app, &mut input, &mut input_active);
+                            }
+                        }
+                        KeyCode::Char('F') => {
+                            if input_active {
+                                input.push('F');
+                            } else {
+                                flatten::handle_flatten(&mut app);
+                            }
+                        }
+                        KeyCode::Char('O') => {
+                            if input_active {
+                                input.push('O');
+                            } else {
+                                open_with::handle_open_with(&mut app);
+                            }
+                        }
+                        KeyCode::Char('E') => {
+                            if input_active {
+                                input.push('E');
+                            } else {
+                                wsl::open_in_windows(&mut app);
+                            }
+                        }
+                        KeyCode::Char('L') => {
+                            if input_active {
+                                input.push('L');
+                            } else {
+                                file_ops::handle_new_symlink(&mut app, &mut input_active);
+                            }
+                        }
                         KeyCode::Char('d')
                             if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
                         {
                             if app.show_bookmark {
                                 bookmark::delete_bookmark(&mut app);
+                            } else if app.show_treemap {
+                                treemap::delete_selected(&mut app);
+                            } else if app.show_report && app.properties.is_some() {
+                                properties::remove_selected_stream(&mut app);
+                            } else if app.show_report && app.report_jobs {
+                                jobs::kill_selected(&mut app);
                             } else {
-                                file_ops::handle_delete(&mut app);
+                                motion::half_page(&mut app, true);
                             }
                         }
+                        KeyCode::Delete if !input_active => file_ops::handle_delete(&mut app),
                         KeyCode::Char('c') => {
                             if input_active {
                                 input.push('c');
@@ -206,6 +753,8 @@ pub fn run_app<B: Backend>(
                         KeyCode::Char('p') => {
                             if input_active {
                                 input.push('p');
+                            } else if app.remote.is_some() {
+                                remote::upload_buffer(&mut app);
                             } else {
                                 if app.files.state.selected().is_some()
                                     || app.dirs.state.selected().is_some()
@@ -218,7 +767,27 @@ pub fn run_app<B: Backend>(
                             if input_active {
                                 input.push('x');
                             } else {
-                                file_ops::extract(&mut app);
+                                file_ops::extract(&mut app, &mut input, &mut input_active);
+                            }
+                        }
+                        KeyCode::Char('R') => {
+                            if input_active {
+                                input.push('R');
+                            } else {
+                                export::handle_export(&mut app);
+                            }
+                        }
+                        KeyCode::Char('+') if app.show_export => {
+                            export::change_depth(&mut app, 1);
+                        }
+                        KeyCode::Char('-') if app.show_export => {
+                            export::change_depth(&mut app, -1);
+                        }
+                        KeyCode::Char('X') => {
+                            if input_active {
+                                input.push('X');
+                            } else {
+                                compress::handle_compress(&mut app);
                             }
                         }
                         KeyCode::Char('r') => {
@@ -229,6 +798,131 @@ pub fn run_app<B: Backend>(
                             }
                         }
 
+                        // TREEMAP
+                        KeyCode::Char('T') => {
+                            if input_active {
+                                input.push('T');
+                            } else if app.show_treemap {
+                                app.show_treemap = false;
+                                app.last_command = None;
+                            } else {
+                                treemap::handle_treemap(&mut app);
+                            }
+                        }
+                        KeyCode::Char('=') => {
+                            if input_active {
+                                input.push('=');
+                            } else {
+                                diff::handle_diff(&mut app);
+                            }
+                        }
+                        KeyCode::Char('H') => {
+                            if input_active {
+                                input.push('H');
+                            } else {
+                                checksum::handle_hash(&mut app);
+                            }
+                        }
+                        KeyCode::Char('V') => {
+                            if input_active {
+                                input.push('V');
+                            } else {
+                                piped::handle_piped(&mut app);
+                            }
+                        }
+                        KeyCode::Char(' ') if app.show_piped && !input_active => {
+                            piped::toggle_mark(&mut app);
+                        }
+                        KeyCode::Char('U') => {
+                            if input_active {
+                                input.push('U');
+                            } else {
+                                du::handle_du(&mut app);
+                            }
+                        }
+
+                        // GIT
+                        KeyCode::Char('<') => {
+                            if input_active {
+                                input.push('<');
+                            } else {
+                                mouse::resize_preview(&mut app, false);
+                            }
+                        }
+                        KeyCode::Char('>') => {
+                            if input_active {
+                                input.push('>');
+                            } else {
+                                mouse::resize_preview(&mut app, true);
+                            }
+                        }
+                        KeyCode::Char('m') => {
+                            if input_active {
+                                input.push('m');
+                            } else {
+                                checksum::handle_manifest(&mut app);
+                            }
+                        }
+                        KeyCode::Char('M') => {
+                            if input_active {
+                                input.push('M');
+                            } else {
+                                checksum::handle_verify(&mut app);
+                            }
+                        }
+                        KeyCode::Char('t') => {
+                            if input_active {
+                                input.push('t');
+                            } else {
+                                themes::handle_themes(&mut app);
+                            }
+                        }
+                        KeyCode::Char('I') => {
+                            if input_active {
+                                input.push('I');
+                            } else if !block_binds(&mut app) {
+                                app.hide_ignored = !app.hide_ignored;
+                                app.update_files();
+                                app.update_dirs();
+                            }
+                        }
+
+                        // TYPE-AHEAD
+                        KeyCode::Char('l') | KeyCode::Right
+                            if !input_active && dir_tree::active(&mut app) =>
+                        {
+                            dir_tree::handle_expand(&mut app);
+                        }
+                        KeyCode::Char('h') | KeyCode::Left
+                            if !input_active && dir_tree::active(&mut app) =>
+                        {
+                            dir_tree::handle_collapse(&mut app);
+                        }
+                        // ranger style, in the three column layout
+                        KeyCode::Char('h') if !input_active && app.layout == PaneLayout::Miller => {
+                            listing::handle_up(&mut app);
+                        }
+                        KeyCode::Char('l') if !input_active && app.layout == PaneLayout::Miller => {
+                            listing::handle_open(&mut app);
+                        }
+
+                        KeyCode::Char('/') => {
+                            if input_active {
+                                input.push('/');
+                            } else {
+                                typeahead::handle_type_ahead(&mut app);
+                            }
+                        }
+
+                        // COMMAND PALETTE
+                        KeyCode::Char(':') => {
+                            if input_active {
+                                input.push(':');
+                            } else {
+                                palette::handle_palette(&mut app, &mut input, &mut input_active);
+                            }
+                        }
+
                         // HELP MENU
                         KeyCode::Char('?') => {
                             if input_active {
@@ -245,7 +939,7 @@ pub fn run_app<B: Backend>(
                         KeyCode::Char('w') => {
                             if input_active {
                                 input.push('w');
-                            } else {
+                            } else if !nav::resume_fzf(&mut app, &mut input, &mut input_active) {
                                 nav::handle_fzf(&mut app, &mut input, &mut input_active);
                             }
                         }
@@ -259,16 +953,76 @@ pub fn run_app<B: Backend>(
 
                         // SUBMIT
                         KeyCode::Enter => {
-                            if app.show_fzf {
+                            if app.show_palette {
+                                let action =
+                                    palette::execute(&mut app, &mut input, &mut input_active);
+
+                                if action == Some(palette::Action::Shell) {
+                                    shell::drop_to_shell(terminal, &mut app)?;
+                                }
+                            } else if app.show_frecent {
+                                frecent::handle_open_frecent(
+                                    &mut app,
+                                    &mut input,
+                                    &mut input_active,
+                                );
+                            } else if app.show_grep {
+                                grep::handle_submit(&mut app, &mut input, &mut input_active);
+                            } else if app.show_fzf {
                                 submit::handle_open_fzf_result(
                                     &mut app,
                                     &mut input,
                                     &mut input_active,
                                 );
+                            } else if app.last_command == Some(Command::Shell) {
+                                shell::run_command(
+                                    terminal,
+                                    &mut app,
+                                    &mut input,
+                                    &mut input_active,
+                                )?;
                             } else if input_active {
                                 submit::handle_submit(&mut app, &mut input, &mut input_active);
                             } else if app.show_bookmark {
                                 submit::handle_open_bookmark(&mut app);
+                            } else if app.show_jumps {
+                                jumps::handle_open_jump(&mut app);
+                            } else if app.show_mounts {
+                                mounts::handle_open_mount(&mut app);
+                            } else if app.show_open_with {
+                                open_with::run(terminal, &mut app)?;
+                            } else if app.show_piped {
+                                piped::handle_open_piped(&mut app);
+                            } else if app.show_export {
+                                export::handle_choose_export(
+                                    &mut app,
+                                    &mut input,
+                                    &mut input_active,
+                                );
+                            } else if app.show_compress {
+                                compress::handle_choose_format(
+                                    &mut app,
+                                    &mut input,
+                                    &mut input_active,
+                                );
+                            } else if app.show_treemap {
+                                treemap::drill_in(&mut app);
+                            } else if app.show_du {
+                                du::drill_in(&mut app);
+                            } else if app.show_report && app.pending_confirm.is_some() {
+                                preflight::confirm(&mut app);
+                            } else if app.show_report && app.pending_retry.is_some() {
+                                locking::retry(&mut app);
+                            } else if app.show_report && app.pending_elevate.is_some() {
+                                elevate::confirm(&mut app, &mut input, &mut input_active);
+                            } else if app.show_report && app.hashes.is_some() {
+                                checksum::copy_hash(&mut app);
+                            } else if app.show_report && app.report_jobs {
+                                jobs::show_output(&mut app);
+                            } else if app.show_report {
+                                app.show_report = false;
+                            } else if app.show_themes {
+                                themes::handle_select_theme(&mut app);
                             } else if app.show_ops_menu {
                                 if app.ops_menu.state.selected().is_none() {
                                     app.show_ops_menu = false;
@@ -276,6 +1030,8 @@ pub fn run_app<B: Backend>(
                                 } else {
                                     file_ops::handle_paste_or_move(&mut app);
                                 }
+                            } else if let Some(file) = nav::picked_file(&app) {
+                                return Ok(Some(file));
                             } else {
                                 submit::handle_submit(&mut app, &mut input, &mut input_active);
                             }
@@ -288,25 +1044,45 @@ pub fn run_app<B: Backend>(
                                 if app.show_fzf {
                                     nav::handle_fzf(&mut app, &mut input, &mut input_active);
                                 }
+                            } else if app.show_treemap {
+                                treemap::drill_out(&mut app);
+                            } else if app.show_du {
+                                du::drill_out(&mut app);
+                            } else if !block_binds(&mut app) {
+                                nav::resume_fzf(&mut app, &mut input, &mut input_active);
                             }
                         }
 
+                        // a count for the next motion
+                        KeyCode::Char(c) if c.is_ascii_digit() && !input_active => {
+                            motion::digit(&mut app, c.to_digit(10).unwrap_or(0) as usize);
+                        }
+
                         // OTHER CHARACTERS
-                        KeyCode::Char(c) => {
-                            if input_active {
-                                input.push(c);
+                        KeyCode::Char(c) if input_active => {
+                            input.push(c);
 
-                                if app.last_command == Some(Command::ShowFzf) {
-                                    nav::handle_fzf(&mut app, &mut input, &mut input_active);
-                                }
+                            if app.last_command == Some(Command::ShowFzf) {
+                                nav::handle_fzf(&mut app, &mut input, &mut input_active);
+                            }
 
-                                if app.show_fzf {
-                                    nav::handle_fzf(&mut app, &mut input, &mut input_active);
-                                }
+                            if app.show_fzf {
+                                nav::handle_fzf(&mut app, &mut input, &mut input_active);
                             }
                         }
                         _ => {}
                     }
+
+                    // every key that edits the query goes through a different arm
+                    if app.show_palette {
+                        palette::filter(&mut app, &input);
+                    }
+                    if app.show_frecent {
+                        frecent::filter(&mut app, &input);
+                    }
+                    if app.show_nav {
+                        nav::update_completions(&mut app, &input);
+                    }
                 }
             }
         }
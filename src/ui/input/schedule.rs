@@ -0,0 +1,293 @@
+use super::du;
+use super::jobs;
+use super::shell::shell_command;
+use crate::app::app::App;
+use crate::ui::display::pane::get_du;
+use std::process::{Child, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// anything more often than this is a busy loop
+const MIN_INTERVAL: Duration = Duration::from_secs(5);
+
+// what a `schedule=` line in the config runs
+enum Task {
+    // re-read the disk usage in the details bar, and the disk usage popup
+    Disk,
+    // re-list the panes and re-read the git status
+    Refresh,
+    // a shell command from the launch directory, a sync for instance
+    Run(String),
+}
+
+enum Running {
+    Disk(Receiver<String>),
+    Command(Child),
+}
+
+pub struct Scheduled {
+    // the config value it came from, `5m refresh`
+    pub spec: String,
+    task: Task,
+    every: Duration,
+    next: Instant,
+    running: Option<(Running, Instant)>,
+    runs: usize,
+    failed: Option<String>,
+}
+
+// 30s, 5m, 2h, or plain seconds
+fn parse_interval(text: &str) -> Option<Duration> {
+    let (number, unit) = match text.find(|c: char| !c.is_ascii_digit()) {
+        Some(idx) => text.split_at(idx),
+        None => (text, "s"),
+    };
+    let number: u64 = number.parse().ok()?;
+
+    let secs = match unit {
+        "s" => number,
+        "m" => number * 60,
+        "h" => number * 3600,
+        _ => return None,
+    };
+
+    Some(Duration::from_secs(secs).max(MIN_INTERVAL))
+}
+
+fn parse(spec: &str) -> Option<Scheduled> {
+    let (interval, task) = spec.trim().split_once(' ')?;
+    let every = parse_interval(interval)?;
+
+    let task = match task.trim() {
+        "disk" => Task::Disk,
+        "refresh" => Task::Refresh,
+        task => Task::Run(task.strip_prefix("run ")?.trim().to_string()),
+    };
+
+    Some(Scheduled {
+        spec: spec.trim().to_string(),
+        task,
+        every,
+        next: Instant::now() + every,
+        running: None,
+        runs: 0,
+        failed: None,
+    })
+}
+
+// the config is read again on every refresh, the jobs already scheduled keep
+// their timers and the cancelled ones stay cancelled until traverse restarts
+pub fn configure(app: &mut App, specs: Vec<String>) {
+    let mut schedule = std::mem::take(&mut app.schedule);
+
+    for spec in specs {
+        let spec = spec.trim().to_string();
+        if app.cancelled_schedule.contains(&spec) || app.schedule.iter().any(|job| job.spec == spec)
+        {
+            continue;
+        }
+
+        match schedule.iter().position(|job| job.spec == spec) {
+            Some(idx) => app.schedule.push(schedule.remove(idx)),
+            None => app.schedule.extend(parse(&spec)),
+        }
+    }
+
+    // removed from the config while running
+    for job in &mut schedule {
+        job.cancel();
+    }
+}
+
+impl Scheduled {
+    fn name(&self) -> String {
+        match &self.task {
+            Task::Disk => "disk usage".to_string(),
+            Task::Refresh => "refresh".to_string(),
+            Task::Run(command) => command.clone(),
+        }
+    }
+
+    fn cancel(&mut self) {
+        if let Some((Running::Command(child), _)) = &mut self.running {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        self.running = None;
+    }
+
+    fn start(&mut self, app: &mut App) {
+        self.next = Instant::now() + self.every;
+        self.runs += 1;
+
+        match &self.task {
+            Task::Disk => {
+                let (tx, rx) = mpsc::channel();
+                let si = app.si_units;
+                thread::spawn(move || {
+                    let _ = tx.send(get_du(si));
+                });
+                self.running = Some((Running::Disk(rx), Instant::now()));
+
+                if app.show_du && app.du_job.is_none() {
+                    du::build(app);
+                }
+            }
+            Task::Refresh => {
+                app.git_refreshed = None;
+                app.update_files();
+                app.update_dirs();
+                app.clamp_selection();
+            }
+            Task::Run(command) => {
+                let child = shell_command(command)
+                    .current_dir(&app.launch_dir)
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .spawn();
+
+                match child {
+                    Ok(child) => self.running = Some((Running::Command(child), Instant::now())),
+                    Err(e) => self.failed = Some(e.to_string()),
+                }
+            }
+        }
+    }
+
+    // true once what was running is done
+    fn poll(&mut self, app: &mut App) -> bool {
+        let done = match &mut self.running {
+            Some((Running::Disk(rx), _)) => match rx.try_recv() {
+                Ok(du) => {
+                    app.cur_du = du;
+                    true
+                }
+                Err(mpsc::TryRecvError::Empty) => false,
+                Err(mpsc::TryRecvError::Disconnected) => true,
+            },
+            Some((Running::Command(child), _)) => match child.try_wait() {
+                Ok(Some(status)) if status.success() => {
+                    self.failed = None;
+                    true
+                }
+                Ok(Some(status)) => {
+                    self.failed = Some(status.to_string());
+                    true
+                }
+                Ok(None) => false,
+                Err(e) => {
+                    self.failed = Some(e.to_string());
+                    true
+                }
+            },
+            None => false,
+        };
+
+        if done {
+            self.running = None;
+        }
+        done
+    }
+
+    // one line for the job history
+    fn line(&self) -> String {
+        let state = match &self.running {
+            Some((_, started)) => {
+                format!("running for {}", jobs::format_elapsed(started.elapsed()))
+            }
+            None => format!(
+                "next in {}",
+                jobs::format_elapsed(self.next.saturating_duration_since(Instant::now()))
+            ),
+        };
+
+        let failed = match &self.failed {
+            Some(err) => format!(", last run failed: {}", err),
+            None => String::new(),
+        };
+
+        format!(
+            "every {}  {}  {}, runs: {}{}",
+            self.spec.split(' ').next().unwrap_or_default(),
+            self.name(),
+            state,
+            self.runs,
+            failed
+        )
+    }
+}
+
+// starts what's due and collects what finished, every tick
+pub fn poll_schedule(app: &mut App) {
+    if app.schedule.is_empty() {
+        return;
+    }
+
+    let mut schedule = std::mem::take(&mut app.schedule);
+
+    for job in &mut schedule {
+        // said once, not on every failing run
+        let failing = job.failed.is_some();
+        if job.poll(app) && !failing {
+            if let (Some(err), Task::Run(command)) = (&job.failed, &job.task) {
+                app.notify_error(format!("Scheduled `{}` failed: {}", command, err));
+            }
+        }
+
+        // a run still going when the next is due is skipped, not doubled up
+        if job.running.is_none() && Instant::now() >= job.next {
+            job.start(app);
+        }
+    }
+
+    // a refresh reads the config into the emptied schedule, only the jobs
+    // that are new there are kept
+    let added = std::mem::replace(&mut app.schedule, schedule);
+    for job in added {
+        if !app.schedule.iter().any(|kept| kept.spec == job.spec) {
+            app.schedule.push(job);
+        }
+    }
+}
+
+pub fn running(app: &App) -> bool {
+    app.schedule.iter().any(|job| job.running.is_some())
+}
+
+// the lines the job history starts with
+pub fn lines(app: &App) -> Vec<String> {
+    if app.schedule.is_empty() {
+        return vec![];
+    }
+
+    let mut lines = vec!["Scheduled (CTRL + d cancels the selected one):".to_string()];
+    lines.extend(app.schedule.iter().map(|job| format!("  {}", job.line())));
+    lines.push(String::new());
+
+    lines
+}
+
+// CTRL + d in the job history
+pub fn cancel_selected(app: &mut App) {
+    let idx = match app.report.state.selected() {
+        Some(idx) if idx >= 1 && idx <= app.schedule.len() => idx - 1,
+        _ => return app.notify_error("Select a scheduled job to cancel it"),
+    };
+
+    let mut job = app.schedule.remove(idx);
+    job.cancel();
+    app.cancelled_schedule.push(job.spec.clone());
+    app.notify(format!(
+        "Cancelled the scheduled {} until traverse restarts",
+        job.name()
+    ));
+
+    jobs::show_history(app);
+    if !app.schedule.is_empty() {
+        app.report
+            .state
+            .select(Some(idx.min(app.schedule.len() - 1) + 1));
+    }
+}
@@ -0,0 +1,39 @@
+use super::run_app::Command;
+use crate::app::app::App;
+
+// the prompts typed into blind: archive passwords, logins and sudo
+pub fn is_secret(command: Option<&Command>) -> bool {
+    matches!(
+        command,
+        Some(Command::Password)
+            | Some(Command::RemotePassword)
+            | Some(Command::ArchivePassword)
+            | Some(Command::SudoPassword)
+    )
+}
+
+// opens the popup for `command` masked, whatever the last one showed
+pub fn ask(app: &mut App, input_active: &mut bool, command: Command) {
+    *input_active = true;
+    app.show_popup = true;
+    app.reveal_input = false;
+    app.last_command = Some(command);
+}
+
+// what the popup shows for `input`, a * per character unless Tab revealed it
+pub fn masked(app: &App, input: &str) -> String {
+    if is_secret(app.last_command.as_ref()) && !app.reveal_input {
+        "*".repeat(input.chars().count())
+    } else {
+        input.to_string()
+    }
+}
+
+// the hint after the prompt's title
+pub fn hint(app: &App) -> &'static str {
+    if app.reveal_input {
+        " (Tab hides it)"
+    } else {
+        " (Tab shows it)"
+    }
+}
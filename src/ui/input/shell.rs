@@ -0,0 +1,322 @@
+use super::jobs;
+use super::run_app::Command;
+use super::terminal_title;
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use crate::ui::display::pane::get_pwd;
+use anyhow::Result;
+use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::backend::Backend;
+use ratatui::terminal::Terminal;
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::Command as SysCommand;
+
+pub fn handle_shell_command(app: &mut App, input_active: &mut bool) {
+    if block_binds(app) || *input_active {
+        return;
+    }
+
+    *input_active = true;
+    app.show_popup = true;
+    app.last_command = Some(Command::Shell);
+}
+
+// bytes that aren't UTF-8 go in through printf, so the command gets the
+// name as it is on disk
+#[cfg(unix)]
+pub fn quote(path: &OsStr) -> String {
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut quoted = String::new();
+    for chunk in path.as_bytes().utf8_chunks() {
+        if !chunk.valid().is_empty() {
+            quoted.push_str(&format!("'{}'", chunk.valid().replace('\'', "'\\''")));
+        }
+        if !chunk.invalid().is_empty() {
+            let octal = chunk
+                .invalid()
+                .iter()
+                .map(|byte| format!("\\{:03o}", byte))
+                .collect::<String>();
+            quoted.push_str(&format!("\"$(printf '{}')\"", octal));
+        }
+    }
+
+    if quoted.is_empty() {
+        quoted.push_str("''");
+    }
+    quoted
+}
+
+#[cfg(windows)]
+pub fn quote(path: &OsStr) -> String {
+    format!("\"{}\"", path.to_string_lossy())
+}
+
+// {} is the entry under the cursor, {files} everything in the move/copy
+// buffer, or the entry under the cursor when the buffer is empty
+pub fn substitute(app: &App, command: &str) -> String {
+    let current = app
+        .selected_entry()
+        .map(|entry| quote(&entry))
+        .unwrap_or_default();
+
+    let files = if app.selected_files.is_empty() {
+        current.clone()
+    } else {
+        app.selected_files
+            .iter()
+            .map(|file| quote(file.as_ref()))
+            .collect::<Vec<String>>()
+            .join(" ")
+    };
+
+    fill(command, &current, &files)
+}
+
+// one pass over the template, so a {} in a substituted name stays as it is
+fn fill(command: &str, current: &str, files: &str) -> String {
+    let mut filled = String::with_capacity(command.len());
+    let mut rest = command;
+
+    while let Some(at) = rest.find('{') {
+        filled.push_str(&rest[..at]);
+        rest = &rest[at..];
+
+        if let Some(after) = rest.strip_prefix("{files}") {
+            filled.push_str(files);
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("{}") {
+            filled.push_str(current);
+            rest = after;
+        } else {
+            filled.push('{');
+            rest = &rest[1..];
+        }
+    }
+
+    filled.push_str(rest);
+    filled
+}
+
+// hands the terminal to whatever `run` starts and takes it back afterwards
+pub fn suspend<B: Backend + Write>(
+    terminal: &mut Terminal<B>,
+    app: &App,
+    run: impl FnOnce(),
+) -> Result<()> {
+    if app.inline.is_some() {
+        terminal.clear()?;
+    } else {
+        execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    }
+    execute!(terminal.backend_mut(), DisableMouseCapture)?;
+    terminal.show_cursor()?;
+    disable_raw_mode()?;
+
+    run();
+    terminal_title::invalidate();
+
+    enable_raw_mode()?;
+    if app.inline.is_none() {
+        execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+    }
+    execute!(terminal.backend_mut(), EnableMouseCapture)?;
+    terminal.clear()?;
+
+    Ok(())
+}
+
+// with --from-stdin, stdin is the pipe the paths came through, commands and
+// the Enter prompt read from the terminal instead
+fn terminal_input() -> Option<File> {
+    #[cfg(unix)]
+    let tty = File::open("/dev/tty");
+    #[cfg(windows)]
+    let tty = File::open("CONIN$");
+
+    tty.ok()
+}
+
+pub fn redirect(child: &mut SysCommand, app: &App) {
+    // with --pick or --from-stdin stdout is the pipe the result goes to
+    if app.pick || app.from_stdin {
+        child.stdout(io::stderr());
+    }
+
+    if app.from_stdin {
+        if let Some(tty) = terminal_input() {
+            child.stdin(tty);
+        }
+    }
+}
+
+pub fn wait_for_enter(app: &App) {
+    match terminal_input().filter(|_| app.from_stdin) {
+        Some(tty) => {
+            let _ = BufReader::new(tty).read_line(&mut String::new());
+        }
+        None => {
+            let _ = io::stdin().read_line(&mut String::new());
+        }
+    }
+}
+
+pub fn shell_command(command: &str) -> SysCommand {
+    #[cfg(unix)]
+    let mut shell = SysCommand::new("sh");
+    #[cfg(unix)]
+    shell.arg("-c").arg(command);
+
+    #[cfg(windows)]
+    let mut shell = SysCommand::new("cmd");
+    #[cfg(windows)]
+    shell.arg("/C").arg(command);
+
+    shell
+}
+
+pub fn run_command<B: Backend + Write>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    input: &mut String,
+    input_active: &mut bool,
+) -> Result<()> {
+    let command = substitute(app, input.trim());
+
+    input.clear();
+    *input_active = false;
+    app.show_popup = false;
+    app.last_command = None;
+
+    if command.is_empty() {
+        return Ok(());
+    }
+
+    // a trailing & leaves the terminal to traverse, J shows the output
+    if let Some(command) = command.strip_suffix('&') {
+        jobs::spawn_command(app, command.trim_end());
+        return Ok(());
+    }
+
+    suspend(terminal, app, || {
+        let mut shell = shell_command(&command);
+        redirect(&mut shell, app);
+
+        eprintln!("$ {}", command);
+        let status = match shell.status() {
+            Ok(status) => match status.code() {
+                Some(code) => format!("exited with {}", code),
+                None => "was killed".to_string(),
+            },
+            Err(e) => format!("couldn't start: {}", e),
+        };
+
+        eprint!("\n[{}] Press Enter to return to traverse", status);
+        wait_for_enter(app);
+    })?;
+
+    // the command may have changed anything in here
+    app.git_refreshed = None;
+    app.update_files();
+    app.update_dirs();
+    app.clamp_selection();
+    app.cur_dir = get_pwd();
+
+    Ok(())
+}
+
+// an interactive shell in the current directory, traverse comes back when
+// it exits
+pub fn drop_to_shell<B: Backend + Write>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()> {
+    #[cfg(unix)]
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "sh".to_string());
+    #[cfg(windows)]
+    let shell = std::env::var("COMSPEC").unwrap_or_else(|_| "cmd".to_string());
+
+    suspend(terminal, app, || {
+        eprintln!("Exit the shell to return to traverse");
+
+        let mut child = SysCommand::new(&shell);
+        redirect(&mut child, app);
+
+        if let Err(e) = child.status() {
+            eprint!("Couldn't start {}: {}, press Enter to return", shell, e);
+            wait_for_enter(app);
+        }
+    })?;
+
+    app.git_refreshed = None;
+    app.update_files();
+    app.update_dirs();
+    app.clamp_selection();
+
+    Ok(())
+}
+
+// the selected file in $PAGER (less without one), for what the preview
+// doesn't read
+pub fn page<B: Backend + Write>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()> {
+    if block_binds(app) || app.remote.is_some() {
+        return Ok(());
+    }
+    let file = match app.files.state.selected().and(app.selected_entry()) {
+        Some(file) => file,
+        None => return Ok(()),
+    };
+    let pager = std::env::var("PAGER")
+        .ok()
+        .filter(|pager| !pager.trim().is_empty())
+        .unwrap_or_else(|| "less".to_string());
+    let command = format!("{} {}", pager, quote(&file));
+
+    suspend(terminal, app, || {
+        let mut shell = shell_command(&command);
+        redirect(&mut shell, app);
+
+        // a pager that isn't installed leaves the shell's complaint to read
+        let failed = match shell.status() {
+            Ok(status) => !status.success(),
+            Err(e) => {
+                eprint!("Couldn't start {}: {}", pager, e);
+                true
+            }
+        };
+        if failed {
+            eprint!("\nPress Enter to return to traverse");
+            wait_for_enter(app);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_both_placeholders() {
+        assert_eq!(
+            fill("cp {files} {}.bak", "'cur'", "'a' 'b'"),
+            "cp 'a' 'b' 'cur'.bak"
+        );
+        assert_eq!(fill("echo {x} {", "'cur'", "'a'"), "echo {x} {");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn leaves_braces_in_names_alone() {
+        let files = [quote(OsStr::new("a{}b")), quote(OsStr::new("{files}"))].join(" ");
+        let current = quote(OsStr::new("cur{}"));
+
+        assert_eq!(
+            fill("tar cf x.tar {files} && ls {}", &current, &files),
+            "tar cf x.tar 'a{}b' '{files}' && ls 'cur{}'"
+        );
+    }
+}
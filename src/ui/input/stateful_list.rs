@@ -1,8 +1,28 @@
 use ratatui::widgets::ListState;
+use std::ops::Range;
+
+// `by` items on from `selected`, around the ends when `wrap` (app.wrap_lists,
+// from `wrap_lists`) or stopping at them
+pub fn stepped(selected: usize, by: isize, len: usize, wrap: bool) -> usize {
+    let moved = selected as isize + by;
+
+    if wrap {
+        moved.rem_euclid(len as isize) as usize
+    } else {
+        moved.clamp(0, len as isize - 1) as usize
+    }
+}
+
+// rows built past the bottom of a window, in case it's drawn a little
+// taller than it was measured
+const MARGIN: usize = 4;
 
 pub struct StatefulList<T> {
     pub state: ListState,
     pub items: Vec<T>,
+    pub offset: usize,
+    // rows the list was last drawn with, what a page is
+    pub height: usize,
 }
 
 impl<T> StatefulList<T> {
@@ -10,36 +30,138 @@ impl<T> StatefulList<T> {
         StatefulList {
             state: ListState::default(),
             items,
+            offset: 0,
+            height: 0,
         }
     }
 
-    pub fn next(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i >= self.items.len() - 1 {
-                    0
-                } else {
-                    i + 1
+    // the highlighted item, none when nothing is or the list is empty with
+    // Some(0) still selected
+    pub fn selected_item(&self) -> Option<&T> {
+        self.state.selected().and_then(|idx| self.items.get(idx))
+    }
+
+    // ratatui keeps the scroll offset of a list to itself, so follow
+    // the same rules here to know which item sits on which row
+    pub fn update_offset(&mut self, height: usize) {
+        self.height = height;
+
+        match self.state.selected() {
+            Some(selected) => {
+                let selected = selected.min(self.items.len().saturating_sub(1));
+
+                if selected >= self.offset + height {
+                    self.offset = selected + 1 - height;
+                } else if selected < self.offset {
+                    self.offset = selected;
                 }
             }
-            None => 0,
-        };
+            None => self.offset = 0,
+        }
+    }
 
-        self.state.select(Some(i));
+    // the items from the offset on that fit, and a state selecting the same
+    // item among them, so a list of 100k entries only builds the rows it
+    // draws. update_offset goes first
+    pub fn window(&self) -> (Range<usize>, ListState) {
+        let start = self.offset.min(self.items.len());
+        let end = (start + self.height + MARGIN).min(self.items.len());
+
+        let mut state = ListState::default();
+        state.select(
+            self.state
+                .selected()
+                .map(|selected| selected.saturating_sub(start)),
+        );
+
+        (start..end, state)
+    }
+
+    pub fn index_at(&self, row: usize) -> Option<usize> {
+        let index = self.offset + row;
+
+        if index < self.items.len() {
+            Some(index)
+        } else {
+            None
+        }
     }
 
-    pub fn previous(&mut self) {
+    pub fn next(&mut self, wrap: bool) {
+        self.step(1, wrap);
+    }
+
+    pub fn previous(&mut self, wrap: bool) {
+        self.step(-1, wrap);
+    }
+
+    fn step(&mut self, by: isize, wrap: bool) {
+        if self.items.is_empty() {
+            return;
+        }
+
         let i = match self.state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.items.len() - 1
-                } else {
-                    i - 1
-                }
-            }
+            Some(i) => stepped(i, by, self.items.len(), wrap),
             None => 0,
         };
 
         self.state.select(Some(i));
     }
+
+    // PageDown and PageUp, never around the ends
+    pub fn page(&mut self, pages: isize) {
+        if self.items.is_empty() {
+            return;
+        }
+
+        let by = self.height.max(1) as isize * pages;
+        let selected = self.state.selected().unwrap_or(0) as isize;
+        let last = self.items.len() as isize - 1;
+
+        self.state
+            .select(Some((selected + by).clamp(0, last) as usize));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stepped_wraps_around_the_ends() {
+        assert_eq!(stepped(4, 1, 5, true), 0);
+        assert_eq!(stepped(0, -1, 5, true), 4);
+        assert_eq!(stepped(1, 12, 5, true), 3);
+    }
+
+    #[test]
+    fn stepped_stops_at_the_ends() {
+        assert_eq!(stepped(4, 1, 5, false), 4);
+        assert_eq!(stepped(0, -1, 5, false), 0);
+        assert_eq!(stepped(2, 1, 5, false), 3);
+    }
+
+    #[test]
+    fn selected_item_of_an_empty_list() {
+        let mut list: StatefulList<&str> = StatefulList::with_items(vec![]);
+        assert_eq!(list.selected_item(), None);
+
+        // what clamp_selection leaves in an empty directory
+        list.state.select(Some(0));
+        assert_eq!(list.selected_item(), None);
+
+        list.items.push("a");
+        assert_eq!(list.selected_item(), Some(&"a"));
+    }
+
+    #[test]
+    fn next_follows_wrap_lists() {
+        let mut list = StatefulList::with_items(vec!["a", "b"]);
+        list.state.select(Some(1));
+
+        list.next(false);
+        assert_eq!(list.state.selected(), Some(1));
+        list.next(true);
+        assert_eq!(list.state.selected(), Some(0));
+    }
 }
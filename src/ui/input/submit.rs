@@ -1,60 +1,177 @@
 use super::*;
 use crate::app::app::App;
+use crate::configuration::templates::{fill, template_for};
 use crate::ui::display::pane::get_pwd;
+use elevate::Elevated;
+use locking::Retry;
 use run_app::Command;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub fn handle_submit(app: &mut App, input: &mut String, input_active: &mut bool) {
     if *input_active {
         if app.last_command == Some(Command::CreateFile) {
-            App::create_file(&input);
+            let template = template_for(input).filter(|_| app.use_template);
+
+            match App::create_file(input) {
+                Ok(_) => {
+                    undo::record_create(app, Path::new(input.as_str()));
+
+                    match template {
+                        Some(template) => match fill(Path::new(input.as_str()), &template) {
+                            Ok(_) => {
+                                app.notify(format!("Created {} from {}", input, template.display()))
+                            }
+                            Err(e) => app.show_error(
+                                &format!("Created {}, but couldn't fill it in", input),
+                                e,
+                            ),
+                        },
+                        None => app.notify(format!("Created {}", input)),
+                    }
+                }
+                Err(e) if elevate::denied(&e) => elevate::offer(
+                    app,
+                    "Create failed",
+                    format!("Couldn't create {}: {}", input, e),
+                    Elevated::CreateFile(elevate::absolute(Path::new(input.as_str()))),
+                ),
+                Err(e) => app.show_error(&format!("Couldn't create {}", input), e),
+            }
             app.update_files();
             app.update_dirs();
             app.last_command = None;
         } else if app.last_command == Some(Command::CreateDir) {
-            App::create_dir(&input);
+            match App::create_dir(input) {
+                Ok(_) => {
+                    undo::record_create(app, Path::new(input.as_str()));
+                    app.notify(format!("Created {}/", input));
+                }
+                Err(e) if elevate::denied(&e) => elevate::offer(
+                    app,
+                    "Create failed",
+                    format!("Couldn't create {}: {}", input, e),
+                    Elevated::CreateDir(elevate::absolute(Path::new(input.as_str()))),
+                ),
+                Err(e) => app.show_error(&format!("Couldn't create {}", input), e),
+            }
             app.update_dirs();
             app.update_files();
             app.last_command = None;
-        } else if app.last_command == Some(Command::RenameFile) {
-            let file = app.files.items[app.files.state.selected().unwrap()]
-                .0
-                .clone();
+        } else if app.last_command == Some(Command::CreateSymlink) {
+            let selected = match app.files.state.selected() {
+                Some(i) => app.files.items.get(i),
+                None => app
+                    .dirs
+                    .state
+                    .selected()
+                    .and_then(|i| app.dirs.items.get(i)),
+            };
+
+            let target = match (std::env::current_dir(), selected) {
+                (Ok(cwd), Some(entry)) => Some(cwd.join(&entry.0)),
+                _ => None,
+            };
 
-            std::fs::rename(file, input.clone()).unwrap();
+            if let Some(target) = target {
+                match App::create_symlink(&target, input) {
+                    Ok(_) => {
+                        undo::record_create(app, Path::new(input.as_str()));
+                        app.notify(format!("Created {} -> {}", input, target.display()));
+                    }
+                    Err(e) => app.show_error(&format!("Couldn't create {}", input), e),
+                }
+            }
+
+            app.update_files();
+            app.update_dirs();
+            app.last_command = None;
+        } else if app.last_command == Some(Command::EditBookmark) {
+            bookmark::save_bookmark_annotation(app, input);
+            app.last_command = None;
+        } else if app.last_command == Some(Command::RenameFile) {
+            match app.selected_file() {
+                Some(file) => {
+                    let to = PathBuf::from(input.as_str());
+                    locking::run(app, Retry::Rename(PathBuf::from(file), to));
+                }
+                None => app.notify_error("The file to rename is gone"),
+            }
             app.update_files();
             app.update_dirs();
             app.last_command = None;
         } else if app.last_command == Some(Command::RenameDir) {
-            let dir = app.dirs.items[app.dirs.state.selected().unwrap()].0.clone();
-
-            std::fs::rename(dir, input.clone()).unwrap();
+            if let Some(dir) = app.selected_dir() {
+                locking::run(
+                    app,
+                    Retry::Rename(dir.into(), PathBuf::from(input.as_str())),
+                );
+            }
             app.update_dirs();
             app.update_files();
             app.last_command = None;
-        } else if app.last_command == Some(Command::ShowNav) {
-            let path = Some(PathBuf::from(input.clone()));
-
-            if path.is_some() {
-                std::env::set_current_dir(path.unwrap()).unwrap();
+        } else if app.last_command == Some(Command::Compress) {
+            let name = std::mem::take(input);
+            *input_active = false;
+            app.show_popup = false;
+            app.last_command = None;
+            compress::submit_compress(app, &name, input, input_active);
 
-                app.cur_dir = std::env::current_dir()
-                    .unwrap()
-                    .to_str()
-                    .unwrap()
-                    .to_string();
+            // the password popup is up
+            if *input_active {
+                return;
+            }
+        } else if app.last_command == Some(Command::ArchivePassword) {
+            compress::submit_password(app, input);
+            app.last_command = None;
+        } else if app.last_command == Some(Command::ExportTree) {
+            export::submit_export(app, input);
+            app.last_command = None;
+        } else if app.last_command == Some(Command::FilterFiles) {
+            filter::submit_filter(app, input);
+            app.last_command = None;
+        } else if app.last_command == Some(Command::PreviewSearch) {
+            preview_search::submit_search(app, input);
+            app.last_command = None;
+        } else if app.last_command == Some(Command::ConflictName) {
+            app.last_command = None;
+            conflicts::submit_name(app, input);
+        } else if app.last_command == Some(Command::SudoPassword) {
+            let password = std::mem::take(input);
+            *input_active = false;
+            app.show_popup = false;
+            app.last_command = None;
+            elevate::submit_password(app, &password, input_active);
 
-                app.update_files();
-                app.update_dirs();
+            // wrong, the popup is up again
+            if *input_active {
+                return;
+            }
+        } else if app.last_command == Some(Command::Password) {
+            extract::submit_password(app, input);
+            app.last_command = None;
+        } else if app.last_command == Some(Command::Connect)
+            || app.last_command == Some(Command::RemotePassword)
+        {
+            let text = std::mem::take(input);
+            *input_active = false;
+            app.show_popup = false;
 
-                app.show_popup = false;
-                app.show_nav = false;
-                app.last_command = None;
+            if app.last_command.take() == Some(Command::Connect) {
+                remote::connect(app, input_active, text.trim(), None);
             } else {
-                app.show_popup = false;
-                app.show_nav = false;
-                app.last_command = None;
+                remote::submit_password(app, input_active, &text);
             }
+
+            // the keys didn't get in, the password popup is up
+            if *input_active {
+                return;
+            }
+        } else if app.last_command == Some(Command::ShowNav) {
+            jumps::change_dir(app, &nav::expand(input));
+
+            app.show_popup = false;
+            app.show_nav = false;
+            app.last_command = None;
         }
 
         input.clear();
@@ -62,19 +179,12 @@ pub fn handle_submit(app: &mut App, input: &mut String, input_active: &mut bool)
         *input_active = false;
         app.update_files();
         app.update_dirs();
+    } else if app.remote.is_some() {
+        remote::handle_enter(app);
     } else {
-        if app.dirs.state.selected().is_some() {
-            if app.dirs.items[app.dirs.state.selected().unwrap()].0 == "../" {
-                let mut path = std::env::current_dir().unwrap();
-                path.pop();
-
-                std::env::set_current_dir(path).unwrap();
-                app.cur_dir = get_pwd();
-            } else {
-                let dir = app.dirs.items[app.dirs.state.selected().unwrap()].0.clone();
-
-                std::env::set_current_dir(dir).unwrap();
-                app.cur_dir = get_pwd();
+        if let Some(dir) = app.selected_dir() {
+            if !app.change_dir(&PathBuf::from(dir)) {
+                return;
             }
             app.update_files();
             app.update_dirs();
@@ -95,63 +205,73 @@ pub fn handle_submit(app: &mut App, input: &mut String, input_active: &mut bool)
     }
 }
 
+// the result's directory, the path kept as it was found so names that
+// aren't UTF-8 get there too
 pub fn handle_open_fzf_result(app: &mut App, input: &mut String, input_active: &mut bool) {
-    if app.fzf_results.state.selected().is_none() {
+    let path = match app
+        .fzf_results
+        .selected_item()
+        .and_then(|path| path.parent())
+    {
+        Some(parent) => parent.to_path_buf(),
+        None => return,
+    };
+    if !jumps::change_dir(app, &path) {
         return;
-    } else {
-        if app.fzf_results.items[app.fzf_results.state.selected().unwrap()]
-            .clone()
-            .is_ascii()
-        {
-            let path = app.fzf_results.items[app.fzf_results.state.selected().unwrap()].clone();
-            let path = PathBuf::from(path).parent().unwrap().to_path_buf();
-            std::env::set_current_dir(path).unwrap();
+    }
 
-            app.update_files();
-            app.update_dirs();
+    app.update_files();
+    app.update_dirs();
 
-            app.show_fzf = false;
-            app.show_popup = false;
-            app.last_command = None;
+    app.show_fzf = false;
+    app.show_popup = false;
+    app.last_command = None;
 
-            input.clear();
-            *input_active = false;
+    // keep the results around so going back lands on the same hit
+    app.fzf_query = input.clone();
+    app.fzf_resume = true;
 
-            app.fzf_results.state.select(None);
-            app.selected_fzf_result = 0;
+    input.clear();
+    *input_active = false;
 
-            app.files.state.select(Some(0));
-            app.dirs.state.select(None);
+    app.files.state.select(Some(0));
+    app.dirs.state.select(None);
 
-            app.cur_dir = get_pwd();
-        }
-    }
+    app.cur_dir = get_pwd();
 }
 
 pub fn handle_open_bookmark(app: &mut App) {
-    if app.bookmarked_dirs.state.selected().is_none() {
+    let path = match app.bookmarked_dirs.state.selected() {
+        Some(idx) => PathBuf::from(&app.bookmarked_dirs.items[idx]),
+        None => return,
+    };
+
+    // a bookmarked file opens its directory with the file highlighted
+    let (dir, file) = match path.parent() {
+        Some(parent) if path.is_file() => (
+            parent.to_path_buf(),
+            path.file_name().map(|name| name.to_os_string()),
+        ),
+        _ => (path, None),
+    };
+
+    if !jumps::change_dir(app, &dir) {
         return;
-    } else {
-        if app.bookmarked_dirs.items[app.bookmarked_dirs.state.selected().unwrap()]
-            .clone()
-            .is_ascii()
-        {
-            let path =
-                app.bookmarked_dirs.items[app.bookmarked_dirs.state.selected().unwrap()].clone();
-            let path = PathBuf::from(path);
-            std::env::set_current_dir(path).unwrap();
+    }
 
-            app.update_files();
-            app.update_dirs();
+    app.update_files();
+    app.update_dirs();
 
-            app.show_bookmark = false;
-            app.show_popup = false;
-            app.last_command = None;
+    app.show_bookmark = false;
+    app.show_popup = false;
+    app.last_command = None;
 
-            app.files.state.select(Some(0));
-            app.dirs.state.select(None);
+    let selected = file
+        .and_then(|file| app.files.items.iter().position(|item| item.0 == file))
+        .unwrap_or(0);
 
-            app.cur_dir = get_pwd();
-        }
-    }
+    app.files.state.select(Some(selected));
+    app.dirs.state.select(None);
+
+    app.cur_dir = get_pwd();
 }
@@ -0,0 +1,122 @@
+use super::remote;
+use crate::app::app::App;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use sysinfo::{System, SystemExt};
+
+// the shell's title, pushed before the first one is set and popped on quit
+static PUSHED: AtomicBool = AtomicBool::new(false);
+// set when the terminal was handed to another program, which may have set
+// its own title and directory
+static STALE: AtomicBool = AtomicBool::new(false);
+
+// what was last told to the terminal, so it's only written on changes
+pub struct TerminalTitle {
+    pub title: bool,
+    pub osc7: bool,
+    host: Option<String>,
+    last: Option<String>,
+}
+
+impl Default for TerminalTitle {
+    fn default() -> TerminalTitle {
+        TerminalTitle {
+            title: true,
+            osc7: true,
+            host: None,
+            last: None,
+        }
+    }
+}
+
+// file://host/path with everything but the plain characters %-escaped
+fn file_url(host: &str, path: &Path) -> String {
+    let mut url = format!("file://{}", host);
+    for byte in path.to_string_lossy().replace('\\', "/").bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'/' | b':' | b'-' | b'.' | b'_' | b'~' => {
+                url.push(byte as char)
+            }
+            _ => url.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    // C:/Users needs the leading slash to be a path
+    if !path.starts_with("/") {
+        url.insert(7 + host.len(), '/');
+    }
+    url
+}
+
+// ~/src/project rather than the whole home path
+fn shorten(dir: &str) -> String {
+    if let Some(home) = dirs::home_dir() {
+        let home = home.to_string_lossy();
+        if let Some(rest) = dir.strip_prefix(home.as_ref()) {
+            if rest.is_empty() || rest.starts_with('/') || rest.starts_with('\\') {
+                return format!("~{}", rest);
+            }
+        }
+    }
+    dir.to_string()
+}
+
+// titles can't hold control characters, they'd end the sequence early
+fn printable(text: &str) -> String {
+    text.chars().filter(|c| !c.is_control()).collect()
+}
+
+// every tick: the title and OSC 7 follow the directory traverse is in, so
+// new tabs, splits and tmux panes open there
+pub fn track(app: &mut App, output: &mut impl Write) {
+    if !app.terminal_title.title && !app.terminal_title.osc7 {
+        return;
+    }
+
+    let remote = remote::title(app);
+    let dir = remote.clone().unwrap_or_else(|| app.cur_dir.clone());
+    if STALE.swap(false, Ordering::SeqCst) {
+        app.terminal_title.last = None;
+    }
+    if app.terminal_title.last.as_ref() == Some(&dir) {
+        return;
+    }
+
+    let mut sequence = String::new();
+    if app.terminal_title.title {
+        if !PUSHED.swap(true, Ordering::SeqCst) {
+            sequence.push_str("\x1b[22;0t");
+        }
+        sequence.push_str(&format!(
+            "\x1b]2;{} - traverse\x07",
+            printable(&shorten(&dir))
+        ));
+    }
+    // a remote directory isn't one a new local shell could start in
+    if app.terminal_title.osc7 && remote.is_none() {
+        let host = app
+            .terminal_title
+            .host
+            .get_or_insert_with(|| System::new().host_name().unwrap_or_default());
+        sequence.push_str(&format!("\x1b]7;{}\x07", file_url(host, Path::new(&dir))));
+    }
+
+    if output.write_all(sequence.as_bytes()).is_ok() {
+        let _ = output.flush();
+    }
+    app.terminal_title.last = Some(dir);
+}
+
+// after a shell or editor had the terminal, everything is written again
+pub fn invalidate() {
+    STALE.store(true, Ordering::SeqCst);
+}
+
+// on the way out the shell gets its own title back
+pub fn restore(output: &mut impl Write) {
+    if PUSHED.swap(false, Ordering::SeqCst) {
+        let _ = output.write_all(b"\x1b[23;0t");
+        let _ = output.flush();
+    }
+}
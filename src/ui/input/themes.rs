@@ -0,0 +1,43 @@
+use super::run_app::Command;
+use crate::app::app::App;
+use crate::configuration::configuration::write_config;
+use crate::configuration::theme::{load_theme, theme_names};
+use crate::ui::display::block::block_binds;
+
+pub fn handle_themes(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    if app.last_command != Some(Command::ShowThemes) {
+        app.themes.items = theme_names();
+
+        let current = app
+            .themes
+            .items
+            .iter()
+            .position(|name| name == &app.theme.name);
+        app.themes.state.select(Some(current.unwrap_or(0)));
+
+        app.show_themes = true;
+        app.last_command = Some(Command::ShowThemes);
+    }
+}
+
+pub fn handle_select_theme(app: &mut App) {
+    let selected = app
+        .themes
+        .state
+        .selected()
+        .and_then(|idx| app.themes.items.get(idx))
+        .cloned();
+
+    if let Some(theme) = selected.as_deref().and_then(load_theme) {
+        write_config("theme", &theme.name);
+        app.notify(format!("Theme: {}", theme.name));
+        app.theme = theme;
+    }
+
+    app.show_themes = false;
+    app.last_command = None;
+}
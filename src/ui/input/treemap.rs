@@ -0,0 +1,121 @@
+use super::preflight;
+use super::run_app::Command;
+use super::stateful_list::StatefulList;
+use crate::app::app::{is_hidden, App};
+use crate::ui::display::block::block_binds;
+use crate::ui::display::pane::get_pwd;
+use std::ffi::OsString;
+use std::fs::read_dir;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+pub fn handle_treemap(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    if app.last_command != Some(Command::ShowTreemap) {
+        build_treemap(app);
+        app.show_treemap = true;
+        app.last_command = Some(Command::ShowTreemap);
+    }
+}
+
+// sizes of everything directly under the cwd, biggest first
+pub fn build_treemap(app: &mut App) {
+    let mut entries: Vec<(OsString, u64)> = vec![];
+
+    if let Ok(dir) = read_dir("./") {
+        for entry in dir.flatten() {
+            let name = entry.file_name();
+
+            if is_hidden(&entry) && !app.show_hidden {
+                continue;
+            }
+
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+
+            let size = if metadata.is_dir() {
+                dir_size(&entry.path())
+            } else {
+                metadata.len()
+            };
+
+            entries.push((name, size));
+        }
+    }
+
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.1));
+
+    app.treemap = StatefulList::with_items(entries);
+
+    if !app.treemap.items.is_empty() {
+        app.treemap.state.select(Some(0));
+    }
+}
+
+pub fn dir_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+fn selected_entry(app: &App) -> Option<PathBuf> {
+    let selected = app.treemap.state.selected()?;
+    app.treemap
+        .items
+        .get(selected)
+        .map(|entry| PathBuf::from(&entry.0))
+}
+
+fn change_dir(app: &mut App, path: &Path) {
+    if !app.change_dir(path) {
+        return;
+    }
+
+    app.cur_dir = get_pwd();
+    app.update_files();
+    app.update_dirs();
+
+    build_treemap(app);
+}
+
+pub fn drill_in(app: &mut App) {
+    if let Some(name) = selected_entry(app) {
+        if name.is_dir() {
+            change_dir(app, &name);
+        }
+    }
+}
+
+pub fn drill_out(app: &mut App) {
+    change_dir(app, Path::new(".."));
+}
+
+pub fn delete_selected(app: &mut App) {
+    if let Some(name) = selected_entry(app) {
+        if !preflight::check_delete(app, &name) {
+            // out of the way so Enter reaches the report
+            app.show_treemap = false;
+        } else if trash::delete(&name).is_ok() {
+            let selected = app.treemap.state.selected().unwrap_or(0);
+
+            app.update_files();
+            app.update_dirs();
+            build_treemap(app);
+
+            if !app.treemap.items.is_empty() {
+                app.treemap
+                    .state
+                    .select(Some(selected.min(app.treemap.items.len() - 1)));
+            }
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::ffi::OsString;
+use std::time::{Duration, Instant};
+
+// a pause this long ends it, so the next keys are commands again
+const TIMEOUT: Duration = Duration::from_millis(1500);
+
+pub struct TypeAhead {
+    pub prefix: String,
+    typed: Instant,
+    // whether anything starts with the prefix
+    pub found: bool,
+}
+
+// /: the next letters jump to the first entry starting with them
+pub fn handle_type_ahead(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    app.type_ahead = Some(TypeAhead {
+        prefix: String::new(),
+        typed: Instant::now(),
+        found: true,
+    });
+}
+
+pub fn active(app: &App) -> bool {
+    app.type_ahead
+        .as_ref()
+        .is_some_and(|type_ahead| type_ahead.typed.elapsed() < TIMEOUT)
+}
+
+// the keys that go to the prefix, anything else ends the type-ahead
+pub fn takes(key: &KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char(_) => !key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT),
+        KeyCode::Backspace => true,
+        _ => false,
+    }
+}
+
+pub fn stop(app: &mut App) {
+    app.type_ahead = None;
+}
+
+pub fn handle_key(app: &mut App, code: KeyCode) {
+    let type_ahead = match app.type_ahead.as_mut() {
+        Some(type_ahead) => type_ahead,
+        None => return,
+    };
+
+    match code {
+        KeyCode::Char(c) => type_ahead.prefix.push(c),
+        KeyCode::Backspace => {
+            type_ahead.prefix.pop();
+        }
+        _ => return,
+    }
+    type_ahead.typed = Instant::now();
+
+    let prefix = type_ahead.prefix.to_lowercase();
+    let found = prefix.is_empty() || jump(app, &prefix);
+
+    if let Some(type_ahead) = app.type_ahead.as_mut() {
+        type_ahead.found = found;
+    }
+}
+
+fn position(items: &[(OsString, String)], prefix: &str) -> Option<usize> {
+    items.iter().position(|(name, _)| {
+        name != "../" && name.to_string_lossy().to_lowercase().starts_with(prefix)
+    })
+}
+
+// the first match in the pane with the selection, or else in the other one,
+// which then takes the selection, the unified listing starts at the top
+fn jump(app: &mut App, prefix: &str) -> bool {
+    let in_dirs = app.unified()
+        || app.files.state.selected().is_none() && app.dirs.state.selected().is_some();
+
+    let files = position(&app.files.items, prefix);
+    let dirs = position(&app.dirs.items, prefix);
+
+    let (files, dirs) = match (in_dirs, files, dirs) {
+        (false, Some(idx), _) | (true, Some(idx), None) => (Some(idx), None),
+        (true, _, Some(idx)) | (false, None, Some(idx)) => (None, Some(idx)),
+        (_, None, None) => return false,
+    };
+
+    app.files.state.select(files);
+    app.dirs.state.select(dirs);
+
+    true
+}
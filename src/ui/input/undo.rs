@@ -0,0 +1,193 @@
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use std::path::{Path, PathBuf};
+use std::process::Command as SysCommand;
+
+// how many operations can be undone
+const MAX_UNDO: usize = 100;
+
+// file operations that can be taken back, with absolute paths so they still
+// work after moving elsewhere
+#[derive(Clone)]
+pub enum Operation {
+    Rename { from: PathBuf, to: PathBuf },
+    // (where it was, where it went) for every entry of a move
+    Move(Vec<(PathBuf, PathBuf)>),
+    Create(PathBuf),
+    Delete(PathBuf),
+}
+
+impl Operation {
+    fn describe(&self) -> String {
+        match self {
+            Operation::Rename { from, to } => {
+                format!("rename of {} to {}", from.display(), to.display())
+            }
+            Operation::Move(moves) if moves.len() == 1 => {
+                format!("move of {}", moves[0].0.display())
+            }
+            Operation::Move(moves) => format!("move of {} entries", moves.len()),
+            Operation::Create(path) => format!("creation of {}", path.display()),
+            Operation::Delete(path) => format!("deletion of {}", path.display()),
+        }
+    }
+}
+
+pub fn record(app: &mut App, operation: Operation) {
+    app.undo_stack.push(operation);
+    if app.undo_stack.len() > MAX_UNDO {
+        app.undo_stack.remove(0);
+    }
+
+    // a new operation starts a new history
+    app.redo_stack.clear();
+}
+
+fn absolute(name: &Path) -> PathBuf {
+    std::env::current_dir()
+        .map(|cwd| cwd.join(name))
+        .unwrap_or_else(|_| PathBuf::from(name))
+}
+
+pub fn record_rename(app: &mut App, from: &Path, to: &Path) {
+    record(
+        app,
+        Operation::Rename {
+            from: absolute(from),
+            to: absolute(to),
+        },
+    );
+}
+
+pub fn record_create(app: &mut App, name: &Path) {
+    record(app, Operation::Create(absolute(name)));
+}
+
+pub fn record_delete(app: &mut App, name: &Path) {
+    record(app, Operation::Delete(absolute(name)));
+}
+
+// never overwrite whatever has shown up at the destination in the meantime
+fn move_back(from: &Path, to: &Path) -> Result<(), String> {
+    if to.exists() {
+        return Err(format!("{} already exists", to.display()));
+    }
+
+    // mv copes with other filesystems, unlike a rename
+    let output = SysCommand::new("mv")
+        .arg(from)
+        .arg(to)
+        .output()
+        .map_err(|err| err.to_string())?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+fn trash(path: &Path) -> Result<(), String> {
+    trash::delete(path).map_err(|err| err.to_string())
+}
+
+// the most recently trashed item that came from `path`
+#[cfg(any(windows, all(unix, not(target_os = "macos"))))]
+fn restore(path: &Path) -> Result<(), String> {
+    use trash::os_limited;
+
+    let item = os_limited::list()
+        .map_err(|err| err.to_string())?
+        .into_iter()
+        .filter(|item| item.original_path() == path)
+        .max_by_key(|item| item.time_deleted)
+        .ok_or_else(|| format!("{} is no longer in the bin", path.display()))?;
+
+    os_limited::restore_all([item]).map_err(|err| err.to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn restore(path: &Path) -> Result<(), String> {
+    Err(format!(
+        "restoring {} from the bin isn't supported on macOS",
+        path.display()
+    ))
+}
+
+fn revert(operation: &Operation) -> Result<(), String> {
+    match operation {
+        Operation::Rename { from, to } => move_back(to, from),
+        Operation::Move(moves) => moves
+            .iter()
+            .rev()
+            .try_for_each(|(from, to)| move_back(to, from)),
+        Operation::Create(path) => trash(path),
+        Operation::Delete(path) => restore(path),
+    }
+}
+
+fn replay(operation: &Operation) -> Result<(), String> {
+    match operation {
+        Operation::Rename { from, to } => move_back(from, to),
+        Operation::Move(moves) => moves.iter().try_for_each(|(from, to)| move_back(from, to)),
+        // it went to the bin on undo, so it comes back with its contents
+        Operation::Create(path) => restore(path),
+        Operation::Delete(path) => trash(path),
+    }
+}
+
+fn refresh(app: &mut App) {
+    app.git_refreshed = None;
+    app.update_files();
+    app.update_dirs();
+    app.clamp_selection();
+}
+
+pub fn undo(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    let operation = match app.undo_stack.pop() {
+        Some(operation) => operation,
+        None => return app.notify_error("Nothing to undo"),
+    };
+
+    match revert(&operation) {
+        Ok(_) => {
+            app.notify(format!("Undid the {}", operation.describe()));
+            app.redo_stack.push(operation);
+        }
+        Err(err) => {
+            app.show_error(&format!("Couldn't undo the {}", operation.describe()), err);
+            // still there to try again
+            app.undo_stack.push(operation);
+        }
+    }
+
+    refresh(app);
+}
+
+pub fn redo(app: &mut App) {
+    if block_binds(app) {
+        return;
+    }
+
+    let operation = match app.redo_stack.pop() {
+        Some(operation) => operation,
+        None => return app.notify_error("Nothing to redo"),
+    };
+
+    match replay(&operation) {
+        Ok(_) => {
+            app.notify(format!("Redid the {}", operation.describe()));
+            app.undo_stack.push(operation);
+        }
+        Err(err) => {
+            app.show_error(&format!("Couldn't redo the {}", operation.describe()), err);
+            app.redo_stack.push(operation);
+        }
+    }
+
+    refresh(app);
+}
@@ -0,0 +1,155 @@
+use crate::app::app::App;
+use crate::ui::display::block::block_binds;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command as SysCommand, Stdio};
+use std::sync::OnceLock;
+
+// WSL sets WSL_DISTRO_NAME for its shells, the kernel name catches the rest
+// (WSL 1 and shells started without the variable)
+pub fn is_wsl() -> bool {
+    static WSL: OnceLock<bool> = OnceLock::new();
+
+    *WSL.get_or_init(|| {
+        cfg!(target_os = "linux")
+            && (std::env::var_os("WSL_DISTRO_NAME").is_some()
+                || std::fs::read_to_string("/proc/sys/kernel/osrelease")
+                    .is_ok_and(|release| release.to_lowercase().contains("microsoft")))
+    })
+}
+
+// C:\... for paths on the Windows drives, \\wsl$\<distro>\... for the rest
+fn windows_path(path: &Path) -> String {
+    let converted = SysCommand::new("wslpath")
+        .arg("-w")
+        .arg(path)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok());
+
+    if let Some(converted) = converted {
+        return converted.trim_end().to_string();
+    }
+
+    // wslpath went missing, do what it would have done
+    let path = path.to_string_lossy();
+    let mut parts = path.trim_start_matches('/').split('/');
+
+    match (parts.next(), parts.next()) {
+        (Some("mnt"), Some(drive)) if drive.len() == 1 => {
+            let rest = parts.collect::<Vec<&str>>().join("\\");
+            format!("{}:\\{}", drive.to_uppercase(), rest)
+        }
+        _ => format!(
+            "\\\\wsl$\\{}{}",
+            std::env::var("WSL_DISTRO_NAME").unwrap_or_default(),
+            path.replace('/', "\\")
+        ),
+    }
+}
+
+// the other way around, for paths the Windows side hands over
+pub fn linux_path(path: &str) -> Option<PathBuf> {
+    let converted = SysCommand::new("wslpath")
+        .arg("-u")
+        .arg(path)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok());
+
+    if let Some(converted) = converted {
+        return Some(PathBuf::from(converted.trim_end()));
+    }
+
+    let mut chars = path.chars();
+    match (chars.next(), chars.next()) {
+        (Some(drive), Some(':')) if drive.is_ascii_alphabetic() => Some(PathBuf::from(format!(
+            "/mnt/{}{}",
+            drive.to_ascii_lowercase(),
+            chars.as_str().replace('\\', "/")
+        ))),
+        _ => None,
+    }
+}
+
+// the move/copy buffer, or the entry under the cursor when it's empty
+pub fn selection(app: &App) -> Vec<PathBuf> {
+    if !app.selected_files.is_empty() {
+        return app.selected_files.iter().map(PathBuf::from).collect();
+    }
+
+    match (std::env::current_dir(), app.selected_entry()) {
+        (Ok(cwd), Some(entry)) => vec![cwd.join(entry)],
+        _ => vec![],
+    }
+}
+
+pub fn copy_windows_path(app: &mut App) {
+    if !is_wsl() || block_binds(app) {
+        return;
+    }
+
+    let paths = selection(app)
+        .iter()
+        .map(|path| windows_path(path))
+        .collect::<Vec<String>>();
+
+    if paths.is_empty() {
+        return;
+    }
+
+    let copied = SysCommand::new("clip.exe")
+        .stdin(Stdio::piped())
+        .spawn()
+        .and_then(|mut clip| {
+            // clip.exe copies once its stdin is closed
+            if let Some(mut stdin) = clip.stdin.take() {
+                stdin.write_all(paths.join("\r\n").as_bytes())?;
+            }
+            clip.wait()
+        });
+
+    match copied {
+        Ok(status) if status.success() => app.open_report("Copied to the Windows clipboard", paths),
+        Ok(status) => app.open_report("clip.exe failed", vec![status.to_string()]),
+        Err(e) => app.open_report("Couldn't run clip.exe", vec![e.to_string()]),
+    }
+}
+
+// wslview (from wslu) knows the default apps, explorer.exe is always there
+pub fn open_in_windows(app: &mut App) {
+    if !is_wsl() || block_binds(app) {
+        return;
+    }
+
+    let mut errors = vec![];
+
+    for path in selection(app) {
+        let opened = SysCommand::new("wslview")
+            .arg(&path)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success());
+
+        if opened {
+            continue;
+        }
+
+        // explorer.exe exits with 1 even when it worked
+        if let Err(e) = SysCommand::new("explorer.exe")
+            .arg(windows_path(&path))
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+        {
+            errors.push(format!("{}: {}", path.display(), e));
+        }
+    }
+
+    if !errors.is_empty() {
+        app.open_report("Couldn't open in Windows", errors);
+    }
+}
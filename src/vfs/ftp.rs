@@ -0,0 +1,442 @@
+use super::{join_remote, ConnectError, Target, VfsEntry, VfsProvider};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+// a server that doesn't answer shouldn't hang traverse for long
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn failed(code: u32, text: &str) -> io::Error {
+    let kind = match code {
+        550 => io::ErrorKind::NotFound,
+        _ => io::ErrorKind::Other,
+    };
+    io::Error::new(kind, format!("the server said {} {}", code, text))
+}
+
+fn to_io(err: ConnectError) -> io::Error {
+    match err {
+        ConnectError::NeedsPassword => io::Error::other("logged out"),
+        ConnectError::Failed(err) => io::Error::other(err),
+    }
+}
+
+// a logged in control connection
+struct Session {
+    control: BufReader<TcpStream>,
+}
+
+impl Session {
+    fn open(
+        addr: &SocketAddr,
+        user: &str,
+        password: Option<&str>,
+    ) -> Result<Session, ConnectError> {
+        let tcp = TcpStream::connect_timeout(addr, TIMEOUT)?;
+        tcp.set_read_timeout(Some(TIMEOUT))?;
+        tcp.set_write_timeout(Some(TIMEOUT))?;
+
+        let mut session = Session {
+            control: BufReader::new(tcp),
+        };
+
+        let (code, text) = session.reply()?;
+        if code != 220 {
+            return Err(failed(code, &text).into());
+        }
+
+        match session.command(&format!("USER {}", user))? {
+            (230, _) => {}
+            (331, _) => {
+                let password = password.ok_or(ConnectError::NeedsPassword)?;
+                match session.command(&format!("PASS {}", password))? {
+                    (230 | 202, _) => {}
+                    (530, _) => {
+                        return Err(ConnectError::Failed("wrong user or password".to_string()))
+                    }
+                    (code, text) => return Err(failed(code, &text).into()),
+                }
+            }
+            (code, text) => return Err(failed(code, &text).into()),
+        }
+
+        session.expect("TYPE I", &[200])?;
+        Ok(session)
+    }
+
+    // the text of a reply, multi-line ones end on their code and a space
+    fn reply(&mut self) -> io::Result<(u32, String)> {
+        let mut line = self.line()?;
+        let code = line
+            .get(..3)
+            .and_then(|code| code.parse::<u32>().ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not an FTP reply"))?;
+
+        if line.as_bytes().get(3) == Some(&b'-') {
+            let last = format!("{} ", code);
+            while !line.starts_with(&last) {
+                line = self.line()?;
+            }
+        }
+
+        Ok((code, line.get(4..).unwrap_or_default().trim().to_string()))
+    }
+
+    fn line(&mut self) -> io::Result<String> {
+        let mut line = String::new();
+        if self.control.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the server hung up",
+            ));
+        }
+        Ok(line)
+    }
+
+    fn command(&mut self, command: &str) -> io::Result<(u32, String)> {
+        self.control
+            .get_mut()
+            .write_all(format!("{}\r\n", command).as_bytes())?;
+        self.reply()
+    }
+
+    fn expect(&mut self, command: &str, ok: &[u32]) -> io::Result<String> {
+        let (code, text) = self.command(command)?;
+        if !ok.contains(&code) {
+            return Err(failed(code, &text));
+        }
+        Ok(text)
+    }
+
+    // a passive data connection to the host the control connection is on,
+    // whatever address the server thinks it has behind a NAT
+    fn data(&mut self) -> io::Result<TcpStream> {
+        let port = pasv_port(&self.expect("PASV", &[227])?)?;
+        let ip = self.control.get_ref().peer_addr()?.ip();
+        let data = TcpStream::connect_timeout(&SocketAddr::new(ip, port), TIMEOUT)?;
+        data.set_read_timeout(Some(TIMEOUT))?;
+        data.set_write_timeout(Some(TIMEOUT))?;
+        Ok(data)
+    }
+
+    // starts `command` on a new data connection
+    fn transfer(&mut self, command: &str) -> io::Result<TcpStream> {
+        let data = self.data()?;
+        self.expect(command, &[125, 150])?;
+        Ok(data)
+    }
+
+    // everything a listing command sends, or the reply it failed with
+    fn listing(&mut self, command: &str) -> io::Result<Result<String, u32>> {
+        let mut data = self.data()?;
+        let (code, _) = self.command(command)?;
+        if code != 125 && code != 150 {
+            return Ok(Err(code));
+        }
+
+        let mut bytes = vec![];
+        data.read_to_end(&mut bytes)?;
+        drop(data);
+        self.reply()?;
+
+        Ok(Ok(String::from_utf8_lossy(&bytes).to_string()))
+    }
+
+    fn is_dir(&mut self, path: &Path) -> io::Result<bool> {
+        Ok(self.command(&format!("CWD {}", path.display()))?.0 == 250)
+    }
+
+    fn read_dir(&mut self, path: &Path) -> io::Result<Vec<VfsEntry>> {
+        // MLSD says what everything is, LIST is for servers without it
+        let (text, machine) = match self.listing(&format!("MLSD {}", path.display()))? {
+            Ok(text) => (text, true),
+            Err(500..=504) => match self.listing(&format!("LIST -a {}", path.display()))? {
+                Ok(text) => (text, false),
+                Err(code) => return Err(failed(code, "listing failed")),
+            },
+            Err(code) => return Err(failed(code, "listing failed")),
+        };
+
+        let mut entries = vec![];
+        for line in text.lines().map(|line| line.trim_end_matches('\r')) {
+            let parsed = if machine {
+                parse_mlsd(line)
+            } else {
+                parse_list(line)
+            };
+            let (name, kind) = match parsed {
+                Some(parsed) => parsed,
+                None => continue,
+            };
+            if name == "." || name == ".." {
+                continue;
+            }
+
+            // links are listed as what they point to
+            let is_dir = match kind {
+                Kind::Dir => true,
+                Kind::File => false,
+                Kind::Link => self.is_dir(&join_remote(path, &name))?,
+            };
+            entries.push(VfsEntry { name, is_dir });
+        }
+
+        Ok(entries)
+    }
+}
+
+enum Kind {
+    File,
+    Dir,
+    Link,
+}
+
+// type=dir;size=4096;modify=20230101000000; name
+fn parse_mlsd(line: &str) -> Option<(String, Kind)> {
+    let (facts, name) = line.split_once(' ')?;
+    let kind = facts.split(';').find_map(|fact| {
+        fact.to_lowercase()
+            .strip_prefix("type=")
+            .map(str::to_string)
+    })?;
+
+    let kind = match kind.as_str() {
+        "cdir" | "pdir" => return None,
+        "dir" => Kind::Dir,
+        kind if kind.contains("slink") => Kind::Link,
+        _ => Kind::File,
+    };
+    Some((name.to_string(), kind))
+}
+
+// drwxr-xr-x 2 user group 4096 Jan  1 00:00 name, from servers that only
+// speak ls
+fn parse_list(line: &str) -> Option<(String, Kind)> {
+    let kind = match line.chars().next()? {
+        'd' => Kind::Dir,
+        'l' => Kind::Link,
+        '-' => Kind::File,
+        _ => return None,
+    };
+
+    // the name is whatever follows the eighth field, spaces and all
+    let mut rest = line;
+    for _ in 0..8 {
+        rest = rest.trim_start();
+        rest = &rest[rest.find(char::is_whitespace)?..];
+    }
+    let name = rest.trim_start().trim_end_matches(['\r', '\n']);
+    let name = match kind {
+        Kind::Link => name.split(" -> ").next()?,
+        _ => name,
+    };
+
+    Some((name.to_string(), kind))
+}
+
+pub struct Ftp {
+    name: String,
+    addr: SocketAddr,
+    user: String,
+    password: Option<String>,
+    // listings share a connection, every transfer gets its own
+    session: Mutex<Session>,
+}
+
+impl Ftp {
+    // anonymous when no user is given, a user without a password asks for one
+    pub fn connect(target: &Target, password: Option<&str>) -> Result<Ftp, ConnectError> {
+        let addr = (target.host.as_str(), target.port.unwrap_or(21))
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| ConnectError::Failed(format!("couldn't resolve {}", target.host)))?;
+
+        let (user, password) = match &target.user {
+            Some(user) => (user.clone(), password.map(str::to_string)),
+            None => ("anonymous".to_string(), Some("anonymous@".to_string())),
+        };
+        let session = Session::open(&addr, &user, password.as_deref())?;
+
+        Ok(Ftp {
+            name: format!("ftp://{}@{}", user, target.host),
+            addr,
+            user,
+            password,
+            session: Mutex::new(session),
+        })
+    }
+
+    pub fn start_dir(&self, target: &Target) -> Result<PathBuf, ConnectError> {
+        if let Some(path) = &target.path {
+            return Ok(PathBuf::from(path));
+        }
+
+        // 257 "/home/user" is the current directory
+        let text = self.shared(|session| session.expect("PWD", &[257]))?;
+        let dir = text
+            .split('"')
+            .nth(1)
+            .ok_or_else(|| ConnectError::Failed("couldn't read the directory".to_string()))?;
+        Ok(PathBuf::from(dir))
+    }
+
+    fn new_session(&self) -> io::Result<Session> {
+        Session::open(&self.addr, &self.user, self.password.as_deref()).map_err(to_io)
+    }
+
+    // runs `op` on the shared connection, logging in again once when the
+    // server closed it for idling
+    fn shared<T>(&self, op: impl Fn(&mut Session) -> io::Result<T>) -> io::Result<T> {
+        let mut session = self.session.lock().unwrap_or_else(|e| e.into_inner());
+
+        match op(&mut session) {
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::BrokenPipe
+                        | io::ErrorKind::ConnectionReset
+                        | io::ErrorKind::UnexpectedEof
+                ) =>
+            {
+                *session = self.new_session()?;
+                op(&mut session)
+            }
+            result => result,
+        }
+    }
+}
+
+// a download, its connection is closed with it
+struct Download {
+    data: TcpStream,
+    session: Session,
+    done: bool,
+}
+
+impl Read for Download {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.data.read(buf)?;
+
+        // the end of the data, the server says whether it all came through
+        if read == 0 && !buf.is_empty() && !self.done {
+            self.done = true;
+            let (code, text) = self.session.reply()?;
+            if code != 226 && code != 250 {
+                return Err(failed(code, &text));
+            }
+        }
+
+        Ok(read)
+    }
+}
+
+impl VfsProvider for Ftp {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<VfsEntry>> {
+        self.shared(|session| session.read_dir(path))
+    }
+
+    fn is_dir(&self, path: &Path) -> io::Result<bool> {
+        self.shared(|session| session.is_dir(path))
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read>> {
+        let mut session = self.new_session()?;
+        let data = session.transfer(&format!("RETR {}", path.display()))?;
+
+        Ok(Box::new(Download {
+            data,
+            session,
+            done: false,
+        }))
+    }
+
+    fn size(&self, path: &Path) -> io::Result<u64> {
+        let text =
+            self.shared(|session| session.expect(&format!("SIZE {}", path.display()), &[213]))?;
+        text.parse()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "not a size"))
+    }
+
+    // REST starts the download part way, closing it early ends it
+    fn read_range(&self, path: &Path, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+        let mut session = self.new_session()?;
+        session.expect(&format!("REST {}", offset), &[350])?;
+        let data = session.transfer(&format!("RETR {}", path.display()))?;
+
+        let mut bytes = vec![];
+        data.take(len).read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn write(&self, path: &Path, source: &mut dyn Read) -> io::Result<u64> {
+        let mut session = self.new_session()?;
+        let mut data = session.transfer(&format!("STOR {}", path.display()))?;
+
+        let written = io::copy(source, &mut data)?;
+        // closing the data connection is how the server knows it's all there
+        drop(data);
+
+        let (code, text) = session.reply()?;
+        if code != 226 && code != 250 {
+            return Err(failed(code, &text));
+        }
+        Ok(written)
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        self.shared(|session| {
+            let (code, text) = session.command(&format!("MKD {}", path.display()))?;
+            if code == 257 || session.is_dir(path)? {
+                return Ok(());
+            }
+            Err(failed(code, &text))
+        })
+    }
+
+    fn join(&self, dir: &Path, name: &str) -> PathBuf {
+        join_remote(dir, name)
+    }
+}
+
+// the port of a 227 reply, "Entering Passive Mode (h1,h2,h3,h4,p1,p2)", the
+// last two numbers being its high and low byte
+fn pasv_port(text: &str) -> io::Result<u16> {
+    let bytes = text
+        .split(|c: char| !c.is_ascii_digit() && c != ',')
+        .find(|part| part.matches(',').count() == 5)
+        .map(|part| {
+            part.split(',')
+                .map(|number| number.parse::<u8>().ok())
+                .collect::<Option<Vec<u8>>>()
+        });
+
+    match bytes {
+        Some(Some(bytes)) => Ok(u16::from(bytes[4]) << 8 | u16::from(bytes[5])),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "couldn't read the passive mode port",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_passive_port() {
+        let port = pasv_port("Entering Passive Mode (192,168,1,2,195,80).").unwrap();
+        assert_eq!(port, 195 * 256 + 80);
+        assert_eq!(pasv_port("=127,0,0,1,255,255").unwrap(), u16::MAX);
+
+        assert!(pasv_port("Entering Passive Mode (192,168,1,2,256,1)").is_err());
+        assert!(pasv_port("Entering Passive Mode (192,168,1,2,1)").is_err());
+        assert!(pasv_port("Entering Passive Mode").is_err());
+    }
+}
@@ -0,0 +1,380 @@
+pub mod ftp;
+#[cfg(feature = "s3")]
+pub mod s3;
+#[cfg(feature = "sftp")]
+pub mod sftp;
+#[cfg(feature = "webdav")]
+pub mod webdav;
+
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// what a range reader fetches at once, aligned so reads around the end of a
+// file all come out of one block
+const BLOCK: u64 = 64 * 1024;
+
+// an entry in a listing, whatever it's stored on
+pub struct VfsEntry {
+    pub name: String,
+    pub is_dir: bool,
+}
+
+// the filesystem access a remote connection needs: listing, reading for the
+// preview and the two halves of a transfer
+pub trait VfsProvider: Send + Sync {
+    // user@host, for titles and messages
+    fn name(&self) -> String;
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<VfsEntry>>;
+    fn is_dir(&self, path: &Path) -> io::Result<bool>;
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read>>;
+    // the length of a file in bytes
+    fn size(&self, path: &Path) -> io::Result<u64>;
+    // up to `len` bytes from `offset` on, fewer at the end of the file
+    fn read_range(&self, path: &Path, offset: u64, len: u64) -> io::Result<Vec<u8>>;
+    // writes everything `source` has to `path`, returns the bytes written
+    fn write(&self, path: &Path, source: &mut dyn Read) -> io::Result<u64>;
+    fn create_dir(&self, path: &Path) -> io::Result<()>;
+
+    // joined the local way, remote paths always use /
+    fn join(&self, dir: &Path, name: &str) -> PathBuf {
+        dir.join(name)
+    }
+}
+
+pub enum ConnectError {
+    // the login needs a password, asking for it could get in
+    NeedsPassword,
+    Failed(String),
+}
+
+impl<E: std::fmt::Display> From<E> for ConnectError {
+    fn from(err: E) -> ConnectError {
+        ConnectError::Failed(err.to_string())
+    }
+}
+
+// user@host:port/path after the scheme, all but the host optional
+pub struct Target {
+    pub user: Option<String>,
+    pub host: String,
+    pub port: Option<u16>,
+    pub path: Option<String>,
+}
+
+impl Target {
+    pub fn parse(text: &str) -> Option<Target> {
+        let (address, path) = match text.find('/') {
+            Some(idx) => (&text[..idx], Some(text[idx..].to_string())),
+            None => (text, None),
+        };
+        let (user, host) = match address.rsplit_once('@') {
+            Some((user, host)) => (Some(user.to_string()), host),
+            None => (None, address),
+        };
+        let (host, port) = match host.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port.parse().ok()?)),
+            None => (host, None),
+        };
+
+        if host.is_empty() || user.as_deref() == Some("") {
+            return None;
+        }
+
+        Some(Target {
+            user,
+            host: host.to_string(),
+            port,
+            path,
+        })
+    }
+}
+
+// `user@host` is SFTP, anything else says what it is: sftp://, ftp://,
+// dav:// or davs:// (or plain http:// and https://) for WebDAV, and
+// s3://bucket/prefix. Returns the connection and the directory to start in
+pub fn connect(
+    target: &str,
+    password: Option<&str>,
+) -> Result<(Arc<dyn VfsProvider>, PathBuf), ConnectError> {
+    let (scheme, rest) = target.split_once("://").unwrap_or(("sftp", target));
+
+    // buckets aren't hosts, s3:// alone lists them and profile@ picks the
+    // credentials
+    if scheme == "s3" {
+        return connect_s3(rest);
+    }
+    let parsed = Target::parse(rest)
+        .ok_or_else(|| ConnectError::Failed(format!("not a host to connect to: {}", target)))?;
+
+    match scheme {
+        "ftp" => {
+            let ftp = ftp::Ftp::connect(&parsed, password)?;
+            let dir = ftp.start_dir(&parsed)?;
+            Ok((Arc::new(ftp), dir))
+        }
+        #[cfg(feature = "sftp")]
+        "sftp" | "ssh" => {
+            let sftp = sftp::connect(&parsed, password)?;
+            let dir = match &parsed.path {
+                Some(path) => PathBuf::from(path),
+                None => sftp.start_dir()?,
+            };
+            Ok((Arc::new(sftp), dir))
+        }
+        #[cfg(feature = "webdav")]
+        "dav" | "davs" | "http" | "https" => {
+            let secure = scheme == "davs" || scheme == "https";
+            let dav = webdav::WebDav::connect(&parsed, secure, password)?;
+            let dir = PathBuf::from(parsed.path.as_deref().unwrap_or("/"));
+            Ok((Arc::new(dav), dir))
+        }
+        #[cfg(not(feature = "sftp"))]
+        "sftp" | "ssh" => Err(ConnectError::Failed(
+            "this traverse was built without SFTP, build it with --features sftp".to_string(),
+        )),
+        #[cfg(not(feature = "webdav"))]
+        "dav" | "davs" | "http" | "https" => Err(ConnectError::Failed(
+            "this traverse was built without WebDAV, build it with --features webdav".to_string(),
+        )),
+        _ => Err(ConnectError::Failed(format!(
+            "unknown protocol {}://",
+            scheme
+        ))),
+    }
+}
+
+#[cfg(feature = "s3")]
+fn connect_s3(rest: &str) -> Result<(Arc<dyn VfsProvider>, PathBuf), ConnectError> {
+    let (profile, path) = match rest.split_once('@') {
+        Some((profile, path)) => (Some(profile), path),
+        None => (None, rest),
+    };
+    let s3 = s3::S3::connect(profile)?;
+    let dir = PathBuf::from(format!("/{}", path.trim_matches('/')));
+    Ok((Arc::new(s3), dir))
+}
+
+#[cfg(not(feature = "s3"))]
+fn connect_s3(_rest: &str) -> Result<(Arc<dyn VfsProvider>, PathBuf), ConnectError> {
+    Err(ConnectError::Failed(
+        "this traverse was built without S3, build it with --features s3".to_string(),
+    ))
+}
+
+// this machine, the other end of uploads and downloads
+pub struct Local;
+
+impl VfsProvider for Local {
+    fn name(&self) -> String {
+        "local".to_string()
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<VfsEntry>> {
+        let mut entries = vec![];
+
+        for entry in fs::read_dir(path)?.filter_map(|entry| entry.ok()) {
+            // links are listed as what they point to
+            let metadata = match fs::metadata(entry.path()) {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+
+            entries.push(VfsEntry {
+                name: entry.file_name().to_string_lossy().to_string(),
+                is_dir: metadata.is_dir(),
+            });
+        }
+
+        Ok(entries)
+    }
+
+    fn is_dir(&self, path: &Path) -> io::Result<bool> {
+        Ok(fs::metadata(path)?.is_dir())
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read>> {
+        Ok(Box::new(fs::File::open(path)?))
+    }
+
+    fn size(&self, path: &Path) -> io::Result<u64> {
+        Ok(fs::metadata(path)?.len())
+    }
+
+    fn read_range(&self, path: &Path, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+        let mut file = fs::File::open(path)?;
+        file.seek(SeekFrom::Start(offset))?;
+
+        let mut bytes = vec![];
+        file.take(len).read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn write(&self, path: &Path, source: &mut dyn Read) -> io::Result<u64> {
+        io::copy(source, &mut fs::File::create(path)?)
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        match fs::create_dir(path) {
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => Ok(()),
+            result => result,
+        }
+    }
+}
+
+// the first `limit` bytes of a file, enough for a preview
+pub fn read_start(vfs: &dyn VfsProvider, path: &Path, limit: u64) -> io::Result<Vec<u8>> {
+    let mut bytes = vec![];
+    vfs.open(path)?.take(limit).read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+// a remote file read a block at a time wherever it's seeked to, so an
+// archive's index can be read without fetching the rest. After `fetches`
+// blocks it stops, a preview never downloads much
+pub struct RangeReader {
+    vfs: Arc<dyn VfsProvider>,
+    path: PathBuf,
+    len: u64,
+    pos: u64,
+    // the last block fetched and where it starts
+    block: (u64, Vec<u8>),
+    fetches: usize,
+}
+
+impl RangeReader {
+    pub fn new(vfs: Arc<dyn VfsProvider>, path: &Path, fetches: usize) -> io::Result<RangeReader> {
+        let len = vfs.size(path)?;
+        Ok(RangeReader {
+            vfs,
+            path: path.to_path_buf(),
+            len,
+            pos: 0,
+            block: (0, vec![]),
+            fetches,
+        })
+    }
+}
+
+impl Read for RangeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let (start, block) = &self.block;
+        if self.pos < *start || self.pos >= start + block.len() as u64 {
+            if self.fetches == 0 {
+                return Err(io::Error::other("read as much as a preview gets"));
+            }
+            self.fetches -= 1;
+
+            let start = self.pos - self.pos % BLOCK;
+            let block = self.vfs.read_range(&self.path, start, BLOCK)?;
+            if start + block.len() as u64 <= self.pos {
+                return Ok(0);
+            }
+            self.block = (start, block);
+        }
+
+        let (start, block) = &self.block;
+        let from = (self.pos - start) as usize;
+        let read = buf.len().min(block.len() - from);
+        buf[..read].copy_from_slice(&block[from..from + read]);
+        self.pos += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for RangeReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+
+        self.pos = pos
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "seek before the start"))?;
+        Ok(self.pos)
+    }
+}
+
+// copies `source` on one filesystem to `target` on another, directories with
+// everything in them, returns the files and bytes copied
+pub fn copy(
+    from: &dyn VfsProvider,
+    source: &Path,
+    to: &dyn VfsProvider,
+    target: &Path,
+) -> io::Result<(usize, u64)> {
+    if !from.is_dir(source)? {
+        let bytes = to.write(target, &mut from.open(source)?)?;
+        return Ok((1, bytes));
+    }
+
+    to.create_dir(target)?;
+
+    let mut copied = (0, 0);
+    for entry in from.read_dir(source)? {
+        let (files, bytes) = copy(
+            from,
+            &from.join(source, &entry.name),
+            to,
+            &to.join(target, &entry.name),
+        )?;
+        copied = (copied.0 + files, copied.1 + bytes);
+    }
+
+    Ok(copied)
+}
+
+// for the providers' join, whatever separator this machine uses
+pub fn join_remote(dir: &Path, name: &str) -> PathBuf {
+    PathBuf::from(format!(
+        "{}/{}",
+        dir.to_string_lossy().trim_end_matches('/'),
+        name
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Target;
+
+    #[test]
+    fn parses_every_part() {
+        let target = Target::parse("me@example.com:2222/srv/www").unwrap();
+
+        assert_eq!(target.user.as_deref(), Some("me"));
+        assert_eq!(target.host, "example.com");
+        assert_eq!(target.port, Some(2222));
+        assert_eq!(target.path.as_deref(), Some("/srv/www"));
+    }
+
+    #[test]
+    fn leaves_out_what_is_not_given() {
+        let target = Target::parse("example.com").unwrap();
+
+        assert_eq!(target.user, None);
+        assert_eq!(target.port, None);
+        assert_eq!(target.path, None);
+    }
+
+    #[test]
+    fn takes_the_last_at_for_the_host() {
+        let target = Target::parse("me@work@example.com").unwrap();
+
+        assert_eq!(target.user.as_deref(), Some("me@work"));
+        assert_eq!(target.host, "example.com");
+    }
+
+    #[test]
+    fn rejects_what_is_missing_or_malformed() {
+        assert!(Target::parse("").is_none());
+        assert!(Target::parse("@example.com").is_none());
+        assert!(Target::parse("example.com:ssh").is_none());
+        assert!(Target::parse("/just/a/path").is_none());
+    }
+}
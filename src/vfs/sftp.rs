@@ -0,0 +1,185 @@
+use super::{join_remote, ConnectError, Target, VfsEntry, VfsProvider};
+use ssh2::{CheckResult, KnownHostFileKind, Session};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// a server that doesn't answer shouldn't hang traverse for long
+const TIMEOUT: Duration = Duration::from_secs(10);
+// the keys tried after the agent, the ones ssh tries by default
+const KEYS: [&str; 3] = ["id_ed25519", "id_ecdsa", "id_rsa"];
+
+pub struct Sftp {
+    name: String,
+    // the session has to outlive the channel
+    _session: Session,
+    sftp: ssh2::Sftp,
+}
+
+// only hosts already in ~/.ssh/known_hosts, with the key they had there
+fn check_host(session: &Session, target: &Target) -> Result<(), ConnectError> {
+    let (key, _) = session
+        .host_key()
+        .ok_or_else(|| ConnectError::Failed("the server sent no host key".to_string()))?;
+
+    let mut known_hosts = session.known_hosts()?;
+    let file = dirs::home_dir()
+        .unwrap_or_default()
+        .join(".ssh/known_hosts");
+    let _ = known_hosts.read_file(&file, KnownHostFileKind::OpenSSH);
+
+    match known_hosts.check_port(&target.host, port(target), key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::Mismatch => Err(ConnectError::Failed(format!(
+            "the host key of {} changed, check it with ssh",
+            target.host
+        ))),
+        CheckResult::NotFound => Err(ConnectError::Failed(format!(
+            "{} isn't in known_hosts yet, connect with ssh once to add it",
+            target.host
+        ))),
+        CheckResult::Failure => Err(ConnectError::Failed(
+            "couldn't check the host key".to_string(),
+        )),
+    }
+}
+
+fn user(target: &Target) -> String {
+    target
+        .user
+        .clone()
+        .or_else(|| std::env::var("USER").ok())
+        .unwrap_or_default()
+}
+
+fn port(target: &Target) -> u16 {
+    target.port.unwrap_or(22)
+}
+
+fn authenticate(
+    session: &Session,
+    target: &Target,
+    password: Option<&str>,
+) -> Result<(), ConnectError> {
+    let user = user(target);
+    if let Some(password) = password {
+        session.userauth_password(&user, password)?;
+        return Ok(());
+    }
+
+    if session.userauth_agent(&user).is_ok() {
+        return Ok(());
+    }
+
+    let ssh_dir = dirs::home_dir().unwrap_or_default().join(".ssh");
+    for key in KEYS {
+        let key = ssh_dir.join(key);
+        if key.exists()
+            && session
+                .userauth_pubkey_file(&user, None, &key, None)
+                .is_ok()
+        {
+            return Ok(());
+        }
+    }
+
+    Err(ConnectError::NeedsPassword)
+}
+
+pub fn connect(target: &Target, password: Option<&str>) -> Result<Sftp, ConnectError> {
+    let addr = (target.host.as_str(), port(target))
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| ConnectError::Failed(format!("couldn't resolve {}", target.host)))?;
+    let tcp = TcpStream::connect_timeout(&addr, TIMEOUT)?;
+
+    let mut session = Session::new()?;
+    session.set_timeout(TIMEOUT.as_millis() as u32);
+    session.set_tcp_stream(tcp);
+    session.handshake()?;
+
+    check_host(&session, target)?;
+    authenticate(&session, target, password)?;
+
+    let sftp = session.sftp()?;
+    Ok(Sftp {
+        name: format!("{}@{}", user(target), target.host),
+        _session: session,
+        sftp,
+    })
+}
+
+impl Sftp {
+    // where the connection starts, the home directory on most servers
+    pub fn start_dir(&self) -> io::Result<PathBuf> {
+        Ok(self.sftp.realpath(Path::new("."))?)
+    }
+}
+
+impl VfsProvider for Sftp {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<VfsEntry>> {
+        let mut entries = vec![];
+
+        for (entry, stat) in self.sftp.readdir(path)? {
+            let name = match entry.file_name() {
+                Some(name) => name.to_string_lossy().to_string(),
+                None => continue,
+            };
+
+            // links are listed as what they point to, broken ones as files
+            let stat = if stat.file_type().is_symlink() {
+                self.sftp.stat(&entry).unwrap_or(stat)
+            } else {
+                stat
+            };
+
+            entries.push(VfsEntry {
+                name,
+                is_dir: stat.is_dir(),
+            });
+        }
+
+        Ok(entries)
+    }
+
+    fn is_dir(&self, path: &Path) -> io::Result<bool> {
+        Ok(self.sftp.stat(path)?.is_dir())
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read>> {
+        Ok(Box::new(self.sftp.open(path)?))
+    }
+
+    fn size(&self, path: &Path) -> io::Result<u64> {
+        Ok(self.sftp.stat(path)?.size.unwrap_or(0))
+    }
+
+    fn read_range(&self, path: &Path, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+        let mut file = self.sftp.open(path)?;
+        file.seek(SeekFrom::Start(offset))?;
+
+        let mut bytes = vec![];
+        file.take(len).read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn write(&self, path: &Path, source: &mut dyn Read) -> io::Result<u64> {
+        io::copy(source, &mut self.sftp.create(path)?)
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        if self.is_dir(path).unwrap_or(false) {
+            return Ok(());
+        }
+        Ok(self.sftp.mkdir(path, 0o755)?)
+    }
+
+    fn join(&self, dir: &Path, name: &str) -> PathBuf {
+        join_remote(dir, name)
+    }
+}
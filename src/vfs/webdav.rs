@@ -0,0 +1,260 @@
+use super::{join_remote, ConnectError, Target, VfsEntry, VfsProvider};
+use base64::Engine;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// a server that doesn't answer shouldn't hang traverse for long
+const TIMEOUT: Duration = Duration::from_secs(10);
+// all a listing needs to know about an entry is whether it's a collection
+const PROPFIND: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:"><d:prop><d:resourcetype/></d:prop></d:propfind>"#;
+
+fn to_io(err: ureq::Error) -> io::Error {
+    match err {
+        ureq::Error::Status(404, _) => {
+            io::Error::new(io::ErrorKind::NotFound, "the server said 404 Not Found")
+        }
+        ureq::Error::Status(code, response) => io::Error::other(format!(
+            "the server said {} {}",
+            code,
+            response.status_text()
+        )),
+        ureq::Error::Transport(transport) => io::Error::other(transport.to_string()),
+    }
+}
+
+// every byte but the unreserved ones and the separators as %XX
+fn encode(path: &str) -> String {
+    path.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = vec![];
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        let hex = text
+            .get(idx + 1..idx + 3)
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match hex {
+            Some(byte) if bytes[idx] == b'%' => {
+                decoded.push(byte);
+                idx += 3;
+            }
+            _ => {
+                decoded.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+// counts what goes out in a PUT
+struct Counted<'a> {
+    source: &'a mut dyn Read,
+    bytes: u64,
+}
+
+impl Read for Counted<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.source.read(buf)?;
+        self.bytes += read as u64;
+        Ok(read)
+    }
+}
+
+pub struct WebDav {
+    name: String,
+    // scheme, host and port, paths are added to it
+    base: String,
+    agent: ureq::Agent,
+    auth: Option<String>,
+}
+
+impl WebDav {
+    // without a login first, a server that wants one asks for the password
+    // of the user in the target
+    pub fn connect(
+        target: &Target,
+        secure: bool,
+        password: Option<&str>,
+    ) -> Result<WebDav, ConnectError> {
+        let scheme = if secure { "https" } else { "http" };
+        let base = match target.port {
+            Some(port) => format!("{}://{}:{}", scheme, target.host, port),
+            None => format!("{}://{}", scheme, target.host),
+        };
+        let auth = match (&target.user, password) {
+            (Some(user), Some(password)) => Some(format!(
+                "Basic {}",
+                base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", user, password))
+            )),
+            _ => None,
+        };
+        let name = match &target.user {
+            Some(user) => format!("{}://{}@{}", scheme, user, target.host),
+            None => base.clone(),
+        };
+
+        let dav = WebDav {
+            name,
+            base,
+            agent: ureq::AgentBuilder::new()
+                .timeout_connect(TIMEOUT)
+                .timeout_read(TIMEOUT)
+                .build(),
+            auth,
+        };
+
+        match dav.propfind(Path::new(target.path.as_deref().unwrap_or("/")), "0") {
+            Ok(_) => Ok(dav),
+            Err(ureq::Error::Status(401, _)) if target.user.is_none() => Err(ConnectError::Failed(
+                "the server wants a login, connect as user@host".to_string(),
+            )),
+            Err(ureq::Error::Status(401, _)) if password.is_none() => {
+                Err(ConnectError::NeedsPassword)
+            }
+            Err(ureq::Error::Status(401, _)) => {
+                Err(ConnectError::Failed("wrong user or password".to_string()))
+            }
+            Err(e) => Err(to_io(e).into()),
+        }
+    }
+
+    fn request(&self, method: &str, path: &Path) -> ureq::Request {
+        let url = format!("{}{}", self.base, encode(&path.to_string_lossy()));
+        let request = self.agent.request(method, &url);
+
+        match &self.auth {
+            Some(auth) => request.set("Authorization", auth),
+            None => request,
+        }
+    }
+
+    // the href of every response and whether it's a collection
+    fn propfind(&self, path: &Path, depth: &str) -> Result<Vec<(String, bool)>, ureq::Error> {
+        let text = self
+            .request("PROPFIND", path)
+            .set("Depth", depth)
+            .set("Content-Type", "application/xml; charset=utf-8")
+            .send_string(PROPFIND)?
+            .into_string()?;
+
+        let document = roxmltree::Document::parse(&text)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+
+        Ok(document
+            .descendants()
+            .filter(|node| node.has_tag_name(("DAV:", "response")))
+            .filter_map(|response| {
+                let href = response
+                    .descendants()
+                    .find(|node| node.has_tag_name(("DAV:", "href")))?
+                    .text()?;
+                let is_dir = response
+                    .descendants()
+                    .any(|node| node.has_tag_name(("DAV:", "collection")));
+
+                // some servers send whole URLs, the path is all that's needed
+                let href = match href.split_once("://") {
+                    Some((_, rest)) => rest.find('/').map(|idx| &rest[idx..]).unwrap_or("/"),
+                    None => href,
+                };
+                Some((decode(href), is_dir))
+            })
+            .collect())
+    }
+}
+
+impl VfsProvider for WebDav {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<VfsEntry>> {
+        let dir = path.to_string_lossy().trim_end_matches('/').to_string();
+
+        Ok(self
+            .propfind(path, "1")
+            .map_err(to_io)?
+            .into_iter()
+            // the directory itself is in the answer too
+            .filter(|(href, _)| href.trim_end_matches('/') != dir)
+            .filter_map(|(href, is_dir)| {
+                let name = href.trim_end_matches('/').rsplit('/').next()?;
+                Some(VfsEntry {
+                    name: name.to_string(),
+                    is_dir,
+                })
+            })
+            .collect())
+    }
+
+    fn is_dir(&self, path: &Path) -> io::Result<bool> {
+        let found = self.propfind(path, "0").map_err(to_io)?;
+        Ok(found.first().is_some_and(|(_, is_dir)| *is_dir))
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read>> {
+        let response = self.request("GET", path).call().map_err(to_io)?;
+        Ok(response.into_reader())
+    }
+
+    fn size(&self, path: &Path) -> io::Result<u64> {
+        let response = self.request("HEAD", path).call().map_err(to_io)?;
+        response
+            .header("Content-Length")
+            .and_then(|len| len.parse().ok())
+            .ok_or_else(|| io::Error::other("the server didn't say how big it is"))
+    }
+
+    fn read_range(&self, path: &Path, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+        let response = self
+            .request("GET", path)
+            .set("Range", &format!("bytes={}-{}", offset, offset + len - 1))
+            .call()
+            .map_err(to_io)?;
+
+        // a server without ranges sends all of it, skip to the part asked for
+        let skip = if response.status() == 206 { 0 } else { offset };
+        let mut reader = response.into_reader();
+        io::copy(&mut reader.by_ref().take(skip), &mut io::sink())?;
+
+        let mut bytes = vec![];
+        reader.take(len).read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn write(&self, path: &Path, source: &mut dyn Read) -> io::Result<u64> {
+        let mut counted = Counted { source, bytes: 0 };
+        self.request("PUT", path)
+            .send(&mut counted)
+            .map_err(to_io)?;
+        Ok(counted.bytes)
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        match self.request("MKCOL", path).call() {
+            Ok(_) => Ok(()),
+            // 405 is what a collection that's already there gets
+            Err(ureq::Error::Status(405, _)) if self.is_dir(path)? => Ok(()),
+            Err(e) => Err(to_io(e)),
+        }
+    }
+
+    fn join(&self, dir: &Path, name: &str) -> PathBuf {
+        join_remote(dir, name)
+    }
+}